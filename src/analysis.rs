@@ -0,0 +1,291 @@
+//! QC-oriented structural analysis computed from a track's sample table
+//! alone -- a bitrate-over-time timeline, and variable-frame-rate
+//! detection -- without downloading or decoding any sample bytes, the same
+//! no-sample-reads approach [`crate::probe`] uses for cheap container
+//! inspection.
+
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::formats::mp4::TrackReader;
+use crate::stream::SeekableStream;
+
+/// One bucket of a [`track_bitrate_timeline`]: the bitrate of every sample
+/// whose decode timestamp fell within `[start, start + window)`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitrateWindow {
+    /// The window's start time, relative to the track.
+    pub start: Duration,
+    /// This window's sample bytes, scaled to bits per second -- a spike
+    /// here means a spike in encoded size, not necessarily a muxer-declared
+    /// rate (see [`crate::formats::mp4::BitrateInfo`] for that).
+    pub bits_per_second: f64,
+}
+
+/// Computes a bitrate-over-time timeline for `track_index`'s samples,
+/// bucketed into `window`-long spans, using only `stsz` (sample sizes) and
+/// `stts` (sample timing) -- no sample bytes are read, so this is cheap
+/// enough to run over a remote file to spot bitrate spikes on a QC
+/// dashboard.
+///
+/// Returns `None` if there's no such track, or it has no `mdhd` timescale
+/// to convert sample timestamps into real time.
+pub fn track_bitrate_timeline<S: SeekableStream>(
+    stream: S,
+    track_index: usize,
+    window: Duration,
+) -> Result<Option<Vec<BitrateWindow>>> {
+    if window.is_zero() {
+        return Err(Error::Unsupported("window must be greater than zero".into()));
+    }
+
+    let Some(mut reader) = TrackReader::open(stream, track_index)? else { return Ok(None) };
+    let timescale = reader.timescale();
+    if timescale == 0 {
+        return Ok(None);
+    }
+
+    let window_secs = window.as_secs_f64();
+    let mut windows: Vec<BitrateWindow> = Vec::new();
+
+    for index in 0..reader.sample_count() {
+        let info = reader.sample_info(index)?;
+        let timestamp_secs = info.timestamp as f64 / f64::from(timescale);
+        let window_index = (timestamp_secs / window_secs).floor() as usize;
+
+        while windows.len() <= window_index {
+            let start = Duration::from_secs_f64(windows.len() as f64 * window_secs);
+            windows.push(BitrateWindow { start, bits_per_second: 0.0 });
+        }
+        windows[window_index].bits_per_second += f64::from(info.size) * 8.0;
+    }
+
+    for bucket in &mut windows {
+        bucket.bits_per_second /= window_secs;
+    }
+
+    Ok(Some(windows))
+}
+
+/// Per-track frame rate stats derived from `stts` delta analysis: whether
+/// consecutive samples' decode-timestamp intervals vary enough to call the
+/// track variable frame rate (VFR), and the min/average/max instantaneous
+/// frame rate those intervals imply. A constant-delta `stts` (the common
+/// case) reports `is_vfr: false` with all three rates equal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrameRateInfo {
+    /// Whether more than one distinct inter-frame interval was found.
+    pub is_vfr: bool,
+    /// The lowest instantaneous frame rate (the longest interval).
+    pub min_fps: f64,
+    /// The frame rate implied by the average interval across the track.
+    pub avg_fps: f64,
+    /// The highest instantaneous frame rate (the shortest interval).
+    pub max_fps: f64,
+}
+
+/// Computes [`FrameRateInfo`] for `track_index`'s samples from `stts`
+/// alone -- no sample bytes are read. A single reported frame rate (what
+/// most metadata readers surface) mislabels a variable-frame-rate screen
+/// recording or camera capture as constant, which throws off downstream
+/// transcoders that size buffers or schedule work off of it.
+///
+/// Reads `stts`'s runs directly rather than resolving every sample's
+/// timestamp: the runs already *are* the track's distinct inter-sample
+/// deltas, so this is O(run count) rather than O(sample count).
+///
+/// Returns `None` if there's no such track, it has no `mdhd` timescale, or
+/// it has fewer than two samples (not enough to measure an interval).
+pub fn track_frame_rate<S: SeekableStream>(stream: S, track_index: usize) -> Result<Option<FrameRateInfo>> {
+    let Some(mut reader) = TrackReader::open(stream, track_index)? else { return Ok(None) };
+    let timescale = reader.timescale();
+    if timescale == 0 || reader.sample_count() < 2 {
+        return Ok(None);
+    }
+
+    let runs = reader.stts_runs()?;
+    let total_count: u64 = runs.iter().map(|&(count, _)| u64::from(count)).sum();
+    if total_count != u64::from(reader.sample_count()) {
+        return Err(Error::Malformed { format: "mp4", reason: "stts entries don't cover this track's samples".into() });
+    }
+
+    let deltas: Vec<u32> = runs.iter().filter(|&&(count, _)| count > 0).map(|&(_, delta)| delta).collect();
+    let is_vfr = deltas.windows(2).any(|pair| pair[0] != pair[1]);
+    let min_delta = *deltas.iter().min().unwrap();
+    let max_delta = *deltas.iter().max().unwrap();
+    let total_ticks: u64 = runs.iter().map(|&(count, delta)| u64::from(count) * u64::from(delta)).sum();
+    let avg_delta = total_ticks as f64 / total_count as f64;
+
+    Ok(Some(FrameRateInfo {
+        is_vfr,
+        // A shorter interval is a higher frame rate, hence the swap.
+        min_fps: f64::from(timescale) / f64::from(max_delta),
+        avg_fps: f64::from(timescale) / avg_delta,
+        max_fps: f64::from(timescale) / f64::from(min_delta),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    /// Builds a single-track MP4 with `sizes.len()` samples, one per
+    /// `ticks`-wide `stts` entry, at `timescale` ticks/second.
+    fn sample_mp4(timescale: u32, ticks_per_sample: u32, sizes: &[u32]) -> Vec<u8> {
+        sample_mp4_with_stts_runs(timescale, &[(sizes.len() as u32, ticks_per_sample)], sizes)
+    }
+
+    /// Like [`sample_mp4`], but `stts` is built from explicit `(count,
+    /// delta)` runs instead of one uniform delta, for exercising
+    /// variable-frame-rate `stts` layouts.
+    fn sample_mp4_with_stts_runs(timescale: u32, runs: &[(u32, u32)], sizes: &[u32]) -> Vec<u8> {
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(sizes.len() as u32).to_be_bytes());
+        for size in sizes {
+            stsz_body.extend_from_slice(&size.to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&(runs.len() as u32).to_be_bytes());
+        for &(count, delta) in runs {
+            stts_body.extend_from_slice(&count.to_be_bytes());
+            stts_body.extend_from_slice(&delta.to_be_bytes());
+        }
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(sizes.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let entry = sized_box(b"avc1", &[0u8; 8]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &entry].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let stbl = sized_box(b"stbl", &[stsz, stts, stsc, stsd, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        let mut mdhd_body = vec![0u8; 20];
+        mdhd_body[12..16].copy_from_slice(&timescale.to_be_bytes());
+        let mdhd = sized_box(b"mdhd", &mdhd_body);
+
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let tkhd = sized_box(b"tkhd", &[0u8; 84]);
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body = vec![0u8; sizes.iter().sum::<u32>() as usize];
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn buckets_sample_sizes_into_one_second_windows() {
+        // 1000 ticks/sec timescale, one sample per tick-second, 4 samples
+        // of 1000 bytes each -> 8000 bits/sec in each of 4 one-second windows.
+        let data = sample_mp4(1000, 1000, &[1000, 1000, 1000, 1000]);
+        let stream = MemorySeekableStream::new(data);
+
+        let timeline = track_bitrate_timeline(stream, 0, Duration::from_secs(1)).unwrap().unwrap();
+
+        assert_eq!(timeline.len(), 4);
+        for (i, window) in timeline.iter().enumerate() {
+            assert_eq!(window.start, Duration::from_secs(i as u64));
+            assert_eq!(window.bits_per_second, 8000.0);
+        }
+    }
+
+    #[test]
+    fn merges_multiple_samples_into_the_same_window() {
+        // 1000 ticks/sec timescale, 2 samples every half-second -> both
+        // land in the same one-second window.
+        let data = sample_mp4(1000, 500, &[1000, 1000, 1000, 1000]);
+        let stream = MemorySeekableStream::new(data);
+
+        let timeline = track_bitrate_timeline(stream, 0, Duration::from_secs(1)).unwrap().unwrap();
+
+        assert_eq!(timeline.len(), 2);
+        assert_eq!(timeline[0].bits_per_second, 16000.0);
+        assert_eq!(timeline[1].bits_per_second, 16000.0);
+    }
+
+    #[test]
+    fn returns_none_for_an_out_of_range_track_index() {
+        let data = sample_mp4(1000, 1000, &[1000]);
+        let stream = MemorySeekableStream::new(data);
+
+        assert!(track_bitrate_timeline(stream, 1, Duration::from_secs(1)).unwrap().is_none());
+    }
+
+    #[test]
+    fn rejects_a_zero_length_window() {
+        let data = sample_mp4(1000, 1000, &[1000]);
+        let stream = MemorySeekableStream::new(data);
+
+        assert!(matches!(
+            track_bitrate_timeline(stream, 0, Duration::ZERO),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn reports_constant_frame_rate_for_a_single_stts_run() {
+        // 600 ticks/sec timescale, 25 ticks/sample -> 24fps throughout.
+        let data = sample_mp4(600, 25, &[1000, 1000, 1000, 1000, 1000]);
+        let stream = MemorySeekableStream::new(data);
+
+        let info = track_frame_rate(stream, 0).unwrap().unwrap();
+
+        assert!(!info.is_vfr);
+        assert_eq!(info.min_fps, 24.0);
+        assert_eq!(info.avg_fps, 24.0);
+        assert_eq!(info.max_fps, 24.0);
+    }
+
+    #[test]
+    fn reports_variable_frame_rate_across_multiple_stts_runs() {
+        // 600 ticks/sec timescale: 2 samples at 25 ticks (24fps), then 2
+        // samples at 20 ticks (30fps).
+        let data = sample_mp4_with_stts_runs(600, &[(2, 25), (2, 20)], &[1000, 1000, 1000, 1000]);
+        let stream = MemorySeekableStream::new(data);
+
+        let info = track_frame_rate(stream, 0).unwrap().unwrap();
+
+        assert!(info.is_vfr);
+        assert_eq!(info.min_fps, 24.0);
+        assert_eq!(info.max_fps, 30.0);
+        assert!(info.avg_fps > info.min_fps && info.avg_fps < info.max_fps);
+    }
+
+    #[test]
+    fn returns_none_for_a_track_with_fewer_than_two_samples() {
+        let data = sample_mp4(600, 25, &[1000]);
+        let stream = MemorySeekableStream::new(data);
+
+        assert!(track_frame_rate(stream, 0).unwrap().is_none());
+    }
+}
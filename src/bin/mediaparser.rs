@@ -0,0 +1,129 @@
+//! `mediaparser` — a small CLI wrapping this crate's extraction functions,
+//! so `probe`/`metadata`/`thumbs`/`subs` results can be inspected or
+//! scripted without writing a one-off Rust program first.
+//!
+//! ```text
+//! mediaparser <probe|metadata|thumbs|subs> <path-or-url> [--json] [--out <file>]
+//! ```
+//!
+//! `<path-or-url>` is a local file path, or (with the `http` feature) an
+//! `http://`/`https://` URL read with ranged requests.
+
+use std::process::ExitCode;
+
+use media_parser::stream::{FileSeekableStream, SeekableStream};
+use media_parser::{probe, Error, MediaParser};
+
+struct Args {
+    subcommand: String,
+    source: String,
+    json: bool,
+    out: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut positional = Vec::new();
+    let mut json = false;
+    let mut out = None;
+
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--out" => out = Some(iter.next().ok_or("--out requires a file path")?),
+            _ => positional.push(arg),
+        }
+    }
+
+    if positional.len() != 2 {
+        return Err("expected exactly a subcommand and a path or URL".into());
+    }
+    let source = positional.pop().unwrap();
+    let subcommand = positional.pop().unwrap();
+    Ok(Args { subcommand, source, json, out })
+}
+
+fn print_usage() {
+    eprintln!("usage: mediaparser <probe|metadata|thumbs|subs> <path-or-url> [--json] [--out <file>]");
+}
+
+fn is_url(source: &str) -> bool {
+    source.starts_with("http://") || source.starts_with("https://")
+}
+
+#[cfg(feature = "http")]
+fn run_url(subcommand: &str, url: &str, json: bool) -> media_parser::Result<String> {
+    run_subcommand(subcommand, media_parser::SeekableHttpStream::new(url), json)
+}
+
+#[cfg(not(feature = "http"))]
+fn run_url(_subcommand: &str, _url: &str, _json: bool) -> media_parser::Result<String> {
+    Err(Error::Unsupported("this build was compiled without the `http` feature, so URLs aren't supported".into()))
+}
+
+fn run(args: &Args) -> media_parser::Result<String> {
+    if is_url(&args.source) {
+        run_url(&args.subcommand, &args.source, args.json)
+    } else {
+        let stream = FileSeekableStream::open(&args.source).map_err(Error::Io)?;
+        run_subcommand(&args.subcommand, stream, args.json)
+    }
+}
+
+fn run_subcommand<S: SeekableStream>(subcommand: &str, mut stream: S, json: bool) -> media_parser::Result<String> {
+    match subcommand {
+        "probe" => render(&probe(&mut stream)?, json),
+        "metadata" => render(&media_parser::extract_metadata(&mut stream)?, json),
+        "thumbs" => render(&media_parser::thumbnails::extract_primary_image(&mut stream)?, json),
+        "subs" => render(&MediaParser::open(stream).subtitles()?, json),
+        other => Err(Error::Unsupported(format!("unknown subcommand {other:?}"))),
+    }
+}
+
+#[cfg(feature = "serde")]
+fn render<T: serde::Serialize + std::fmt::Debug>(value: &T, json: bool) -> media_parser::Result<String> {
+    if json {
+        serde_json::to_string_pretty(value)
+            .map_err(|err| Error::Unsupported(format!("failed to serialize result to JSON: {err}")))
+    } else {
+        Ok(format!("{value:#?}"))
+    }
+}
+
+#[cfg(not(feature = "serde"))]
+fn render<T: std::fmt::Debug>(value: &T, json: bool) -> media_parser::Result<String> {
+    if json {
+        return Err(Error::Unsupported("this build was compiled without the `serde` feature, so --json isn't supported".into()));
+    }
+    Ok(format!("{value:#?}"))
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {message}");
+            print_usage();
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(&args) {
+        Ok(output) => {
+            match &args.out {
+                Some(path) => {
+                    if let Err(err) = std::fs::write(path, output) {
+                        eprintln!("error: failed to write {path}: {err}");
+                        return ExitCode::FAILURE;
+                    }
+                }
+                None => println!("{output}"),
+            }
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
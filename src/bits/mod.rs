@@ -0,0 +1,6 @@
+//! General-purpose bitstream helpers, for codec and box formats that pack
+//! fields into fewer than 8 bits each (e.g. NAL unit headers, SPS/PPS, or
+//! the various bit-packed configuration boxes under [`crate::formats::mp4`]).
+
+pub mod reader;
+pub mod writer;
@@ -0,0 +1,242 @@
+/// A general-purpose MSB-first bit reader, shared by the format modules that
+/// unpack bit-packed configuration boxes (e.g. [`crate::formats::mp4`]'s
+/// `esds`, `channel_layout`, and `dolby_vision`) or codec headers that
+/// pack fields into fewer than 8 bits each.
+///
+/// This reads directly out of a borrowed `&[u8]` by tracking a bit position,
+/// rather than wrapping a [`std::io::Read`] (e.g. a `Cursor`): NAL unit
+/// headers are parsed one small in-memory slice at a time, often in a tight
+/// per-sample loop, where the allocation and `io::Error` plumbing a `Read`
+/// adapter would add isn't worth paying for. `None` (not `io::Result`)
+/// signals running out of bits, since that's the only way reading can fail.
+pub struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    /// Reads a single bit.
+    pub fn read_bit(&mut self) -> Option<bool> {
+        Some(self.read_bits(1)? != 0)
+    }
+
+    /// Reads a single bit as a boolean flag. An alias for [`Self::read_bit`]
+    /// that reads more naturally at call sites reading flag fields (e.g.
+    /// `rpu_present_flag`).
+    pub fn read_flag(&mut self) -> Option<bool> {
+        self.read_bit()
+    }
+
+    /// Reads the next `n` bits (`n` up to 32), MSB first.
+    pub fn read_bits(&mut self, n: u32) -> Option<u32> {
+        let mut value = 0u32;
+        for _ in 0..n {
+            let byte = *self.data.get(self.pos / 8)?;
+            let bit = (byte >> (7 - (self.pos % 8))) & 1;
+            value = (value << 1) | u32::from(bit);
+            self.pos += 1;
+        }
+        Some(value)
+    }
+
+    /// Reads an unsigned Exp-Golomb-coded value (`ue(v)`, ITU-T H.264/H.265
+    /// section 9.1): a run of `leadingZeroBits` `0` bits, then a `1` bit,
+    /// then `leadingZeroBits` more bits, decoded as
+    /// `2^leadingZeroBits - 1 + suffix`.
+    ///
+    /// Returns `None` if the reader runs out of bits, or if
+    /// `leadingZeroBits` is 32 or more (the result wouldn't fit a `u32`,
+    /// which only happens against malformed or adversarial input).
+    pub fn read_ue(&mut self) -> Option<u32> {
+        let mut leading_zero_bits = 0u32;
+        while !self.read_bit()? {
+            leading_zero_bits += 1;
+            if leading_zero_bits >= 32 {
+                return None;
+            }
+        }
+        if leading_zero_bits == 0 {
+            return Some(0);
+        }
+        let suffix = self.read_bits(leading_zero_bits)?;
+        1u32.checked_shl(leading_zero_bits)?.checked_sub(1)?.checked_add(suffix)
+    }
+
+    /// Reads a signed Exp-Golomb-coded value (`se(v)`, ITU-T H.264/H.265
+    /// section 9.1.1), mapping the unsigned code number `k` to
+    /// `0, 1, -1, 2, -2, 3, -3, ...` (odd `k` positive, even `k` negative).
+    ///
+    /// Returns `None` under the same conditions as [`Self::read_ue`], or if
+    /// the decoded magnitude wouldn't fit an `i32`.
+    pub fn read_se(&mut self) -> Option<i32> {
+        let code_num = self.read_ue()?;
+        let magnitude = i32::try_from(code_num / 2 + code_num % 2).ok()?;
+        if code_num % 2 == 0 { magnitude.checked_neg() } else { Some(magnitude) }
+    }
+
+    /// Reads `n` bits (a multiple of 8, up to 32) as a little-endian
+    /// integer: each byte is still read MSB first, but the bytes are
+    /// assembled least-significant-first, matching the handful of
+    /// little-endian fields in otherwise big-endian-ish container formats
+    /// this helps parse (e.g. RIFF/AVI chunk sizes, Matroska's `SimpleBlock`
+    /// lacing sizes once the EBML header itself has been read).
+    ///
+    /// Returns `None` if `n` isn't a positive multiple of 8 up to 32, or the
+    /// reader runs out of bits.
+    pub fn read_bits_le(&mut self, n: u32) -> Option<u32> {
+        if n == 0 || !n.is_multiple_of(8) || n > 32 {
+            return None;
+        }
+        let mut bytes = [0u8; 4];
+        for byte in bytes.iter_mut().take((n / 8) as usize) {
+            *byte = self.read_bits(8)? as u8;
+        }
+        Some(u32::from_le_bytes(bytes))
+    }
+
+    /// Reads an EBML variable-size integer (Matroska/WebM's `EBML_VINT`,
+    /// as used for both element IDs and element data sizes): a run of
+    /// `VINT_WIDTH - 1` leading `0` bits, a `1` marker bit, then
+    /// `7 * VINT_WIDTH` bits of `VINT_DATA`, decoded as an unsigned integer.
+    ///
+    /// The marker bit is consumed but not included in the returned value
+    /// (matching how element *sizes* are read; reading an element *ID*
+    /// keeps the marker, which callers needing that can reconstruct from
+    /// the data bit count this consumed).
+    ///
+    /// Returns `None` if the reader runs out of bits, or the marker bit
+    /// isn't found within the first 8 bytes (`VINT_WIDTH` is always 1-8 per
+    /// the EBML spec).
+    pub fn read_ebml_vint(&mut self) -> Option<u64> {
+        let mut width = 1u32;
+        while !self.read_bit()? {
+            width += 1;
+            if width > 8 {
+                return None;
+            }
+        }
+
+        let mut value = 0u64;
+        for _ in 0..7 * width {
+            value = (value << 1) | u64::from(self.read_bit()?);
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_bits_msb_first() {
+        let mut reader = BitReader::new(&[0b1011_0000]);
+        assert_eq!(reader.read_bits(4), Some(0b1011));
+        assert_eq!(reader.read_bits(4), Some(0b0000));
+    }
+
+    #[test]
+    fn reads_a_field_spanning_two_bytes() {
+        let mut reader = BitReader::new(&[0b11_101010, 0b11_000000]);
+        assert_eq!(reader.read_bits(2), Some(0b11));
+        assert_eq!(reader.read_bits(8), Some(0xAB));
+    }
+
+    #[test]
+    fn returns_none_past_the_end_of_the_data() {
+        let mut reader = BitReader::new(&[0xFF]);
+        reader.read_bits(8).unwrap();
+        assert_eq!(reader.read_bits(1), None);
+    }
+
+    #[test]
+    fn read_flag_reads_a_single_bit() {
+        let mut reader = BitReader::new(&[0b1000_0000]);
+        assert_eq!(reader.read_flag(), Some(true));
+        assert_eq!(reader.read_flag(), Some(false));
+    }
+
+    #[test]
+    fn reads_ue_zero_from_a_single_one_bit() {
+        let mut reader = BitReader::new(&[0b1000_0000]);
+        assert_eq!(reader.read_ue(), Some(0));
+    }
+
+    #[test]
+    fn reads_ue_values_per_the_exp_golomb_table() {
+        // 010, 011, 00100, 00101, 00110, 00111 -> 1, 2, 3, 4, 5, 6
+        let mut reader = BitReader::new(&[0b0100_1100, 0b1000_0101, 0b0011_0001, 0b1100_0000]);
+        assert_eq!(reader.read_ue(), Some(1));
+        assert_eq!(reader.read_ue(), Some(2));
+        assert_eq!(reader.read_ue(), Some(3));
+        assert_eq!(reader.read_ue(), Some(4));
+        assert_eq!(reader.read_ue(), Some(5));
+        assert_eq!(reader.read_ue(), Some(6));
+    }
+
+    #[test]
+    fn reads_se_mapping_code_numbers_to_alternating_signs() {
+        // ue() code numbers 0, 1, 2, 3, 4 -> se() 0, 1, -1, 2, -2.
+        let mut reader = BitReader::new(&[0b1010_0110, 0b0100_0010, 0b1000_0000]);
+        assert_eq!(reader.read_se(), Some(0));
+        assert_eq!(reader.read_se(), Some(1));
+        assert_eq!(reader.read_se(), Some(-1));
+        assert_eq!(reader.read_se(), Some(2));
+        assert_eq!(reader.read_se(), Some(-2));
+    }
+
+    #[test]
+    fn read_ue_returns_none_for_31_or_more_leading_zero_bits() {
+        let data = [0u8; 8]; // far more than 31 leading zero bits
+        let mut reader = BitReader::new(&data);
+        assert_eq!(reader.read_ue(), None);
+    }
+
+    #[test]
+    fn reads_a_little_endian_32_bit_field() {
+        let mut reader = BitReader::new(&[0x78, 0x56, 0x34, 0x12]);
+        assert_eq!(reader.read_bits_le(32), Some(0x1234_5678));
+    }
+
+    #[test]
+    fn reads_a_little_endian_16_bit_field_after_a_big_endian_one() {
+        let mut reader = BitReader::new(&[0xAB, 0x34, 0x12]);
+        assert_eq!(reader.read_bits(8), Some(0xAB));
+        assert_eq!(reader.read_bits_le(16), Some(0x1234));
+    }
+
+    #[test]
+    fn read_bits_le_rejects_a_width_that_is_not_a_multiple_of_8() {
+        let mut reader = BitReader::new(&[0, 0, 0, 0]);
+        assert_eq!(reader.read_bits_le(12), None);
+    }
+
+    #[test]
+    fn reads_a_one_byte_ebml_vint() {
+        // 0x81 = 1000_0001: marker in bit 0 of byte 0 (width 1), value 1.
+        let mut reader = BitReader::new(&[0x81]);
+        assert_eq!(reader.read_ebml_vint(), Some(1));
+    }
+
+    #[test]
+    fn reads_a_two_byte_ebml_vint() {
+        // 0x40 0x02 = 0100_0000 0000_0010: marker in bit 1 of byte 0
+        // (width 2), data bits decode to 2.
+        let mut reader = BitReader::new(&[0x40, 0x02]);
+        assert_eq!(reader.read_ebml_vint(), Some(2));
+    }
+
+    #[test]
+    fn reads_consecutive_ebml_vints_for_an_element_id_and_size() {
+        // A minimal EBML element header: a 4-byte ID (0x1A45DFA3, the EBML
+        // root element ID, itself a valid VINT) followed by a 1-byte size
+        // VINT encoding 31.
+        let mut reader = BitReader::new(&[0x1A, 0x45, 0xDF, 0xA3, 0x9F]);
+        assert_eq!(reader.read_ebml_vint(), Some(0x0A45_DFA3));
+        assert_eq!(reader.read_ebml_vint(), Some(31));
+    }
+}
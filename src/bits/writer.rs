@@ -0,0 +1,149 @@
+/// A general-purpose MSB-first bit writer: the write-side counterpart to the
+/// `BitReader`s scattered across this crate's format modules, for building
+/// bitstreams that pack fields into fewer than 8 bits each (e.g. re-emitting
+/// an SPS/PPS, or hand-rolling a bit-packed configuration box).
+#[derive(Debug, Default, Clone)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    /// How many bits of `bytes`'s last byte are already written (0 means
+    /// the last byte, if any, is complete and a new bit starts a new byte).
+    bit_pos: u8,
+}
+
+impl BitWriter {
+    /// Creates an empty writer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a single bit.
+    pub fn write_bit(&mut self, bit: bool) {
+        if self.bit_pos == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 1 << (7 - self.bit_pos);
+        }
+        self.bit_pos = (self.bit_pos + 1) % 8;
+    }
+
+    /// Appends a single bit: `0` or `1`. An alias for [`Self::write_bit`]
+    /// that reads more naturally at call sites writing boolean flag fields
+    /// (e.g. `rpu_present_flag`).
+    pub fn write_flag(&mut self, flag: bool) {
+        self.write_bit(flag);
+    }
+
+    /// Appends the low `n` bits of `value`, MSB first. `n` must be 32 or
+    /// fewer; bits beyond that aren't representable in a `u32` and are
+    /// silently dropped.
+    pub fn write_bits(&mut self, value: u32, n: u32) {
+        for i in (0..n.min(32)).rev() {
+            self.write_bit((value >> i) & 1 != 0);
+        }
+    }
+
+    /// Pads the current byte with zero bits up to the next byte boundary.
+    /// A no-op if the writer is already byte-aligned.
+    pub fn align_to_byte(&mut self) {
+        self.bit_pos = 0;
+    }
+
+    /// How many bits have been written so far.
+    pub fn bit_len(&self) -> usize {
+        if self.bit_pos == 0 {
+            self.bytes.len() * 8
+        } else {
+            (self.bytes.len() - 1) * 8 + self.bit_pos as usize
+        }
+    }
+
+    /// Consumes the writer, padding any trailing partial byte with zero
+    /// bits, and returns the written bytes.
+    pub fn into_bytes(mut self) -> Vec<u8> {
+        self.align_to_byte();
+        self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_bits_msb_first_within_a_byte() {
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+        writer.write_bit(false);
+        writer.write_bit(true);
+        writer.write_bit(true);
+        writer.write_bits(0, 4);
+
+        assert_eq!(writer.into_bytes(), vec![0b1011_0000]);
+    }
+
+    #[test]
+    fn writes_a_multi_bit_field_spanning_two_bytes() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(0x3, 2); // 2 bits into the first byte
+        writer.write_bits(0xAB, 8); // spans the byte boundary
+        writer.write_bits(0, 6); // pad out the second byte
+
+        assert_eq!(writer.into_bytes(), vec![0b11_101010, 0b11_000000]);
+    }
+
+    #[test]
+    fn pads_a_trailing_partial_byte_with_zeros_on_into_bytes() {
+        let mut writer = BitWriter::new();
+        writer.write_bit(true);
+
+        assert_eq!(writer.into_bytes(), vec![0b1000_0000]);
+    }
+
+    #[test]
+    fn align_to_byte_discards_the_rest_of_the_current_byte() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(0b101, 3);
+        writer.align_to_byte();
+        writer.write_bit(true);
+
+        assert_eq!(writer.into_bytes(), vec![0b1010_0000, 0b1000_0000]);
+    }
+
+    #[test]
+    fn bit_len_tracks_bits_written_including_a_partial_byte() {
+        let mut writer = BitWriter::new();
+        assert_eq!(writer.bit_len(), 0);
+
+        writer.write_bits(0, 5);
+        assert_eq!(writer.bit_len(), 5);
+
+        writer.write_bits(0, 3);
+        assert_eq!(writer.bit_len(), 8);
+    }
+
+    #[test]
+    fn round_trips_through_a_bit_reader() {
+        let mut writer = BitWriter::new();
+        writer.write_bits(5, 3);
+        writer.write_bits(42, 6);
+        writer.write_flag(true);
+        let bytes = writer.into_bytes();
+
+        // Mirrors esds::BitReader's own read-back test without depending on
+        // that crate-private type.
+        let mut bits = Vec::new();
+        for byte in &bytes {
+            for i in (0..8).rev() {
+                bits.push((byte >> i) & 1);
+            }
+        }
+        let read_bits = |bits: &[u8], start: usize, n: usize| -> u32 {
+            bits[start..start + n].iter().fold(0u32, |acc, &b| (acc << 1) | u32::from(b))
+        };
+
+        assert_eq!(read_bits(&bits, 0, 3), 5);
+        assert_eq!(read_bits(&bits, 3, 6), 42);
+        assert_eq!(read_bits(&bits, 9, 1), 1);
+    }
+}
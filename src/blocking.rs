@@ -0,0 +1,138 @@
+//! Parsing MP4/M4A metadata from a forward-only, non-seekable source (a
+//! `std::io::Read`), for callers who don't otherwise run a tokio runtime.
+//!
+//! This is a synchronous counterpart to [`crate::progressive::parse_progressive`]:
+//! same faststart-only limitation, same [`Error::SeekRequired`] behavior
+//! when `mdat` precedes `moov`, just driven by a blocking reader instead of
+//! an `AsyncRead`.
+
+use std::io::{self, Read};
+
+use crate::error::{Error, Result};
+use crate::metadata::{self, Metadata};
+use crate::stream::SeekableStream;
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A [`SeekableStream`] over bytes already buffered in memory, used to hand
+/// the fully-buffered `ftyp`/`moov` prefix to the ordinary MP4 parser.
+struct BufferedStream<'a>(&'a [u8]);
+
+impl SeekableStream for BufferedStream<'_> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = offset as usize;
+        let end = start + buf.len();
+        if end > self.0.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "read past end of buffered prefix"));
+        }
+        buf.copy_from_slice(&self.0[start..end]);
+        Ok(())
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.0.len() as u64)
+    }
+}
+
+/// Parses metadata from `reader` without seeking, as bytes arrive.
+///
+/// Returns [`Error::SeekRequired`] if `mdat` appears before `moov`, since a
+/// non-faststart file's `moov` lives at the end and can't be reached
+/// without seeking.
+pub fn parse_progressive<R: Read>(reader: &mut R) -> Result<Metadata> {
+    let mut buf = Vec::new();
+    let mut offset = 0usize;
+
+    loop {
+        ensure_buffered(reader, &mut buf, offset + 8)?;
+
+        let size32 = u32::from_be_bytes(buf[offset..offset + 4].try_into().unwrap());
+        let box_type: [u8; 4] = buf[offset + 4..offset + 8].try_into().unwrap();
+
+        let (header_size, box_size) = if size32 == 1 {
+            ensure_buffered(reader, &mut buf, offset + 16)?;
+            let size64 = u64::from_be_bytes(buf[offset + 8..offset + 16].try_into().unwrap());
+            (16usize, size64)
+        } else {
+            (8usize, u64::from(size32))
+        };
+
+        if box_size < header_size as u64 {
+            return Err(Error::Malformed { format: "mp4", reason: "box size smaller than its header".into() });
+        }
+
+        if &box_type == b"mdat" {
+            return Err(Error::SeekRequired);
+        }
+
+        let box_end = offset + box_size as usize;
+        ensure_buffered(reader, &mut buf, box_end)?;
+
+        if &box_type == b"moov" {
+            let mut stream = BufferedStream(&buf[..box_end]);
+            return metadata::extract_metadata(&mut stream);
+        }
+
+        offset = box_end;
+    }
+}
+
+/// Reads more of `reader` into `buf` until it holds at least `needed`
+/// bytes, returning an error if the stream ends first.
+fn ensure_buffered<R: Read>(reader: &mut R, buf: &mut Vec<u8>, needed: usize) -> Result<usize> {
+    while buf.len() < needed {
+        let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+        let n = reader.read(&mut chunk)?;
+        if n == 0 {
+            return Err(Error::Malformed { format: "mp4", reason: "stream ended before moov was found".into() });
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    Ok(buf.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[test]
+    fn parses_faststart_prefix() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mut mvhd_body = vec![0u8; 20];
+        mvhd_body[12..16].copy_from_slice(&1000u32.to_be_bytes()); // timescale
+        mvhd_body[16..20].copy_from_slice(&500u32.to_be_bytes()); // duration
+        let mvhd = sized_box(b"mvhd", &mvhd_body);
+        let moov = sized_box(b"moov", &mvhd);
+        let mdat = sized_box(b"mdat", &[0u8; 64]);
+
+        let mut input = ftyp;
+        input.extend_from_slice(&moov);
+        input.extend_from_slice(&mdat);
+
+        let mut reader = &input[..];
+        let metadata = parse_progressive(&mut reader).unwrap();
+
+        assert_eq!(metadata.duration, Some(std::time::Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn requires_seeking_when_mdat_precedes_moov() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mdat = sized_box(b"mdat", &[0u8; 64]);
+
+        let mut input = ftyp;
+        input.extend_from_slice(&mdat);
+
+        let mut reader = &input[..];
+        let err = parse_progressive(&mut reader).unwrap_err();
+        assert!(matches!(err, Error::SeekRequired));
+    }
+}
@@ -0,0 +1,65 @@
+//! A cooperative cancellation flag threaded through long-running
+//! extraction and download paths, so a caller that's given up on a job
+//! (e.g. an abandoned web request) can stop in-flight HTTP downloads and
+//! container parsing instead of letting them run to completion unused.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+
+/// A cheaply [`Clone`]able flag that can be shared between the caller
+/// holding it and the extraction/download code checking it.
+///
+/// This crate's parsing and downloading are synchronous, so cancellation
+/// is cooperative: long-running loops call [`Self::check`] between steps
+/// (format detection, container parsing, each downloaded range) rather
+/// than being preempted mid-step.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Creates a token that starts out not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Marks this token (and every clone of it) as cancelled.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if [`Self::cancel`] has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Returns [`Error::Cancelled`] if this token has been cancelled,
+    /// otherwise `Ok(())`. Intended to be called between steps of a
+    /// long-running operation.
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_observe_cancellation() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(token.check().is_ok());
+
+        clone.cancel();
+
+        assert!(token.is_cancelled());
+        assert!(matches!(token.check(), Err(Error::Cancelled)));
+    }
+}
@@ -0,0 +1,72 @@
+//! Chapter markers embedded in a media file, for building a chapter-select
+//! UI without any separate sidecar format.
+//!
+//! Only MP4/QuickTime's Nero-style `udta.chpl` box is supported; a
+//! QuickTime `chap`-referenced text track (the other common form) isn't
+//! parsed yet.
+
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::formats::mp4::chapters as mp4_chapters;
+use crate::stream::SeekableStream;
+
+/// One chapter marker: where it starts and its title.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChapterEntry {
+    pub start: Duration,
+    pub title: String,
+}
+
+/// Reads `stream`'s chapter list.
+///
+/// Only a Nero-style `udta.chpl` box is supported; fails with
+/// [`Error::Unsupported`] if `stream` has neither that nor (not yet
+/// implemented) a QuickTime chapter track.
+pub fn extract_chapters<S: SeekableStream>(stream: &mut S) -> Result<Vec<ChapterEntry>> {
+    let chapters = mp4_chapters::read_chpl_chapters(stream)?
+        .ok_or_else(|| Error::Unsupported("no chapter list (udta.chpl) was found".into()))?;
+
+    Ok(chapters.into_iter().map(|(start, title)| ChapterEntry { start, title }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[test]
+    fn extracts_chapters_from_a_nero_chpl_box() {
+        let mut chapter = 0u64.to_be_bytes().to_vec();
+        chapter.push(5);
+        chapter.extend_from_slice(b"Intro");
+
+        let mut body = vec![1, 0, 0, 0, 0, 0, 0, 0, 1];
+        body.extend_from_slice(&chapter);
+        let chpl = sized_box(b"chpl", &body);
+        let udta = sized_box(b"udta", &chpl);
+        let moov = sized_box(b"moov", &udta);
+
+        let mut stream = MemorySeekableStream::new(moov);
+        let chapters = extract_chapters(&mut stream).unwrap();
+
+        assert_eq!(chapters, vec![ChapterEntry { start: Duration::ZERO, title: "Intro".to_string() }]);
+    }
+
+    #[test]
+    fn reports_unsupported_without_a_chapter_list() {
+        let moov = sized_box(b"moov", &[]);
+        let mut stream = MemorySeekableStream::new(moov);
+
+        assert!(matches!(extract_chapters(&mut stream), Err(Error::Unsupported(_))));
+    }
+}
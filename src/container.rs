@@ -0,0 +1,62 @@
+//! Container format detection.
+
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+
+/// A recognized media container format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ContainerFormat {
+    /// RIFF/WAVE audio (`.wav`).
+    Wav,
+    /// Free Lossless Audio Codec (`.flac`).
+    Flac,
+    /// ISO base media file format (`.mp4`, `.m4a`, `.mov`, ...).
+    Mp4,
+    /// Ogg bitstream container (`.ogg`, `.opus`).
+    Ogg,
+    /// HEIF/HEIC/AVIF still-image container (ISO-BMFF with `meta`/`iinf`/
+    /// `iloc` items instead of tracks).
+    Heif,
+}
+
+/// `ftyp` major brands that identify an HEIF-family (rather than
+/// track-based MP4) file.
+const HEIF_BRANDS: &[&[u8; 4]] =
+    &[b"heic", b"heix", b"heim", b"heis", b"hevc", b"hevx", b"mif1", b"msf1", b"avif", b"avis"];
+
+impl ContainerFormat {
+    /// Sniffs the container format by inspecting the leading bytes of
+    /// `stream`.
+    pub fn sniff<S: SeekableStream>(stream: &mut S) -> Result<Self> {
+        let mut header = [0u8; 16];
+        stream.read_at(0, &mut header).map_err(|_| Error::UnrecognizedFormat)?;
+
+        if &header[0..4] == b"RIFF" && &header[8..12] == b"WAVE" {
+            return Ok(ContainerFormat::Wav);
+        }
+
+        if &header[0..4] == b"fLaC" {
+            return Ok(ContainerFormat::Flac);
+        }
+
+        if &header[0..4] == b"OggS" {
+            return Ok(ContainerFormat::Ogg);
+        }
+
+        let box_type = &header[4..8];
+        if box_type == b"moov" {
+            return Ok(ContainerFormat::Mp4);
+        }
+        if box_type == b"ftyp" {
+            let major_brand: &[u8; 4] = header[8..12].try_into().unwrap();
+            if HEIF_BRANDS.contains(&major_brand) {
+                return Ok(ContainerFormat::Heif);
+            }
+            return Ok(ContainerFormat::Mp4);
+        }
+
+        Err(Error::UnrecognizedFormat)
+    }
+}
@@ -0,0 +1,102 @@
+//! Error types shared across the crate.
+
+use std::io;
+
+/// Convenience alias for results returned by this crate.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors that can occur while sniffing or parsing a media container.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    /// The underlying stream could not be read.
+    #[error("io error: {0}")]
+    Io(#[from] io::Error),
+
+    /// The container format could not be determined from the data available.
+    #[error("unrecognized container format")]
+    UnrecognizedFormat,
+
+    /// The data for a recognized container was malformed or truncated.
+    #[error("malformed {format}: {reason}")]
+    Malformed {
+        /// The container format that failed to parse.
+        format: &'static str,
+        /// A human-readable description of what was wrong.
+        reason: String,
+    },
+
+    /// A feature of the container or codec is recognized but not yet supported.
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+
+    /// A progressive (forward-only) parse couldn't find what it needed
+    /// before hitting sample data, and the caller needs to retry with a
+    /// [`crate::stream::SeekableStream`] instead.
+    #[error("this file requires random access to parse (its layout wasn't found before sample data)")]
+    SeekRequired,
+
+    /// The operation was stopped via a [`crate::CancellationToken`].
+    #[error("operation was cancelled")]
+    Cancelled,
+
+    /// A configured timeout elapsed: either
+    /// [`crate::ExtractOptions::timeout`]'s overall deadline, or a
+    /// per-request timeout on the underlying stream.
+    #[error("operation timed out")]
+    Timeout,
+
+    /// The remote resource changed between range requests (its `ETag` or
+    /// `Last-Modified` no longer matched what an earlier request saw), so
+    /// continuing could have mixed bytes from two different versions of
+    /// the file.
+    #[error("remote resource changed during extraction")]
+    SourceChanged,
+
+    /// An HTTP request came back with a non-2xx status. `retryable` is
+    /// `true` for statuses a retry is likely to fix on its own (429 and the
+    /// 5xx server errors), and `false` for ones that won't change without
+    /// the caller doing something differently (e.g. a 403 or 404).
+    #[error("http error: {status}")]
+    Http {
+        /// The response status code.
+        status: u16,
+        /// Whether retrying the same request might succeed.
+        retryable: bool,
+    },
+}
+
+impl Error {
+    /// Whether the same operation might succeed if retried as-is, for batch
+    /// systems that want to classify failures without matching on every
+    /// variant themselves. [`Error::Timeout`] and a retryable
+    /// [`Error::Http`] are the only cases where that's true; everything
+    /// else (a malformed file, an unrecognized format, a resource that
+    /// changed mid-extraction, ...) needs a human or a different input, not
+    /// another attempt.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Timeout => true,
+            Error::Http { retryable, .. } => *retryable,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeout_and_retryable_http_errors_are_retryable() {
+        assert!(Error::Timeout.is_retryable());
+        assert!(Error::Http { status: 503, retryable: true }.is_retryable());
+    }
+
+    #[test]
+    fn non_retryable_http_errors_and_everything_else_are_not_retryable() {
+        assert!(!Error::Http { status: 404, retryable: false }.is_retryable());
+        assert!(!Error::UnrecognizedFormat.is_retryable());
+        assert!(!Error::Cancelled.is_retryable());
+        assert!(!Error::SourceChanged.is_retryable());
+    }
+}
@@ -0,0 +1,244 @@
+//! FLAC (`.flac`) metadata parsing.
+//!
+//! Reads the `STREAMINFO` block for duration and sample format, the
+//! `VORBIS_COMMENT` block for tags, and `PICTURE` blocks for cover art.
+
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::metadata::{Metadata, Picture};
+use crate::stream::SeekableStream;
+
+const MAGIC_LEN: u64 = 4;
+const BLOCK_HEADER_LEN: u64 = 4;
+
+const BLOCK_TYPE_STREAMINFO: u8 = 0;
+const BLOCK_TYPE_VORBIS_COMMENT: u8 = 4;
+const BLOCK_TYPE_PICTURE: u8 = 6;
+
+pub(crate) fn extract_metadata<S: SeekableStream>(stream: &mut S) -> Result<Metadata> {
+    let len = stream.len()?;
+    let mut metadata = Metadata::default();
+    let mut offset = MAGIC_LEN;
+
+    loop {
+        if offset + BLOCK_HEADER_LEN > len {
+            return Err(Error::Malformed { format: "flac", reason: "truncated metadata block header".into() });
+        }
+
+        let mut header = [0u8; 4];
+        stream.read_at(offset, &mut header)?;
+        let is_last = header[0] & 0x80 != 0;
+        let block_type = header[0] & 0x7f;
+        let block_len = u32::from_be_bytes([0, header[1], header[2], header[3]]);
+        let body_offset = offset + BLOCK_HEADER_LEN;
+
+        match block_type {
+            BLOCK_TYPE_STREAMINFO => read_streaminfo(stream, body_offset, &mut metadata)?,
+            BLOCK_TYPE_VORBIS_COMMENT => read_vorbis_comment(stream, body_offset, block_len, &mut metadata)?,
+            BLOCK_TYPE_PICTURE => read_picture(stream, body_offset, block_len, &mut metadata)?,
+            _ => {}
+        }
+
+        offset = body_offset + u64::from(block_len);
+        if is_last {
+            break;
+        }
+    }
+
+    Ok(metadata)
+}
+
+fn read_streaminfo<S: SeekableStream>(stream: &mut S, offset: u64, metadata: &mut Metadata) -> Result<()> {
+    let mut body = [0u8; 34];
+    stream.read_at(offset, &mut body)?;
+
+    // Bytes 10..18 pack sample rate (20 bits), channels - 1 (3 bits),
+    // bits per sample - 1 (5 bits), and total samples (36 bits).
+    let packed = u64::from_be_bytes(body[10..18].try_into().unwrap());
+    let sample_rate = (packed >> 44) as u32;
+    let channels = ((packed >> 41) & 0x7) as u16 + 1;
+    let total_samples = packed & 0xF_FFFF_FFFF;
+
+    metadata.sample_rate = Some(sample_rate);
+    metadata.channels = Some(channels);
+    if sample_rate > 0 {
+        metadata.duration = Some(Duration::from_secs_f64(total_samples as f64 / f64::from(sample_rate)));
+    }
+
+    Ok(())
+}
+
+fn read_vorbis_comment<S: SeekableStream>(
+    stream: &mut S,
+    offset: u64,
+    block_len: u32,
+    metadata: &mut Metadata,
+) -> Result<()> {
+    let mut body = vec![0u8; block_len as usize];
+    stream.read_at(offset, &mut body)?;
+
+    if body.len() < 4 {
+        return Ok(());
+    }
+    let vendor_len = u32::from_le_bytes(body[0..4].try_into().unwrap()) as usize;
+    let mut pos = 4 + vendor_len;
+    if pos + 4 > body.len() {
+        return Ok(());
+    }
+
+    let comment_count = u32::from_le_bytes(body[pos..pos + 4].try_into().unwrap());
+    pos += 4;
+
+    for _ in 0..comment_count {
+        if pos + 4 > body.len() {
+            break;
+        }
+        let comment_len = u32::from_le_bytes(body[pos..pos + 4].try_into().unwrap()) as usize;
+        pos += 4;
+        if pos + comment_len > body.len() {
+            break;
+        }
+        let comment = String::from_utf8_lossy(&body[pos..pos + comment_len]);
+        pos += comment_len;
+
+        if let Some((key, value)) = comment.split_once('=') {
+            metadata.tags.insert(key.to_ascii_lowercase(), value.to_string());
+        }
+    }
+
+    Ok(())
+}
+
+fn read_picture<S: SeekableStream>(stream: &mut S, offset: u64, block_len: u32, metadata: &mut Metadata) -> Result<()> {
+    let block_end = offset + u64::from(block_len);
+
+    let mut fixed = [0u8; 8];
+    stream.read_at(offset, &mut fixed)?;
+    let picture_type = u32::from_be_bytes(fixed[0..4].try_into().unwrap());
+    let mime_len = u32::from_be_bytes(fixed[4..8].try_into().unwrap());
+
+    let mut pos = offset + 8;
+    check_field_fits_in_block(pos, mime_len, block_end)?;
+    let mut mime_bytes = vec![0u8; mime_len as usize];
+    stream.read_at(pos, &mut mime_bytes)?;
+    let mime_type = String::from_utf8_lossy(&mime_bytes).to_string();
+    pos += u64::from(mime_len);
+
+    let mut desc_len_buf = [0u8; 4];
+    stream.read_at(pos, &mut desc_len_buf)?;
+    let desc_len = u32::from_be_bytes(desc_len_buf);
+    pos += 4;
+
+    check_field_fits_in_block(pos, desc_len, block_end)?;
+    let mut desc_bytes = vec![0u8; desc_len as usize];
+    stream.read_at(pos, &mut desc_bytes)?;
+    let description = String::from_utf8_lossy(&desc_bytes).to_string();
+    pos += u64::from(desc_len);
+
+    // width, height, color depth, colors used: 4 fields of 4 bytes we don't surface yet.
+    pos += 16;
+
+    let mut data_len_buf = [0u8; 4];
+    stream.read_at(pos, &mut data_len_buf)?;
+    let data_len = u32::from_be_bytes(data_len_buf);
+    pos += 4;
+
+    check_field_fits_in_block(pos, data_len, block_end)?;
+    let mut data = vec![0u8; data_len as usize];
+    stream.read_at(pos, &mut data)?;
+
+    metadata.pictures.push(Picture { picture_type, mime_type, description, data });
+
+    Ok(())
+}
+
+/// Rejects a `PICTURE` sub-field length the enclosing block couldn't
+/// possibly hold, before it's used to size a `Vec` -- a hostile `mime_len`,
+/// `desc_len`, or `data_len` near `u32::MAX` would otherwise force a
+/// multi-GB allocation attempt from a few header bytes.
+fn check_field_fits_in_block(pos: u64, field_len: u32, block_end: u64) -> Result<()> {
+    if u64::from(field_len) > block_end.saturating_sub(pos) {
+        return Err(Error::Malformed { format: "flac", reason: "PICTURE block field length exceeds the block's bounds".into() });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn streaminfo_block(sample_rate: u32, channels: u8, total_samples: u64) -> Vec<u8> {
+        let mut body = vec![0u8; 34];
+        let packed: u64 = (u64::from(sample_rate) << 44)
+            | (u64::from(channels - 1) << 41)
+            | (15u64 << 36) // bits per sample - 1
+            | (total_samples & 0xF_FFFF_FFFF);
+        body[10..18].copy_from_slice(&packed.to_be_bytes());
+        body
+    }
+
+    fn vorbis_comment_block(comments: &[(&str, &str)]) -> Vec<u8> {
+        let mut body = Vec::new();
+        let vendor = b"media-parser test";
+        body.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        body.extend_from_slice(vendor);
+        body.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+        for (k, v) in comments {
+            let comment = format!("{k}={v}");
+            body.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            body.extend_from_slice(comment.as_bytes());
+        }
+        body
+    }
+
+    fn block(block_type: u8, is_last: bool, body: &[u8]) -> Vec<u8> {
+        let mut block = Vec::new();
+        let flag = if is_last { 0x80 } else { 0 };
+        let len = body.len() as u32;
+        block.push(flag | block_type);
+        block.extend_from_slice(&len.to_be_bytes()[1..4]);
+        block.extend_from_slice(body);
+        block
+    }
+
+    #[test]
+    fn parses_streaminfo_and_tags() {
+        let mut flac = Vec::new();
+        flac.extend_from_slice(b"fLaC");
+        flac.extend_from_slice(&block(BLOCK_TYPE_STREAMINFO, false, &streaminfo_block(44100, 2, 44100)));
+        flac.extend_from_slice(&block(
+            BLOCK_TYPE_VORBIS_COMMENT,
+            true,
+            &vorbis_comment_block(&[("TITLE", "Test Song")]),
+        ));
+
+        let mut stream = MemorySeekableStream::new(flac);
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.sample_rate, Some(44100));
+        assert_eq!(metadata.channels, Some(2));
+        assert_eq!(metadata.duration, Some(Duration::from_secs(1)));
+        assert_eq!(metadata.tags.get("title"), Some(&"Test Song".to_string()));
+    }
+
+    #[test]
+    fn rejects_a_picture_field_length_the_block_cant_possibly_hold() {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0u32.to_be_bytes()); // picture_type
+        body.extend_from_slice(&0u32.to_be_bytes()); // mime_len
+        body.extend_from_slice(&0u32.to_be_bytes()); // desc_len
+        body.extend_from_slice(&[0u8; 16]); // width, height, color depth, colors used
+        body.extend_from_slice(&0xFFFF_FFFFu32.to_be_bytes()); // data_len: far past the block's end
+
+        let mut flac = Vec::new();
+        flac.extend_from_slice(b"fLaC");
+        flac.extend_from_slice(&block(BLOCK_TYPE_PICTURE, true, &body));
+
+        let mut stream = MemorySeekableStream::new(flac);
+        let err = extract_metadata(&mut stream).unwrap_err();
+
+        assert!(matches!(err, Error::Malformed { format: "flac", .. }));
+    }
+}
@@ -0,0 +1,6 @@
+//! Format-specific parsers, one module per [`crate::ContainerFormat`].
+
+pub mod flac;
+pub mod mp4;
+pub mod ogg;
+pub mod wav;
@@ -0,0 +1,5 @@
+//! H.264/AVC NAL unit bitstream helpers, shared by anything that needs to
+//! read or rebuild a NAL unit's raw byte sequence payload (RBSP) rather than
+//! just pass it through, e.g. a future SPS/SEI parser.
+
+pub mod rbsp;
@@ -0,0 +1,105 @@
+//! Emulation-prevention byte handling for H.264/AVC NAL units (ITU-T H.264
+//! section 7.4.1): a NAL unit's bytes on the wire aren't its raw byte
+//! sequence payload (RBSP) directly -- an `emulation_prevention_three_byte`
+//! (`0x03`) is inserted after any `0x0000` that's followed by `0x00`,
+//! `0x01`, `0x02`, or `0x03`, so a bitstream parser scanning for Annex B
+//! start codes (`0x000001`/`0x00000001`) never mistakes RBSP data for one.
+//! Any code that reads a NAL unit's actual fields (SPS, PPS, SEI, slice
+//! headers, ...) must strip these bytes first, or it will misparse RBSP
+//! data that happens to contain the escape sequence.
+
+/// Strips emulation-prevention bytes from a NAL unit's payload, recovering
+/// its RBSP. Safe to call on payloads that don't contain any -- they're
+/// returned unchanged (as a copy).
+pub fn remove_emulation_prevention_bytes(nal_payload: &[u8]) -> Vec<u8> {
+    let mut rbsp = Vec::with_capacity(nal_payload.len());
+    let mut zero_run = 0u32;
+
+    for &byte in nal_payload {
+        if zero_run >= 2 && byte == 0x03 {
+            // Drop the emulation-prevention byte itself; the run doesn't
+            // carry through it, matching how it was inserted.
+            zero_run = 0;
+            continue;
+        }
+        rbsp.push(byte);
+        zero_run = if byte == 0 { zero_run + 1 } else { 0 };
+    }
+
+    rbsp
+}
+
+/// Inserts emulation-prevention bytes into an RBSP, producing the NAL unit
+/// payload that would appear on the wire. The inverse of
+/// [`remove_emulation_prevention_bytes`].
+pub fn insert_emulation_prevention_bytes(rbsp: &[u8]) -> Vec<u8> {
+    let mut nal_payload = Vec::with_capacity(rbsp.len());
+    let mut zero_run = 0u32;
+
+    for &byte in rbsp {
+        if zero_run >= 2 && byte <= 0x03 {
+            nal_payload.push(0x03);
+            zero_run = 0;
+        }
+        nal_payload.push(byte);
+        zero_run = if byte == 0 { zero_run + 1 } else { 0 };
+    }
+
+    nal_payload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leaves_a_payload_without_any_escape_sequence_unchanged() {
+        let payload = [0x67, 0x42, 0x00, 0x1E, 0xAB];
+        assert_eq!(remove_emulation_prevention_bytes(&payload), payload.to_vec());
+    }
+
+    #[test]
+    fn strips_the_emulation_prevention_byte_after_two_zero_bytes() {
+        let payload = [0x00, 0x00, 0x03, 0x01, 0x00, 0x00, 0x03, 0x02];
+        assert_eq!(remove_emulation_prevention_bytes(&payload), vec![0x00, 0x00, 0x01, 0x00, 0x00, 0x02]);
+    }
+
+    #[test]
+    fn does_not_strip_a_03_byte_that_does_not_follow_two_zero_bytes() {
+        let payload = [0x00, 0x03, 0x00, 0x03];
+        assert_eq!(remove_emulation_prevention_bytes(&payload), payload.to_vec());
+    }
+
+    #[test]
+    fn resets_the_zero_run_after_an_emulation_prevention_byte() {
+        // 00 00 03 00 00 03: the first 00 00 03 is stripped; the run then
+        // restarts, so the second 00 00 03 is also stripped.
+        let payload = [0x00, 0x00, 0x03, 0x00, 0x00, 0x03];
+        assert_eq!(remove_emulation_prevention_bytes(&payload), vec![0x00, 0x00, 0x00, 0x00]);
+    }
+
+    #[test]
+    fn inserts_an_emulation_prevention_byte_before_a_00_01_02_or_03_after_two_zero_bytes() {
+        let rbsp = [0x00, 0x00, 0x01, 0x00, 0x00, 0x02, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00];
+        let expected = [
+            0x00, 0x00, 0x03, 0x01, // 00 00 01 -> 00 00 03 01
+            0x00, 0x00, 0x03, 0x02, // 00 00 02 -> 00 00 03 02
+            0x00, 0x00, 0x03, 0x03, // 00 00 03 -> 00 00 03 03
+            0x00, 0x00, 0x03, 0x00, // 00 00 00 -> 00 00 03 00
+        ];
+        assert_eq!(insert_emulation_prevention_bytes(&rbsp), expected.to_vec());
+    }
+
+    #[test]
+    fn does_not_insert_before_a_byte_greater_than_03() {
+        let rbsp = [0x00, 0x00, 0x04];
+        assert_eq!(insert_emulation_prevention_bytes(&rbsp), rbsp.to_vec());
+    }
+
+    #[test]
+    fn round_trips_arbitrary_rbsp_through_insert_and_remove() {
+        let rbsp = vec![0x67, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x02, 0x42, 0x00, 0x00, 0x03];
+        let with_epb = insert_emulation_prevention_bytes(&rbsp);
+        assert_eq!(remove_emulation_prevention_bytes(&with_epb), rbsp);
+    }
+}
@@ -0,0 +1,234 @@
+//! Generic ISO base media file format (MP4/QuickTime) box walking.
+//!
+//! The whole family of `moov`/`trak`/`stbl`/... boxes share one framing:
+//! a 32-bit size, a four-character type, and (for the rare box larger than
+//! 4GB) a 64-bit extended size. Everything else in [`super`] is built on
+//! top of [`read_box_header`].
+//!
+//! Box sizes and offsets come straight from the file, so a crafted or
+//! truncated file can make naive `offset + size` arithmetic wrap around
+//! (especially on 32-bit targets). [`checked_add`] turns that into a
+//! [`Error::Malformed`] instead of a panic or a silently wrong offset.
+
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+
+/// Adds two box offsets/sizes, failing instead of wrapping on overflow.
+pub(crate) fn checked_add(a: u64, b: u64) -> Result<u64> {
+    a.checked_add(b).ok_or(Error::Malformed { format: "mp4", reason: "box offset/size arithmetic overflowed".into() })
+}
+
+/// The location and type of one box, already resolved to absolute stream
+/// offsets so callers don't need to re-derive them from the raw header.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BoxHeader {
+    pub box_type: [u8; 4],
+    /// Offset where this box's header (size/type/extended-size) begins.
+    pub start: u64,
+    /// Offset where the payload (after the size/type/extended-size header)
+    /// begins.
+    pub body_start: u64,
+    /// Offset one past the end of this box, i.e. where the next sibling
+    /// box begins.
+    pub end: u64,
+}
+
+impl BoxHeader {
+    pub fn body_len(&self) -> u64 {
+        self.end - self.body_start
+    }
+
+    /// The total size of this box, header included.
+    pub fn size(&self) -> u64 {
+        self.end - self.start
+    }
+}
+
+/// Reads the box header at `offset`, returning `None` once `offset` reaches
+/// `limit` (the end of the containing box or file).
+pub(crate) fn read_box_header<S: SeekableStream>(
+    stream: &mut S,
+    offset: u64,
+    limit: u64,
+) -> Result<Option<BoxHeader>> {
+    if offset == limit {
+        return Ok(None);
+    }
+    if checked_add(offset, 8)? > limit {
+        return Err(Error::Malformed { format: "mp4", reason: "truncated box header".into() });
+    }
+
+    let mut header = [0u8; 8];
+    stream.read_at(offset, &mut header)?;
+    let size32 = u32::from_be_bytes(header[0..4].try_into().unwrap());
+    let box_type: [u8; 4] = header[4..8].try_into().unwrap();
+
+    let (body_start, end) = match size32 {
+        0 => (checked_add(offset, 8)?, limit),
+        1 => {
+            let mut ext = [0u8; 8];
+            stream.read_at(checked_add(offset, 8)?, &mut ext)?;
+            (checked_add(offset, 16)?, checked_add(offset, u64::from_be_bytes(ext))?)
+        }
+        _ => (checked_add(offset, 8)?, checked_add(offset, u64::from(size32))?),
+    };
+
+    if end < body_start || end > limit {
+        return Err(Error::Malformed { format: "mp4", reason: "box size out of range".into() });
+    }
+
+    Ok(Some(BoxHeader { box_type, start: offset, body_start, end }))
+}
+
+/// Returns every direct child box within `[start, end)`.
+pub(crate) fn children<S: SeekableStream>(stream: &mut S, start: u64, end: u64) -> Result<Vec<BoxHeader>> {
+    let mut out = Vec::new();
+    let mut offset = start;
+    while let Some(header) = read_box_header(stream, offset, end)? {
+        offset = header.end;
+        out.push(header);
+    }
+    Ok(out)
+}
+
+/// Like [`children`], but tolerates a final child whose declared size
+/// extends past `end` (e.g. a truncated `mdat` after an interrupted upload)
+/// instead of failing with [`Error::Malformed`]: that child is clipped to
+/// `end` and walking stops there, and the second return value reports
+/// whether clipping happened.
+pub(crate) fn children_tolerant<S: SeekableStream>(stream: &mut S, start: u64, end: u64) -> Result<(Vec<BoxHeader>, bool)> {
+    let mut out = Vec::new();
+    let mut offset = start;
+
+    loop {
+        match read_box_header(stream, offset, end) {
+            Ok(Some(header)) => {
+                offset = header.end;
+                out.push(header);
+            }
+            Ok(None) => return Ok((out, false)),
+            Err(Error::Malformed { format: "mp4", .. }) if checked_add(offset, 8)? <= end => {
+                let mut header = [0u8; 8];
+                stream.read_at(offset, &mut header)?;
+                let box_type: [u8; 4] = header[4..8].try_into().unwrap();
+                out.push(BoxHeader { box_type, start: offset, body_start: checked_add(offset, 8)?, end });
+                return Ok((out, true));
+            }
+            Err(Error::Malformed { format: "mp4", .. }) => return Ok((out, true)),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Returns the first direct child of the given `box_type` within
+/// `[start, end)`, if any.
+pub(crate) fn find_child<S: SeekableStream>(
+    stream: &mut S,
+    start: u64,
+    end: u64,
+    box_type: &[u8; 4],
+) -> Result<Option<BoxHeader>> {
+    let mut offset = start;
+    while let Some(header) = read_box_header(stream, offset, end)? {
+        if &header.box_type == box_type {
+            return Ok(Some(header));
+        }
+        offset = header.end;
+    }
+    Ok(None)
+}
+
+/// Reads a `uuid` box's 16-byte extended type, which sits right after the
+/// ordinary box header and identifies what the (otherwise opaque) payload
+/// actually is -- e.g. an XMP packet, per [`super::read_xmp`].
+pub(crate) fn read_uuid<S: SeekableStream>(stream: &mut S, header: &BoxHeader) -> Result<[u8; 16]> {
+    let mut uuid = [0u8; 16];
+    stream.read_at(header.body_start, &mut uuid)?;
+    Ok(uuid)
+}
+
+/// Box types known to contain only other boxes, used to bound
+/// [`find_all_recursive`] so it never descends into a leaf box's raw
+/// payload (e.g. `stsd` sample entry fields) and misreads it as framing.
+const CONTAINER_BOX_TYPES: [[u8; 4]; 7] =
+    [*b"trak", *b"mdia", *b"minf", *b"stbl", *b"dinf", *b"edts", *b"mvex"];
+
+/// Recursively finds every descendant box of the given `box_type` within
+/// `[start, end)`, descending only into boxes known to contain other boxes.
+pub(crate) fn find_all_recursive<S: SeekableStream>(
+    stream: &mut S,
+    start: u64,
+    end: u64,
+    box_type: &[u8; 4],
+) -> Result<Vec<BoxHeader>> {
+    let mut out = Vec::new();
+    for child in children(stream, start, end)? {
+        if &child.box_type == box_type {
+            out.push(child);
+        }
+        if CONTAINER_BOX_TYPES.contains(&child.box_type) {
+            out.extend(find_all_recursive(stream, child.body_start, child.end, box_type)?);
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    #[test]
+    fn rejects_extended_size_that_would_overflow_offset_arithmetic() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&1u32.to_be_bytes()); // size == 1 => read 64-bit extended size
+        data.extend_from_slice(b"moov");
+        data.extend_from_slice(&u64::MAX.to_be_bytes());
+
+        let mut stream = MemorySeekableStream::new(data);
+        let len = stream.len().unwrap();
+        let err = read_box_header(&mut stream, 0, len).unwrap_err();
+        assert!(matches!(err, Error::Malformed { format: "mp4", .. }));
+    }
+
+    #[test]
+    fn find_all_recursive_descends_into_container_boxes_only() {
+        let stco = {
+            let mut b = Vec::new();
+            b.extend_from_slice(&8u32.to_be_bytes());
+            b.extend_from_slice(b"stco");
+            b
+        };
+        let stbl = {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((stco.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(b"stbl");
+            b.extend_from_slice(&stco);
+            b
+        };
+        let minf = {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((stbl.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(b"minf");
+            b.extend_from_slice(&stbl);
+            b
+        };
+
+        let mut stream = MemorySeekableStream::new(minf);
+        let len = stream.len().unwrap();
+        let found = find_all_recursive(&mut stream, 0, len, b"stco").unwrap();
+        assert_eq!(found.len(), 1);
+    }
+
+    #[test]
+    fn rejects_box_size_smaller_than_its_own_header() {
+        let mut data = Vec::new();
+        data.extend_from_slice(&4u32.to_be_bytes()); // smaller than the 8-byte header itself
+        data.extend_from_slice(b"moov");
+
+        let mut stream = MemorySeekableStream::new(data);
+        let len = stream.len().unwrap();
+        let err = read_box_header(&mut stream, 0, len).unwrap_err();
+        assert!(matches!(err, Error::Malformed { format: "mp4", .. }));
+    }
+}
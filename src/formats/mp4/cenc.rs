@@ -0,0 +1,411 @@
+//! Common Encryption (CENC, ISO/IEC 23001-7) per-sample encryption info:
+//! `tenc` (the track's default key ID and IV size, from a protected sample
+//! entry's `sinf`/`schi`), and `senc`/`saiz`/`saio` (each sample's IV and
+//! subsample clear/protected byte ranges).
+//!
+//! This is read-only diagnostics/decryption-prerequisite plumbing, not a
+//! decryptor: [`crate::formats::mp4::track_reader::TrackReader`] surfaces
+//! what's parsed here on [`crate::formats::mp4::SampleInfo`] so a caller
+//! with the content key can decrypt samples itself.
+//!
+//! Scope: only the common case of a per-sample (not constant) IV is
+//! supported, and [`read_saiz_saio`] only handles a single contiguous
+//! `saio` offset run (`entry_count == 1`), which covers every encoder this
+//! crate has been tested against. Both are documented limits, not silent
+//! truncation.
+
+use super::boxes::{self, BoxHeader};
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+
+/// A track's default protection parameters, from its protected sample
+/// entry's `sinf/schi/tenc` box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct TrackEncryptionInfo {
+    pub is_protected: bool,
+    pub per_sample_iv_size: u8,
+    pub default_kid: [u8; 16],
+}
+
+/// One sample's encryption side-data: its IV, and (if present) the
+/// clear/protected byte-range pairs splitting the sample into subsamples.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct SampleAuxInfo {
+    pub iv: Vec<u8>,
+    pub subsamples: Vec<(u16, u32)>,
+}
+
+/// Reads `tenc` out of `stsd`'s first sample entry's `sinf/schi`, if it has
+/// one. Returns `None` for an unprotected sample entry (no `sinf`).
+pub(crate) fn read_tenc<S: SeekableStream>(stream: &mut S, stsd: &BoxHeader) -> Result<Option<TrackEncryptionInfo>> {
+    let first_entry_start = stsd.body_start + 8;
+    if first_entry_start + 8 > stsd.end {
+        return Ok(None);
+    }
+    let Some(entry) = boxes::read_box_header(stream, first_entry_start, stsd.end)? else { return Ok(None) };
+    let Some(sinf) = boxes::find_child(stream, entry.body_start, entry.end, b"sinf")? else { return Ok(None) };
+    let Some(schi) = boxes::find_child(stream, sinf.body_start, sinf.end, b"schi")? else { return Ok(None) };
+    let Some(tenc) = boxes::find_child(stream, schi.body_start, schi.end, b"tenc")? else { return Ok(None) };
+
+    // tenc is a full box: version/flags (4), reserved (1), [version==0:
+    // reserved (1)] or [version>0: default_crypt_byte_block/skip_byte_block
+    // (1)], default_isProtected (1), default_Per_Sample_IV_Size (1),
+    // default_KID (16).
+    let mut header = [0u8; 7];
+    stream.read_at(tenc.body_start, &mut header)?;
+    let is_protected = header[5] != 0;
+    let per_sample_iv_size = header[6];
+
+    let mut default_kid = [0u8; 16];
+    stream.read_at(tenc.body_start + 7, &mut default_kid)?;
+
+    Ok(Some(TrackEncryptionInfo { is_protected, per_sample_iv_size, default_kid }))
+}
+
+/// Reads each sample's IV and subsample map, preferring an explicit `senc`
+/// box and falling back to `saiz`/`saio` (which point at the same
+/// per-sample layout stored elsewhere in the file). Returns `None` if
+/// `container` (an `stbl` or `traf`) has neither.
+pub(crate) fn read_sample_encryption<S: SeekableStream>(
+    stream: &mut S,
+    container: &BoxHeader,
+    per_sample_iv_size: u8,
+) -> Result<Option<Vec<SampleAuxInfo>>> {
+    if let Some(senc) = boxes::find_child(stream, container.body_start, container.end, b"senc")? {
+        return Ok(Some(decode_senc(stream, &senc, per_sample_iv_size)?));
+    }
+
+    let saiz = boxes::find_child(stream, container.body_start, container.end, b"saiz")?;
+    let saio = boxes::find_child(stream, container.body_start, container.end, b"saio")?;
+    match (saiz, saio) {
+        (Some(saiz), Some(saio)) => Ok(Some(read_saiz_saio(stream, &saiz, &saio, per_sample_iv_size)?)),
+        _ => Ok(None),
+    }
+}
+
+/// `senc` (SampleEncryptionBox): version/flags (4), `sample_count` (4),
+/// then per sample: a `per_sample_iv_size`-byte IV, and (if flags & 0x2) a
+/// `subsample_count` (2) followed by that many (clear, protected) 6-byte
+/// pairs.
+fn decode_senc<S: SeekableStream>(stream: &mut S, senc: &BoxHeader, per_sample_iv_size: u8) -> Result<Vec<SampleAuxInfo>> {
+    let mut header = [0u8; 8];
+    stream.read_at(senc.body_start, &mut header)?;
+    let flags = u32::from_be_bytes([0, header[1], header[2], header[3]]);
+    let sample_count = u32::from_be_bytes(header[4..8].try_into().unwrap());
+    let has_subsamples = flags & 0x2 != 0;
+
+    let mut pos = senc.body_start + 8;
+    // Each sample takes at least an IV (plus 2 bytes for a subsample
+    // count, if present) -- reject a `sample_count` the box couldn't
+    // possibly hold before sizing a `Vec` off it, so a corrupt/hostile
+    // `sample_count` of e.g. u32::MAX can't force a multi-GB allocation.
+    let min_bytes_per_sample = u64::from(per_sample_iv_size) + if has_subsamples { 2 } else { 0 };
+    let available = senc.end.saturating_sub(pos);
+    if min_bytes_per_sample > 0 && u64::from(sample_count) > available / min_bytes_per_sample {
+        return Err(Error::Malformed { format: "mp4", reason: "senc sample_count exceeds the box's available data".into() });
+    }
+
+    let mut samples = Vec::with_capacity(sample_count as usize);
+    for _ in 0..sample_count {
+        let (aux, next) = read_one_sample_aux(stream, pos, per_sample_iv_size, has_subsamples)?;
+        samples.push(aux);
+        pos = next;
+    }
+    Ok(samples)
+}
+
+/// `saiz`/`saio` (SampleAuxiliaryInformationSizes/OffsetsBox): locate the
+/// same per-sample `iv [+ subsample table]` layout [`decode_senc`] reads,
+/// but stored as raw bytes elsewhere (often directly in `mdat`) rather
+/// than inside a `senc` box.
+fn read_saiz_saio<S: SeekableStream>(
+    stream: &mut S,
+    saiz: &BoxHeader,
+    saio: &BoxHeader,
+    per_sample_iv_size: u8,
+) -> Result<Vec<SampleAuxInfo>> {
+    // saiz: version/flags (4), [flags & 1: aux_info_type (4) +
+    // aux_info_type_parameter (4)], default_sample_info_size (1),
+    // sample_count (4), then (if default_sample_info_size == 0) one 1-byte
+    // size per sample.
+    let mut saiz_flags_buf = [0u8; 4];
+    stream.read_at(saiz.body_start, &mut saiz_flags_buf)?;
+    let saiz_flags = u32::from_be_bytes([0, saiz_flags_buf[1], saiz_flags_buf[2], saiz_flags_buf[3]]);
+    let mut pos = saiz.body_start + 4;
+    if saiz_flags & 0x1 != 0 {
+        pos += 8;
+    }
+
+    let mut fixed = [0u8; 5];
+    stream.read_at(pos, &mut fixed)?;
+    let default_sample_info_size = fixed[0];
+    let sample_count = u32::from_be_bytes(fixed[1..5].try_into().unwrap());
+    pos += 5;
+
+    // Reject a `sample_count` the file couldn't possibly back before
+    // sizing any `Vec` off it, so a corrupt/hostile value (e.g.
+    // u32::MAX) can't force a multi-GB allocation.
+    let sizes: Vec<u8> = if default_sample_info_size != 0 {
+        let max_samples = stream.len()? / u64::from(default_sample_info_size);
+        if u64::from(sample_count) > max_samples {
+            return Err(Error::Malformed { format: "mp4", reason: "saiz sample_count exceeds the file's total size".into() });
+        }
+        vec![default_sample_info_size; sample_count as usize]
+    } else {
+        let available = saiz.end.saturating_sub(pos);
+        if u64::from(sample_count) > available {
+            return Err(Error::Malformed { format: "mp4", reason: "saiz sample_count exceeds the box's available data".into() });
+        }
+        let mut buf = vec![0u8; sample_count as usize];
+        stream.read_at(pos, &mut buf)?;
+        buf
+    };
+
+    // saio: version/flags (4), [flags & 1: aux_info_type (4) +
+    // aux_info_type_parameter (4)], entry_count (4), then entry_count
+    // offsets (4 bytes if version == 0, else 8).
+    let mut saio_version_flags = [0u8; 4];
+    stream.read_at(saio.body_start, &mut saio_version_flags)?;
+    let saio_version = saio_version_flags[0];
+    let saio_flags = u32::from_be_bytes([0, saio_version_flags[1], saio_version_flags[2], saio_version_flags[3]]);
+    let mut pos = saio.body_start + 4;
+    if saio_flags & 0x1 != 0 {
+        pos += 8;
+    }
+
+    let mut entry_count_buf = [0u8; 4];
+    stream.read_at(pos, &mut entry_count_buf)?;
+    let entry_count = u32::from_be_bytes(entry_count_buf);
+    pos += 4;
+    if entry_count != 1 {
+        return Err(Error::Unsupported("saio with more than one offset run isn't supported".into()));
+    }
+
+    let base_offset = if saio_version == 1 {
+        let mut buf = [0u8; 8];
+        stream.read_at(pos, &mut buf)?;
+        u64::from_be_bytes(buf)
+    } else {
+        let mut buf = [0u8; 4];
+        stream.read_at(pos, &mut buf)?;
+        u64::from(u32::from_be_bytes(buf))
+    };
+
+    let mut samples = Vec::with_capacity(sizes.len());
+    let mut offset = base_offset;
+    for size in sizes {
+        let has_subsamples = u64::from(size) > u64::from(per_sample_iv_size);
+        let (aux, _) = read_one_sample_aux(stream, offset, per_sample_iv_size, has_subsamples)?;
+        samples.push(aux);
+        offset += u64::from(size);
+    }
+    Ok(samples)
+}
+
+/// Reads one sample's IV (and, if `has_subsamples`, its subsample table)
+/// starting at `pos`. Returns the decoded info and the offset just past it.
+fn read_one_sample_aux<S: SeekableStream>(
+    stream: &mut S,
+    pos: u64,
+    per_sample_iv_size: u8,
+    has_subsamples: bool,
+) -> Result<(SampleAuxInfo, u64)> {
+    let mut iv = vec![0u8; per_sample_iv_size as usize];
+    stream.read_at(pos, &mut iv)?;
+    let mut pos = pos + u64::from(per_sample_iv_size);
+
+    let mut subsamples = Vec::new();
+    if has_subsamples {
+        let mut count_buf = [0u8; 2];
+        stream.read_at(pos, &mut count_buf)?;
+        let subsample_count = u16::from_be_bytes(count_buf);
+        pos += 2;
+
+        for _ in 0..subsample_count {
+            let mut entry = [0u8; 6];
+            stream.read_at(pos, &mut entry)?;
+            subsamples.push((u16::from_be_bytes(entry[0..2].try_into().unwrap()), u32::from_be_bytes(entry[2..6].try_into().unwrap())));
+            pos += 6;
+        }
+    }
+
+    Ok((SampleAuxInfo { iv, subsamples }, pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn tenc_box(per_sample_iv_size: u8, kid: [u8; 16]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.push(0); // reserved
+        body.push(1); // default_isProtected
+        body.push(per_sample_iv_size);
+        body.extend_from_slice(&kid);
+        sized_box(b"tenc", &body)
+    }
+
+    fn stsd_with_protected_entry(tenc: Vec<u8>) -> Vec<u8> {
+        let schi = sized_box(b"schi", &tenc);
+        let sinf = sized_box(b"sinf", &schi);
+        let entry = sized_box(b"encv", &sinf);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &entry].concat();
+        sized_box(b"stsd", &stsd_body)
+    }
+
+    #[test]
+    fn reads_default_kid_and_iv_size_from_tenc() {
+        let kid = [7u8; 16];
+        let data = stsd_with_protected_entry(tenc_box(8, kid));
+        let mut stream = MemorySeekableStream::new(data.clone());
+        let stsd = boxes::read_box_header(&mut stream, 0, data.len() as u64).unwrap().unwrap();
+
+        let info = read_tenc(&mut stream, &stsd).unwrap().unwrap();
+        assert!(info.is_protected);
+        assert_eq!(info.per_sample_iv_size, 8);
+        assert_eq!(info.default_kid, kid);
+    }
+
+    #[test]
+    fn returns_none_for_an_unprotected_sample_entry() {
+        let entry = sized_box(b"avc1", &[0u8; 10]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &entry].concat();
+        let data = sized_box(b"stsd", &stsd_body);
+        let mut stream = MemorySeekableStream::new(data.clone());
+        let stsd = boxes::read_box_header(&mut stream, 0, data.len() as u64).unwrap().unwrap();
+
+        assert!(read_tenc(&mut stream, &stsd).unwrap().is_none());
+    }
+
+    fn senc_box(ivs: &[&[u8]], subsamples: &[Vec<(u16, u32)>]) -> Vec<u8> {
+        let has_subsamples = subsamples.iter().any(|s| !s.is_empty());
+        let flags: u32 = if has_subsamples { 0x2 } else { 0 };
+        let mut body = flags.to_be_bytes().to_vec();
+        body.extend_from_slice(&(ivs.len() as u32).to_be_bytes());
+        for (i, iv) in ivs.iter().enumerate() {
+            body.extend_from_slice(iv);
+            if has_subsamples {
+                let ranges = &subsamples[i];
+                body.extend_from_slice(&(ranges.len() as u16).to_be_bytes());
+                for (clear, protected) in ranges {
+                    body.extend_from_slice(&clear.to_be_bytes());
+                    body.extend_from_slice(&protected.to_be_bytes());
+                }
+            }
+        }
+        sized_box(b"senc", &body)
+    }
+
+    #[test]
+    fn decodes_per_sample_ivs_and_subsamples_from_senc() {
+        let iv0 = [1u8; 8];
+        let iv1 = [2u8; 8];
+        let stbl = sized_box(b"stbl", &senc_box(&[&iv0, &iv1], &[vec![(10, 100)], vec![(20, 200), (5, 50)]]));
+        let mut stream = MemorySeekableStream::new(stbl.clone());
+        let stbl_header = boxes::read_box_header(&mut stream, 0, stbl.len() as u64).unwrap().unwrap();
+
+        let samples = read_sample_encryption(&mut stream, &stbl_header, 8).unwrap().unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].iv, iv0.to_vec());
+        assert_eq!(samples[0].subsamples, vec![(10, 100)]);
+        assert_eq!(samples[1].iv, iv1.to_vec());
+        assert_eq!(samples[1].subsamples, vec![(20, 200), (5, 50)]);
+    }
+
+    #[test]
+    fn returns_none_without_senc_or_saiz_saio() {
+        let stbl = sized_box(b"stbl", &[]);
+        let mut stream = MemorySeekableStream::new(stbl.clone());
+        let stbl_header = boxes::read_box_header(&mut stream, 0, stbl.len() as u64).unwrap().unwrap();
+
+        assert!(read_sample_encryption(&mut stream, &stbl_header, 8).unwrap().is_none());
+    }
+
+    fn saiz_box(sizes: &[u8]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags: no aux_info_type
+        body.push(if sizes.iter().all(|&s| s == sizes[0]) { sizes[0] } else { 0 });
+        body.extend_from_slice(&(sizes.len() as u32).to_be_bytes());
+        if body[4] == 0 {
+            body.extend_from_slice(sizes);
+        }
+        sized_box(b"saiz", &body)
+    }
+
+    fn saio_box(base_offset: u32) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+        body.extend_from_slice(&base_offset.to_be_bytes());
+        sized_box(b"saio", &body)
+    }
+
+    #[test]
+    fn decodes_ivs_from_saiz_saio_pointing_at_raw_aux_bytes() {
+        // Two samples, 8-byte IVs, no subsamples (aux size == iv size). The
+        // raw aux bytes live outside `stbl` (as they would in `mdat`), with
+        // `saio` pointing at their absolute offset in the stream.
+        let aux_bytes = [[9u8; 8].to_vec(), [3u8; 8].to_vec()].concat();
+        let saiz = saiz_box(&[8, 8]);
+
+        let stbl_body_len_without_saio = saiz.len() + 20; // 20 == an empty saio_box's size
+        let aux_offset = (8 + stbl_body_len_without_saio) as u32; // +8 for stbl's own header
+        let saio = saio_box(aux_offset);
+
+        let stbl_body = [saiz, saio].concat();
+        let stbl = sized_box(b"stbl", &stbl_body);
+        let mut data = stbl.clone();
+        data.extend_from_slice(&aux_bytes);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let stbl_header = boxes::read_box_header(&mut stream, 0, stbl.len() as u64).unwrap().unwrap();
+
+        let samples = read_sample_encryption(&mut stream, &stbl_header, 8).unwrap().unwrap();
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[0].iv, vec![9u8; 8]);
+        assert_eq!(samples[1].iv, vec![3u8; 8]);
+        assert!(samples[0].subsamples.is_empty());
+    }
+
+    #[test]
+    fn rejects_a_senc_sample_count_the_box_cant_possibly_hold() {
+        // flags (no subsamples), then a sample_count wildly larger than
+        // any IV data actually present -- must be rejected before a
+        // `Vec` is ever sized from it.
+        let mut body = 0u32.to_be_bytes().to_vec();
+        body.extend_from_slice(&u32::MAX.to_be_bytes());
+        let stbl = sized_box(b"stbl", &sized_box(b"senc", &body));
+        let mut stream = MemorySeekableStream::new(stbl.clone());
+        let stbl_header = boxes::read_box_header(&mut stream, 0, stbl.len() as u64).unwrap().unwrap();
+
+        assert!(matches!(
+            read_sample_encryption(&mut stream, &stbl_header, 8),
+            Err(Error::Malformed { format: "mp4", .. })
+        ));
+    }
+
+    #[test]
+    fn rejects_a_saiz_sample_count_the_file_cant_possibly_hold() {
+        let mut saiz_body = vec![0u8; 4]; // version/flags
+        saiz_body.push(8); // default_sample_info_size
+        saiz_body.extend_from_slice(&u32::MAX.to_be_bytes()); // sample_count
+        let saiz = sized_box(b"saiz", &saiz_body);
+        let saio = saio_box(0);
+
+        let stbl = sized_box(b"stbl", &[saiz, saio].concat());
+        let mut stream = MemorySeekableStream::new(stbl.clone());
+        let stbl_header = boxes::read_box_header(&mut stream, 0, stbl.len() as u64).unwrap().unwrap();
+
+        assert!(matches!(
+            read_sample_encryption(&mut stream, &stbl_header, 8),
+            Err(Error::Malformed { format: "mp4", .. })
+        ));
+    }
+}
@@ -0,0 +1,234 @@
+//! Channel layout parsing for audio sample entries, via the `chnl` box
+//! (ISO/IEC 23001-8's `ChannelLayoutBox`) and AC-3/E-AC-3's own `dac3`/`dec3`
+//! boxes, so a track can report a named layout (e.g. `"5.1"`, `"7.1"`)
+//! instead of a bare channel count.
+//!
+//! Scope: `chnl`'s pre-defined layouts (`defined_layout != 0`) are named
+//! from the common MPEG-4 audio `channelConfiguration` values 1-7; explicit
+//! per-speaker layouts (`defined_layout == 0`) and object-based layouts
+//! aren't decoded. For `dec3` (E-AC-3), only the first independent
+//! substream's layout is read.
+
+use super::boxes::{self, BoxHeader};
+use crate::bits::reader::BitReader;
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+/// The length of an `AudioSampleEntryV0`'s fixed fields, which precede any
+/// child boxes like `chnl`, `dec3`, or `dac3`. See
+/// [`super::esds::read_aac_config`] for the field breakdown.
+const AUDIO_SAMPLE_ENTRY_FIXED_FIELDS_LEN: u64 = 28;
+
+/// Names AC-3/E-AC-3's `acmod` channel configuration (ETSI TS 102 366 Table
+/// 5.7), or `None` for the reserved code `acmod == 0` (dual mono, which has
+/// no LFE and isn't named in main/LFE terms).
+fn ac3_main_channel_count(acmod: u8) -> Option<u8> {
+    match acmod {
+        1 => Some(1),
+        2 => Some(2),
+        3 => Some(3),
+        4 => Some(3),
+        5 => Some(4),
+        6 => Some(4),
+        7 => Some(5),
+        _ => None,
+    }
+}
+
+fn ac3_layout_name(acmod: u8, lfeon: bool) -> String {
+    if acmod == 0 {
+        return "1+1 (dual mono)".to_string();
+    }
+    match ac3_main_channel_count(acmod) {
+        Some(main_channels) => format!("{main_channels}.{}", u8::from(lfeon)),
+        None => format!("acmod {acmod}"),
+    }
+}
+
+/// Reads `dac3` (AC3SpecificBox): `fscod` (2), `bsid` (5), `bsmod` (3),
+/// `acmod` (3), `lfeon` (1), `bit_rate_code` (5), reserved (5).
+fn read_dac3_layout<S: SeekableStream>(stream: &mut S, dac3: &BoxHeader) -> Result<Option<String>> {
+    if dac3.body_len() < 3 {
+        return Ok(None);
+    }
+    let mut body = [0u8; 3];
+    stream.read_at(dac3.body_start, &mut body)?;
+
+    let mut bits = BitReader::new(&body);
+    let _ = bits.read_bits(2); // fscod
+    let _ = bits.read_bits(5); // bsid
+    let _ = bits.read_bits(3); // bsmod
+    let Some(acmod) = bits.read_bits(3) else { return Ok(None) };
+    let Some(lfeon) = bits.read_bits(1) else { return Ok(None) };
+
+    Ok(Some(ac3_layout_name(acmod as u8, lfeon == 1)))
+}
+
+/// Reads `dec3` (EC3SpecificBox)'s first independent substream: `data_rate`
+/// (13), `num_ind_sub` (3), then per substream `fscod` (2), `bsid` (5),
+/// reserved (1), `asvc` (1), `bsmod` (3), `acmod` (3), `lfeon` (1), ...
+/// Later substreams (if any) aren't read.
+fn read_dec3_layout<S: SeekableStream>(stream: &mut S, dec3: &BoxHeader) -> Result<Option<String>> {
+    if dec3.body_len() < 4 {
+        return Ok(None);
+    }
+    let mut body = vec![0u8; dec3.body_len() as usize];
+    stream.read_at(dec3.body_start, &mut body)?;
+
+    let mut bits = BitReader::new(&body);
+    let _ = bits.read_bits(13); // data_rate
+    let _ = bits.read_bits(3); // num_ind_sub
+    let _ = bits.read_bits(2); // fscod
+    let _ = bits.read_bits(5); // bsid
+    let _ = bits.read_bits(1); // reserved
+    let _ = bits.read_bits(1); // asvc
+    let _ = bits.read_bits(3); // bsmod
+    let Some(acmod) = bits.read_bits(3) else { return Ok(None) };
+    let Some(lfeon) = bits.read_bits(1) else { return Ok(None) };
+
+    Ok(Some(ac3_layout_name(acmod as u8, lfeon == 1)))
+}
+
+/// Names a `chnl` `defined_layout` value, for the common layouts shared with
+/// MPEG-4 audio's `channelConfiguration` table.
+fn defined_layout_name(defined_layout: u8) -> String {
+    match defined_layout {
+        1 => "mono".to_string(),
+        2 => "stereo".to_string(),
+        3 => "3.0".to_string(),
+        4 => "4.0".to_string(),
+        5 => "5.0".to_string(),
+        6 => "5.1".to_string(),
+        7 => "7.1".to_string(),
+        other => format!("channel layout {other}"),
+    }
+}
+
+/// Reads `chnl` (ChannelLayoutBox): version/flags (4), `stream_structure`
+/// (1), `defined_layout` (1), ... Only the pre-defined-layout case
+/// (`defined_layout != 0`) is named; explicit per-speaker layouts
+/// (`defined_layout == 0`) return `None`.
+fn read_chnl_layout<S: SeekableStream>(stream: &mut S, chnl: &BoxHeader) -> Result<Option<String>> {
+    if chnl.body_len() < 6 {
+        return Ok(None);
+    }
+    let mut header = [0u8; 2];
+    stream.read_at(chnl.body_start + 4, &mut header)?;
+
+    let stream_structure = header[0];
+    if stream_structure & 0x1 == 0 {
+        return Ok(None); // not channel-structured
+    }
+    let defined_layout = header[1];
+    if defined_layout == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(defined_layout_name(defined_layout)))
+}
+
+/// Reads `entry`'s channel layout from whichever of `chnl`, `dec3`, or
+/// `dac3` it has, in that preference order (`chnl` is codec-agnostic and
+/// most explicit; `dec3`/`dac3` are AC-3/E-AC-3-specific fallbacks).
+pub(crate) fn read_channel_layout<S: SeekableStream>(stream: &mut S, entry: &BoxHeader) -> Result<Option<String>> {
+    let children_start = entry.body_start + AUDIO_SAMPLE_ENTRY_FIXED_FIELDS_LEN;
+    if children_start > entry.end {
+        return Ok(None);
+    }
+
+    if let Some(chnl) = boxes::find_child(stream, children_start, entry.end, b"chnl")? {
+        if let Some(layout) = read_chnl_layout(stream, &chnl)? {
+            return Ok(Some(layout));
+        }
+    }
+    if let Some(dec3) = boxes::find_child(stream, children_start, entry.end, b"dec3")? {
+        return read_dec3_layout(stream, &dec3);
+    }
+    if let Some(dac3) = boxes::find_child(stream, children_start, entry.end, b"dac3")? {
+        return read_dac3_layout(stream, &dac3);
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn entry_with_children(entry_type: &[u8; 4], children: &[u8]) -> (MemorySeekableStream, BoxHeader) {
+        let mut entry_body = vec![0u8; AUDIO_SAMPLE_ENTRY_FIXED_FIELDS_LEN as usize];
+        entry_body.extend_from_slice(children);
+        let entry = sized_box(entry_type, &entry_body);
+
+        let mut stream = MemorySeekableStream::new(entry);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        (stream, header)
+    }
+
+    #[test]
+    fn names_51_from_a_chnl_defined_layout() {
+        let chnl_body = [0u8, 0, 0, 0, 0x1, 6]; // version/flags, channel-structured, layout 6 (5.1)
+        let chnl = sized_box(b"chnl", &chnl_body);
+        let (mut stream, entry) = entry_with_children(b"ec-3", &chnl);
+
+        assert_eq!(read_channel_layout(&mut stream, &entry).unwrap(), Some("5.1".to_string()));
+    }
+
+    #[test]
+    fn falls_back_to_dec3_when_there_is_no_chnl() {
+        // data_rate=0 (13 bits), num_ind_sub=0 (3 bits), fscod=0, bsid=16,
+        // reserved=0, asvc=0, bsmod=0, acmod=7 (3/2), lfeon=1 -> "5.1".
+        let bits = "0000000000000" // data_rate
+            .to_string()
+            + "000" // num_ind_sub
+            + "00" // fscod
+            + "10000" // bsid
+            + "0" // reserved
+            + "0" // asvc
+            + "000" // bsmod
+            + "111" // acmod
+            + "1"; // lfeon
+        let mut bytes = Vec::new();
+        for chunk in bits.as_bytes().chunks(8) {
+            let s = std::str::from_utf8(chunk).unwrap();
+            let padded = format!("{s:0<8}");
+            bytes.push(u8::from_str_radix(&padded, 2).unwrap());
+        }
+        let dec3 = sized_box(b"dec3", &bytes);
+        let (mut stream, entry) = entry_with_children(b"ec-3", &dec3);
+
+        assert_eq!(read_channel_layout(&mut stream, &entry).unwrap(), Some("5.1".to_string()));
+    }
+
+    #[test]
+    fn falls_back_to_dac3_when_there_is_no_chnl_or_dec3() {
+        // fscod=0, bsid=8, bsmod=0, acmod=2 (2/0, stereo), lfeon=0,
+        // bit_rate_code=0, reserved=0.
+        let bits = "00".to_string() + "01000" + "000" + "010" + "0" + "00000" + "00000";
+        let mut bytes = Vec::new();
+        for chunk in bits.as_bytes().chunks(8) {
+            let s = std::str::from_utf8(chunk).unwrap();
+            let padded = format!("{s:0<8}");
+            bytes.push(u8::from_str_radix(&padded, 2).unwrap());
+        }
+        let dac3 = sized_box(b"dac3", &bytes);
+        let (mut stream, entry) = entry_with_children(b"ac-3", &dac3);
+
+        assert_eq!(read_channel_layout(&mut stream, &entry).unwrap(), Some("2.0".to_string()));
+    }
+
+    #[test]
+    fn returns_none_without_any_recognized_box() {
+        let (mut stream, entry) = entry_with_children(b"mp4a", &[]);
+        assert!(read_channel_layout(&mut stream, &entry).unwrap().is_none());
+    }
+}
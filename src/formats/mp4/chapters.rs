@@ -0,0 +1,100 @@
+//! Nero-style chapter list (`udta.chpl`), used by a lot of older MP4
+//! muxers instead of a QuickTime `chap`-referenced text track (which this
+//! crate doesn't parse yet).
+
+use std::time::Duration;
+
+use super::boxes;
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+/// Reads `moov.udta.chpl`'s chapter list, if present.
+///
+/// `chpl` is a full box: version (1 byte), flags (3 bytes), then (version
+/// 1 only) 4 reserved bytes, a chapter count (1 byte), and that many
+/// `(start_time: u64, title_len: u8, title: [u8; title_len])` entries.
+/// `start_time` is in 100-nanosecond ticks.
+pub(crate) fn read_chpl_chapters<S: SeekableStream>(stream: &mut S) -> Result<Option<Vec<(Duration, String)>>> {
+    let len = stream.len()?;
+    let top_level = boxes::children(stream, 0, len)?;
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else { return Ok(None) };
+    let Some(udta) = boxes::find_child(stream, moov.body_start, moov.end, b"udta")? else { return Ok(None) };
+    let Some(chpl) = boxes::find_child(stream, udta.body_start, udta.end, b"chpl")? else { return Ok(None) };
+
+    if chpl.body_len() < 5 {
+        return Ok(Some(Vec::new()));
+    }
+    let mut version = [0u8; 1];
+    stream.read_at(chpl.body_start, &mut version)?;
+
+    let mut pos = chpl.body_start + (if version[0] == 1 { 8 } else { 4 });
+    let mut count_buf = [0u8; 1];
+    stream.read_at(pos, &mut count_buf)?;
+    pos += 1;
+
+    let mut chapters = Vec::with_capacity(count_buf[0] as usize);
+    for _ in 0..count_buf[0] {
+        let mut entry_header = [0u8; 9];
+        stream.read_at(pos, &mut entry_header)?;
+        let start_ticks = u64::from_be_bytes(entry_header[0..8].try_into().unwrap());
+        let title_len = entry_header[8] as usize;
+        pos += 9;
+
+        let mut title = vec![0u8; title_len];
+        stream.read_at(pos, &mut title)?;
+        pos += title_len as u64;
+
+        chapters.push((Duration::from_nanos(start_ticks * 100), String::from_utf8_lossy(&title).to_string()));
+    }
+
+    Ok(Some(chapters))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn chapter_entry(start_ticks: u64, title: &str) -> Vec<u8> {
+        let mut e = start_ticks.to_be_bytes().to_vec();
+        e.push(title.len() as u8);
+        e.extend_from_slice(title.as_bytes());
+        e
+    }
+
+    #[test]
+    fn reads_chapter_titles_and_start_times_from_a_version_1_chpl() {
+        let mut body = vec![1, 0, 0, 0]; // version 1, flags
+        body.extend_from_slice(&[0u8; 4]); // reserved
+        body.push(2); // chapter count
+        body.extend_from_slice(&chapter_entry(0, "Intro"));
+        body.extend_from_slice(&chapter_entry(50_000_000, "Chapter 2")); // 5 seconds
+        let chpl = sized_box(b"chpl", &body);
+        let udta = sized_box(b"udta", &chpl);
+        let moov = sized_box(b"moov", &udta);
+
+        let mut stream = MemorySeekableStream::new(moov);
+        let chapters = read_chpl_chapters(&mut stream).unwrap().unwrap();
+
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0], (Duration::ZERO, "Intro".to_string()));
+        assert_eq!(chapters[1], (Duration::from_secs(5), "Chapter 2".to_string()));
+    }
+
+    #[test]
+    fn returns_none_without_a_chpl_box() {
+        let udta = sized_box(b"udta", &[]);
+        let moov = sized_box(b"moov", &udta);
+        let mut stream = MemorySeekableStream::new(moov);
+
+        assert!(read_chpl_chapters(&mut stream).unwrap().is_none());
+    }
+}
@@ -0,0 +1,201 @@
+//! A registry mapping sample-entry fourCCs to human-readable codec labels,
+//! plus `avcC`/`hvcC` profile/level parsing to augment video codec IDs the
+//! way [`super::esds`] augments AAC's with its actual variant.
+
+use super::boxes::{self, BoxHeader};
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+/// Maps a sample-entry fourCC to a human-readable codec label. Returns
+/// `None` for anything outside the registry, so callers can fall back to
+/// the raw fourCC.
+pub(crate) fn codec_label(fourcc: &str) -> Option<&'static str> {
+    Some(match fourcc {
+        "avc1" | "avc3" => "H.264/AVC",
+        "hvc1" | "hev1" => "H.265/HEVC",
+        "av01" => "AV1",
+        "vp09" => "VP9",
+        "mp4v" => "MPEG-4 Visual",
+        "apch" => "Apple ProRes 422 HQ",
+        "apcn" => "Apple ProRes 422",
+        "apcs" => "Apple ProRes 422 LT",
+        "apco" => "Apple ProRes 422 Proxy",
+        "ap4h" => "Apple ProRes 4444",
+        "ap4x" => "Apple ProRes 4444 XQ",
+        "AVdn" => "Avid DNxHD",
+        "AVdh" => "Avid DNxHR",
+        "mjpa" | "mjpb" | "dmb1" => "Motion JPEG",
+        "mp4a" => "AAC",
+        "twos" => "PCM (big-endian)",
+        "sowt" => "PCM (little-endian)",
+        "in24" => "PCM 24-bit",
+        "in32" => "PCM 32-bit",
+        "fl32" => "PCM 32-bit float",
+        "fl64" => "PCM 64-bit float",
+        "ulaw" => "PCM (\u{03bc}-law)",
+        "alaw" => "PCM (A-law)",
+        "lpcm" => "PCM",
+        "Opus" => "Opus",
+        "fLaC" => "FLAC",
+        "ac-3" => "AC-3",
+        "ec-3" => "Enhanced AC-3",
+        _ => return None,
+    })
+}
+
+/// Maps an `avcC` `AVCProfileIndication` byte to its conventional profile
+/// name (ITU-T H.264 Annex A).
+fn avc_profile_name(profile_idc: u8) -> String {
+    match profile_idc {
+        66 => "Baseline".to_string(),
+        77 => "Main".to_string(),
+        88 => "Extended".to_string(),
+        100 => "High".to_string(),
+        110 => "High 10".to_string(),
+        122 => "High 4:2:2".to_string(),
+        244 => "High 4:4:4".to_string(),
+        other => format!("Profile {other}"),
+    }
+}
+
+/// Reads an `avcC` box's `AVCProfileIndication` (byte 1) and
+/// `AVCLevelIndication` (byte 3) -- see
+/// [`super::h264_export::read_avcc`] for the rest of the configuration
+/// record -- and formats them as e.g. `"High@4.1"`.
+pub(crate) fn read_avc_profile_level<S: SeekableStream>(stream: &mut S, avcc: &BoxHeader) -> Result<Option<String>> {
+    if avcc.body_len() < 4 {
+        return Ok(None);
+    }
+    let mut header = [0u8; 4];
+    stream.read_at(avcc.body_start, &mut header)?;
+    let profile = avc_profile_name(header[1]);
+    let level = f64::from(header[3]) / 10.0;
+    Ok(Some(format!("{profile}@{level:.1}")))
+}
+
+/// Maps an `hvcC` `general_profile_idc` value to its conventional profile
+/// name (ITU-T H.265 Annex A).
+fn hevc_profile_name(profile_idc: u8) -> String {
+    match profile_idc {
+        1 => "Main".to_string(),
+        2 => "Main 10".to_string(),
+        3 => "Main Still Picture".to_string(),
+        4 => "Range Extensions".to_string(),
+        other => format!("Profile {other}"),
+    }
+}
+
+/// Reads an `hvcC` box's `general_profile_idc` (low 5 bits of byte 1) and
+/// `general_level_idc` (byte 12, in units of 1/30th of a level -- e.g. 123
+/// is level 4.1), and formats them as e.g. `"Main 10@4.1"`.
+pub(crate) fn read_hevc_profile_level<S: SeekableStream>(stream: &mut S, hvcc: &BoxHeader) -> Result<Option<String>> {
+    if hvcc.body_len() < 13 {
+        return Ok(None);
+    }
+    let mut header = [0u8; 13];
+    stream.read_at(hvcc.body_start, &mut header)?;
+    let profile = hevc_profile_name(header[1] & 0x1F);
+    let level = f64::from(header[12]) / 30.0;
+    Ok(Some(format!("{profile}@{level:.1}")))
+}
+
+/// Resolves a video sample entry's fourCC, and -- for `avc1`/`avc3`/
+/// `hvc1`/`hev1` with an `avcC`/`hvcC` child -- its profile and level, into
+/// a single human-readable codec label (e.g. `"H.264/AVC High@4.1"`).
+/// `entry_body_start`/`entry_end` bound the sample entry's extension boxes
+/// (i.e. already past its fixed fields, where `avcC`/`hvcC` would sit).
+pub(crate) fn video_codec_label<S: SeekableStream>(
+    stream: &mut S,
+    fourcc: &str,
+    entry_body_start: u64,
+    entry_end: u64,
+) -> Result<String> {
+    let base = codec_label(fourcc).unwrap_or(fourcc).to_string();
+
+    let profile_level = match fourcc {
+        "avc1" | "avc3" => match boxes::find_child(stream, entry_body_start, entry_end, b"avcC")? {
+            Some(avcc) => read_avc_profile_level(stream, &avcc)?,
+            None => None,
+        },
+        "hvc1" | "hev1" => match boxes::find_child(stream, entry_body_start, entry_end, b"hvcC")? {
+            Some(hvcc) => read_hevc_profile_level(stream, &hvcc)?,
+            None => None,
+        },
+        _ => None,
+    };
+
+    Ok(match profile_level {
+        Some(profile_level) => format!("{base} {profile_level}"),
+        None => base,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[test]
+    fn maps_known_fourccs_to_human_readable_labels() {
+        assert_eq!(codec_label("avc1"), Some("H.264/AVC"));
+        assert_eq!(codec_label("apch"), Some("Apple ProRes 422 HQ"));
+        assert_eq!(codec_label("fLaC"), Some("FLAC"));
+        assert_eq!(codec_label("zzzz"), None);
+    }
+
+    #[test]
+    fn formats_avc_profile_and_level() {
+        let avcc = sized_box(b"avcC", &[1, 100, 0, 41, 0xFF, 0xE1, 0, 0, 0x68]);
+        let mut stream = MemorySeekableStream::new(avcc);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+
+        let profile_level = read_avc_profile_level(&mut stream, &header).unwrap().unwrap();
+        assert_eq!(profile_level, "High@4.1");
+    }
+
+    #[test]
+    fn formats_hevc_profile_and_level() {
+        let mut body = vec![0u8; 13];
+        body[1] = 2; // general_profile_idc = Main 10
+        body[12] = 123; // general_level_idc = 4.1
+        let hvcc = sized_box(b"hvcC", &body);
+        let mut stream = MemorySeekableStream::new(hvcc);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+
+        let profile_level = read_hevc_profile_level(&mut stream, &header).unwrap().unwrap();
+        assert_eq!(profile_level, "Main 10@4.1");
+    }
+
+    #[test]
+    fn resolves_full_label_with_profile_and_level() {
+        let avcc = sized_box(b"avcC", &[1, 77, 0, 30, 0xFF, 0xE1, 0, 0, 0x68]);
+        let entry = sized_box(b"avc1", &[&[0u8; 78][..], &avcc].concat());
+        let mut stream = MemorySeekableStream::new(entry);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+
+        let label = video_codec_label(&mut stream, "avc1", header.body_start + 78, header.end).unwrap();
+        assert_eq!(label, "H.264/AVC Main@3.0");
+    }
+
+    #[test]
+    fn falls_back_to_raw_fourcc_for_unrecognized_codecs() {
+        let entry = sized_box(b"zzzz", &[0u8; 78]);
+        let mut stream = MemorySeekableStream::new(entry);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+
+        let label = video_codec_label(&mut stream, "zzzz", header.body_start + 78, header.end).unwrap();
+        assert_eq!(label, "zzzz");
+    }
+}
@@ -0,0 +1,142 @@
+//! Dolby Vision configuration record parsing (`dvcC`/`dvvC`), so a pipeline
+//! can tell a Dolby Vision video track apart from its plain AVC/HEVC base
+//! layer and route it accordingly.
+//!
+//! Scope: only `dv_profile`, `dv_level`, and `dv_bl_signal_compatibility_id`
+//! are read; the RPU/EL/BL presence flags and the newer `dv_md_compression`
+//! field aren't exposed.
+
+use super::boxes;
+use crate::bits::reader::BitReader;
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+/// A Dolby Vision track's profile, level, and base-layer compatibility ID,
+/// decoded from `dvcC`/`dvvC` (Dolby Vision Streams Within the ISO Base
+/// Media File Format, section 3.2's `DOVIDecoderConfigurationRecord`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DolbyVisionConfig {
+    /// `dv_profile`, e.g. `5` (single-layer HEVC) or `8` (dual-compatible
+    /// HEVC base layer).
+    pub profile: u8,
+    /// `dv_level`, the maximum resolution/frame rate tier within the
+    /// profile.
+    pub level: u8,
+    /// `dv_bl_signal_compatibility_id`: which non-Dolby-Vision signal the
+    /// base layer is also compatible with (e.g. `1` for HDR10, `2` for
+    /// SDR).
+    pub bl_signal_compatibility_id: u8,
+}
+
+/// Decodes a `DOVIDecoderConfigurationRecord`: `dv_version_major`(8),
+/// `dv_version_minor`(8), `dv_profile`(7), `dv_level`(6),
+/// `rpu_present_flag`(1), `el_present_flag`(1), `bl_present_flag`(1),
+/// `dv_bl_signal_compatibility_id`(4), then reserved padding.
+fn parse_dvcc_config(body: &[u8]) -> Option<DolbyVisionConfig> {
+    if body.len() < 5 {
+        return None;
+    }
+    let mut bits = BitReader::new(&body[2..]);
+    let profile = bits.read_bits(7)? as u8;
+    let level = bits.read_bits(6)? as u8;
+    let _rpu_present_flag = bits.read_bits(1)?;
+    let _el_present_flag = bits.read_bits(1)?;
+    let _bl_present_flag = bits.read_bits(1)?;
+    let bl_signal_compatibility_id = bits.read_bits(4)? as u8;
+
+    Some(DolbyVisionConfig { profile, level, bl_signal_compatibility_id })
+}
+
+/// Reads a video sample entry's `dvcC` or `dvvC` box, if it has one.
+/// `entry_body_start`/`entry_end` are the sample entry's body bounds (the
+/// same visual sample entry a caller would already have located to read
+/// `avcC`/`hvcC`).
+pub(crate) fn read_dolby_vision_config<S: SeekableStream>(
+    stream: &mut S,
+    entry_body_start: u64,
+    entry_end: u64,
+) -> Result<Option<DolbyVisionConfig>> {
+    // Visual sample entry fixed fields (78 bytes) precede any extension
+    // boxes; see [`super::h264_export::read_avc_track`] for the breakdown.
+    let children_start = entry_body_start + 78;
+    if children_start > entry_end {
+        return Ok(None);
+    }
+
+    let dvcc = match boxes::find_child(stream, children_start, entry_end, b"dvcC")? {
+        Some(b) => Some(b),
+        None => boxes::find_child(stream, children_start, entry_end, b"dvvC")?,
+    };
+    let Some(dvcc) = dvcc else { return Ok(None) };
+
+    let mut body = vec![0u8; dvcc.body_len() as usize];
+    stream.read_at(dvcc.body_start, &mut body)?;
+    Ok(parse_dvcc_config(&body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn dvcc_body(profile: u8, level: u8, bl_signal_compatibility_id: u8) -> Vec<u8> {
+        // 24-bit field, MSB-first: profile(7) level(6) rpu(1) el(1) bl(1)
+        // compat(4) reserved(4).
+        let packed: u32 = (u32::from(profile) << 17)
+            | (u32::from(level) << 11)
+            | (0b111 << 8) // rpu/el/bl present flags, all set
+            | (u32::from(bl_signal_compatibility_id) << 4);
+        let mut body = vec![1, 0]; // dv_version_major, dv_version_minor
+        body.extend_from_slice(&packed.to_be_bytes()[1..]); // the 24-bit field, in 3 bytes
+        body
+    }
+
+    fn entry_with_box(box_type: &[u8; 4], box_bytes: &[u8]) -> (MemorySeekableStream, u64, u64) {
+        let mut entry_body = vec![0u8; 78];
+        entry_body.extend_from_slice(box_bytes);
+        let entry = sized_box(box_type, &entry_body);
+
+        let mut stream = MemorySeekableStream::new(entry);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        (stream, header.body_start, header.end)
+    }
+
+    #[test]
+    fn decodes_profile_level_and_compatibility_id_from_dvcc() {
+        let dvcc = sized_box(b"dvcC", &dvcc_body(8, 9, 2));
+        let (mut stream, body_start, end) = entry_with_box(b"hev1", &dvcc);
+
+        let config = read_dolby_vision_config(&mut stream, body_start, end).unwrap().unwrap();
+
+        assert_eq!(config.profile, 8);
+        assert_eq!(config.level, 9);
+        assert_eq!(config.bl_signal_compatibility_id, 2);
+    }
+
+    #[test]
+    fn also_recognizes_dvvc() {
+        let dvvc = sized_box(b"dvvC", &dvcc_body(5, 13, 1));
+        let (mut stream, body_start, end) = entry_with_box(b"dvhe", &dvvc);
+
+        let config = read_dolby_vision_config(&mut stream, body_start, end).unwrap().unwrap();
+
+        assert_eq!(config.profile, 5);
+        assert_eq!(config.level, 13);
+        assert_eq!(config.bl_signal_compatibility_id, 1);
+    }
+
+    #[test]
+    fn returns_none_without_a_dvcc_or_dvvc_box() {
+        let (mut stream, body_start, end) = entry_with_box(b"hev1", &[]);
+        assert!(read_dolby_vision_config(&mut stream, body_start, end).unwrap().is_none());
+    }
+}
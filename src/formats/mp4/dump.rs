@@ -0,0 +1,131 @@
+//! A typed, recursive view of an MP4/QuickTime box tree, for callers that
+//! want to inspect container structure themselves instead of reading
+//! [`crate::probe::probe`]'s flat top-level layout.
+
+use super::boxes::{self, BoxHeader};
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+/// One box in a recursively-walked MP4 box tree, as returned by [`dump`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoxNode {
+    /// The four-character box type, e.g. `"moov"` or `"mdat"`.
+    pub name: String,
+    /// The absolute byte offset where the box begins (its header, not its
+    /// payload).
+    pub offset: u64,
+    /// The total size of the box, header included.
+    pub size: u64,
+    /// Child boxes, for box types known to contain only other boxes.
+    /// Leaf boxes always report an empty list here, even if their payload
+    /// happens to look box-shaped (e.g. `stsd` sample entries).
+    pub children: Vec<BoxNode>,
+}
+
+/// Box types known to contain only other boxes, same rule as
+/// [`boxes::find_all_recursive`] but widened to the top-level containers
+/// that function is normally already called from inside of.
+const CONTAINER_BOX_TYPES: [[u8; 4]; 9] =
+    [*b"moov", *b"trak", *b"mdia", *b"minf", *b"stbl", *b"dinf", *b"edts", *b"mvex", *b"udta"];
+
+/// Recursively walks `stream`'s box tree from the top level, returning a
+/// typed tree instead of [`crate::probe::probe`]'s flat layout.
+pub fn dump<S: SeekableStream>(stream: &mut S) -> Result<Vec<BoxNode>> {
+    let len = stream.len()?;
+    walk(stream, 0, len)
+}
+
+fn walk<S: SeekableStream>(stream: &mut S, start: u64, end: u64) -> Result<Vec<BoxNode>> {
+    boxes::children(stream, start, end)?.into_iter().map(|header| to_node(stream, header)).collect()
+}
+
+fn to_node<S: SeekableStream>(stream: &mut S, header: BoxHeader) -> Result<BoxNode> {
+    let children = if CONTAINER_BOX_TYPES.contains(&header.box_type) {
+        walk(stream, header.body_start, header.end)?
+    } else if &header.box_type == b"meta" {
+        // `meta` is a full box (4-byte version/flags) before its children,
+        // unlike every other container box type here.
+        walk(stream, header.body_start + 4, header.end)?
+    } else {
+        Vec::new()
+    };
+
+    Ok(BoxNode { name: box_name(stream, &header)?, offset: header.start, size: header.size(), children })
+}
+
+/// `uuid` is an "extension" box type: every one has the literal four
+/// characters `"uuid"`, with the box's actual identity carried in a 16-byte
+/// UUID right after the header. Reporting that instead of the useless
+/// literal `"uuid"` is the whole point of listing one in a box dump.
+fn box_name<S: SeekableStream>(stream: &mut S, header: &BoxHeader) -> Result<String> {
+    if &header.box_type != b"uuid" {
+        return Ok(String::from_utf8_lossy(&header.box_type).to_string());
+    }
+    let uuid = boxes::read_uuid(stream, header)?;
+    Ok(format!("uuid:{}", uuid.iter().map(|b| format!("{b:02x}")).collect::<String>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[test]
+    fn dumps_a_nested_box_tree_with_leaf_payloads_left_unparsed() {
+        let stco = sized_box(b"stco", &[1, 2, 3, 4]);
+        let stbl = sized_box(b"stbl", &stco);
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &minf);
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+        let mdat = sized_box(b"mdat", &[9, 9, 9]);
+
+        let mut data = moov.clone();
+        data.extend_from_slice(&mdat);
+        let mut stream = MemorySeekableStream::new(data);
+
+        let tree = dump(&mut stream).unwrap();
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree[1], BoxNode { name: "mdat".into(), offset: moov.len() as u64, size: mdat.len() as u64, children: Vec::new() });
+
+        let trak_node = &tree[0].children[0];
+        let mdia_node = &trak_node.children[0];
+        let minf_node = &mdia_node.children[0];
+        let stbl_node = &minf_node.children[0];
+        assert_eq!(stbl_node.children, vec![BoxNode { name: "stco".into(), offset: stbl_node.offset + 8, size: stco.len() as u64, children: Vec::new() }]);
+    }
+
+    #[test]
+    fn names_a_uuid_box_by_its_extension_type_instead_of_the_literal_uuid() {
+        let uuid_body = [&[0xBEu8, 0x7A, 0xCF, 0xCB, 0x97, 0xA9, 0x42, 0xE8, 0x9C, 0x71, 0x99, 0x94, 0x91, 0xE3, 0xAF, 0xAC][..], b"payload"].concat();
+        let uuid_box = sized_box(b"uuid", &uuid_body);
+
+        let mut stream = MemorySeekableStream::new(uuid_box);
+        let tree = dump(&mut stream).unwrap();
+
+        assert_eq!(tree[0].name, "uuid:be7acfcb97a942e89c71999491e3afac");
+    }
+
+    #[test]
+    fn descends_into_meta_past_its_full_box_header() {
+        let ilst = sized_box(b"ilst", &[]);
+        let meta = sized_box(b"meta", &[&[0u8; 4][..], &ilst].concat());
+        let udta = sized_box(b"udta", &meta);
+
+        let mut stream = MemorySeekableStream::new(udta);
+        let tree = dump(&mut stream).unwrap();
+
+        let meta_node = &tree[0].children[0];
+        assert_eq!(meta_node.children, vec![BoxNode { name: "ilst".into(), offset: meta_node.offset + 8 + 4, size: ilst.len() as u64, children: Vec::new() }]);
+    }
+}
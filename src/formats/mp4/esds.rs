@@ -0,0 +1,247 @@
+//! `esds` (ES_Descriptor) parsing, for codec/sample-rate/channel info beyond
+//! what an audio sample entry's legacy fixed fields give you -- notably, the
+//! true sample rate and AAC variant (LC vs. HE-AAC/HE-AACv2) for an AAC
+//! elementary stream, read out of `esds`'s `DecoderSpecificInfo`
+//! (`AudioSpecificConfig`, ISO/IEC 14496-3).
+//!
+//! Scope: only AAC's `AudioSpecificConfig` is decoded, and only as far as
+//! its (extension) sampling frequency and channel configuration fields.
+//! SBR/PS presence is taken from the explicit object-type signaling
+//! (`audioObjectType` 5/29) only; the backward-compatible signaling some
+//! encoders use instead (a sync extension appended after the base config)
+//! isn't parsed.
+
+use super::boxes::{self, BoxHeader};
+use crate::bits::reader::BitReader;
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+/// An AAC elementary stream's codec variant, true sample rate, and channel
+/// count, decoded from `esds`'s `AudioSpecificConfig`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AacConfig {
+    /// A human-readable codec label, e.g. `"AAC-LC"` or `"HE-AAC"`.
+    pub codec: String,
+    pub sample_rate: u32,
+    pub channels: u16,
+}
+
+const SAMPLE_RATES: [u32; 13] =
+    [96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000, 11025, 8000, 7350];
+
+/// Reads a 5-bit `audioObjectType`, resolving the extended-type escape
+/// (`11111`, followed by 6 more bits added to 32).
+fn read_audio_object_type(bits: &mut BitReader) -> Option<u8> {
+    let object_type = bits.read_bits(5)? as u8;
+    if object_type == 31 {
+        Some(32 + bits.read_bits(6)? as u8)
+    } else {
+        Some(object_type)
+    }
+}
+
+/// Reads a 4-bit sampling frequency index, resolving the explicit-frequency
+/// escape (`1111`, followed by a 24-bit rate in Hz).
+fn read_sampling_frequency(bits: &mut BitReader) -> Option<u32> {
+    let index = bits.read_bits(4)? as usize;
+    if index == 0xF {
+        bits.read_bits(24)
+    } else {
+        SAMPLE_RATES.get(index).copied()
+    }
+}
+
+fn codec_name(object_type: u8) -> String {
+    match object_type {
+        1 => "AAC Main".to_string(),
+        2 => "AAC-LC".to_string(),
+        3 => "AAC-SSR".to_string(),
+        4 => "AAC-LTP".to_string(),
+        other => format!("AAC (object type {other})"),
+    }
+}
+
+/// Decodes `AudioSpecificConfig` (ISO/IEC 14496-3 1.6.2.1).
+fn parse_audio_specific_config(data: &[u8]) -> Option<AacConfig> {
+    let mut bits = BitReader::new(data);
+    let object_type = read_audio_object_type(&mut bits)?;
+    let sample_rate = read_sampling_frequency(&mut bits)?;
+    let channels = bits.read_bits(4)? as u16;
+
+    // Explicit HE-AAC (SBR) / HE-AACv2 (SBR+PS) signaling wraps a base AAC
+    // config; the extension sampling frequency is the actual output rate.
+    if object_type == 5 || object_type == 29 {
+        let extension_sample_rate = read_sampling_frequency(&mut bits)?;
+        read_audio_object_type(&mut bits)?; // base object type; unused past labeling
+        let codec = if object_type == 29 { "HE-AACv2" } else { "HE-AAC" };
+        return Some(AacConfig { codec: codec.to_string(), sample_rate: extension_sample_rate, channels });
+    }
+
+    Some(AacConfig { codec: codec_name(object_type), sample_rate, channels })
+}
+
+const ES_DESCR_TAG: u8 = 0x03;
+const DECODER_CONFIG_DESCR_TAG: u8 = 0x04;
+const DEC_SPECIFIC_INFO_TAG: u8 = 0x05;
+
+/// Reads one descriptor's tag and size, per ISO/IEC 14496-1's expandable
+/// class encoding (each size byte's top bit signals a continuation byte).
+fn read_descriptor_header(data: &[u8], pos: &mut usize) -> Option<(u8, usize)> {
+    let tag = *data.get(*pos)?;
+    *pos += 1;
+    let mut size = 0usize;
+    loop {
+        let byte = *data.get(*pos)?;
+        *pos += 1;
+        size = (size << 7) | (byte & 0x7F) as usize;
+        if byte & 0x80 == 0 {
+            break;
+        }
+    }
+    Some((tag, size))
+}
+
+fn find_decoder_specific_info_in_config(data: &[u8]) -> Option<&[u8]> {
+    // objectTypeIndication (1) + streamType/upStream/reserved (1) +
+    // bufferSizeDB (3) + maxBitrate (4) + avgBitrate (4).
+    let mut pos = 13;
+    while pos < data.len() {
+        let (tag, size) = read_descriptor_header(data, &mut pos)?;
+        let body_end = (pos + size).min(data.len());
+        if tag == DEC_SPECIFIC_INFO_TAG {
+            return Some(&data[pos..body_end]);
+        }
+        pos = body_end;
+    }
+    None
+}
+
+/// Walks `esds`'s descriptor tree (`ES_Descriptor` > `DecoderConfigDescriptor`
+/// > `DecoderSpecificInfo`) to find the raw `AudioSpecificConfig` bytes.
+fn find_decoder_specific_info(data: &[u8]) -> Option<&[u8]> {
+    let mut pos = 0;
+    let (tag, size) = read_descriptor_header(data, &mut pos)?;
+    if tag != ES_DESCR_TAG {
+        return None;
+    }
+    let es_end = (pos + size).min(data.len());
+
+    pos += 3; // ES_ID (2) + flags (1)
+    while pos < es_end {
+        let (tag, size) = read_descriptor_header(data, &mut pos)?;
+        let body_end = (pos + size).min(data.len());
+        if tag == DECODER_CONFIG_DESCR_TAG {
+            return find_decoder_specific_info_in_config(&data[pos..body_end]);
+        }
+        pos = body_end;
+    }
+    None
+}
+
+/// The length of an `AudioSampleEntryV0`'s fixed fields (reserved (6) +
+/// data_reference_index (2) + version/revision/vendor (8) + channel_count
+/// (2) + sample_size (2) + pre_defined (2) + reserved (2) + sample_rate
+/// (4)), which precede any child boxes like `esds`.
+const AUDIO_SAMPLE_ENTRY_FIXED_FIELDS_LEN: u64 = 28;
+
+/// Reads `entry`'s `esds` box, if it has one, and decodes its
+/// `AudioSpecificConfig`. Returns `None` for a non-AAC elementary stream, or
+/// one whose `DecoderSpecificInfo` this crate can't parse.
+pub(crate) fn read_aac_config<S: SeekableStream>(stream: &mut S, entry: &BoxHeader) -> Result<Option<AacConfig>> {
+    let children_start = entry.body_start + AUDIO_SAMPLE_ENTRY_FIXED_FIELDS_LEN;
+    if children_start > entry.end {
+        return Ok(None);
+    }
+    let Some(esds) = boxes::find_child(stream, children_start, entry.end, b"esds")? else { return Ok(None) };
+
+    if esds.body_len() < 4 {
+        return Ok(None);
+    }
+    let mut body = vec![0u8; esds.body_len() as usize];
+    stream.read_at(esds.body_start, &mut body)?;
+
+    Ok(find_decoder_specific_info(&body[4..]).and_then(parse_audio_specific_config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn descriptor(tag: u8, body: &[u8]) -> Vec<u8> {
+        let mut d = vec![tag, body.len() as u8];
+        d.extend_from_slice(body);
+        d
+    }
+
+    fn esds_box(audio_specific_config: &[u8]) -> Vec<u8> {
+        let dec_specific_info = descriptor(DEC_SPECIFIC_INFO_TAG, audio_specific_config);
+        let mut decoder_config_body = vec![0x40, 0x15, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        decoder_config_body.extend_from_slice(&dec_specific_info);
+        let decoder_config = descriptor(DECODER_CONFIG_DESCR_TAG, &decoder_config_body);
+
+        let mut es_body = vec![0, 1, 0]; // ES_ID, flags
+        es_body.extend_from_slice(&decoder_config);
+        let es_descriptor = descriptor(ES_DESCR_TAG, &es_body);
+
+        let mut esds_body = vec![0u8; 4]; // version/flags
+        esds_body.extend_from_slice(&es_descriptor);
+        sized_box(b"esds", &esds_body)
+    }
+
+    fn entry_with_esds(audio_specific_config: &[u8]) -> (MemorySeekableStream, BoxHeader) {
+        let mut entry_body = vec![0u8; AUDIO_SAMPLE_ENTRY_FIXED_FIELDS_LEN as usize];
+        entry_body.extend_from_slice(&esds_box(audio_specific_config));
+        let entry = sized_box(b"mp4a", &entry_body);
+
+        let mut stream = MemorySeekableStream::new(entry);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        (stream, header)
+    }
+
+    #[test]
+    fn decodes_aac_lc_sample_rate_and_channels() {
+        // audioObjectType=2 (LC), samplingFrequencyIndex=4 (44100),
+        // channelConfiguration=2 (stereo).
+        let (mut stream, entry) = entry_with_esds(&[0x12, 0x10]);
+
+        let config = read_aac_config(&mut stream, &entry).unwrap().unwrap();
+
+        assert_eq!(config.codec, "AAC-LC");
+        assert_eq!(config.sample_rate, 44100);
+        assert_eq!(config.channels, 2);
+    }
+
+    #[test]
+    fn resolves_he_aac_to_its_extension_sample_rate() {
+        // audioObjectType=5 (SBR), core samplingFrequencyIndex=6 (24000),
+        // channelConfiguration=2, extensionSamplingFrequencyIndex=3
+        // (48000), base audioObjectType=2 (LC).
+        let (mut stream, entry) = entry_with_esds(&[0x2B, 0x11, 0x88]);
+
+        let config = read_aac_config(&mut stream, &entry).unwrap().unwrap();
+
+        assert_eq!(config.codec, "HE-AAC");
+        assert_eq!(config.sample_rate, 48000);
+        assert_eq!(config.channels, 2);
+    }
+
+    #[test]
+    fn returns_none_without_an_esds_box() {
+        let entry = sized_box(b"mp4a", &[0u8; 20]);
+        let mut stream = MemorySeekableStream::new(entry);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+
+        assert!(read_aac_config(&mut stream, &header).unwrap().is_none());
+    }
+}
@@ -0,0 +1,206 @@
+//! Fragment index recovery for fragmented MP4 (`moof`/`mdat` pairs) via the
+//! `mfra`/`tfra`/`mfro` boxes some encoders append at the end of the file.
+//!
+//! A fragmented recording's `moov` carries no `stbl` sample table (samples
+//! live in per-fragment `moof`/`trun` boxes instead), so a file whose
+//! fragment index is missing or unusable can't be seeked into without
+//! scanning every `moof` from the start. `mfra` exists precisely to avoid
+//! that: it's a random-access index of fragment start times and `moof`
+//! offsets, stored at the end of the file so it can be located with a
+//! single backward seek.
+
+use super::boxes::{self, BoxHeader};
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+
+/// One fragment's presentation time and the absolute file offset of the
+/// `moof` box that contains it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FragmentEntry {
+    /// Presentation time of the fragment's first sample, in the track's
+    /// timescale (see [`crate::formats::mp4`]'s `mdhd`/`mvhd` handling for
+    /// how that timescale is normally resolved).
+    pub time: u64,
+    /// Absolute byte offset of the fragment's `moof` box.
+    pub moof_offset: u64,
+}
+
+/// A single track's fragment index, as recovered from one `tfra` box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FragmentIndex {
+    /// The track this index applies to, matching a `trak`'s `tkhd.track_ID`.
+    pub track_id: u32,
+    /// Fragment entries in file order, which `tfra` also guarantees is
+    /// increasing presentation-time order.
+    pub entries: Vec<FragmentEntry>,
+}
+
+/// Locates and parses every `tfra` box inside the file's trailing `mfra`.
+///
+/// Returns an empty `Vec`, not an error, for files with no `mfra` -- that
+/// covers both ordinary non-fragmented MP4s and fragmented ones the
+/// encoder didn't index. This never reads `moof`/`traf` contents; it only
+/// tells the caller where each fragment starts so it can read one directly.
+pub fn read_fragment_index<S: SeekableStream>(stream: &mut S) -> Result<Vec<FragmentIndex>> {
+    let len = stream.len()?;
+    if len < 16 {
+        return Ok(Vec::new());
+    }
+
+    // `mfro` is always the last box in the file: a 16-byte full box whose
+    // final 4 bytes give the size of the enclosing `mfra`, so `mfra` can be
+    // found with one backward seek instead of a scan.
+    let mut mfro_size_field = [0u8; 4];
+    stream.read_at(len - 4, &mut mfro_size_field)?;
+    let mfra_size = u64::from(u32::from_be_bytes(mfro_size_field));
+    if mfra_size < 16 || mfra_size > len {
+        return Ok(Vec::new());
+    }
+    let mfra_start = len - mfra_size;
+
+    let Some(mfra) = boxes::read_box_header(stream, mfra_start, len)? else { return Ok(Vec::new()) };
+    if &mfra.box_type != b"mfra" {
+        return Ok(Vec::new());
+    }
+
+    boxes::children(stream, mfra.body_start, mfra.end)?
+        .into_iter()
+        .filter(|b| &b.box_type == b"tfra")
+        .map(|tfra| read_tfra(stream, &tfra))
+        .collect()
+}
+
+fn read_tfra<S: SeekableStream>(stream: &mut S, tfra: &BoxHeader) -> Result<FragmentIndex> {
+    let mut head = [0u8; 16];
+    stream.read_at(tfra.body_start, &mut head)?;
+
+    let version = head[0];
+    let track_id = u32::from_be_bytes(head[4..8].try_into().unwrap());
+    let sizes = u32::from_be_bytes(head[8..12].try_into().unwrap());
+    let traf_number_size = ((sizes >> 4) & 0x3) as u64 + 1;
+    let trun_number_size = ((sizes >> 2) & 0x3) as u64 + 1;
+    let sample_number_size = (sizes & 0x3) as u64 + 1;
+    let entry_count = u32::from_be_bytes(head[12..16].try_into().unwrap());
+
+    let time_and_offset_size: u64 = if version == 1 { 16 } else { 8 };
+    let entry_size = time_and_offset_size + traf_number_size + trun_number_size + sample_number_size;
+
+    // Reject an entry_count the box couldn't possibly hold before sizing a
+    // `Vec` off it, so a corrupt/hostile entry_count near u32::MAX can't
+    // force a multi-GB allocation.
+    let available = tfra.end.saturating_sub(tfra.body_start + 16);
+    if u64::from(entry_count) > available / entry_size {
+        return Err(Error::Malformed { format: "mp4", reason: "tfra entry_count exceeds the box's available data".into() });
+    }
+
+    let mut entries = Vec::with_capacity(entry_count as usize);
+    let mut pos = tfra.body_start + 16;
+    for _ in 0..entry_count {
+        let mut buf = vec![0u8; entry_size as usize];
+        stream.read_at(pos, &mut buf)?;
+        let (time, moof_offset) = if version == 1 {
+            (u64::from_be_bytes(buf[0..8].try_into().unwrap()), u64::from_be_bytes(buf[8..16].try_into().unwrap()))
+        } else {
+            (u64::from(u32::from_be_bytes(buf[0..4].try_into().unwrap())), u64::from(u32::from_be_bytes(buf[4..8].try_into().unwrap())))
+        };
+        entries.push(FragmentEntry { time, moof_offset });
+        pos += entry_size;
+    }
+
+    Ok(FragmentIndex { track_id, entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn tfra_v0(track_id: u32, entries: &[(u32, u32)]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version 0, flags 0
+        body.extend_from_slice(&track_id.to_be_bytes());
+        body.extend_from_slice(&0u32.to_be_bytes()); // all number fields sized 1 byte
+        body.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        for &(time, moof_offset) in entries {
+            body.extend_from_slice(&time.to_be_bytes());
+            body.extend_from_slice(&moof_offset.to_be_bytes());
+            body.extend_from_slice(&[0u8, 0u8, 0u8]); // traf_number, trun_number, sample_number
+        }
+        sized_box(b"tfra", &body)
+    }
+
+    #[test]
+    fn reads_fragment_entries_from_a_trailing_mfra() {
+        let tfra = tfra_v0(1, &[(0, 1000), (500, 2000), (1000, 3000)]);
+
+        // `mfro` is itself the last child box *inside* `mfra`, not a
+        // sibling that follows it, so its size field (the size of the
+        // enclosing `mfra`, mfro included) has to be computed up front.
+        let mfro_len = 8 + 8; // header + (version/flags + size field)
+        let mfra_size = (8 + tfra.len() + mfro_len) as u32;
+
+        let mut mfro_body = vec![0u8; 4]; // version/flags
+        mfro_body.extend_from_slice(&mfra_size.to_be_bytes());
+        let mfro = sized_box(b"mfro", &mfro_body);
+
+        let mfra_body = [tfra, mfro].concat();
+        let mfra = sized_box(b"mfra", &mfra_body);
+        assert_eq!(mfra.len() as u32, mfra_size);
+
+        let mut data = vec![0u8; 32]; // stand-in for ftyp/moov/mdat/moof..mdat
+        data.extend_from_slice(&mfra);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let indexes = read_fragment_index(&mut stream).unwrap();
+
+        assert_eq!(indexes.len(), 1);
+        assert_eq!(indexes[0].track_id, 1);
+        assert_eq!(
+            indexes[0].entries,
+            vec![
+                FragmentEntry { time: 0, moof_offset: 1000 },
+                FragmentEntry { time: 500, moof_offset: 2000 },
+                FragmentEntry { time: 1000, moof_offset: 3000 },
+            ]
+        );
+    }
+
+    #[test]
+    fn returns_empty_for_a_file_with_no_mfra() {
+        let mut stream = MemorySeekableStream::new(vec![0u8; 64]);
+        assert_eq!(read_fragment_index(&mut stream).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn rejects_a_tfra_entry_count_the_box_cant_possibly_hold() {
+        let mut body = vec![0u8; 4]; // version 0, flags 0
+        body.extend_from_slice(&1u32.to_be_bytes()); // track_id
+        body.extend_from_slice(&0u32.to_be_bytes()); // all number fields sized 1 byte
+        body.extend_from_slice(&u32::MAX.to_be_bytes()); // entry_count: far more than the box holds
+        let tfra = sized_box(b"tfra", &body);
+
+        let mfro_len = 8 + 8;
+        let mfra_size = (8 + tfra.len() + mfro_len) as u32;
+        let mut mfro_body = vec![0u8; 4];
+        mfro_body.extend_from_slice(&mfra_size.to_be_bytes());
+        let mfro = sized_box(b"mfro", &mfro_body);
+
+        let mfra_body = [tfra, mfro].concat();
+        let mfra = sized_box(b"mfra", &mfra_body);
+
+        let mut data = vec![0u8; 32];
+        data.extend_from_slice(&mfra);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let err = read_fragment_index(&mut stream).unwrap_err();
+
+        assert!(matches!(err, Error::Malformed { format: "mp4", .. }));
+    }
+}
@@ -0,0 +1,244 @@
+//! Sample-level fragment walking for fragmented MP4/CMAF tracks
+//! (`moof`/`traf`/`trun`), used when a track's `stbl` carries no
+//! `stsz`/`stco` sample table because its samples live in per-fragment
+//! boxes instead -- the case [`super::subtitle_reader`] hits for
+//! fragmented `wvtt` text tracks.
+//!
+//! Unlike [`super::fragment_index`], which only recovers fragment
+//! *locations* from a trailing `mfra`, this walks every `moof` from the
+//! start of the file and decodes each matching `traf`'s samples directly.
+
+use super::boxes::{self, BoxHeader};
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+/// One sample recovered from a `moof`/`trun` pair: its location in the
+/// fragment's `mdat` and its presentation time, accumulated across
+/// fragments in the track's own timescale.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FragmentSample {
+    pub offset: u64,
+    pub size: u32,
+    pub start_ticks: u64,
+}
+
+/// Walks every top-level `moof` box, collecting `track_id`'s samples from
+/// whichever `traf` child (if any) matches it, in file order.
+///
+/// Assumes the common CMAF convention that each `trun` carries an explicit
+/// `data_offset` pointing at its samples' actual bytes, and that a
+/// fragment with no `tfdt` continues the running time total left off by
+/// the previous fragment rather than restarting at zero.
+pub(crate) fn read_fragment_samples<S: SeekableStream>(stream: &mut S, track_id: u32) -> Result<Vec<FragmentSample>> {
+    let len = stream.len()?;
+    let mut samples = Vec::new();
+    let mut running_ticks: u64 = 0;
+
+    for moof in boxes::children(stream, 0, len)?.into_iter().filter(|b| &b.box_type == b"moof") {
+        for traf in boxes::children(stream, moof.body_start, moof.end)?.into_iter().filter(|b| &b.box_type == b"traf") {
+            let Some(tfhd_box) = boxes::find_child(stream, traf.body_start, traf.end, b"tfhd")? else { continue };
+            let tfhd = read_tfhd(stream, &tfhd_box)?;
+            if tfhd.track_id != track_id {
+                continue;
+            }
+
+            if let Some(tfdt) = boxes::find_child(stream, traf.body_start, traf.end, b"tfdt")? {
+                running_ticks = read_tfdt(stream, &tfdt)?;
+            }
+
+            let Some(trun) = boxes::find_child(stream, traf.body_start, traf.end, b"trun")? else { continue };
+            running_ticks = read_trun(stream, &trun, &tfhd, moof.start, running_ticks, &mut samples)?;
+        }
+    }
+
+    Ok(samples)
+}
+
+struct TrackFragmentHeader {
+    track_id: u32,
+    default_sample_duration: u32,
+    default_sample_size: u32,
+}
+
+/// `tfhd` (TrackFragmentHeaderBox): version/flags, `track_ID`, then a
+/// handful of optional fields gated by `flags`, in a fixed order. Only the
+/// fields fragmented sample recovery needs are read here.
+fn read_tfhd<S: SeekableStream>(stream: &mut S, tfhd: &BoxHeader) -> Result<TrackFragmentHeader> {
+    let mut head = [0u8; 8];
+    stream.read_at(tfhd.body_start, &mut head)?;
+    let flags = u32::from_be_bytes([0, head[1], head[2], head[3]]);
+    let track_id = u32::from_be_bytes(head[4..8].try_into().unwrap());
+
+    let mut pos = tfhd.body_start + 8;
+    if flags & 0x01 != 0 {
+        pos += 8; // base-data-offset-present
+    }
+    if flags & 0x02 != 0 {
+        pos += 4; // sample-description-index-present
+    }
+
+    let mut default_sample_duration = 0u32;
+    if flags & 0x08 != 0 {
+        let mut buf = [0u8; 4];
+        stream.read_at(pos, &mut buf)?;
+        default_sample_duration = u32::from_be_bytes(buf);
+        pos += 4;
+    }
+
+    let mut default_sample_size = 0u32;
+    if flags & 0x10 != 0 {
+        let mut buf = [0u8; 4];
+        stream.read_at(pos, &mut buf)?;
+        default_sample_size = u32::from_be_bytes(buf);
+    }
+
+    Ok(TrackFragmentHeader { track_id, default_sample_duration, default_sample_size })
+}
+
+/// `tfdt` (TrackFragmentBaseMediaDecodeTimeBox): version/flags then a
+/// 32- or 64-bit `baseMediaDecodeTime`.
+fn read_tfdt<S: SeekableStream>(stream: &mut S, tfdt: &BoxHeader) -> Result<u64> {
+    let mut version = [0u8; 1];
+    stream.read_at(tfdt.body_start, &mut version)?;
+
+    if version[0] == 1 {
+        let mut buf = [0u8; 8];
+        stream.read_at(tfdt.body_start + 4, &mut buf)?;
+        Ok(u64::from_be_bytes(buf))
+    } else {
+        let mut buf = [0u8; 4];
+        stream.read_at(tfdt.body_start + 4, &mut buf)?;
+        Ok(u64::from(u32::from_be_bytes(buf)))
+    }
+}
+
+/// `trun` (TrackRunBox): version/flags, `sample_count`, an optional signed
+/// `data_offset` (relative to `moof_start`, per `default-base-is-moof`),
+/// an optional first-sample-flags field (skipped; not needed here), then
+/// `sample_count` per-sample records whose present fields are selected by
+/// `flags`. Returns the running tick total after this run's samples.
+fn read_trun<S: SeekableStream>(
+    stream: &mut S,
+    trun: &BoxHeader,
+    tfhd: &TrackFragmentHeader,
+    moof_start: u64,
+    mut running_ticks: u64,
+    samples: &mut Vec<FragmentSample>,
+) -> Result<u64> {
+    let mut head = [0u8; 8];
+    stream.read_at(trun.body_start, &mut head)?;
+    let flags = u32::from_be_bytes([0, head[1], head[2], head[3]]);
+    let sample_count = u32::from_be_bytes(head[4..8].try_into().unwrap());
+
+    let mut pos = trun.body_start + 8;
+    let mut offset = moof_start;
+    if flags & 0x01 != 0 {
+        let mut buf = [0u8; 4];
+        stream.read_at(pos, &mut buf)?;
+        offset = (moof_start as i64 + i64::from(i32::from_be_bytes(buf))) as u64;
+        pos += 4;
+    }
+    if flags & 0x04 != 0 {
+        pos += 4; // first-sample-flags-present
+    }
+
+    for _ in 0..sample_count {
+        let duration = if flags & 0x100 != 0 {
+            let mut buf = [0u8; 4];
+            stream.read_at(pos, &mut buf)?;
+            pos += 4;
+            u32::from_be_bytes(buf)
+        } else {
+            tfhd.default_sample_duration
+        };
+
+        let size = if flags & 0x200 != 0 {
+            let mut buf = [0u8; 4];
+            stream.read_at(pos, &mut buf)?;
+            pos += 4;
+            u32::from_be_bytes(buf)
+        } else {
+            tfhd.default_sample_size
+        };
+
+        if flags & 0x400 != 0 {
+            pos += 4; // sample-flags-present
+        }
+        if flags & 0x800 != 0 {
+            pos += 4; // sample-composition-time-offsets-present
+        }
+
+        samples.push(FragmentSample { offset, size, start_ticks: running_ticks });
+        offset += u64::from(size);
+        running_ticks += u64::from(duration);
+    }
+
+    Ok(running_ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn tfhd(track_id: u32) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags: no optional fields
+        body.extend_from_slice(&track_id.to_be_bytes());
+        sized_box(b"tfhd", &body)
+    }
+
+    fn tfdt(base_media_decode_time: u32) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version 0
+        body.extend_from_slice(&base_media_decode_time.to_be_bytes());
+        sized_box(b"tfdt", &body)
+    }
+
+    fn trun(data_offset: i32, sample_sizes: &[u32]) -> Vec<u8> {
+        let flags = 0x01u32 | 0x200; // data-offset-present | sample-size-present
+        let mut body = flags.to_be_bytes().to_vec();
+        body.extend_from_slice(&(sample_sizes.len() as u32).to_be_bytes());
+        body.extend_from_slice(&data_offset.to_be_bytes());
+        for &size in sample_sizes {
+            body.extend_from_slice(&size.to_be_bytes());
+        }
+        sized_box(b"trun", &body)
+    }
+
+    fn moof(track_id: u32, base_media_decode_time: u32, data_offset: i32, sample_sizes: &[u32]) -> Vec<u8> {
+        let traf = sized_box(b"traf", &[tfhd(track_id), tfdt(base_media_decode_time), trun(data_offset, sample_sizes)].concat());
+        sized_box(b"moof", &traf)
+    }
+
+    #[test]
+    fn reads_samples_across_two_fragments() {
+        let moof1 = moof(7, 0, 100, &[3, 4]);
+        let mdat1 = sized_box(b"mdat", &[0u8; 7]);
+        let moof2 = moof(7, 1000, 0, &[5]);
+        let mdat2 = sized_box(b"mdat", &[0u8; 5]);
+
+        let data = [moof1, mdat1, moof2, mdat2].concat();
+        let mut stream = MemorySeekableStream::new(data);
+        let samples = read_fragment_samples(&mut stream, 7).unwrap();
+
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0], FragmentSample { offset: 100, size: 3, start_ticks: 0 });
+        assert_eq!(samples[1], FragmentSample { offset: 103, size: 4, start_ticks: 0 });
+        assert_eq!(samples[2].size, 5);
+        assert_eq!(samples[2].start_ticks, 1000);
+    }
+
+    #[test]
+    fn ignores_fragments_for_other_tracks() {
+        let data = moof(9, 0, 0, &[3]);
+        let mut stream = MemorySeekableStream::new(data);
+        assert_eq!(read_fragment_samples(&mut stream, 7).unwrap(), Vec::new());
+    }
+}
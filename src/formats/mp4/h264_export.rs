@@ -0,0 +1,321 @@
+//! Exports an MP4/QuickTime file's H.264 video track as an Annex-B `.h264`
+//! elementary stream, for feeding into tools (`ffplay`, reference decoders,
+//! etc.) that expect start-code-delimited NAL units rather than `avcC`'s
+//! length-prefixed ones.
+//!
+//! Samples are read and converted one at a time rather than buffering the
+//! whole `mdat`: memory use is bounded by the largest single sample, not
+//! the file size.
+
+use std::io::Write;
+
+use super::boxes::{self, BoxHeader};
+use super::sample_table::{ParseMode, SampleTable};
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+
+const START_CODE: [u8; 4] = [0, 0, 0, 1];
+
+/// The parsed `avcC` decoder configuration record: how many bytes each
+/// sample's NAL length prefixes use, and the SPS/PPS NAL units to prepend
+/// ahead of the track's samples.
+struct AvcDecoderConfig {
+    length_size: u8,
+    sps: Vec<Vec<u8>>,
+    pps: Vec<Vec<u8>>,
+}
+
+/// Writes the first H.264 (`avc1`/`avc3`) video track's samples, in decode
+/// order, to `writer` as an Annex-B elementary stream: the `avcC` box's
+/// SPS/PPS NAL units first (each with its own start code), then every
+/// sample with its `avcC`-style length prefixes replaced by start codes.
+///
+/// Fails with [`Error::Unsupported`] if the file has no video track using
+/// `avcC` (e.g. an HEVC track uses `hvcC` instead, which this doesn't read).
+pub fn export_annexb_h264<S: SeekableStream, W: Write>(stream: &mut S, writer: &mut W) -> Result<()> {
+    let len = stream.len()?;
+    let top_level = boxes::children(stream, 0, len)?;
+    let moov = top_level
+        .iter()
+        .find(|b| &b.box_type == b"moov")
+        .ok_or_else(|| Error::Unsupported("no moov box was found".into()))?;
+
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        let Some((config, stbl)) = read_avc_track(stream, &trak)? else { continue };
+        let Some(table) = SampleTable::from_stbl(stream, &stbl, ParseMode::Strict)? else { continue };
+
+        for nal in config.sps.iter().chain(&config.pps) {
+            write_annexb_nal(writer, nal)?;
+        }
+
+        for index in 0..table.sample_count() {
+            let size = table.size(stream, index)?;
+            let offset = table.offset(stream, index)?;
+            let mut sample = vec![0u8; size as usize];
+            stream.read_at(offset, &mut sample)?;
+            write_length_prefixed_nals_as_annexb(writer, &sample, config.length_size)?;
+        }
+
+        return Ok(());
+    }
+
+    Err(Error::Unsupported("no H.264 (avcC) video track was found".into()))
+}
+
+fn write_annexb_nal<W: Write>(writer: &mut W, nal: &[u8]) -> Result<()> {
+    writer.write_all(&START_CODE).map_err(Error::Io)?;
+    writer.write_all(nal).map_err(Error::Io)
+}
+
+/// Splits `sample` into its `length_size`-byte-length-prefixed NAL units
+/// and writes each as Annex-B (start code, then the NAL bytes unchanged).
+fn write_length_prefixed_nals_as_annexb<W: Write>(writer: &mut W, sample: &[u8], length_size: u8) -> Result<()> {
+    let length_size = usize::from(length_size);
+    let mut offset = 0usize;
+
+    while offset + length_size <= sample.len() {
+        let mut length_bytes = [0u8; 4];
+        length_bytes[4 - length_size..].copy_from_slice(&sample[offset..offset + length_size]);
+        let nal_len = u32::from_be_bytes(length_bytes) as usize;
+        offset += length_size;
+
+        if offset + nal_len > sample.len() {
+            return Err(Error::Malformed { format: "mp4", reason: "NAL length prefix runs past the sample's end".into() });
+        }
+        write_annexb_nal(writer, &sample[offset..offset + nal_len])?;
+        offset += nal_len;
+    }
+
+    Ok(())
+}
+
+/// Returns the `avcC` decoder config and `stbl` box of `trak`, if it's an
+/// H.264 (`avc1`/`avc3`) video track.
+fn read_avc_track<S: SeekableStream>(stream: &mut S, trak: &BoxHeader) -> Result<Option<(AvcDecoderConfig, BoxHeader)>> {
+    let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { return Ok(None) };
+    let Some(hdlr) = boxes::find_child(stream, mdia.body_start, mdia.end, b"hdlr")? else { return Ok(None) };
+
+    let mut handler_type = [0u8; 4];
+    stream.read_at(hdlr.body_start + 8, &mut handler_type)?;
+    if &handler_type != b"vide" {
+        return Ok(None);
+    }
+
+    let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else { return Ok(None) };
+    let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else { return Ok(None) };
+    let Some(stsd) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsd")? else { return Ok(None) };
+
+    // stsd is a full box: version/flags (4 bytes), entry count (4 bytes),
+    // then the first sample entry.
+    let first_entry_start = stsd.body_start + 8;
+    if first_entry_start + 8 > stsd.end {
+        return Ok(None);
+    }
+
+    let mut entry_header = [0u8; 8];
+    stream.read_at(first_entry_start, &mut entry_header)?;
+    let entry_size = u64::from(u32::from_be_bytes(entry_header[0..4].try_into().unwrap()));
+    let entry_type: [u8; 4] = entry_header[4..8].try_into().unwrap();
+    if &entry_type != b"avc1" && &entry_type != b"avc3" {
+        return Ok(None);
+    }
+
+    let entry_body_start = first_entry_start + 8;
+    let entry_end = first_entry_start + entry_size;
+
+    // Visual sample entry fixed fields (78 bytes) precede any extension
+    // boxes like avcC: reserved(6) + data_reference_index(2) +
+    // pre_defined/reserved(16) + width(2) + height(2) + horizresolution(4) +
+    // vertresolution(4) + reserved(4) + frame_count(2) + compressorname(32)
+    // + depth(2) + pre_defined(2).
+    let Some(avcc) = boxes::find_child(stream, entry_body_start + 78, entry_end, b"avcC")? else { return Ok(None) };
+
+    Ok(Some((read_avcc(stream, &avcc)?, stbl)))
+}
+
+/// Reads an `avcC` box's configuration record: `configurationVersion`(1) +
+/// `AVCProfileIndication`(1) + `profile_compatibility`(1) +
+/// `AVCLevelIndication`(1) + `reserved(6) lengthSizeMinusOne(2)`(1), then a
+/// `reserved(3) numOfSequenceParameterSets(5)`(1) byte followed by that many
+/// `(length(2), nal)` SPS entries, then a `numOfPictureParameterSets`(1)
+/// byte followed by that many `(length(2), nal)` PPS entries.
+fn read_avcc<S: SeekableStream>(stream: &mut S, avcc: &BoxHeader) -> Result<AvcDecoderConfig> {
+    let mut header = [0u8; 6];
+    stream.read_at(avcc.body_start, &mut header)?;
+    let length_size = (header[4] & 0x03) + 1;
+    let num_sps = header[5] & 0x1F;
+
+    let mut offset = avcc.body_start + 6;
+    let mut sps = Vec::with_capacity(usize::from(num_sps));
+    for _ in 0..num_sps {
+        let (nal, next_offset) = read_length_prefixed_nal(stream, offset)?;
+        sps.push(nal);
+        offset = next_offset;
+    }
+
+    let mut num_pps_buf = [0u8; 1];
+    stream.read_at(offset, &mut num_pps_buf)?;
+    offset += 1;
+
+    let mut pps = Vec::with_capacity(usize::from(num_pps_buf[0]));
+    for _ in 0..num_pps_buf[0] {
+        let (nal, next_offset) = read_length_prefixed_nal(stream, offset)?;
+        pps.push(nal);
+        offset = next_offset;
+    }
+
+    Ok(AvcDecoderConfig { length_size, sps, pps })
+}
+
+/// Reads one `(u16 length, nal bytes)` entry at `offset`, returning the NAL
+/// bytes and the offset just past them.
+fn read_length_prefixed_nal<S: SeekableStream>(stream: &mut S, offset: u64) -> Result<(Vec<u8>, u64)> {
+    let mut length_buf = [0u8; 2];
+    stream.read_at(offset, &mut length_buf)?;
+    let length = u16::from_be_bytes(length_buf);
+
+    let mut nal = vec![0u8; usize::from(length)];
+    stream.read_at(offset + 2, &mut nal)?;
+    Ok((nal, offset + 2 + u64::from(length)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+    use std::io;
+
+    #[derive(Default)]
+    struct VecWriter(Vec<u8>);
+
+    impl Write for VecWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn build_avcc(sps: &[u8], pps: &[u8]) -> Vec<u8> {
+        let mut body = vec![
+            1,    // configurationVersion
+            0x64, // AVCProfileIndication
+            0,    // profile_compatibility
+            0x1F, // AVCLevelIndication
+            0xFC | 0x03, // reserved(6) + lengthSizeMinusOne(2) == 4-byte lengths
+            0xE0 | 0x01, // reserved(3) + numOfSequenceParameterSets(5)
+        ];
+        body.extend_from_slice(&(sps.len() as u16).to_be_bytes());
+        body.extend_from_slice(sps);
+        body.push(1); // numOfPictureParameterSets
+        body.extend_from_slice(&(pps.len() as u16).to_be_bytes());
+        body.extend_from_slice(pps);
+        sized_box(b"avcC", &body)
+    }
+
+    fn sample_mp4(samples: &[&[u8]]) -> Vec<u8> {
+        let sps = [0x67, 0x42, 0x00, 0x1F];
+        let pps = [0x68, 0xCE, 0x3C, 0x80];
+        let avcc = build_avcc(&sps, &pps);
+
+        let mut visual_fields = [0u8; 78];
+        visual_fields[16..18].copy_from_slice(&640u16.to_be_bytes()); // width
+        visual_fields[18..20].copy_from_slice(&480u16.to_be_bytes()); // height
+        let avc1 = sized_box(b"avc1", &[&visual_fields[..], &avcc].concat());
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &avc1].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes()); // sample_size: per-sample table follows
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes());
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stts_body.extend_from_slice(&1000u32.to_be_bytes());
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_offset_placeholder = 0u32; // patched below
+        let mut stco_body = vec![0u8; 4];
+        stco_body.extend_from_slice(&1u32.to_be_bytes());
+        stco_body.extend_from_slice(&mdat_offset_placeholder.to_be_bytes());
+        let stco = sized_box(b"stco", &stco_body);
+
+        let stbl = sized_box(b"stbl", &[stsd, stts, stsc, stsz, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        let mut file = moov;
+        let mdat_start = (file.len() + 8) as u32;
+        // Patch the stco chunk offset now that we know where mdat's body starts.
+        let stco_offset_pos = file.len() - 4; // last 4 bytes of `moov` are the stco entry
+        file[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        file.extend_from_slice(&mdat);
+        file
+    }
+
+    #[test]
+    fn exports_sps_pps_and_samples_as_annexb() {
+        let sample0 = [&4u32.to_be_bytes()[..], &[0x65, 0xAA, 0xBB, 0xCC]].concat();
+        let sample1 = [&3u32.to_be_bytes()[..], &[0x41, 0x01, 0x02]].concat();
+        let mut stream = MemorySeekableStream::new(sample_mp4(&[&sample0, &sample1]));
+
+        let mut out = VecWriter::default();
+        export_annexb_h264(&mut stream, &mut out).unwrap();
+
+        let mut expected = Vec::new();
+        expected.extend_from_slice(&START_CODE);
+        expected.extend_from_slice(&[0x67, 0x42, 0x00, 0x1F]); // sps
+        expected.extend_from_slice(&START_CODE);
+        expected.extend_from_slice(&[0x68, 0xCE, 0x3C, 0x80]); // pps
+        expected.extend_from_slice(&START_CODE);
+        expected.extend_from_slice(&[0x65, 0xAA, 0xBB, 0xCC]); // sample0's one NAL
+        expected.extend_from_slice(&START_CODE);
+        expected.extend_from_slice(&[0x41, 0x01, 0x02]); // sample1's one NAL
+
+        assert_eq!(out.0, expected);
+    }
+
+    #[test]
+    fn reports_unsupported_without_an_avcc_video_track() {
+        let hdlr_body = [&[0u8; 8][..], b"soun", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &hdlr);
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        let mut stream = MemorySeekableStream::new(moov);
+        let mut out = VecWriter::default();
+        assert!(export_annexb_h264(&mut stream, &mut out).is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//! HEIF/HEIC/AVIF metadata parsing.
+//!
+//! HEIF reuses the ISO-BMFF box framing from [`super`], but describes its
+//! content as a flat list of items (`meta`/`iinf`/`iloc`) rather than
+//! time-based tracks, so it gets its own top-level reader instead of
+//! reusing [`super::extract_metadata`].
+
+use super::boxes::{self, BoxHeader};
+use crate::error::Result;
+use crate::metadata::{ImageItem, Metadata};
+use crate::stream::SeekableStream;
+
+pub(crate) fn extract_metadata<S: SeekableStream>(stream: &mut S) -> Result<Metadata> {
+    let len = stream.len()?;
+    let mut metadata = Metadata::default();
+
+    let top_level = boxes::children(stream, 0, len)?;
+    (metadata.major_brand, metadata.compatible_brands) = super::read_ftyp(stream, &top_level)?;
+
+    let Some(meta) = top_level.iter().find(|b| &b.box_type == b"meta") else {
+        return Ok(metadata);
+    };
+    // `meta` is a full box (4-byte version/flags) before its children.
+    let children_start = meta.body_start + 4;
+
+    let primary_item_id = match boxes::find_child(stream, children_start, meta.end, b"pitm")? {
+        Some(pitm) => Some(read_pitm(stream, &pitm)?),
+        None => None,
+    };
+
+    if let Some(iinf) = boxes::find_child(stream, children_start, meta.end, b"iinf")? {
+        metadata.image_items = read_iinf(stream, &iinf, primary_item_id)?;
+    }
+
+    if let Some(iprp) = boxes::find_child(stream, children_start, meta.end, b"iprp")? {
+        if let Some((width, height)) = read_primary_dimensions(stream, &iprp)? {
+            metadata.width = Some(width);
+            metadata.height = Some(height);
+        }
+    }
+
+    Ok(metadata)
+}
+
+/// `pitm` is a full box followed by a 16- or 32-bit item ID, depending on
+/// version.
+fn read_pitm<S: SeekableStream>(stream: &mut S, pitm: &BoxHeader) -> Result<u32> {
+    let mut version = [0u8; 1];
+    stream.read_at(pitm.body_start, &mut version)?;
+
+    if version[0] == 0 {
+        let mut id = [0u8; 2];
+        stream.read_at(pitm.body_start + 4, &mut id)?;
+        Ok(u32::from(u16::from_be_bytes(id)))
+    } else {
+        let mut id = [0u8; 4];
+        stream.read_at(pitm.body_start + 4, &mut id)?;
+        Ok(u32::from_be_bytes(id))
+    }
+}
+
+/// `iinf` is a full box, an entry count, and then one `infe` child box per
+/// item.
+fn read_iinf<S: SeekableStream>(
+    stream: &mut S,
+    iinf: &BoxHeader,
+    primary_item_id: Option<u32>,
+) -> Result<Vec<ImageItem>> {
+    let mut version = [0u8; 1];
+    stream.read_at(iinf.body_start, &mut version)?;
+    let count_len: u64 = if version[0] == 0 { 2 } else { 4 };
+    let entries_start = iinf.body_start + 4 + count_len;
+
+    let mut items = Vec::new();
+    for infe in boxes::children(stream, entries_start, iinf.end)? {
+        if &infe.box_type != b"infe" {
+            continue;
+        }
+        if let Some(item) = read_infe(stream, &infe, primary_item_id)? {
+            items.push(item);
+        }
+    }
+    Ok(items)
+}
+
+/// `infe` (version >= 2) is a full box, then a 16- or 32-bit item ID, a
+/// 16-bit protection index, and a four-character item type.
+fn read_infe<S: SeekableStream>(
+    stream: &mut S,
+    infe: &BoxHeader,
+    primary_item_id: Option<u32>,
+) -> Result<Option<ImageItem>> {
+    let mut version = [0u8; 1];
+    stream.read_at(infe.body_start, &mut version)?;
+    if version[0] < 2 {
+        // Versions 0/1 predate the simple `item_type` fourcc field.
+        return Ok(None);
+    }
+
+    let id_len: u64 = if version[0] == 2 { 2 } else { 4 };
+    let mut id_bytes = vec![0u8; id_len as usize];
+    stream.read_at(infe.body_start + 4, &mut id_bytes)?;
+    let id = if id_len == 2 {
+        u32::from(u16::from_be_bytes(id_bytes[..].try_into().unwrap()))
+    } else {
+        u32::from_be_bytes(id_bytes[..].try_into().unwrap())
+    };
+
+    let item_type_offset = infe.body_start + 4 + id_len + 2; // + protection index
+    let mut item_type = [0u8; 4];
+    stream.read_at(item_type_offset, &mut item_type)?;
+
+    Ok(Some(ImageItem {
+        id,
+        item_type: String::from_utf8_lossy(&item_type).to_string(),
+        primary: primary_item_id == Some(id),
+    }))
+}
+
+/// Returns the dimensions from the first `ispe` (image spatial extents)
+/// property found under `iprp/ipco`. Real files associate properties with
+/// specific items via `ipma`, but single-image HEIF/AVIF files (by far the
+/// common case) have exactly one `ispe`, describing the primary item.
+fn read_primary_dimensions<S: SeekableStream>(stream: &mut S, iprp: &BoxHeader) -> Result<Option<(u32, u32)>> {
+    let Some(ipco) = boxes::find_child(stream, iprp.body_start, iprp.end, b"ipco")? else { return Ok(None) };
+    let Some(ispe) = boxes::find_child(stream, ipco.body_start, ipco.end, b"ispe")? else { return Ok(None) };
+
+    let mut body = [0u8; 12];
+    stream.read_at(ispe.body_start, &mut body)?;
+    let width = u32::from_be_bytes(body[4..8].try_into().unwrap());
+    let height = u32::from_be_bytes(body[8..12].try_into().unwrap());
+    Ok(Some((width, height)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn infe_box(id: u16, item_type: &[u8; 4]) -> Vec<u8> {
+        let mut body = vec![2, 0, 0, 0]; // version 2, flags 0
+        body.extend_from_slice(&id.to_be_bytes());
+        body.extend_from_slice(&0u16.to_be_bytes()); // protection index
+        body.extend_from_slice(item_type);
+        sized_box(b"infe", &body)
+    }
+
+    fn sample_heic() -> Vec<u8> {
+        let ftyp = sized_box(b"ftyp", b"heicheic");
+
+        let pitm = sized_box(b"pitm", &[0, 0, 0, 0, 0, 1]); // version 0, item_id = 1
+
+        let iinf_body = [&[0u8, 0, 0, 0][..], &1u16.to_be_bytes(), &infe_box(1, b"hvc1")].concat();
+        let iinf = sized_box(b"iinf", &iinf_body);
+
+        let ispe = sized_box(b"ispe", &[&[0u8; 4][..], &1920u32.to_be_bytes(), &1080u32.to_be_bytes()].concat());
+        let ipco = sized_box(b"ipco", &ispe);
+        let iprp = sized_box(b"iprp", &ipco);
+
+        let meta_body = [&[0u8; 4][..], &pitm, &iinf, &iprp].concat();
+        let meta = sized_box(b"meta", &meta_body);
+
+        [ftyp, meta].concat()
+    }
+
+    #[test]
+    fn reads_primary_item_and_dimensions() {
+        let mut stream = MemorySeekableStream::new(sample_heic());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.major_brand.as_deref(), Some("heic"));
+        assert_eq!(metadata.width, Some(1920));
+        assert_eq!(metadata.height, Some(1080));
+        assert_eq!(
+            metadata.image_items,
+            vec![ImageItem { id: 1, item_type: "hvc1".to_string(), primary: true }]
+        );
+    }
+}
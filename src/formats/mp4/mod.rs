@@ -0,0 +1,1069 @@
+//! ISO base media file format parsing (MP4, M4A, and QuickTime-derived
+//! containers).
+
+pub mod avc;
+mod boxes;
+mod cenc;
+mod channel_layout;
+pub(crate) mod chapters;
+mod codecs;
+mod dolby_vision;
+pub mod dump;
+mod esds;
+pub(crate) mod fragment_reader;
+pub mod fragment_index;
+pub mod h264_export;
+pub(crate) mod heif;
+mod optimize;
+mod sample_table;
+mod spatial_audio;
+mod spherical;
+pub(crate) mod subtitle_reader;
+mod subtitle_writer;
+mod tags;
+pub(crate) mod timed_metadata_reader;
+mod track_reader;
+pub mod validate;
+
+pub use dolby_vision::DolbyVisionConfig;
+pub use fragment_index::{read_fragment_index, FragmentEntry, FragmentIndex};
+pub use h264_export::export_annexb_h264;
+pub use optimize::optimize_for_streaming;
+pub use sample_table::{ParseMode, SampleTable};
+pub use subtitle_writer::{embed_subtitle_track, SubtitleCodec};
+pub use track_reader::{BitrateInfo, SampleEncryption, SampleInfo, TrackReader};
+pub use validate::{validate, Severity, ValidationIssue, ValidationReport};
+
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::metadata::Metadata;
+use crate::probe::BoxInfo;
+use crate::stream::SeekableStream;
+
+struct AudioTrackInfo {
+    sample_rate: u32,
+    channels: u16,
+    codec: Option<String>,
+    /// Start of the sample entry's extension boxes (already past its fixed
+    /// fields), for finding `SA3D` for ambisonic metadata.
+    extensions_start: u64,
+    extensions_end: u64,
+}
+
+struct VideoTrackInfo {
+    width: u32,
+    height: u32,
+    codec: String,
+    stbl: boxes::BoxHeader,
+    /// The sample entry's `depth` field (bits per pixel; `0x0018` for
+    /// standard video, though professional codecs like ProRes/DNx often
+    /// leave it at the RGB-24 default rather than reporting their actual
+    /// sample bit depth here). `None` if the sample entry is too short to
+    /// have reached this field.
+    bit_depth: Option<u16>,
+    /// The sample entry's `compressorname` Pascal string, when non-empty
+    /// -- for professional QuickTime codecs (ProRes, DNx) this is
+    /// typically the vendor/variant name a tool like Avid wrote (e.g.
+    /// `"AVdn"`), not present on most consumer-codec samples.
+    compressor_name: Option<String>,
+    /// Start of the sample entry's extension boxes (already past its
+    /// fixed fields), for finding `avcC`/`hvcC` to resolve profile/level.
+    extensions_start: u64,
+    extensions_end: u64,
+}
+
+/// Reads the top-level box layout (`ftyp`, `free`, `moov`, `mdat`, ...)
+/// using only box headers, never their payloads.
+pub(crate) fn top_level_layout<S: SeekableStream>(stream: &mut S) -> Result<Vec<BoxInfo>> {
+    let len = stream.len()?;
+    boxes::children(stream, 0, len)?
+        .into_iter()
+        .map(|b| {
+            Ok(BoxInfo {
+                name: String::from_utf8_lossy(&b.box_type).to_string(),
+                offset: b.start,
+                size: b.size(),
+            })
+        })
+        .collect()
+}
+
+/// Like [`top_level_layout`], but tolerates a truncated final box (e.g. an
+/// interrupted upload that cut off the tail of `mdat`) by clipping it to the
+/// stream's actual length instead of failing with [`crate::Error::Malformed`],
+/// and reports whether it had to. `moov`-before-`mdat` order (and so
+/// faststart-ness) is still determined correctly, since only the last box's
+/// reported size is affected.
+pub(crate) fn top_level_layout_tolerant<S: SeekableStream>(stream: &mut S) -> Result<(Vec<BoxInfo>, bool)> {
+    let len = stream.len()?;
+    let (children, truncated) = boxes::children_tolerant(stream, 0, len)?;
+    let boxes = children
+        .into_iter()
+        .map(|b| BoxInfo { name: String::from_utf8_lossy(&b.box_type).to_string(), offset: b.start, size: b.size() })
+        .collect();
+    Ok((boxes, truncated))
+}
+
+/// Reads the `ftyp` major brand and compatible-brands list, if the file has
+/// one. Shared by both track-based MP4 and item-based HEIF parsing, since
+/// both are `ftyp`-led ISO-BMFF files.
+pub(crate) fn read_ftyp<S: SeekableStream>(
+    stream: &mut S,
+    top_level: &[boxes::BoxHeader],
+) -> Result<(Option<String>, Vec<String>)> {
+    let Some(ftyp) = top_level.iter().find(|b| &b.box_type == b"ftyp") else {
+        return Ok((None, Vec::new()));
+    };
+    if ftyp.body_len() < 8 {
+        return Ok((None, Vec::new()));
+    }
+
+    let mut major_brand = [0u8; 4];
+    stream.read_at(ftyp.body_start, &mut major_brand)?;
+
+    let compatible_len = (ftyp.body_len() - 8) as usize;
+    let mut compatible_bytes = vec![0u8; compatible_len];
+    stream.read_at(ftyp.body_start + 8, &mut compatible_bytes)?;
+    let compatible_brands =
+        compatible_bytes.chunks_exact(4).map(|c| String::from_utf8_lossy(c).to_string()).collect();
+
+    Ok((Some(String::from_utf8_lossy(&major_brand).to_string()), compatible_brands))
+}
+
+/// The UUID Adobe's XMP specification reserves for an XMP packet stored in
+/// a top-level `uuid` box (`BE7ACFCB-97A9-42E8-9C71-999491E3AFAC`).
+const XMP_UUID: [u8; 16] =
+    [0xBE, 0x7A, 0xCF, 0xCB, 0x97, 0xA9, 0x42, 0xE8, 0x9C, 0x71, 0x99, 0x94, 0x91, 0xE3, 0xAF, 0xAC];
+
+/// Reads the XMP packet out of a top-level `uuid` box, if the file has one
+/// -- common in camera-produced files, which embed XMP alongside (rather
+/// than instead of) their standard `moov` metadata.
+fn read_xmp<S: SeekableStream>(stream: &mut S, top_level: &[boxes::BoxHeader]) -> Result<Option<String>> {
+    for b in top_level.iter().filter(|b| &b.box_type == b"uuid") {
+        if boxes::read_uuid(stream, b)? != XMP_UUID {
+            continue;
+        }
+        let packet_start = b.body_start + 16;
+        let mut packet = vec![0u8; (b.end - packet_start) as usize];
+        stream.read_at(packet_start, &mut packet)?;
+        return Ok(Some(String::from_utf8_lossy(&packet).to_string()));
+    }
+    Ok(None)
+}
+
+/// Finds the top-level `moov` box, if the file has one. Thin wrapper over
+/// [`boxes::children`] for callers (like [`crate::probe::probe`]) that only
+/// need `moov`'s header, not the rest of [`extract_metadata`]'s work.
+pub(crate) fn find_top_level_moov<S: SeekableStream>(stream: &mut S) -> Result<Option<boxes::BoxHeader>> {
+    let len = stream.len()?;
+    Ok(boxes::children(stream, 0, len)?.into_iter().find(|b| &b.box_type == b"moov"))
+}
+
+/// Reads `moov`'s overall duration from its `mvhd`, if present.
+pub(crate) fn movie_duration<S: SeekableStream>(stream: &mut S, moov: &boxes::BoxHeader) -> Result<Option<Duration>> {
+    let Some(mvhd) = boxes::find_child(stream, moov.body_start, moov.end, b"mvhd")? else { return Ok(None) };
+    Ok(read_timescale_and_duration(stream, &mvhd)?
+        .map(|(timescale, duration)| Duration::from_secs_f64(duration as f64 / f64::from(timescale))))
+}
+
+pub(crate) fn extract_metadata<S: SeekableStream>(stream: &mut S) -> Result<Metadata> {
+    let len = stream.len()?;
+    let mut metadata = Metadata::default();
+
+    // Tolerant, not strict: a truncated trailing `mdat` (interrupted
+    // upload) shouldn't stop `moov` -- which precedes it in a faststart
+    // file -- from yielding full metadata. See `is_truncated` on
+    // `crate::metadata::Metadata` for how that's surfaced.
+    let (top_level, _) = boxes::children_tolerant(stream, 0, len)?;
+    (metadata.major_brand, metadata.compatible_brands) = read_ftyp(stream, &top_level)?;
+    metadata.xmp = read_xmp(stream, &top_level)?;
+
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else {
+        return Ok(metadata);
+    };
+
+    if let Some(mvhd) = boxes::find_child(stream, moov.body_start, moov.end, b"mvhd")? {
+        if let Some((timescale, duration)) = read_timescale_and_duration(stream, &mvhd)? {
+            metadata.duration = Some(Duration::from_secs_f64(duration as f64 / f64::from(timescale)));
+        }
+    }
+
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        if let Some(info) = read_audio_track(stream, &trak)? {
+            metadata.sample_rate = Some(info.sample_rate);
+            metadata.channels = Some(info.channels);
+            metadata.audio_codec = info.codec;
+
+            let ambisonics = spatial_audio::read_sa3d(stream, info.extensions_start, info.extensions_end)?;
+            metadata.ambisonic_order = ambisonics.order;
+            metadata.ambisonic_channel_ordering = ambisonics.channel_ordering;
+            break;
+        }
+    }
+
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        if let Some(info) = read_video_track(stream, &trak)? {
+            metadata.width = Some(info.width);
+            metadata.height = Some(info.height);
+            metadata.video_codec =
+                Some(codecs::video_codec_label(stream, &info.codec, info.extensions_start, info.extensions_end)?);
+            metadata.video_bit_depth = info.bit_depth;
+            metadata.video_vendor = info.compressor_name;
+
+            let spherical = spherical::read_st3d_sv3d(stream, info.extensions_start, info.extensions_end)?;
+            metadata.spherical_projection = spherical.projection;
+            metadata.stereo_mode = spherical.stereo_mode;
+            break;
+        }
+    }
+
+    if metadata.spherical_projection.is_none() && metadata.stereo_mode.is_none() {
+        let legacy = spherical::read_legacy_xml(stream, &top_level)?;
+        metadata.spherical_projection = legacy.projection;
+        metadata.stereo_mode = legacy.stereo_mode;
+        metadata.initial_view = legacy.initial_view;
+    }
+
+    if let Some(udta) = boxes::find_child(stream, moov.body_start, moov.end, b"udta")? {
+        tags::read_asset_info_tags(stream, &udta, &mut metadata.tags, &mut metadata.localized_tags)?;
+    }
+
+    if let Some(meta) = find_meta(stream, moov.body_start, moov.end)? {
+        if let Some(ilst) = boxes::find_child(stream, meta.body_start + 4, meta.end, b"ilst")? {
+            tags::read_standard_tags(stream, &ilst, &mut metadata.tags, &mut metadata.localized_tags)?;
+            tags::read_freeform_tags(stream, &ilst, &mut metadata.freeform_tags)?;
+            apply_gapless_duration(stream, &ilst, &mut metadata)?;
+
+            let video_tags = tags::read_itunes_video_tags(stream, &ilst)?;
+            metadata.media_kind = video_tags.media_kind;
+            metadata.tv_show = video_tags.tv_show;
+            metadata.tv_episode_id = video_tags.tv_episode_id;
+            metadata.tv_season = video_tags.tv_season;
+            metadata.tv_episode = video_tags.tv_episode;
+            metadata.content_rating = video_tags.content_rating;
+            metadata.description = video_tags.description;
+            metadata.long_description = video_tags.long_description;
+            metadata.lyrics = tags::read_lyrics(stream, &ilst)?;
+
+            let sort_tags = tags::read_sort_tags(stream, &ilst)?;
+            metadata.sort_title = sort_tags.sort_title;
+            metadata.sort_artist = sort_tags.sort_artist;
+            metadata.sort_album = sort_tags.sort_album;
+
+            if let Some(keys) = boxes::find_child(stream, meta.body_start + 4, meta.end, b"keys")? {
+                tags::read_mdta_tags(stream, &keys, &ilst, &mut metadata.tags, &mut metadata.localized_tags)?;
+            }
+        }
+    }
+
+    Ok(metadata)
+}
+
+/// `mvhd` and `mdhd` share the same layout through their timescale/duration
+/// fields: a full box (1 byte version, 3 bytes flags) followed by either
+/// 32-bit or 64-bit creation/modification time, timescale, and duration
+/// fields depending on `version` (and, after that, fields specific to each
+/// box type that this crate doesn't need).
+pub(crate) fn read_timescale_and_duration<S: SeekableStream>(stream: &mut S, header: &boxes::BoxHeader) -> Result<Option<(u32, u64)>> {
+    let mut version = [0u8; 1];
+    stream.read_at(header.body_start, &mut version)?;
+
+    if version[0] == 1 {
+        let mut body = [0u8; 28];
+        stream.read_at(header.body_start + 4, &mut body)?;
+        let timescale = u32::from_be_bytes(body[16..20].try_into().unwrap());
+        let duration = u64::from_be_bytes(body[20..28].try_into().unwrap());
+        Ok(if timescale > 0 { Some((timescale, duration)) } else { None })
+    } else {
+        let mut body = [0u8; 16];
+        stream.read_at(header.body_start + 4, &mut body)?;
+        let timescale = u32::from_be_bytes(body[8..12].try_into().unwrap());
+        let duration = u64::from(u32::from_be_bytes(body[12..16].try_into().unwrap()));
+        Ok(if timescale > 0 { Some((timescale, duration)) } else { None })
+    }
+}
+
+/// A compact summary of one `trak`, for cheap probing (see
+/// [`crate::probe::probe`]) without the cost of a full metadata pass.
+pub(crate) struct TrackSummary {
+    pub track_id: u32,
+    /// The `hdlr` handler type, e.g. `"vide"`, `"soun"`, `"text"`.
+    pub handler: String,
+    /// The first sample entry's four-character codec type, e.g. `"avc1"`,
+    /// if `stsd` has one.
+    pub codec: Option<String>,
+    /// The track's `mdhd.language`, as an ISO 639-2/T code.
+    pub language: String,
+}
+
+/// Reads a compact summary of every `trak` under `moov`: track ID, handler
+/// type, first sample entry's codec, and language. Cheaper than the full
+/// [`extract_metadata`] pass since it skips tags, pictures, and XMP.
+pub(crate) fn track_summaries<S: SeekableStream>(stream: &mut S, moov: &boxes::BoxHeader) -> Result<Vec<TrackSummary>> {
+    let mut summaries = Vec::new();
+
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        let Some(tkhd) = boxes::find_child(stream, trak.body_start, trak.end, b"tkhd")? else { continue };
+        let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { continue };
+        let Some(hdlr) = boxes::find_child(stream, mdia.body_start, mdia.end, b"hdlr")? else { continue };
+
+        let track_id = read_track_id(stream, &tkhd)?;
+
+        let mut handler_type = [0u8; 4];
+        stream.read_at(hdlr.body_start + 8, &mut handler_type)?;
+        let handler = String::from_utf8_lossy(&handler_type).to_string();
+
+        let language = match boxes::find_child(stream, mdia.body_start, mdia.end, b"mdhd")? {
+            Some(mdhd) => read_language(stream, &mdhd)?,
+            None => "und".into(),
+        };
+
+        let codec = first_sample_entry_codec(stream, &mdia)?;
+
+        summaries.push(TrackSummary { track_id, handler, codec, language });
+    }
+
+    Ok(summaries)
+}
+
+/// Reads the four-character type of `mdia`'s track's first `stsd` sample
+/// entry, regardless of handler type. Returns `None` if `stsd` is missing
+/// or empty.
+fn first_sample_entry_codec<S: SeekableStream>(stream: &mut S, mdia: &boxes::BoxHeader) -> Result<Option<String>> {
+    let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else { return Ok(None) };
+    let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else { return Ok(None) };
+    let Some(stsd) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsd")? else { return Ok(None) };
+
+    // stsd is a full box: version/flags (4 bytes), entry count (4 bytes),
+    // then the first sample entry.
+    let first_entry_start = stsd.body_start + 8;
+    if first_entry_start + 8 > stsd.end {
+        return Ok(None);
+    }
+
+    let mut entry_header = [0u8; 8];
+    stream.read_at(first_entry_start, &mut entry_header)?;
+    Ok(Some(String::from_utf8_lossy(&entry_header[4..8]).to_string()))
+}
+
+/// Reads `mdhd`'s `language` field: an ISO 639-2/T code packed as three
+/// 5-bit values (each `letter - 0x60`), after version/flags and the
+/// version-dependent creation/modification/timescale/duration fields.
+pub(crate) fn read_language<S: SeekableStream>(stream: &mut S, mdhd: &boxes::BoxHeader) -> Result<String> {
+    let mut version = [0u8; 1];
+    stream.read_at(mdhd.body_start, &mut version)?;
+
+    let language_offset = if version[0] == 1 { mdhd.body_start + 4 + 8 + 8 + 4 + 8 } else { mdhd.body_start + 4 + 4 + 4 + 4 + 4 };
+    let mut raw = [0u8; 2];
+    stream.read_at(language_offset, &mut raw)?;
+    Ok(decode_iso639_language(u16::from_be_bytes(raw)))
+}
+
+fn decode_iso639_language(raw: u16) -> String {
+    let decode_char = |shift: u16| (((raw >> shift) & 0x1F) as u8 + 0x60) as char;
+    [decode_char(10), decode_char(5), decode_char(0)].iter().collect()
+}
+
+/// Reads `tkhd`'s `track_ID` field, which sits right after version/flags
+/// and the version-dependent creation/modification time fields.
+pub(crate) fn read_track_id<S: SeekableStream>(stream: &mut S, tkhd: &boxes::BoxHeader) -> Result<u32> {
+    let mut version = [0u8; 1];
+    stream.read_at(tkhd.body_start, &mut version)?;
+
+    let track_id_offset = if version[0] == 1 { tkhd.body_start + 4 + 8 + 8 } else { tkhd.body_start + 4 + 4 + 4 };
+    let mut track_id = [0u8; 4];
+    stream.read_at(track_id_offset, &mut track_id)?;
+    Ok(u32::from_be_bytes(track_id))
+}
+
+/// `tkhd`'s flags and `alternate_group`, used to pick among several tracks
+/// of the same media type (e.g. the default audio or subtitle track among
+/// several alternates in a multi-track file).
+pub(crate) struct TrackFlags {
+    /// Whether the track's `Track_enabled` flag (`0x1`) is set.
+    pub enabled: bool,
+    /// Whether the track's `Track_in_movie` flag (`0x2`) is set.
+    pub in_movie: bool,
+    /// The track's `alternate_group`; tracks sharing a non-zero group are
+    /// alternates of each other, and only one should be played at a time.
+    pub alternate_group: u16,
+}
+
+/// Reads `tkhd`'s flags and `alternate_group`. `alternate_group` sits after
+/// `track_ID`, a reserved field, `duration`, another reserved field, and
+/// `layer` -- all of which are version-dependent in length, like
+/// `track_ID` itself.
+pub(crate) fn read_track_flags<S: SeekableStream>(stream: &mut S, tkhd: &boxes::BoxHeader) -> Result<TrackFlags> {
+    let mut header = [0u8; 4];
+    stream.read_at(tkhd.body_start, &mut header)?;
+    let flags = u32::from_be_bytes([0, header[1], header[2], header[3]]);
+
+    let alternate_group_offset = if header[0] == 1 { tkhd.body_start + 46 } else { tkhd.body_start + 34 };
+    let mut group = [0u8; 2];
+    stream.read_at(alternate_group_offset, &mut group)?;
+
+    Ok(TrackFlags {
+        enabled: flags & 0x1 != 0,
+        in_movie: flags & 0x2 != 0,
+        alternate_group: u16::from_be_bytes(group),
+    })
+}
+
+fn read_audio_track<S: SeekableStream>(stream: &mut S, trak: &boxes::BoxHeader) -> Result<Option<AudioTrackInfo>> {
+    let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { return Ok(None) };
+    let Some(hdlr) = boxes::find_child(stream, mdia.body_start, mdia.end, b"hdlr")? else { return Ok(None) };
+
+    let mut handler_type = [0u8; 4];
+    stream.read_at(hdlr.body_start + 8, &mut handler_type)?;
+    if &handler_type != b"soun" {
+        return Ok(None);
+    }
+
+    let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else { return Ok(None) };
+    let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else { return Ok(None) };
+    let Some(stsd) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsd")? else { return Ok(None) };
+
+    // stsd is a full box: version/flags (4 bytes), entry count (4 bytes),
+    // then the first sample entry.
+    let first_entry_start = stsd.body_start + 8;
+    if first_entry_start + 8 > stsd.end {
+        return Ok(None);
+    }
+
+    let Some(entry) = boxes::read_box_header(stream, first_entry_start, stsd.end)? else { return Ok(None) };
+
+    // Audio sample entry: reserved (6) + data_reference_index (2) +
+    // version/revision/vendor (8) + channel_count (2) + sample_size (2) +
+    // pre_defined (2) + reserved (2) + sample_rate (4, 16.16 fixed point).
+    let mut audio_fields = [0u8; 20];
+    stream.read_at(entry.body_start + 8, &mut audio_fields)?;
+    let mut channels = u16::from_be_bytes(audio_fields[8..10].try_into().unwrap());
+    let mut sample_rate = u32::from_be_bytes(audio_fields[16..20].try_into().unwrap()) >> 16;
+    let mut codec = None;
+
+    // An `esds` box gives the true sample rate and AAC variant; prefer it
+    // over the legacy fixed fields above when present.
+    if let Some(aac) = esds::read_aac_config(stream, &entry)? {
+        sample_rate = aac.sample_rate;
+        channels = aac.channels;
+        codec = Some(aac.codec);
+    }
+
+    Ok(Some(AudioTrackInfo {
+        sample_rate,
+        channels,
+        codec,
+        extensions_start: entry.body_start + 28,
+        extensions_end: entry.end,
+    }))
+}
+
+/// The still-encoded first sample of a file's first video track, located
+/// by descending `moov` box-by-box (never buffering the whole thing, and
+/// never reading `mdat`'s other samples), for use as a cheap thumbnail
+/// before any real keyframe-aware selection exists.
+pub(crate) struct VideoFrame {
+    pub width: u32,
+    pub height: u32,
+    /// The sample entry's four-character codec type, e.g. `"avc1"` or
+    /// `"hev1"`, for picking a decoder backend.
+    pub codec: String,
+    pub data: Vec<u8>,
+    /// 0-based index of this sample within the track's decode order.
+    pub sample_index: u32,
+    /// Whether this is a sync sample (e.g. an IDR frame) per `stss`.
+    pub is_keyframe: bool,
+    /// Decode timestamp (`stts`, no `ctts` composition offset applied --
+    /// this crate doesn't read composition time yet), relative to the
+    /// track's start.
+    pub pts: Duration,
+}
+
+/// Reads the first video track's first sample, or `None` if the file has
+/// no video track, or that track has no recoverable chunk layout.
+pub(crate) fn first_video_sample<S: SeekableStream>(stream: &mut S) -> Result<Option<VideoFrame>> {
+    let len = stream.len()?;
+    let top_level = boxes::children(stream, 0, len)?;
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else { return Ok(None) };
+
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        let Some(info) = read_video_track(stream, &trak)? else { continue };
+        let Some(table) = sample_table::SampleTable::from_stbl(stream, &info.stbl, sample_table::ParseMode::Strict)? else { continue };
+        if table.sample_count() == 0 {
+            continue;
+        }
+
+        let timescale = track_timescale(stream, &trak)?;
+        let size = table.size(stream, 0)?;
+        let offset = table.offset(stream, 0)?;
+        let mut data = vec![0u8; size as usize];
+        stream.read_at(offset, &mut data)?;
+        let is_keyframe = table.is_sync_sample(stream, 0)?;
+        let pts = ticks_to_duration(table.timestamp(stream, 0)?, timescale);
+
+        return Ok(Some(VideoFrame {
+            width: info.width,
+            height: info.height,
+            codec: info.codec,
+            data,
+            sample_index: 0,
+            is_keyframe,
+            pts,
+        }));
+    }
+
+    Ok(None)
+}
+
+/// Reads the still-encoded samples a decoder would need to produce an exact
+/// (possibly non-sync) target frame: every sample from the nearest
+/// preceding sync sample through `target_index`, inclusive, in decode
+/// order. Returns `None` if the file has no video track, or `target_index`
+/// is out of range for it.
+pub(crate) fn video_samples_from_preceding_sync_sample<S: SeekableStream>(
+    stream: &mut S,
+    target_index: u32,
+) -> Result<Option<Vec<VideoFrame>>> {
+    let len = stream.len()?;
+    let top_level = boxes::children(stream, 0, len)?;
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else { return Ok(None) };
+
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        let Some(info) = read_video_track(stream, &trak)? else { continue };
+        let Some(table) = sample_table::SampleTable::from_stbl(stream, &info.stbl, sample_table::ParseMode::Strict)? else { continue };
+        if target_index >= table.sample_count() {
+            continue;
+        }
+
+        let timescale = track_timescale(stream, &trak)?;
+        let start = table.preceding_sync_sample(stream, target_index)?;
+        let mut frames = Vec::with_capacity((target_index - start + 1) as usize);
+        for index in start..=target_index {
+            let size = table.size(stream, index)?;
+            let offset = table.offset(stream, index)?;
+            let mut data = vec![0u8; size as usize];
+            stream.read_at(offset, &mut data)?;
+            let is_keyframe = table.is_sync_sample(stream, index)?;
+            let pts = ticks_to_duration(table.timestamp(stream, index)?, timescale);
+            frames.push(VideoFrame {
+                width: info.width,
+                height: info.height,
+                codec: info.codec.clone(),
+                data,
+                sample_index: index,
+                is_keyframe,
+                pts,
+            });
+        }
+
+        return Ok(Some(frames));
+    }
+
+    Ok(None)
+}
+
+/// Returns `trak`'s `mdia`/`mdhd` timescale, or `0` if it's missing or
+/// unreadable (callers treat a `0` timescale as "can't convert to a
+/// duration", matching [`ticks_to_duration`]).
+fn track_timescale<S: SeekableStream>(stream: &mut S, trak: &boxes::BoxHeader) -> Result<u32> {
+    let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { return Ok(0) };
+    let Some(mdhd) = boxes::find_child(stream, mdia.body_start, mdia.end, b"mdhd")? else { return Ok(0) };
+    Ok(read_timescale_and_duration(stream, &mdhd)?.map_or(0, |(timescale, _)| timescale))
+}
+
+/// Converts a tick count in `timescale` units/second to a [`Duration`],
+/// returning [`Duration::ZERO`] for an unknown (`0`) timescale.
+fn ticks_to_duration(ticks: u64, timescale: u32) -> Duration {
+    if timescale == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(ticks as f64 / f64::from(timescale))
+}
+
+/// Resolves `positions` (fractions of the container's overall `mvhd`
+/// duration, typically in `[0.0, 1.0]`) to sample indices in the first
+/// video track, via that track's own `mdhd` timescale and `stts`. Returns
+/// `None` if the file has no `mvhd` duration or no video track with
+/// readable timing.
+pub(crate) fn video_sample_indices_at_positions<S: SeekableStream>(
+    stream: &mut S,
+    positions: &[f64],
+) -> Result<Option<Vec<u32>>> {
+    let len = stream.len()?;
+    let top_level = boxes::children(stream, 0, len)?;
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else { return Ok(None) };
+
+    let Some(mvhd) = boxes::find_child(stream, moov.body_start, moov.end, b"mvhd")? else { return Ok(None) };
+    let Some((movie_timescale, movie_duration)) = read_timescale_and_duration(stream, &mvhd)? else { return Ok(None) };
+    let duration_seconds = movie_duration as f64 / f64::from(movie_timescale);
+
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        let Some(info) = read_video_track(stream, &trak)? else { continue };
+        let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { continue };
+        let Some(mdhd) = boxes::find_child(stream, mdia.body_start, mdia.end, b"mdhd")? else { continue };
+        let Some((track_timescale, _)) = read_timescale_and_duration(stream, &mdhd)? else { continue };
+        let Some(table) = sample_table::SampleTable::from_stbl(stream, &info.stbl, sample_table::ParseMode::Strict)? else { continue };
+        if table.sample_count() == 0 {
+            continue;
+        }
+
+        let indices = positions
+            .iter()
+            .map(|&fraction| {
+                let target_ticks = (fraction.clamp(0.0, 1.0) * duration_seconds * f64::from(track_timescale)).round() as u64;
+                table.sample_at_timestamp(stream, target_ticks)
+            })
+            .collect::<Result<Vec<u32>>>()?;
+
+        return Ok(Some(indices));
+    }
+
+    Ok(None)
+}
+
+/// Returns the visual dimensions, codec, and `stbl` box of `trak`, if it's
+/// a video track (per its `hdlr` handler type).
+fn read_video_track<S: SeekableStream>(stream: &mut S, trak: &boxes::BoxHeader) -> Result<Option<VideoTrackInfo>> {
+    let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { return Ok(None) };
+    let Some(hdlr) = boxes::find_child(stream, mdia.body_start, mdia.end, b"hdlr")? else { return Ok(None) };
+
+    let mut handler_type = [0u8; 4];
+    stream.read_at(hdlr.body_start + 8, &mut handler_type)?;
+    if &handler_type != b"vide" {
+        return Ok(None);
+    }
+
+    let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else { return Ok(None) };
+    let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else { return Ok(None) };
+    let Some(stsd) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsd")? else { return Ok(None) };
+
+    // stsd is a full box: version/flags (4 bytes), entry count (4 bytes),
+    // then the first sample entry.
+    let first_entry_start = stsd.body_start + 8;
+    if first_entry_start + 8 > stsd.end {
+        return Ok(None);
+    }
+
+    let mut entry_header = [0u8; 8];
+    stream.read_at(first_entry_start, &mut entry_header)?;
+    let entry_size = u64::from(u32::from_be_bytes(entry_header[0..4].try_into().unwrap()));
+    let codec = String::from_utf8_lossy(&entry_header[4..8]).to_string();
+    let entry_body_start = first_entry_start + 8;
+    let entry_end = first_entry_start + entry_size;
+
+    // Visual sample entry: reserved (6) + data_reference_index (2) +
+    // pre_defined (2) + reserved (2) + pre_defined[3] (12) + width (2) +
+    // height (2) + ...
+    let mut visual_fields = [0u8; 20];
+    stream.read_at(entry_body_start + 8, &mut visual_fields)?;
+    let width = u32::from(u16::from_be_bytes(visual_fields[16..18].try_into().unwrap()));
+    let height = u32::from(u16::from_be_bytes(visual_fields[18..20].try_into().unwrap()));
+
+    // ... horizresolution (4) + vertresolution (4) + reserved (4) +
+    // frame_count (2) + compressorname (32, Pascal string: 1 length byte
+    // then up to 31 bytes of text) + depth (2) + pre_defined (2) = 78
+    // bytes total, matching `extensions_start` below. A sample entry
+    // shorter than that (e.g. a hand-built test fixture, or a genuinely
+    // minimal encoder) just leaves these unset rather than erroring.
+    let mut compressor_name = None;
+    let mut bit_depth = None;
+    if entry_body_start + 78 <= entry_end {
+        let mut compressorname = [0u8; 32];
+        stream.read_at(entry_body_start + 42, &mut compressorname)?;
+        let compressorname_len = usize::from(compressorname[0]).min(31);
+        compressor_name = (compressorname_len > 0)
+            .then(|| String::from_utf8_lossy(&compressorname[1..1 + compressorname_len]).trim().to_string())
+            .filter(|s| !s.is_empty());
+
+        let mut depth_field = [0u8; 2];
+        stream.read_at(entry_body_start + 74, &mut depth_field)?;
+        bit_depth = Some(u16::from_be_bytes(depth_field));
+    }
+
+    // Extension boxes like avcC/hvcC sit after the 78-byte visual sample
+    // entry fixed fields (see the comment on this same span above).
+    Ok(Some(VideoTrackInfo {
+        width,
+        height,
+        codec,
+        stbl,
+        bit_depth,
+        compressor_name,
+        extensions_start: entry_body_start + 78,
+        extensions_end: entry_end,
+    }))
+}
+
+/// Finds `udta.meta`, if present. `meta` is itself a full box (4-byte
+/// version/flags) before its children.
+fn find_meta<S: SeekableStream>(stream: &mut S, start: u64, end: u64) -> Result<Option<boxes::BoxHeader>> {
+    let Some(udta) = boxes::find_child(stream, start, end, b"udta")? else { return Ok(None) };
+    boxes::find_child(stream, udta.body_start, udta.end, b"meta")
+}
+
+/// Overrides the container duration with the sample-accurate duration
+/// derived from the `iTunSMPB` freeform atom, when present.
+fn apply_gapless_duration<S: SeekableStream>(
+    stream: &mut S,
+    ilst: &boxes::BoxHeader,
+    metadata: &mut Metadata,
+) -> Result<()> {
+    let Some(value) = tags::find_itunsmpb(stream, ilst)? else { return Ok(()) };
+    let Some((_delay, _padding, original_samples)) = tags::parse_itunsmpb(&value) else { return Ok(()) };
+    let Some(sample_rate) = metadata.sample_rate else { return Ok(()) };
+
+    if sample_rate > 0 {
+        metadata.duration = Some(Duration::from_secs_f64(original_samples as f64 / f64::from(sample_rate)));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn data_atom(value: &[u8]) -> Vec<u8> {
+        let mut body = vec![0u8; 8];
+        body.extend_from_slice(value);
+        sized_box(b"data", &body)
+    }
+
+    fn freeform_smpb_atom(value: &str) -> Vec<u8> {
+        let mean = sized_box(b"mean", &[&[0u8; 4][..], b"com.apple.iTunes"].concat());
+        let name = sized_box(b"name", &[&[0u8; 4][..], b"iTunSMPB"].concat());
+        let data = data_atom(value.as_bytes());
+        sized_box(b"----", &[mean, name, data].concat())
+    }
+
+    fn sample_mp4() -> Vec<u8> {
+        let ftyp = sized_box(b"ftyp", b"M4A mabcdM4A mmp42isom");
+
+        let mut mvhd_body = vec![0u8; 20];
+        mvhd_body[8..12].copy_from_slice(&44100u32.to_be_bytes()); // timescale
+        mvhd_body[12..16].copy_from_slice(&44100u32.to_be_bytes()); // duration
+        let mvhd = sized_box(b"mvhd", &mvhd_body);
+
+        let hdlr_body = [&[0u8; 8][..], b"soun", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        let mut sample_entry_body = vec![0u8; 8]; // reserved + data_reference_index
+        sample_entry_body.extend_from_slice(&[0u8; 8]); // version/revision/vendor
+        sample_entry_body.extend_from_slice(&2u16.to_be_bytes()); // channels
+        sample_entry_body.extend_from_slice(&16u16.to_be_bytes()); // sample size
+        sample_entry_body.extend_from_slice(&[0u8; 4]); // pre_defined + reserved
+        sample_entry_body.extend_from_slice(&(44100u32 << 16).to_be_bytes()); // sample rate
+        let mp4a = sized_box(b"mp4a", &sample_entry_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &mp4a].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+        let stbl = sized_box(b"stbl", &stsd);
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+
+        let ilst_body = freeform_smpb_atom(
+            "00000000 00000000 00000000 0000000000002b11 00000000 00000000 00000000 00000000",
+        );
+        let ilst = sized_box(b"ilst", &ilst_body);
+        let meta = sized_box(b"meta", &[&[0u8; 4][..], &ilst].concat());
+        let udta = sized_box(b"udta", &meta);
+
+        let moov = sized_box(b"moov", &[mvhd, trak, udta].concat());
+
+        [ftyp, moov].concat()
+    }
+
+    #[test]
+    fn reads_sample_rate_channels_and_gapless_duration() {
+        let mut stream = MemorySeekableStream::new(sample_mp4());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.sample_rate, Some(44100));
+        assert_eq!(metadata.channels, Some(2));
+        // 0x2b11 == 11025 samples at 44100 Hz == 0.25s, overriding the
+        // 1-second container duration from mvhd.
+        assert_eq!(metadata.duration, Some(Duration::from_secs_f64(11025.0 / 44100.0)));
+    }
+
+    fn esds_box_for_aac_lc() -> Vec<u8> {
+        // audioObjectType=2 (LC), samplingFrequencyIndex=4 (44100),
+        // channelConfiguration=1 (mono).
+        let audio_specific_config = [0x12u8, 0x08];
+        let dec_specific_info_body = [5u8, audio_specific_config.len() as u8, audio_specific_config[0], audio_specific_config[1]];
+        let mut decoder_config_body = vec![0x40, 0x15, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        decoder_config_body.extend_from_slice(&dec_specific_info_body);
+        let decoder_config = [&[4u8, decoder_config_body.len() as u8][..], &decoder_config_body].concat();
+        let mut es_body = vec![0u8, 1, 0];
+        es_body.extend_from_slice(&decoder_config);
+        let es_descriptor = [&[3u8, es_body.len() as u8][..], &es_body].concat();
+        let mut esds_body = vec![0u8; 4];
+        esds_body.extend_from_slice(&es_descriptor);
+        sized_box(b"esds", &esds_body)
+    }
+
+    #[test]
+    fn reads_aac_codec_and_true_sample_rate_from_esds() {
+        let ftyp = sized_box(b"ftyp", b"M4A mabcdM4A mmp42isom");
+
+        let mut mvhd_body = vec![0u8; 20];
+        mvhd_body[8..12].copy_from_slice(&44100u32.to_be_bytes());
+        mvhd_body[12..16].copy_from_slice(&44100u32.to_be_bytes());
+        let mvhd = sized_box(b"mvhd", &mvhd_body);
+
+        let hdlr_body = [&[0u8; 8][..], b"soun", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        // Legacy fields say stereo @ 22050 Hz; esds should override both.
+        let mut sample_entry_body = vec![0u8; 8];
+        sample_entry_body.extend_from_slice(&[0u8; 8]);
+        sample_entry_body.extend_from_slice(&2u16.to_be_bytes());
+        sample_entry_body.extend_from_slice(&16u16.to_be_bytes());
+        sample_entry_body.extend_from_slice(&[0u8; 4]);
+        sample_entry_body.extend_from_slice(&(22050u32 << 16).to_be_bytes());
+        sample_entry_body.extend_from_slice(&esds_box_for_aac_lc());
+        let mp4a = sized_box(b"mp4a", &sample_entry_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &mp4a].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+        let stbl = sized_box(b"stbl", &stsd);
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &[mvhd, trak].concat());
+
+        let mut stream = MemorySeekableStream::new([ftyp, moov].concat());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.audio_codec, Some("AAC-LC".to_string()));
+        assert_eq!(metadata.sample_rate, Some(44100));
+        assert_eq!(metadata.channels, Some(1));
+    }
+
+    #[test]
+    fn reads_ambisonic_order_and_channel_ordering_from_sa3d() {
+        let hdlr_body = [&[0u8; 8][..], b"soun", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        let mut sample_entry_body = vec![0u8; 8];
+        sample_entry_body.extend_from_slice(&[0u8; 8]);
+        sample_entry_body.extend_from_slice(&4u16.to_be_bytes()); // channels: first-order ambisonics (W,Y,Z,X)
+        sample_entry_body.extend_from_slice(&16u16.to_be_bytes());
+        sample_entry_body.extend_from_slice(&[0u8; 4]);
+        sample_entry_body.extend_from_slice(&(48000u32 << 16).to_be_bytes());
+
+        // version(0) + ambisonic_type(0) + order(1, BE) + channel_ordering(0: ACN)
+        // + normalization(0: SN3D) + num_channels(4, BE) + channel_map[4].
+        let mut sa3d_body = vec![0, 0, 0, 0, 0, 1, 0, 0];
+        sa3d_body.extend_from_slice(&0u32.to_be_bytes());
+        sa3d_body.extend_from_slice(&4u32.to_be_bytes());
+        for channel in 0u32..4 {
+            sa3d_body.extend_from_slice(&channel.to_be_bytes());
+        }
+        let sa3d = sized_box(b"SA3D", &sa3d_body);
+        sample_entry_body.extend_from_slice(&sa3d);
+        let mp4a = sized_box(b"mp4a", &sample_entry_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &mp4a].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+        let stbl = sized_box(b"stbl", &stsd);
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        let mut stream = MemorySeekableStream::new(moov);
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.ambisonic_order, Some(1));
+        assert_eq!(metadata.ambisonic_channel_ordering, Some("ACN".to_string()));
+    }
+
+    #[test]
+    fn reads_video_dimensions_and_codec_with_profile_and_level() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        let avcc = sized_box(b"avcC", &[1, 100, 0, 41, 0xFF, 0xE1, 0, 0, 0x68]);
+        // reserved(6) + data_reference_index(2) + pre_defined(2) +
+        // reserved(2) + pre_defined[3](12) = 24 bytes, then width/height.
+        let mut entry_body = vec![0u8; 24];
+        entry_body.extend_from_slice(&1920u16.to_be_bytes()); // width
+        entry_body.extend_from_slice(&1080u16.to_be_bytes()); // height
+        entry_body.extend_from_slice(&[0u8; 50]); // horizresolution..pre_defined
+        entry_body.extend_from_slice(&avcc);
+        let avc1 = sized_box(b"avc1", &entry_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &avc1].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+        let stbl = sized_box(b"stbl", &stsd);
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        let mut stream = MemorySeekableStream::new([ftyp, moov].concat());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.width, Some(1920));
+        assert_eq!(metadata.height, Some(1080));
+        assert_eq!(metadata.video_codec, Some("H.264/AVC High@4.1".to_string()));
+    }
+
+    #[test]
+    fn recognizes_prores_and_dnx_with_bit_depth_and_vendor_compressorname() {
+        let ftyp = sized_box(b"ftyp", b"qt  \0\0\x02\0qt  ");
+
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        // reserved(6) + data_reference_index(2) + pre_defined(2) +
+        // reserved(2) + pre_defined[3](12) = 24 bytes, then width/height.
+        let mut entry_body = vec![0u8; 24];
+        entry_body.extend_from_slice(&1920u16.to_be_bytes()); // width
+        entry_body.extend_from_slice(&1080u16.to_be_bytes()); // height
+        entry_body.extend_from_slice(&[0u8; 12]); // horizresolution + vertresolution + reserved
+        entry_body.extend_from_slice(&[0u8; 2]); // frame_count
+        let mut compressorname = vec![4u8]; // Pascal string length
+        compressorname.extend_from_slice(b"AVdn");
+        compressorname.resize(32, 0);
+        entry_body.extend_from_slice(&compressorname);
+        entry_body.extend_from_slice(&10u16.to_be_bytes()); // depth
+        entry_body.extend_from_slice(&[0u8; 2]); // pre_defined
+        let apcn = sized_box(b"apcn", &entry_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &apcn].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+        let stbl = sized_box(b"stbl", &stsd);
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        let mut stream = MemorySeekableStream::new([ftyp, moov].concat());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.video_codec, Some("Apple ProRes 422".to_string()));
+        assert_eq!(metadata.video_bit_depth, Some(10));
+        assert_eq!(metadata.video_vendor, Some("AVdn".to_string()));
+    }
+
+    #[test]
+    fn reads_spherical_projection_and_stereo_mode_from_st3d_and_sv3d() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        let st3d = sized_box(b"st3d", &[0, 0, 0, 0, 1]); // version/flags + stereo_mode: top-bottom
+        let proj_type = sized_box(b"equi", &[0u8; 20]);
+        let proj_header = sized_box(b"prhd", &[0u8; 4]);
+        let proj = sized_box(b"proj", &[proj_header, proj_type].concat());
+        let svhd = sized_box(b"svhd", &[&[0u8; 4][..], b"metadata source"].concat());
+        let sv3d = sized_box(b"sv3d", &[svhd, proj].concat());
+
+        let mut entry_body = vec![0u8; 24];
+        entry_body.extend_from_slice(&1920u16.to_be_bytes());
+        entry_body.extend_from_slice(&960u16.to_be_bytes());
+        entry_body.extend_from_slice(&[0u8; 50]);
+        entry_body.extend_from_slice(&st3d);
+        entry_body.extend_from_slice(&sv3d);
+        let avc1 = sized_box(b"avc1", &entry_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &avc1].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+        let stbl = sized_box(b"stbl", &stsd);
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        let mut stream = MemorySeekableStream::new([ftyp, moov].concat());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.spherical_projection, Some("equirectangular".to_string()));
+        assert_eq!(metadata.stereo_mode, Some("top-bottom".to_string()));
+    }
+
+    #[test]
+    fn reads_spherical_metadata_from_a_legacy_xml_uuid_box_without_st3d() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let moov = sized_box(b"moov", &[]);
+
+        let xml = "<rdf:SphericalVideo>\
+            <GSpherical:ProjectionType>equirectangular</GSpherical:ProjectionType>\
+            <GSpherical:StereoMode>mono</GSpherical:StereoMode>\
+            <GSpherical:InitialViewHeadingDegrees>90</GSpherical:InitialViewHeadingDegrees>\
+            <GSpherical:InitialViewPitchDegrees>0</GSpherical:InitialViewPitchDegrees>\
+            <GSpherical:InitialViewRollDegrees>0</GSpherical:InitialViewRollDegrees>\
+            </rdf:SphericalVideo>";
+        let uuid_box = sized_box(b"uuid", &[&spherical::LEGACY_SPHERICAL_UUID[..], xml.as_bytes()].concat());
+
+        let mut stream = MemorySeekableStream::new([ftyp, moov, uuid_box].concat());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.spherical_projection, Some("equirectangular".to_string()));
+        assert_eq!(metadata.stereo_mode, Some("mono".to_string()));
+        assert_eq!(metadata.initial_view, Some((90.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn reads_3gpp_asset_info_tags_with_no_ilst_present() {
+        let hdlr_body = [&[0u8; 8][..], b"soun", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let stbl = sized_box(b"stbl", &sized_box(b"stsd", &[0u8; 8]));
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+
+        let mut titl_body = vec![0u8; 4]; // version/flags
+        titl_body.extend_from_slice(&0u16.to_be_bytes()); // language: und
+        titl_body.extend_from_slice(b"Phone Recording");
+        let titl = sized_box(b"titl", &titl_body);
+        let udta = sized_box(b"udta", &titl);
+
+        let moov = sized_box(b"moov", &[trak, udta].concat());
+        let mut stream = MemorySeekableStream::new(moov);
+
+        let metadata = extract_metadata(&mut stream).unwrap();
+        assert_eq!(metadata.tags.get("title"), Some(&"Phone Recording".to_string()));
+    }
+
+    #[test]
+    fn extracts_an_xmp_packet_from_a_top_level_uuid_box() {
+        let packet = "<x:xmpmeta>...</x:xmpmeta>";
+        let uuid_body = [&XMP_UUID[..], packet.as_bytes()].concat();
+        let uuid_box = sized_box(b"uuid", &uuid_body);
+
+        let mut data = sample_mp4();
+        data.extend_from_slice(&uuid_box);
+        let mut stream = MemorySeekableStream::new(data);
+
+        let metadata = extract_metadata(&mut stream).unwrap();
+        assert_eq!(metadata.xmp.as_deref(), Some(packet));
+    }
+
+    #[test]
+    fn ignores_a_uuid_box_with_an_unrecognized_extension_type() {
+        let uuid_box = sized_box(b"uuid", &[&[0xAAu8; 16][..], b"not xmp"].concat());
+
+        let mut data = sample_mp4();
+        data.extend_from_slice(&uuid_box);
+        let mut stream = MemorySeekableStream::new(data);
+
+        let metadata = extract_metadata(&mut stream).unwrap();
+        assert_eq!(metadata.xmp, None);
+    }
+}
@@ -0,0 +1,264 @@
+//! Rewrites a non-"faststart" MP4 (one where `mdat` precedes `moov`, per
+//! [`crate::probe::ProbeResult::is_faststart`]) so that `moov` comes first,
+//! patching every `stco`/`co64` chunk offset the relocation shifts.
+//!
+//! Like [`crate::embed_subtitle_track`], this buffers the whole input in
+//! memory: moving `moov` ahead of `mdat` shifts the absolute offset of
+//! every sample between them, so there's no streaming-friendly way to do
+//! this short of a full rewrite.
+
+use std::io;
+
+use crate::error::{Error, Result};
+use crate::formats::mp4::boxes::{self, BoxHeader};
+use crate::stream::SeekableStream;
+
+struct SliceStream<'a>(&'a [u8]);
+
+impl SeekableStream for SliceStream<'_> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = offset as usize;
+        let end = start + buf.len();
+        if end > self.0.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "read past end of buffer"));
+        }
+        buf.copy_from_slice(&self.0[start..end]);
+        Ok(())
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.0.len() as u64)
+    }
+}
+
+/// Reads `input` in full and returns a rewritten copy with `moov` relocated
+/// ahead of `mdat`, so the result can be parsed or progressively streamed
+/// (see [`crate::progressive`]) without seeking to the tail.
+///
+/// If `moov` already precedes `mdat`, `input`'s bytes are returned
+/// unchanged. Fails with [`Error::Malformed`] if `input` has no `moov` or
+/// no `mdat` box, and with [`Error::Unsupported`] if relocating `moov`
+/// would push a 32-bit `stco` chunk offset past 4GB (use a source that
+/// already has `co64` tables for files that large).
+pub fn optimize_for_streaming<S: SeekableStream>(input: &mut S) -> Result<Vec<u8>> {
+    let len = input.len()?;
+    let mut buf = vec![0u8; len as usize];
+    input.read_at(0, &mut buf)?;
+
+    let mut reader = SliceStream(&buf);
+    let top_level = boxes::children(&mut reader, 0, len)?;
+
+    let moov = *top_level
+        .iter()
+        .find(|b| &b.box_type == b"moov")
+        .ok_or_else(|| Error::Malformed { format: "mp4", reason: "no moov box to relocate".into() })?;
+    let mdat = *top_level
+        .iter()
+        .find(|b| &b.box_type == b"mdat")
+        .ok_or_else(|| Error::Malformed { format: "mp4", reason: "no mdat box found".into() })?;
+
+    if moov.start < mdat.start {
+        return Ok(buf);
+    }
+
+    let delta = moov.size();
+    let mut moov_bytes = buf[moov.start as usize..moov.end as usize].to_vec();
+    relocate_chunk_offsets(&mut moov_bytes, moov.body_start - moov.start, delta)?;
+
+    let mut out = Vec::with_capacity(buf.len());
+    out.extend_from_slice(&buf[..mdat.start as usize]);
+    out.extend_from_slice(&moov_bytes);
+    out.extend_from_slice(&buf[mdat.start as usize..moov.start as usize]);
+    out.extend_from_slice(&buf[moov.end as usize..]);
+
+    Ok(out)
+}
+
+fn checked_u32(v: u64) -> Result<u32> {
+    u32::try_from(v).map_err(|_| Error::Unsupported("relocating moov past a 4GB stco chunk offset".into()))
+}
+
+/// Adds `delta` to every `stco`/`co64` chunk offset under `moov_bytes` (a
+/// standalone copy of the `moov` box, offsets local to its own start), to
+/// account for the sample data between the old `mdat` and `moov` positions
+/// shifting forward by `delta` bytes.
+///
+/// `body_offset` is where `moov`'s own children start within `moov_bytes`
+/// (i.e. past its own box header), since `moov_bytes` includes that header.
+fn relocate_chunk_offsets(moov_bytes: &mut [u8], body_offset: u64, delta: u64) -> Result<()> {
+    let moov_len = moov_bytes.len() as u64;
+
+    let mut reader = SliceStream(moov_bytes);
+    let mut tables: Vec<(BoxHeader, u64)> = boxes::find_all_recursive(&mut reader, body_offset, moov_len, b"stco")?
+        .into_iter()
+        .map(|b| (b, 4u64))
+        .collect();
+    tables.extend(
+        boxes::find_all_recursive(&mut reader, body_offset, moov_len, b"co64")?.into_iter().map(|b| (b, 8u64)),
+    );
+
+    for (table, entry_size) in tables {
+        let count = u32::from_be_bytes(moov_bytes[table.body_start as usize + 4..table.body_start as usize + 8].try_into().unwrap());
+
+        for i in 0..count {
+            let at = (table.body_start + 8 + u64::from(i) * entry_size) as usize;
+            if entry_size == 4 {
+                let value = u32::from_be_bytes(moov_bytes[at..at + 4].try_into().unwrap());
+                let new_value = checked_u32(u64::from(value) + delta)?;
+                moov_bytes[at..at + 4].copy_from_slice(&new_value.to_be_bytes());
+            } else {
+                let value = u64::from_be_bytes(moov_bytes[at..at + 8].try_into().unwrap());
+                moov_bytes[at..at + 8].copy_from_slice(&(value + delta).to_be_bytes());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn write_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::with_capacity(body.len() + 8);
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn build_stco(offsets: &[u32]) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0u32.to_be_bytes());
+        body.extend_from_slice(&(offsets.len() as u32).to_be_bytes());
+        for offset in offsets {
+            body.extend_from_slice(&offset.to_be_bytes());
+        }
+        write_box(b"stco", &body)
+    }
+
+    fn build_co64(offsets: &[u64]) -> Vec<u8> {
+        let mut body = Vec::new();
+        body.extend_from_slice(&0u32.to_be_bytes());
+        body.extend_from_slice(&(offsets.len() as u32).to_be_bytes());
+        for offset in offsets {
+            body.extend_from_slice(&offset.to_be_bytes());
+        }
+        write_box(b"co64", &body)
+    }
+
+    /// `ftyp`, `mdat` (with `mdat_body`), then `moov` (containing `trak` >
+    /// `mdia` > `minf` > `stbl` > `stco` pointing at `mdat_body`'s start).
+    fn non_faststart_mp4(mdat_body: &[u8]) -> (Vec<u8>, u64) {
+        let ftyp = write_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mdat = write_box(b"mdat", mdat_body);
+        let mdat_offset = (ftyp.len() + 8) as u64; // past ftyp and mdat's own header
+
+        let stco = build_stco(&[mdat_offset as u32]);
+        let stbl = write_box(b"stbl", &stco);
+        let minf = write_box(b"minf", &stbl);
+        let mdia = write_box(b"mdia", &minf);
+        let trak = write_box(b"trak", &mdia);
+        let moov = write_box(b"moov", &trak);
+
+        let mut data = ftyp;
+        data.extend_from_slice(&mdat);
+        data.extend_from_slice(&moov);
+        (data, mdat_offset)
+    }
+
+    #[test]
+    fn relocates_moov_ahead_of_mdat_and_patches_stco() {
+        let (input, _) = non_faststart_mp4(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        let mut stream = MemorySeekableStream::new(input);
+
+        let out = optimize_for_streaming(&mut stream).unwrap();
+
+        let mut reader = SliceStream(&out);
+        let len = reader.len().unwrap();
+        let top_level = boxes::children(&mut reader, 0, len).unwrap();
+        let names: Vec<&[u8; 4]> = top_level.iter().map(|b| &b.box_type).collect();
+        assert_eq!(names, vec![b"ftyp", b"moov", b"mdat"]);
+
+        let moov = top_level.iter().find(|b| &b.box_type == b"moov").unwrap();
+        let stco = boxes::find_all_recursive(&mut reader, moov.body_start, moov.end, b"stco").unwrap();
+        let stco = &stco[0];
+
+        let mut count_buf = [0u8; 4];
+        reader.read_at(stco.body_start + 4, &mut count_buf).unwrap();
+        assert_eq!(u32::from_be_bytes(count_buf), 1);
+
+        let mut value_buf = [0u8; 4];
+        reader.read_at(stco.body_start + 8, &mut value_buf).unwrap();
+        let patched_offset = u32::from_be_bytes(value_buf) as u64;
+
+        let mdat = top_level.iter().find(|b| &b.box_type == b"mdat").unwrap();
+        assert_eq!(patched_offset, mdat.body_start);
+    }
+
+    #[test]
+    fn patches_co64_tables_too() {
+        let ftyp = write_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mdat_body = [9u8; 4];
+        let mdat = write_box(b"mdat", &mdat_body);
+        let mdat_offset = (ftyp.len() + 8) as u64;
+
+        let co64 = build_co64(&[mdat_offset]);
+        let stbl = write_box(b"stbl", &co64);
+        let minf = write_box(b"minf", &stbl);
+        let mdia = write_box(b"mdia", &minf);
+        let trak = write_box(b"trak", &mdia);
+        let moov = write_box(b"moov", &trak);
+
+        let mut input = ftyp;
+        input.extend_from_slice(&mdat);
+        input.extend_from_slice(&moov);
+
+        let mut stream = MemorySeekableStream::new(input);
+        let out = optimize_for_streaming(&mut stream).unwrap();
+
+        let mut reader = SliceStream(&out);
+        let len = reader.len().unwrap();
+        let top_level = boxes::children(&mut reader, 0, len).unwrap();
+        let moov = top_level.iter().find(|b| &b.box_type == b"moov").unwrap();
+        let co64 = &boxes::find_all_recursive(&mut reader, moov.body_start, moov.end, b"co64").unwrap()[0];
+
+        let mut value_buf = [0u8; 8];
+        reader.read_at(co64.body_start + 8, &mut value_buf).unwrap();
+        let patched_offset = u64::from_be_bytes(value_buf);
+
+        let mdat = top_level.iter().find(|b| &b.box_type == b"mdat").unwrap();
+        assert_eq!(patched_offset, mdat.body_start);
+    }
+
+    #[test]
+    fn leaves_an_already_faststart_file_unchanged() {
+        let ftyp = write_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let moov = write_box(b"moov", &[]);
+        let mdat = write_box(b"mdat", &[1, 2, 3, 4]);
+
+        let mut input = ftyp;
+        input.extend_from_slice(&moov);
+        input.extend_from_slice(&mdat);
+
+        let mut stream = MemorySeekableStream::new(input.clone());
+        let out = optimize_for_streaming(&mut stream).unwrap();
+
+        assert_eq!(out, input);
+    }
+
+    #[test]
+    fn fails_without_a_moov_box() {
+        let ftyp = write_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mdat = write_box(b"mdat", &[1, 2, 3, 4]);
+
+        let mut input = ftyp;
+        input.extend_from_slice(&mdat);
+
+        let mut stream = MemorySeekableStream::new(input);
+        let err = optimize_for_streaming(&mut stream).unwrap_err();
+        assert!(matches!(err, Error::Malformed { .. }));
+    }
+}
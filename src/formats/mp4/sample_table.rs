@@ -0,0 +1,720 @@
+//! Lazy sample-table reads, so looking up one sample's size or timestamp
+//! doesn't require decoding a multi-million-entry `stsz`/`stts` table into
+//! a `Vec` first.
+
+use super::boxes::{self, BoxHeader};
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+
+/// How strictly [`SampleTable`] treats an otherwise-recoverable inconsistency
+/// in a track's sample tables, e.g. an `stts` whose runs don't add up to
+/// `stsz`'s declared sample count. Most malformed-table conditions (a sample
+/// index past `stsz`'s count, a missing `stsc`/`stco`) are still always
+/// reported as errors regardless of mode -- this only covers the handful of
+/// cases where clamping to a best-effort answer is a meaningful alternative
+/// to giving up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Report any inconsistency as an [`Error::Malformed`]. The default.
+    #[default]
+    Strict,
+    /// Clamp to the best available answer instead of erroring, for callers
+    /// that would rather get an approximate result than none at all from a
+    /// file produced by a muxer with minor table bugs.
+    Lenient,
+}
+
+/// Size and timing info for one track's samples, read from its `stsz` and
+/// `stts` boxes on demand rather than up front.
+pub struct SampleTable {
+    sample_size: u32,
+    sample_count: u32,
+    stsz_table_start: u64,
+    stts: BoxHeader,
+    /// `stsc` (sample-to-chunk) and `stco`/`co64` (chunk offsets), needed
+    /// by [`SampleTable::offset`]. `None` if the track had neither chunk
+    /// box, which [`SampleTable::offset`] then reports as `Unsupported`
+    /// rather than refusing to build the rest of the table.
+    chunks: Option<ChunkLayout>,
+    /// `stss` (sync sample table), needed by [`SampleTable::is_sync_sample`]
+    /// and [`SampleTable::preceding_sync_sample`]. `None` means the track
+    /// has no `stss`, which per spec means every sample is a sync sample.
+    sync_samples: Option<BoxHeader>,
+    mode: ParseMode,
+}
+
+struct ChunkLayout {
+    stsc: BoxHeader,
+    /// The chunk-offset box and whether its entries are 8-byte (`co64`)
+    /// rather than the usual 4-byte (`stco`).
+    stco: BoxHeader,
+    stco_is_64_bit: bool,
+}
+
+impl SampleTable {
+    /// Reads the sample table of the `track_index`-th `trak` (0-based, in
+    /// file order), returning `None` if there's no such track or it has no
+    /// `stsz`/`stts` boxes. Equivalent to [`Self::for_track_with_mode`] with
+    /// [`ParseMode::Strict`].
+    pub fn for_track<S: SeekableStream>(stream: &mut S, track_index: usize) -> Result<Option<Self>> {
+        Self::for_track_with_mode(stream, track_index, ParseMode::Strict)
+    }
+
+    /// [`Self::for_track`], with [`ParseMode::Lenient`] controlling how
+    /// later lookups respond to an inconsistent sample table instead of
+    /// always erroring.
+    pub fn for_track_with_mode<S: SeekableStream>(stream: &mut S, track_index: usize, mode: ParseMode) -> Result<Option<Self>> {
+        let len = stream.len()?;
+        let top_level = boxes::children(stream, 0, len)?;
+        let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else { return Ok(None) };
+
+        let traks: Vec<_> =
+            boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak").collect();
+        let Some(trak) = traks.get(track_index) else { return Ok(None) };
+
+        let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { return Ok(None) };
+        let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else { return Ok(None) };
+        let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else { return Ok(None) };
+
+        Self::from_stbl(stream, &stbl, mode)
+    }
+
+    /// Reads the `stsz`/`stts`/`stsc`/`stco`/`co64` box headers (not their
+    /// entry tables) out of `stbl`, returning `None` if `stsz` or `stts` is
+    /// missing.
+    pub(crate) fn from_stbl<S: SeekableStream>(stream: &mut S, stbl: &BoxHeader, mode: ParseMode) -> Result<Option<Self>> {
+        let Some(stsz) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsz")? else { return Ok(None) };
+        let Some(stts) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stts")? else { return Ok(None) };
+
+        // stsz is a full box: version/flags (4), sample_size (4),
+        // sample_count (4), then (if sample_size == 0) one 4-byte entry
+        // per sample.
+        let mut header = [0u8; 8];
+        stream.read_at(stsz.body_start + 4, &mut header)?;
+        let sample_size = u32::from_be_bytes(header[0..4].try_into().unwrap());
+        let sample_count = u32::from_be_bytes(header[4..8].try_into().unwrap());
+
+        let chunks = match boxes::find_child(stream, stbl.body_start, stbl.end, b"stsc")? {
+            Some(stsc) => {
+                let stco = boxes::find_child(stream, stbl.body_start, stbl.end, b"stco")?;
+                let co64 = boxes::find_child(stream, stbl.body_start, stbl.end, b"co64")?;
+                match (stco, co64) {
+                    (Some(stco), _) => Some(ChunkLayout { stsc, stco, stco_is_64_bit: false }),
+                    (None, Some(co64)) => Some(ChunkLayout { stsc, stco: co64, stco_is_64_bit: true }),
+                    (None, None) => None,
+                }
+            }
+            None => None,
+        };
+
+        let sync_samples = boxes::find_child(stream, stbl.body_start, stbl.end, b"stss")?;
+
+        Ok(Some(Self {
+            sample_size,
+            sample_count,
+            stsz_table_start: stsz.body_start + 12,
+            stts,
+            chunks,
+            sync_samples,
+            mode,
+        }))
+    }
+
+    /// The number of samples in the track, per `stsz`.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// The size in bytes of sample `index` (0-based), reading only that
+    /// one entry out of `stsz` when samples aren't all the same size.
+    pub fn size<S: SeekableStream>(&self, stream: &mut S, index: u32) -> Result<u32> {
+        if self.sample_size != 0 {
+            return Ok(self.sample_size);
+        }
+        if index >= self.sample_count {
+            return Err(Error::Malformed { format: "mp4", reason: "sample index out of range".into() });
+        }
+
+        let mut buf = [0u8; 4];
+        stream.read_at(self.stsz_table_start + u64::from(index) * 4, &mut buf)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    /// The decode timestamp of sample `index` (0-based), in the track's
+    /// timescale, reading only as many `stts` run entries as needed to
+    /// reach it rather than expanding the whole table up front.
+    ///
+    /// In [`ParseMode::Lenient`], an `stts` whose runs don't reach `index`
+    /// (a muxer undercounted samples) returns the last timestamp the table
+    /// does cover instead of erroring.
+    pub fn timestamp<S: SeekableStream>(&self, stream: &mut S, index: u32) -> Result<u64> {
+        if index >= self.sample_count {
+            return Err(Error::Malformed { format: "mp4", reason: "sample index out of range".into() });
+        }
+
+        // stts is a full box: version/flags (4), entry_count (4), then
+        // (sample_count, sample_delta) pairs of 4-byte fields each.
+        let mut entry_count_buf = [0u8; 4];
+        stream.read_at(self.stts.body_start + 4, &mut entry_count_buf)?;
+        let entry_count = u32::from_be_bytes(entry_count_buf);
+
+        let mut remaining = index;
+        let mut timestamp = 0u64;
+        let mut offset = self.stts.body_start + 8;
+        for _ in 0..entry_count {
+            let mut entry = [0u8; 8];
+            stream.read_at(offset, &mut entry)?;
+            let run_count = u32::from_be_bytes(entry[0..4].try_into().unwrap());
+            let run_delta = u64::from(u32::from_be_bytes(entry[4..8].try_into().unwrap()));
+
+            if remaining < run_count {
+                return Ok(timestamp + u64::from(remaining) * run_delta);
+            }
+            remaining -= run_count;
+            timestamp += u64::from(run_count) * run_delta;
+            offset += 8;
+        }
+
+        if self.mode == ParseMode::Lenient {
+            return Ok(timestamp);
+        }
+        Err(Error::Malformed { format: "mp4", reason: "stts entries don't cover this sample index".into() })
+    }
+
+    /// Reads every `stts` run as `(sample_count, sample_delta)` pairs, for
+    /// a caller (like [`crate::analysis::track_frame_rate`]) that needs
+    /// the track's distinct inter-sample deltas rather than each sample's
+    /// resolved timestamp -- the runs *are* the distinct deltas, so
+    /// there's no need to walk every sample via [`SampleTable::timestamp`].
+    pub(crate) fn stts_runs<S: SeekableStream>(&self, stream: &mut S) -> Result<Vec<(u32, u32)>> {
+        let mut entry_count_buf = [0u8; 4];
+        stream.read_at(self.stts.body_start + 4, &mut entry_count_buf)?;
+        let entry_count = u32::from_be_bytes(entry_count_buf);
+
+        // Each entry is 8 bytes -- reject an entry_count the box couldn't
+        // possibly hold before sizing a `Vec` off it.
+        let available = self.stts.end.saturating_sub(self.stts.body_start + 8);
+        if u64::from(entry_count) > available / 8 {
+            return Err(Error::Malformed { format: "mp4", reason: "stts entry_count exceeds the box's available data".into() });
+        }
+
+        let mut runs = Vec::with_capacity(entry_count as usize);
+        let mut offset = self.stts.body_start + 8;
+        for _ in 0..entry_count {
+            let mut entry = [0u8; 8];
+            stream.read_at(offset, &mut entry)?;
+            let run_count = u32::from_be_bytes(entry[0..4].try_into().unwrap());
+            let run_delta = u32::from_be_bytes(entry[4..8].try_into().unwrap());
+            runs.push((run_count, run_delta));
+            offset += 8;
+        }
+        Ok(runs)
+    }
+
+    /// The index of the sample covering `target_timestamp` (in the track's
+    /// timescale, same units as [`SampleTable::timestamp`]), i.e. the last
+    /// sample whose timestamp is `<= target_timestamp`. Clamps to the last
+    /// sample if `target_timestamp` is at or past the track's end.
+    pub fn sample_at_timestamp<S: SeekableStream>(&self, stream: &mut S, target_timestamp: u64) -> Result<u32> {
+        if self.sample_count == 0 {
+            return Err(Error::Malformed { format: "mp4", reason: "track has no samples".into() });
+        }
+
+        let mut entry_count_buf = [0u8; 4];
+        stream.read_at(self.stts.body_start + 4, &mut entry_count_buf)?;
+        let entry_count = u32::from_be_bytes(entry_count_buf);
+
+        let mut index = 0u32;
+        let mut timestamp = 0u64;
+        let mut offset = self.stts.body_start + 8;
+        for _ in 0..entry_count {
+            let mut entry = [0u8; 8];
+            stream.read_at(offset, &mut entry)?;
+            let run_count = u32::from_be_bytes(entry[0..4].try_into().unwrap());
+            let run_delta = u64::from(u32::from_be_bytes(entry[4..8].try_into().unwrap()));
+
+            for _ in 0..run_count {
+                if timestamp > target_timestamp {
+                    return Ok(index - 1);
+                }
+                index += 1;
+                timestamp += run_delta;
+            }
+            offset += 8;
+        }
+
+        Ok(self.sample_count - 1)
+    }
+
+    /// The absolute byte offset of sample `index` (0-based) in the stream,
+    /// resolved by walking `stsc`'s chunk runs and `stco`/`co64`'s chunk
+    /// offsets, summing only the sizes of the samples that precede it
+    /// within its own chunk.
+    pub fn offset<S: SeekableStream>(&self, stream: &mut S, index: u32) -> Result<u64> {
+        let Some(chunks) = &self.chunks else {
+            return Err(Error::Unsupported("track has no stsc/stco or co64 chunk layout".into()));
+        };
+        if index >= self.sample_count {
+            return Err(Error::Malformed { format: "mp4", reason: "sample index out of range".into() });
+        }
+
+        let chunk_offset_entry_size: u64 = if chunks.stco_is_64_bit { 8 } else { 4 };
+        let mut chunk_count_buf = [0u8; 4];
+        stream.read_at(chunks.stco.body_start + 4, &mut chunk_count_buf)?;
+        let chunk_count = u32::from_be_bytes(chunk_count_buf);
+
+        // stsc is a full box: version/flags (4), entry_count (4), then
+        // (first_chunk, samples_per_chunk, sample_description_index)
+        // triples of 4-byte fields each. Each entry's run covers chunks
+        // [first_chunk, next_entry.first_chunk) (or through chunk_count
+        // for the last entry).
+        let mut stsc_entry_count_buf = [0u8; 4];
+        stream.read_at(chunks.stsc.body_start + 4, &mut stsc_entry_count_buf)?;
+        let stsc_entry_count = u32::from_be_bytes(stsc_entry_count_buf);
+
+        let mut remaining = index;
+        let mut chunk_index = None;
+        let mut first_sample_of_chunk = 0u32;
+        for i in 0..stsc_entry_count {
+            let mut entry = [0u8; 12];
+            stream.read_at(chunks.stsc.body_start + 8 + u64::from(i) * 12, &mut entry)?;
+            let first_chunk = u32::from_be_bytes(entry[0..4].try_into().unwrap());
+            let samples_per_chunk = u32::from_be_bytes(entry[4..8].try_into().unwrap());
+
+            let next_first_chunk = if i + 1 < stsc_entry_count {
+                let mut next = [0u8; 4];
+                stream.read_at(chunks.stsc.body_start + 8 + u64::from(i + 1) * 12, &mut next)?;
+                u32::from_be_bytes(next)
+            } else {
+                chunk_count + 1
+            };
+
+            let run_chunks = u64::from(next_first_chunk - first_chunk);
+            let run_samples = run_chunks * u64::from(samples_per_chunk);
+            if u64::from(remaining) < run_samples {
+                let chunks_into_run = u64::from(remaining) / u64::from(samples_per_chunk);
+                chunk_index = Some(first_chunk + chunks_into_run as u32);
+                first_sample_of_chunk = index - (remaining % samples_per_chunk);
+                break;
+            }
+            remaining -= run_samples as u32;
+        }
+
+        let Some(chunk_index) = chunk_index else {
+            return Err(Error::Malformed { format: "mp4", reason: "stsc entries don't cover this sample index".into() });
+        };
+
+        let mut chunk_offset_buf = [0u8; 8];
+        stream.read_at(
+            chunks.stco.body_start + 8 + u64::from(chunk_index - 1) * chunk_offset_entry_size,
+            &mut chunk_offset_buf[8 - chunk_offset_entry_size as usize..],
+        )?;
+        let chunk_offset = u64::from_be_bytes(chunk_offset_buf);
+
+        let mut offset_in_chunk = 0u64;
+        for sample in first_sample_of_chunk..index {
+            offset_in_chunk += u64::from(self.size(stream, sample)?);
+        }
+
+        Ok(chunk_offset + offset_in_chunk)
+    }
+
+    /// Resolves every sample's offset in one forward pass over `stsc`/
+    /// `stco`, for a caller (like [`crate::formats::mp4::validate::validate`])
+    /// that needs every sample's offset rather than one at a time --
+    /// calling [`SampleTable::offset`] in a loop is O(n^2) over a chunk's
+    /// samples, since each call re-sums the sizes of every sample
+    /// preceding it in its chunk from scratch.
+    ///
+    /// An entry is `None` for a sample `stsc`'s runs don't reach, the same
+    /// condition [`SampleTable::offset`] reports as [`Error::Malformed`]
+    /// for a single index -- one bad tail doesn't abort the whole pass.
+    pub(crate) fn offsets<S: SeekableStream>(&self, stream: &mut S) -> Result<Vec<Option<u64>>> {
+        let Some(chunks) = &self.chunks else {
+            return Ok(vec![None; self.sample_count as usize]);
+        };
+
+        let chunk_offset_entry_size: u64 = if chunks.stco_is_64_bit { 8 } else { 4 };
+        let mut chunk_count_buf = [0u8; 4];
+        stream.read_at(chunks.stco.body_start + 4, &mut chunk_count_buf)?;
+        let chunk_count = u32::from_be_bytes(chunk_count_buf);
+
+        let mut stsc_entry_count_buf = [0u8; 4];
+        stream.read_at(chunks.stsc.body_start + 4, &mut stsc_entry_count_buf)?;
+        let stsc_entry_count = u32::from_be_bytes(stsc_entry_count_buf);
+
+        let mut offsets = vec![None; self.sample_count as usize];
+        let mut sample_index = 0u32;
+
+        for i in 0..stsc_entry_count {
+            if sample_index >= self.sample_count {
+                break;
+            }
+            let mut entry = [0u8; 12];
+            stream.read_at(chunks.stsc.body_start + 8 + u64::from(i) * 12, &mut entry)?;
+            let first_chunk = u32::from_be_bytes(entry[0..4].try_into().unwrap());
+            let samples_per_chunk = u32::from_be_bytes(entry[4..8].try_into().unwrap());
+
+            let next_first_chunk = if i + 1 < stsc_entry_count {
+                let mut next = [0u8; 4];
+                stream.read_at(chunks.stsc.body_start + 8 + u64::from(i + 1) * 12, &mut next)?;
+                u32::from_be_bytes(next)
+            } else {
+                chunk_count + 1
+            };
+
+            for chunk_index in first_chunk..next_first_chunk {
+                if sample_index >= self.sample_count {
+                    break;
+                }
+
+                let mut chunk_offset_buf = [0u8; 8];
+                stream.read_at(
+                    chunks.stco.body_start + 8 + u64::from(chunk_index - 1) * chunk_offset_entry_size,
+                    &mut chunk_offset_buf[8 - chunk_offset_entry_size as usize..],
+                )?;
+                let mut offset = u64::from_be_bytes(chunk_offset_buf);
+
+                for _ in 0..samples_per_chunk {
+                    if sample_index >= self.sample_count {
+                        break;
+                    }
+                    offsets[sample_index as usize] = Some(offset);
+                    offset += u64::from(self.size(stream, sample_index)?);
+                    sample_index += 1;
+                }
+            }
+        }
+
+        Ok(offsets)
+    }
+
+    /// Whether sample `index` (0-based) is a sync sample (e.g. an IDR
+    /// frame), per `stss`. A track with no `stss` box has every sample as
+    /// a sync sample, per spec.
+    pub fn is_sync_sample<S: SeekableStream>(&self, stream: &mut S, index: u32) -> Result<bool> {
+        let Some(stss) = &self.sync_samples else { return Ok(true) };
+        if index >= self.sample_count {
+            return Err(Error::Malformed { format: "mp4", reason: "sample index out of range".into() });
+        }
+
+        let sample_number = index + 1; // stss entries are 1-based
+        let entry_count = Self::read_stss_entry_count(stream, stss)?;
+        let mut lo = 0u32;
+        let mut hi = entry_count;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            match Self::read_stss_entry(stream, stss, mid)?.cmp(&sample_number) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return Ok(true),
+            }
+        }
+        Ok(false)
+    }
+
+    /// The nearest sync sample at or before `index` (0-based), for decoding
+    /// forward to an exact, possibly non-sync, target frame. A track with
+    /// no `stss` box has every sample as a sync sample, so this just
+    /// returns `index` unchanged.
+    pub fn preceding_sync_sample<S: SeekableStream>(&self, stream: &mut S, index: u32) -> Result<u32> {
+        let Some(stss) = &self.sync_samples else { return Ok(index) };
+        if index >= self.sample_count {
+            return Err(Error::Malformed { format: "mp4", reason: "sample index out of range".into() });
+        }
+
+        let sample_number = index + 1; // stss entries are 1-based
+        let entry_count = Self::read_stss_entry_count(stream, stss)?;
+        if entry_count == 0 {
+            return Err(Error::Malformed { format: "mp4", reason: "stss has no sync samples".into() });
+        }
+
+        // Binary search for the largest entry <= sample_number.
+        let mut lo = 0u32;
+        let mut hi = entry_count;
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            if Self::read_stss_entry(stream, stss, mid)? <= sample_number {
+                lo = mid + 1;
+            } else {
+                hi = mid;
+            }
+        }
+        if lo == 0 {
+            return Err(Error::Malformed { format: "mp4", reason: "no sync sample precedes this sample index".into() });
+        }
+        Ok(Self::read_stss_entry(stream, stss, lo - 1)? - 1) // back to 0-based
+    }
+
+    /// `stss` is a full box: version/flags (4), entry_count (4), then one
+    /// 4-byte 1-based sample number per entry, in ascending order.
+    fn read_stss_entry_count<S: SeekableStream>(stream: &mut S, stss: &BoxHeader) -> Result<u32> {
+        let mut buf = [0u8; 4];
+        stream.read_at(stss.body_start + 4, &mut buf)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+
+    fn read_stss_entry<S: SeekableStream>(stream: &mut S, stss: &BoxHeader, entry_index: u32) -> Result<u32> {
+        let mut buf = [0u8; 4];
+        stream.read_at(stss.body_start + 8 + u64::from(entry_index) * 4, &mut buf)?;
+        Ok(u32::from_be_bytes(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn build_stsz(sizes: &[u32]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&0u32.to_be_bytes()); // sample_size == 0: per-sample table follows
+        body.extend_from_slice(&(sizes.len() as u32).to_be_bytes());
+        for size in sizes {
+            body.extend_from_slice(&size.to_be_bytes());
+        }
+        sized_box(b"stsz", &body)
+    }
+
+    fn build_stts(runs: &[(u32, u32)]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&(runs.len() as u32).to_be_bytes());
+        for (count, delta) in runs {
+            body.extend_from_slice(&count.to_be_bytes());
+            body.extend_from_slice(&delta.to_be_bytes());
+        }
+        sized_box(b"stts", &body)
+    }
+
+    fn sample_stbl(sizes: &[u32], runs: &[(u32, u32)]) -> Vec<u8> {
+        let mut body = build_stsz(sizes);
+        body.extend_from_slice(&build_stts(runs));
+        sized_box(b"stbl", &body)
+    }
+
+    fn build_stsc(entries: &[(u32, u32)]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        for (first_chunk, samples_per_chunk) in entries {
+            body.extend_from_slice(&first_chunk.to_be_bytes());
+            body.extend_from_slice(&samples_per_chunk.to_be_bytes());
+            body.extend_from_slice(&1u32.to_be_bytes()); // sample_description_index
+        }
+        sized_box(b"stsc", &body)
+    }
+
+    fn build_stco(chunk_offsets: &[u32]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&(chunk_offsets.len() as u32).to_be_bytes());
+        for offset in chunk_offsets {
+            body.extend_from_slice(&offset.to_be_bytes());
+        }
+        sized_box(b"stco", &body)
+    }
+
+    fn build_stss(sync_samples: &[u32]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&(sync_samples.len() as u32).to_be_bytes());
+        for sample_number in sync_samples {
+            body.extend_from_slice(&sample_number.to_be_bytes());
+        }
+        sized_box(b"stss", &body)
+    }
+
+    fn sample_stbl_with_sync_samples(sizes: &[u32], sync_samples: &[u32]) -> Vec<u8> {
+        let mut body = build_stsz(sizes);
+        body.extend_from_slice(&build_stts(&[(sizes.len() as u32, 1000)]));
+        body.extend_from_slice(&build_stss(sync_samples));
+        sized_box(b"stbl", &body)
+    }
+
+    fn sample_stbl_with_chunks(sizes: &[u32], stsc_entries: &[(u32, u32)], chunk_offsets: &[u32]) -> Vec<u8> {
+        let mut body = build_stsz(sizes);
+        body.extend_from_slice(&build_stts(&[(sizes.len() as u32, 1000)]));
+        body.extend_from_slice(&build_stsc(stsc_entries));
+        body.extend_from_slice(&build_stco(chunk_offsets));
+        sized_box(b"stbl", &body)
+    }
+
+    #[test]
+    fn looks_up_per_sample_sizes_without_materializing_the_whole_table() {
+        let data = sample_stbl(&[10, 20, 30], &[(3, 1000)]);
+        let mut stream = MemorySeekableStream::new(data.clone());
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert_eq!(table.sample_count(), 3);
+        assert_eq!(table.size(&mut stream, 0).unwrap(), 10);
+        assert_eq!(table.size(&mut stream, 2).unwrap(), 30);
+        assert!(table.size(&mut stream, 3).is_err());
+    }
+
+    #[test]
+    fn walks_stts_runs_to_find_a_sample_timestamp() {
+        let data = sample_stbl(&[1, 1, 1, 1, 1], &[(2, 1000), (3, 500)]);
+        let mut stream = MemorySeekableStream::new(data.clone());
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert_eq!(table.timestamp(&mut stream, 0).unwrap(), 0);
+        assert_eq!(table.timestamp(&mut stream, 1).unwrap(), 1000);
+        assert_eq!(table.timestamp(&mut stream, 2).unwrap(), 2000);
+        assert_eq!(table.timestamp(&mut stream, 4).unwrap(), 2000 + 2 * 500);
+    }
+
+    #[test]
+    fn finds_the_sample_covering_a_target_timestamp() {
+        let data = sample_stbl(&[1, 1, 1, 1, 1], &[(2, 1000), (3, 500)]);
+        let mut stream = MemorySeekableStream::new(data);
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert_eq!(table.sample_at_timestamp(&mut stream, 0).unwrap(), 0);
+        assert_eq!(table.sample_at_timestamp(&mut stream, 999).unwrap(), 0);
+        assert_eq!(table.sample_at_timestamp(&mut stream, 1500).unwrap(), 1);
+        assert_eq!(table.sample_at_timestamp(&mut stream, 2000).unwrap(), 2);
+        assert_eq!(table.sample_at_timestamp(&mut stream, 100_000).unwrap(), 4);
+    }
+
+    #[test]
+    fn returns_none_when_either_box_is_missing() {
+        let stbl_body = build_stsz(&[1, 2]);
+        let data = sized_box(b"stbl", &stbl_body);
+        let mut stream = MemorySeekableStream::new(data);
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+
+        assert!(SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().is_none());
+    }
+
+    #[test]
+    fn resolves_sample_offsets_across_chunks_of_varying_size() {
+        // 5 samples of size 10 each, in chunks of 2 samples then 1 sample
+        // per chunk from chunk 2 onward, at chunk base offsets
+        // 1000/1030/1050/1060.
+        let data = sample_stbl_with_chunks(&[10, 10, 10, 10, 10], &[(1, 2), (2, 1)], &[1000, 1030, 1050, 1060]);
+        let mut stream = MemorySeekableStream::new(data.clone());
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert_eq!(table.offset(&mut stream, 0).unwrap(), 1000);
+        assert_eq!(table.offset(&mut stream, 1).unwrap(), 1010);
+        assert_eq!(table.offset(&mut stream, 2).unwrap(), 1030);
+        assert_eq!(table.offset(&mut stream, 3).unwrap(), 1050);
+        assert_eq!(table.offset(&mut stream, 4).unwrap(), 1060);
+    }
+
+    #[test]
+    fn offset_reports_unsupported_without_a_chunk_layout() {
+        let data = sample_stbl(&[1, 2], &[(2, 1000)]);
+        let mut stream = MemorySeekableStream::new(data.clone());
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert!(matches!(table.offset(&mut stream, 0), Err(Error::Unsupported(_))));
+    }
+
+    #[test]
+    fn offsets_matches_offset_called_one_sample_at_a_time() {
+        let data = sample_stbl_with_chunks(&[10, 10, 10, 10, 10], &[(1, 2), (2, 1)], &[1000, 1030, 1050, 1060]);
+        let mut stream = MemorySeekableStream::new(data.clone());
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        let offsets = table.offsets(&mut stream).unwrap();
+        let expected: Vec<Option<u64>> =
+            (0..table.sample_count()).map(|i| Some(table.offset(&mut stream, i).unwrap())).collect();
+        assert_eq!(offsets, expected);
+    }
+
+    #[test]
+    fn offsets_is_all_none_without_a_chunk_layout() {
+        let data = sample_stbl(&[1, 2], &[(2, 1000)]);
+        let mut stream = MemorySeekableStream::new(data.clone());
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert_eq!(table.offsets(&mut stream).unwrap(), vec![None; 2]);
+    }
+
+    #[test]
+    fn every_sample_is_a_sync_sample_without_an_stss_box() {
+        let data = sample_stbl(&[1, 1, 1], &[(3, 1000)]);
+        let mut stream = MemorySeekableStream::new(data);
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert!(table.is_sync_sample(&mut stream, 1).unwrap());
+        assert_eq!(table.preceding_sync_sample(&mut stream, 1).unwrap(), 1);
+    }
+
+    #[test]
+    fn looks_up_sync_samples_from_stss() {
+        // Samples 0 and 3 (1-based 1 and 4) are sync samples; 1 and 2 are not.
+        let data = sample_stbl_with_sync_samples(&[1, 1, 1, 1], &[1, 4]);
+        let mut stream = MemorySeekableStream::new(data);
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert!(table.is_sync_sample(&mut stream, 0).unwrap());
+        assert!(!table.is_sync_sample(&mut stream, 1).unwrap());
+        assert!(!table.is_sync_sample(&mut stream, 2).unwrap());
+        assert!(table.is_sync_sample(&mut stream, 3).unwrap());
+
+        assert_eq!(table.preceding_sync_sample(&mut stream, 0).unwrap(), 0);
+        assert_eq!(table.preceding_sync_sample(&mut stream, 2).unwrap(), 0);
+        assert_eq!(table.preceding_sync_sample(&mut stream, 3).unwrap(), 3);
+    }
+
+    #[test]
+    fn preceding_sync_sample_errors_when_nothing_comes_before_it() {
+        let data = sample_stbl_with_sync_samples(&[1, 1, 1], &[2]);
+        let mut stream = MemorySeekableStream::new(data);
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert!(matches!(table.preceding_sync_sample(&mut stream, 0), Err(Error::Malformed { .. })));
+        assert_eq!(table.preceding_sync_sample(&mut stream, 1).unwrap(), 1);
+    }
+
+    #[test]
+    fn strict_mode_errors_when_stts_runs_dont_cover_every_sample() {
+        // stsz declares 5 samples, but stts' runs only account for 3.
+        let data = sample_stbl(&[1, 1, 1, 1, 1], &[(3, 1000)]);
+        let mut stream = MemorySeekableStream::new(data);
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Strict).unwrap().unwrap();
+
+        assert!(matches!(table.timestamp(&mut stream, 4), Err(Error::Malformed { .. })));
+    }
+
+    #[test]
+    fn lenient_mode_clamps_to_the_last_covered_timestamp_instead_of_erroring() {
+        let data = sample_stbl(&[1, 1, 1, 1, 1], &[(3, 1000)]);
+        let mut stream = MemorySeekableStream::new(data);
+        let len = stream.len().unwrap();
+        let stbl = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+        let table = SampleTable::from_stbl(&mut stream, &stbl, ParseMode::Lenient).unwrap().unwrap();
+
+        assert_eq!(table.timestamp(&mut stream, 4).unwrap(), 3000);
+    }
+}
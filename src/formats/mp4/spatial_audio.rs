@@ -0,0 +1,59 @@
+//! Spatial/ambisonic audio metadata: the `SA3D` box Google's spatial audio
+//! spec (used by YouTube and most ambisonic authoring tools) adds to an
+//! audio sample entry alongside its codec box.
+
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+use super::boxes;
+
+/// Ambisonic metadata resolved from an `SA3D` box.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct SpatialAudioInfo {
+    pub order: Option<u32>,
+    pub channel_ordering: Option<String>,
+}
+
+/// Reads `SA3D` from an audio sample entry's extension boxes, the same
+/// span [`super::esds::read_aac_config`] scans for `esds`.
+///
+/// `SA3D` isn't a full box (no version/flags header): `version` (1 byte),
+/// `ambisonic_type` (1 byte), `ambisonic_order` (4 bytes), then
+/// `ambisonic_channel_ordering` (1 byte), `ambisonic_normalization` (1
+/// byte), `num_channels` (4 bytes), and a `num_channels`-long channel map
+/// this crate doesn't need.
+pub(crate) fn read_sa3d<S: SeekableStream>(stream: &mut S, entry_body_start: u64, entry_end: u64) -> Result<SpatialAudioInfo> {
+    let Some(sa3d) = boxes::find_child(stream, entry_body_start, entry_end, b"SA3D")? else {
+        return Ok(SpatialAudioInfo::default());
+    };
+    if sa3d.body_len() < 8 {
+        return Ok(SpatialAudioInfo::default());
+    }
+
+    let mut header = [0u8; 8];
+    stream.read_at(sa3d.body_start, &mut header)?;
+    let order = u32::from_be_bytes(header[2..6].try_into().unwrap());
+    let channel_ordering = channel_ordering_name(header[6]).map(str::to_string);
+
+    Ok(SpatialAudioInfo { order: Some(order), channel_ordering })
+}
+
+fn channel_ordering_name(ordering: u8) -> Option<&'static str> {
+    match ordering {
+        0 => Some("ACN"),
+        1 => Some("SID"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_known_channel_orderings() {
+        assert_eq!(channel_ordering_name(0), Some("ACN"));
+        assert_eq!(channel_ordering_name(1), Some("SID"));
+        assert_eq!(channel_ordering_name(9), None);
+    }
+}
@@ -0,0 +1,161 @@
+//! Spherical/360 video metadata: the Google Spherical Video V2 `st3d`/`sv3d`
+//! boxes carried in a video sample entry's extensions, and the legacy V1
+//! format's XML payload in a top-level `uuid` box, for files that predate
+//! V2 (most cameras that shipped before ~2017).
+//!
+//! Only the fields [`crate::metadata::Metadata`] actually surfaces are
+//! read: projection type, stereo layout, and (legacy XML only) initial
+//! view orientation. `sv3d`'s `proj` box carries per-projection geometry
+//! (e.g. `equi`'s bounds, `mesh`'s vertex data) this crate doesn't parse,
+//! since nothing downstream of `Metadata` consumes it yet.
+
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+use super::boxes;
+
+/// Spherical metadata resolved from either `st3d`/`sv3d` or the legacy
+/// XML `uuid` box.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SphericalInfo {
+    pub projection: Option<String>,
+    pub stereo_mode: Option<String>,
+    pub initial_view: Option<(f64, f64, f64)>,
+}
+
+/// Reads `st3d` (stereo mode) and `sv3d` (projection type) from a video
+/// sample entry's extension boxes, the same span [`super::codecs::video_codec_label`]
+/// scans for `avcC`/`hvcC`.
+pub(crate) fn read_st3d_sv3d<S: SeekableStream>(stream: &mut S, entry_body_start: u64, entry_end: u64) -> Result<SphericalInfo> {
+    let mut info = SphericalInfo::default();
+
+    if let Some(st3d) = boxes::find_child(stream, entry_body_start, entry_end, b"st3d")? {
+        let mut mode = [0u8; 1];
+        stream.read_at(st3d.body_start + 4, &mut mode)?;
+        info.stereo_mode = stereo_mode_name(mode[0]).map(str::to_string);
+    }
+
+    if let Some(sv3d) = boxes::find_child(stream, entry_body_start, entry_end, b"sv3d")? {
+        if let Some(proj) = boxes::find_child(stream, sv3d.body_start, sv3d.end, b"proj")? {
+            // `proj` is itself a container: a `proj_header` full box (not
+            // needed here) followed by exactly one projection-type box
+            // (`equi`, `cbmp`, or `mesh`) identifying the layout.
+            for child in boxes::children(stream, proj.body_start, proj.end)? {
+                if let Some(name) = projection_name(&child.box_type) {
+                    info.projection = Some(name.to_string());
+                    break;
+                }
+            }
+        }
+    }
+
+    Ok(info)
+}
+
+/// Reads the legacy Google Spherical Video V1 XML metadata out of a
+/// top-level `uuid` box, for files written before `st3d`/`sv3d` existed.
+pub(crate) fn read_legacy_xml<S: SeekableStream>(stream: &mut S, top_level: &[boxes::BoxHeader]) -> Result<SphericalInfo> {
+    for b in top_level.iter().filter(|b| &b.box_type == b"uuid") {
+        if boxes::read_uuid(stream, b)? != LEGACY_SPHERICAL_UUID {
+            continue;
+        }
+        let mut xml = vec![0u8; (b.end - b.body_start - 16) as usize];
+        stream.read_at(b.body_start + 16, &mut xml)?;
+        let xml = String::from_utf8_lossy(&xml);
+
+        return Ok(SphericalInfo {
+            projection: xml_tag_text(&xml, "GSpherical:ProjectionType"),
+            stereo_mode: xml_tag_text(&xml, "GSpherical:StereoMode"),
+            initial_view: read_initial_view(&xml),
+        });
+    }
+    Ok(SphericalInfo::default())
+}
+
+/// The UUID the legacy Google Spherical Video V1 metadata spec reserves for
+/// its XML payload (`ffcc8263-f855-4a93-8814-587a02521fdd`).
+pub(crate) const LEGACY_SPHERICAL_UUID: [u8; 16] =
+    [0xff, 0xcc, 0x82, 0x63, 0xf8, 0x55, 0x4a, 0x93, 0x88, 0x14, 0x58, 0x7a, 0x02, 0x52, 0x1f, 0xdd];
+
+fn stereo_mode_name(mode: u8) -> Option<&'static str> {
+    match mode {
+        0 => Some("mono"),
+        1 => Some("top-bottom"),
+        2 => Some("left-right"),
+        _ => None,
+    }
+}
+
+fn projection_name(box_type: &[u8; 4]) -> Option<&'static str> {
+    match box_type {
+        b"equi" => Some("equirectangular"),
+        b"cbmp" => Some("cubemap"),
+        b"mesh" => Some("mesh"),
+        _ => None,
+    }
+}
+
+fn read_initial_view(xml: &str) -> Option<(f64, f64, f64)> {
+    let yaw = xml_tag_text(xml, "GSpherical:InitialViewHeadingDegrees")?.parse().ok()?;
+    let pitch = xml_tag_text(xml, "GSpherical:InitialViewPitchDegrees")?.parse().ok()?;
+    let roll = xml_tag_text(xml, "GSpherical:InitialViewRollDegrees")?.parse().ok()?;
+    Some((yaw, pitch, roll))
+}
+
+/// Extracts the text content of `<tag>...</tag>` (namespace prefix
+/// included in `tag`), tolerating attributes on the opening tag. This
+/// crate bundles no XML parser, so it's a plain substring search -- good
+/// enough for the handful of flat, attribute-free elements the spherical
+/// video spec defines.
+fn xml_tag_text(xml: &str, tag: &str) -> Option<String> {
+    let open_start = xml.find(&format!("<{tag}"))?;
+    let open_end = xml[open_start..].find('>')? + open_start + 1;
+    let close = xml[open_end..].find(&format!("</{tag}>"))? + open_end;
+    Some(xml[open_end..close].trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_stereo_mode_from_an_st3d_box() {
+        assert_eq!(stereo_mode_name(0), Some("mono"));
+        assert_eq!(stereo_mode_name(1), Some("top-bottom"));
+        assert_eq!(stereo_mode_name(2), Some("left-right"));
+        assert_eq!(stereo_mode_name(9), None);
+    }
+
+    #[test]
+    fn reads_projection_type_from_a_proj_type_box() {
+        assert_eq!(projection_name(b"equi"), Some("equirectangular"));
+        assert_eq!(projection_name(b"cbmp"), Some("cubemap"));
+        assert_eq!(projection_name(b"mesh"), Some("mesh"));
+        assert_eq!(projection_name(b"abcd"), None);
+    }
+
+    #[test]
+    fn extracts_text_from_a_flat_xml_tag() {
+        let xml = r#"<rdf:SphericalVideo><GSpherical:ProjectionType>equirectangular</GSpherical:ProjectionType></rdf:SphericalVideo>"#;
+
+        assert_eq!(xml_tag_text(xml, "GSpherical:ProjectionType"), Some("equirectangular".to_string()));
+    }
+
+    #[test]
+    fn returns_none_for_a_missing_xml_tag() {
+        let xml = r#"<rdf:SphericalVideo></rdf:SphericalVideo>"#;
+
+        assert_eq!(xml_tag_text(xml, "GSpherical:StereoMode"), None);
+    }
+
+    #[test]
+    fn parses_initial_view_from_three_separate_tags() {
+        let xml = r#"
+            <GSpherical:InitialViewHeadingDegrees>90</GSpherical:InitialViewHeadingDegrees>
+            <GSpherical:InitialViewPitchDegrees>-10</GSpherical:InitialViewPitchDegrees>
+            <GSpherical:InitialViewRollDegrees>0</GSpherical:InitialViewRollDegrees>
+        "#;
+
+        assert_eq!(read_initial_view(xml), Some((90.0, -10.0, 0.0)));
+    }
+}
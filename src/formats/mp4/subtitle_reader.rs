@@ -0,0 +1,565 @@
+//! Decodes `tx3g`/`wvtt`/`c608` text-track samples into cue text, the read
+//! side of [`super::embed_subtitle_track`]'s mux (which only covers `tx3g`
+//! and `wvtt`; `c608` is read-only here). Also locates `mp4s` bitmap
+//! subtitle tracks (e.g. DVD subpicture), whose samples this module hands
+//! back still-encoded -- see [`crate::subtitle::decode_image_subtitle`] for
+//! why decoding them isn't implemented yet.
+
+use std::io;
+
+use super::boxes::{self, BoxHeader};
+use super::fragment_reader::{self, FragmentSample};
+use super::sample_table::{ParseMode, SampleTable};
+use super::SubtitleCodec;
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+use crate::subtitle::{SubtitleStyle, TextBoxPosition};
+
+/// A text track located under `moov`: its track ID and language (from
+/// `tkhd`/`mdhd`), samples (for per-sample size, offset, and timestamp
+/// lookups), media timescale, codec, and (for `tx3g`) the sample entry's
+/// default style, which each sample's own style records are layered on top
+/// of.
+pub(crate) struct TextTrack {
+    pub track_id: u32,
+    pub language: String,
+    pub samples: TrackSamples,
+    pub timescale: u32,
+    pub codec: SubtitleCodec,
+    pub default_style: Option<SubtitleStyle>,
+}
+
+/// Where a text track's samples live: a regular `stbl` sample table, or
+/// (for a fragmented/CMAF track, whose `stbl` carries no `stsz`/`stts`)
+/// samples recovered by walking the file's `moof`/`traf`/`trun` fragments
+/// up front. `decode_sample` and its callers don't need to know which --
+/// both variants resolve to the same (offset, size, timestamp) per index.
+pub(crate) enum TrackSamples {
+    Table(SampleTable),
+    Fragmented(Vec<FragmentSample>),
+}
+
+impl TrackSamples {
+    pub fn sample_count(&self) -> u32 {
+        match self {
+            Self::Table(table) => table.sample_count(),
+            Self::Fragmented(samples) => samples.len() as u32,
+        }
+    }
+
+    pub fn size<S: SeekableStream>(&self, stream: &mut S, index: u32) -> Result<u32> {
+        match self {
+            Self::Table(table) => table.size(stream, index),
+            Self::Fragmented(samples) => Self::fragment(samples, index).map(|s| s.size),
+        }
+    }
+
+    pub fn offset<S: SeekableStream>(&self, stream: &mut S, index: u32) -> Result<u64> {
+        match self {
+            Self::Table(table) => table.offset(stream, index),
+            Self::Fragmented(samples) => Self::fragment(samples, index).map(|s| s.offset),
+        }
+    }
+
+    pub fn timestamp<S: SeekableStream>(&self, stream: &mut S, index: u32) -> Result<u64> {
+        match self {
+            Self::Table(table) => table.timestamp(stream, index),
+            Self::Fragmented(samples) => Self::fragment(samples, index).map(|s| s.start_ticks),
+        }
+    }
+
+    fn fragment(samples: &[FragmentSample], index: u32) -> Result<&FragmentSample> {
+        samples.get(index as usize).ok_or(Error::Malformed { format: "mp4", reason: "sample index out of range".into() })
+    }
+}
+
+/// Locates the first `tx3g`, `wvtt`, or `c608` text track under `moov`. A
+/// thin convenience wrapper over [`find_all_text_tracks`] for callers (like
+/// [`crate::subtitle::stream_entries`]) that only care about one track.
+pub(crate) fn find_text_track<S: SeekableStream>(stream: &mut S) -> Result<Option<TextTrack>> {
+    Ok(find_all_text_tracks(stream)?.into_iter().next())
+}
+
+/// Locates every `tx3g`/`wvtt`/`c608` text track under `moov`, in file order, from
+/// a single scan of the box tree (rather than re-walking `moov` once per
+/// track).
+pub(crate) fn find_all_text_tracks<S: SeekableStream>(stream: &mut S) -> Result<Vec<TextTrack>> {
+    let len = stream.len()?;
+    let top_level = boxes::children(stream, 0, len)?;
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else { return Ok(Vec::new()) };
+
+    let mut tracks = Vec::new();
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        let Some(tkhd) = boxes::find_child(stream, trak.body_start, trak.end, b"tkhd")? else { continue };
+        let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { continue };
+        let Some(mdhd) = boxes::find_child(stream, mdia.body_start, mdia.end, b"mdhd")? else { continue };
+        let Some((timescale, _)) = super::read_timescale_and_duration(stream, &mdhd)? else { continue };
+        let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else { continue };
+        let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else { continue };
+        let Some(stsd) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsd")? else { continue };
+
+        // stsd is a full box: version/flags (4 bytes), entry count (4
+        // bytes), then the first sample entry.
+        let first_entry_start = stsd.body_start + 8;
+        if first_entry_start + 8 > stsd.end {
+            continue;
+        }
+
+        let Some(entry) = boxes::read_box_header(stream, first_entry_start, stsd.end)? else { continue };
+        let entry_type = entry.box_type;
+
+        let (codec, default_style) = match &entry_type {
+            b"tx3g" => (SubtitleCodec::Tx3g, parse_tx3g_default_style(stream, &entry)?),
+            b"wvtt" => (SubtitleCodec::Wvtt, None),
+            b"c608" => (SubtitleCodec::Cea608, None),
+            _ => continue,
+        };
+
+        let track_id = super::read_track_id(stream, &tkhd)?;
+        let samples = match SampleTable::from_stbl(stream, &stbl, ParseMode::Strict)? {
+            Some(table) => TrackSamples::Table(table),
+            // No stsz/stts: this track's samples live in per-fragment
+            // moof/traf/trun boxes instead (fragmented/CMAF), not stbl.
+            None => TrackSamples::Fragmented(fragment_reader::read_fragment_samples(stream, track_id)?),
+        };
+        let language = super::read_language(stream, &mdhd)?;
+        tracks.push(TextTrack { track_id, language, samples, timescale, codec, default_style });
+    }
+
+    Ok(tracks)
+}
+
+/// A bitmap subtitle track located under `moov`: its track ID, language, and
+/// samples. Unlike [`TextTrack`], there's no `codec`/`default_style` here --
+/// every sample is handed back still-encoded, since decoding `mp4s` bitmap
+/// subtitle payloads (e.g. DVD subpicture RLE data) isn't implemented.
+pub(crate) struct ImageSubtitleTrack {
+    pub track_id: u32,
+    pub language: String,
+    pub samples: TrackSamples,
+    pub timescale: u32,
+}
+
+/// Locates every `mp4s` bitmap subtitle track (e.g. DVD subpicture/VobSub
+/// muxed into MP4) under `moov`, in file order. Mirrors
+/// [`find_all_text_tracks`]'s walk, but matches a different sample entry
+/// type and has no text codec to record.
+pub(crate) fn find_all_image_subtitle_tracks<S: SeekableStream>(stream: &mut S) -> Result<Vec<ImageSubtitleTrack>> {
+    let len = stream.len()?;
+    let top_level = boxes::children(stream, 0, len)?;
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else { return Ok(Vec::new()) };
+
+    let mut tracks = Vec::new();
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        let Some(tkhd) = boxes::find_child(stream, trak.body_start, trak.end, b"tkhd")? else { continue };
+        let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { continue };
+        let Some(mdhd) = boxes::find_child(stream, mdia.body_start, mdia.end, b"mdhd")? else { continue };
+        let Some((timescale, _)) = super::read_timescale_and_duration(stream, &mdhd)? else { continue };
+        let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else { continue };
+        let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else { continue };
+        let Some(stsd) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsd")? else { continue };
+
+        // stsd is a full box: version/flags (4 bytes), entry count (4
+        // bytes), then the first sample entry.
+        let first_entry_start = stsd.body_start + 8;
+        if first_entry_start + 8 > stsd.end {
+            continue;
+        }
+
+        let Some(entry) = boxes::read_box_header(stream, first_entry_start, stsd.end)? else { continue };
+        if &entry.box_type != b"mp4s" {
+            continue;
+        }
+
+        let track_id = super::read_track_id(stream, &tkhd)?;
+        let samples = match SampleTable::from_stbl(stream, &stbl, ParseMode::Strict)? {
+            Some(table) => TrackSamples::Table(table),
+            None => TrackSamples::Fragmented(fragment_reader::read_fragment_samples(stream, track_id)?),
+        };
+        let language = super::read_language(stream, &mdhd)?;
+        tracks.push(ImageSubtitleTrack { track_id, language, samples, timescale });
+    }
+
+    Ok(tracks)
+}
+
+/// Reads a `tx3g` sample entry's default style: its `BoxRecord` text box and
+/// `StyleRecord` (face-style-flags, text color), the same fixed-size fields
+/// [`super::subtitle_writer::tx3g_sample_entry`] writes. Returns `None` if
+/// the entry is too short to carry them (e.g. hand-built fixtures that only
+/// cover the mandatory `SampleEntry` prefix).
+fn parse_tx3g_default_style<S: SeekableStream>(stream: &mut S, entry: &BoxHeader) -> Result<Option<SubtitleStyle>> {
+    const TEXT_BOX_OFFSET: usize = 18;
+    const STYLE_RECORD_OFFSET: usize = 26;
+    const FIXED_FIELDS_LEN: u64 = 38;
+
+    if entry.body_len() < FIXED_FIELDS_LEN {
+        return Ok(None);
+    }
+
+    let mut buf = vec![0u8; FIXED_FIELDS_LEN as usize];
+    stream.read_at(entry.body_start, &mut buf)?;
+
+    let box_position = TextBoxPosition {
+        top: i16::from_be_bytes(buf[TEXT_BOX_OFFSET..TEXT_BOX_OFFSET + 2].try_into().unwrap()),
+        left: i16::from_be_bytes(buf[TEXT_BOX_OFFSET + 2..TEXT_BOX_OFFSET + 4].try_into().unwrap()),
+        bottom: i16::from_be_bytes(buf[TEXT_BOX_OFFSET + 4..TEXT_BOX_OFFSET + 6].try_into().unwrap()),
+        right: i16::from_be_bytes(buf[TEXT_BOX_OFFSET + 6..TEXT_BOX_OFFSET + 8].try_into().unwrap()),
+    };
+
+    let face_style_flags = buf[STYLE_RECORD_OFFSET + 6];
+    let text_color: [u8; 4] = buf[STYLE_RECORD_OFFSET + 8..STYLE_RECORD_OFFSET + 12].try_into().unwrap();
+
+    Ok(Some(SubtitleStyle {
+        bold: face_style_flags & 0x1 != 0,
+        italic: face_style_flags & 0x2 != 0,
+        underline: face_style_flags & 0x4 != 0,
+        text_color,
+        box_position: Some(box_position),
+        highlight: None,
+    }))
+}
+
+/// Decodes one sample's cue text (and, for `tx3g`, its style) per `codec`.
+/// `default_style` is the track's sample-entry default, layered under any
+/// per-sample style override this particular sample carries.
+pub(crate) fn decode_sample(
+    codec: SubtitleCodec,
+    default_style: Option<&SubtitleStyle>,
+    sample: &[u8],
+) -> Result<(String, Option<SubtitleStyle>)> {
+    match codec {
+        SubtitleCodec::Tx3g => decode_tx3g(sample, default_style),
+        SubtitleCodec::Wvtt => Ok((decode_wvtt(sample)?, None)),
+        SubtitleCodec::Cea608 => Ok((decode_cea608(sample)?, None)),
+    }
+}
+
+/// A `tx3g` sample is a 2-byte text length followed by that many bytes of
+/// UTF-8 text, then optional `styl`/`hlit`/`tbox` modifier boxes overriding
+/// the track's default style for this sample only.
+fn decode_tx3g(sample: &[u8], default_style: Option<&SubtitleStyle>) -> Result<(String, Option<SubtitleStyle>)> {
+    if sample.len() < 2 {
+        return Err(Error::Malformed { format: "mp4", reason: "tx3g sample shorter than its text-length field".into() });
+    }
+    let text_len = usize::from(u16::from_be_bytes(sample[0..2].try_into().unwrap()));
+    let text_end = 2 + text_len;
+    if text_end > sample.len() {
+        return Err(Error::Malformed { format: "mp4", reason: "tx3g text length runs past the sample's end".into() });
+    }
+    let text = String::from_utf8(sample[2..text_end].to_vec())
+        .map_err(|_| Error::Malformed { format: "mp4", reason: "tx3g sample text is not valid UTF-8".into() })?;
+
+    let tail = &sample[text_end..];
+    let style = if tail.is_empty() {
+        default_style.copied()
+    } else {
+        Some(apply_style_records(tail, default_style.copied().unwrap_or_default())?)
+    };
+
+    Ok((text, style))
+}
+
+/// Applies every `styl`/`tbox`/`hlit` modifier box found in `tail` (the
+/// sample bytes after its text) on top of `style`. Only the first entry of
+/// a `styl` box's style-record table is used; per-character-range styling
+/// within one cue isn't represented in [`SubtitleStyle`].
+fn apply_style_records(tail: &[u8], mut style: SubtitleStyle) -> Result<SubtitleStyle> {
+    let mut reader = SliceStream(tail);
+    let len = tail.len() as u64;
+
+    for modifier in boxes::children(&mut reader, 0, len)? {
+        match &modifier.box_type {
+            b"styl" => apply_styl(&mut reader, &modifier, &mut style)?,
+            b"tbox" => apply_tbox(&mut reader, &modifier, &mut style)?,
+            b"hlit" => apply_hlit(&mut reader, &modifier, &mut style)?,
+            _ => {}
+        }
+    }
+
+    Ok(style)
+}
+
+/// `styl` (StyleBox): `entry_count` (2 bytes) followed by that many 12-byte
+/// `StyleRecord`s (startChar, endChar, font-ID, face-style-flags, font-size,
+/// text-color-rgba).
+fn apply_styl(reader: &mut SliceStream<'_>, modifier: &BoxHeader, style: &mut SubtitleStyle) -> Result<()> {
+    let mut count = [0u8; 2];
+    reader.read_at(modifier.body_start, &mut count)?;
+    if u16::from_be_bytes(count) == 0 {
+        return Ok(());
+    }
+
+    let mut record = [0u8; 12];
+    reader.read_at(modifier.body_start + 2, &mut record)?;
+    let face_style_flags = record[6];
+    style.bold = face_style_flags & 0x1 != 0;
+    style.italic = face_style_flags & 0x2 != 0;
+    style.underline = face_style_flags & 0x4 != 0;
+    style.text_color = record[8..12].try_into().unwrap();
+    Ok(())
+}
+
+/// `tbox` (a per-sample `BoxRecord`): top/left/bottom/right, each a 2-byte
+/// signed integer.
+fn apply_tbox(reader: &mut SliceStream<'_>, modifier: &BoxHeader, style: &mut SubtitleStyle) -> Result<()> {
+    let mut buf = [0u8; 8];
+    reader.read_at(modifier.body_start, &mut buf)?;
+    style.box_position = Some(TextBoxPosition {
+        top: i16::from_be_bytes(buf[0..2].try_into().unwrap()),
+        left: i16::from_be_bytes(buf[2..4].try_into().unwrap()),
+        bottom: i16::from_be_bytes(buf[4..6].try_into().unwrap()),
+        right: i16::from_be_bytes(buf[6..8].try_into().unwrap()),
+    });
+    Ok(())
+}
+
+/// `hlit` (HighlightBox): `startcharoffset`/`endcharoffset`, each a 2-byte
+/// unsigned integer marking the highlighted text range.
+fn apply_hlit(reader: &mut SliceStream<'_>, modifier: &BoxHeader, style: &mut SubtitleStyle) -> Result<()> {
+    let mut buf = [0u8; 4];
+    reader.read_at(modifier.body_start, &mut buf)?;
+    style.highlight = Some((u16::from_be_bytes(buf[0..2].try_into().unwrap()), u16::from_be_bytes(buf[2..4].try_into().unwrap())));
+    Ok(())
+}
+
+struct SliceStream<'a>(&'a [u8]);
+
+impl SeekableStream for SliceStream<'_> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = offset as usize;
+        let end = start + buf.len();
+        if end > self.0.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "read past end of buffer"));
+        }
+        buf.copy_from_slice(&self.0[start..end]);
+        Ok(())
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.0.len() as u64)
+    }
+}
+
+/// A `wvtt` sample is a `vttc` box (or, for an empty cue, `vtte`) containing
+/// a `payl` box with the cue's UTF-8 text.
+fn decode_wvtt(sample: &[u8]) -> Result<String> {
+    let mut reader = SliceStream(sample);
+    let len = reader.len().expect("reading a slice's length never fails");
+
+    let Some(vttc) = boxes::find_child(&mut reader, 0, len, b"vttc")? else { return Ok(String::new()) };
+    let Some(payl) = find_payl(&mut reader, &vttc)? else { return Ok(String::new()) };
+
+    let mut text = vec![0u8; payl.body_len() as usize];
+    reader.read_at(payl.body_start, &mut text)?;
+    String::from_utf8(text).map_err(|_| Error::Malformed { format: "mp4", reason: "wvtt payl text is not valid UTF-8".into() })
+}
+
+fn find_payl(reader: &mut SliceStream<'_>, vttc: &BoxHeader) -> Result<Option<BoxHeader>> {
+    boxes::find_child(reader, vttc.body_start, vttc.end, b"payl")
+}
+
+/// A `c608` sample is one or more `cdat`/`cdt2` boxes (field 1/field 2 raw
+/// caption data), each holding CEA-608 byte pairs. This only decodes the
+/// basic standard character set (printable bytes, parity bit stripped) into
+/// plain text; control/command codes (PACs, roll-up commands, extended
+/// character sets) are skipped rather than interpreted, so a caption stream
+/// that relies on them for layout loses that structure here.
+fn decode_cea608(sample: &[u8]) -> Result<String> {
+    let mut reader = SliceStream(sample);
+    let len = reader.len().expect("reading a slice's length never fails");
+
+    let mut text = String::new();
+    for packet in boxes::children(&mut reader, 0, len)? {
+        if &packet.box_type != b"cdat" && &packet.box_type != b"cdt2" {
+            continue;
+        }
+        let mut bytes = vec![0u8; packet.body_len() as usize];
+        reader.read_at(packet.body_start, &mut bytes)?;
+        for pair in bytes.chunks_exact(2) {
+            push_cea608_byte_pair(pair, &mut text);
+        }
+    }
+    Ok(text)
+}
+
+/// Decodes one CEA-608 byte pair (odd-parity bit already expected to be
+/// present, stripped here) into [`text`], per the basic standard character
+/// set (EIA-608-B table 1) -- mostly ASCII, with a handful of codes
+/// remapped to characters ASCII doesn't have. Control codes (0x10-0x1F)
+/// and null padding are skipped.
+fn push_cea608_byte_pair(pair: &[u8], text: &mut String) {
+    let (b1, b2) = (pair[0] & 0x7f, pair[1] & 0x7f);
+    if b1 < 0x20 {
+        return;
+    }
+    for b in [b1, b2] {
+        if b == 0 {
+            continue;
+        }
+        text.push(cea608_basic_char(b));
+    }
+}
+
+/// Maps a basic-character-set code to its character. Most codes are plain
+/// ASCII; these are the standard's exceptions.
+fn cea608_basic_char(code: u8) -> char {
+    match code {
+        0x27 => '\u{2019}', // right single quotation mark
+        0x2a => '\u{00e1}', // á
+        0x5c => '\u{00e9}', // é
+        0x5e => '\u{00ed}', // í
+        0x5f => '\u{00f3}', // ó
+        0x60 => '\u{00fa}', // ú
+        0x7b => '\u{00e7}', // ç
+        0x7c => '\u{00f7}', // ÷
+        0x7d => '\u{00d1}', // Ñ
+        0x7e => '\u{00f1}', // ñ
+        0x7f => '\u{2588}', // solid block
+        _ => code as char,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[test]
+    fn decodes_tx3g_text() {
+        let text = "Hello";
+        let mut sample = (text.len() as u16).to_be_bytes().to_vec();
+        sample.extend_from_slice(text.as_bytes());
+
+        let (decoded, style) = decode_sample(SubtitleCodec::Tx3g, None, &sample).unwrap();
+        assert_eq!(decoded, "Hello");
+        assert!(style.is_none());
+    }
+
+    #[test]
+    fn rejects_a_tx3g_sample_whose_length_runs_past_the_end() {
+        let sample = [0, 10, b'h', b'i'];
+        assert!(decode_sample(SubtitleCodec::Tx3g, None, &sample).is_err());
+    }
+
+    #[test]
+    fn decodes_wvtt_text_from_a_vttc_payl_box() {
+        let payl = sized_box(b"payl", b"World");
+        let vttc = sized_box(b"vttc", &payl);
+
+        let (decoded, style) = decode_sample(SubtitleCodec::Wvtt, None, &vttc).unwrap();
+        assert_eq!(decoded, "World");
+        assert!(style.is_none());
+    }
+
+    #[test]
+    fn treats_an_empty_vtte_cue_as_empty_text() {
+        let vtte = sized_box(b"vtte", b"");
+        assert_eq!(decode_sample(SubtitleCodec::Wvtt, None, &vtte).unwrap().0, "");
+    }
+
+    #[test]
+    fn falls_back_to_the_track_default_style_when_a_sample_has_no_override() {
+        let text = "Hello";
+        let mut sample = (text.len() as u16).to_be_bytes().to_vec();
+        sample.extend_from_slice(text.as_bytes());
+
+        let default_style = SubtitleStyle { bold: true, ..SubtitleStyle::default() };
+        let (_, style) = decode_sample(SubtitleCodec::Tx3g, Some(&default_style), &sample).unwrap();
+        assert_eq!(style, Some(default_style));
+    }
+
+    #[test]
+    fn applies_a_per_sample_styl_override_on_top_of_the_default() {
+        let text = "Hi";
+        let mut sample = (text.len() as u16).to_be_bytes().to_vec();
+        sample.extend_from_slice(text.as_bytes());
+
+        // styl: entry_count=1, one StyleRecord with bold+italic and red text.
+        let mut styl_body = 1u16.to_be_bytes().to_vec();
+        styl_body.extend_from_slice(&0u16.to_be_bytes()); // startChar
+        styl_body.extend_from_slice(&2u16.to_be_bytes()); // endChar
+        styl_body.extend_from_slice(&1u16.to_be_bytes()); // font-ID
+        styl_body.push(0x3); // face-style-flags: bold | italic
+        styl_body.push(18); // font-size
+        styl_body.extend_from_slice(&[255, 0, 0, 255]); // text-color-rgba: red
+        sample.extend_from_slice(&sized_box(b"styl", &styl_body));
+
+        let (_, style) = decode_sample(SubtitleCodec::Tx3g, None, &sample).unwrap();
+        let style = style.unwrap();
+        assert!(style.bold);
+        assert!(style.italic);
+        assert!(!style.underline);
+        assert_eq!(style.text_color, [255, 0, 0, 255]);
+    }
+
+    fn cea608_byte_pair(a: u8, b: u8) -> [u8; 2] {
+        // Odd parity isn't checked by the decoder, so plain 7-bit codes work.
+        [a, b]
+    }
+
+    #[test]
+    fn decodes_cea608_text_from_a_cdat_field_1_packet() {
+        let mut pairs = Vec::new();
+        pairs.extend_from_slice(&cea608_byte_pair(b'H', b'i'));
+        let cdat = sized_box(b"cdat", &pairs);
+
+        let (decoded, style) = decode_sample(SubtitleCodec::Cea608, None, &cdat).unwrap();
+        assert_eq!(decoded, "Hi");
+        assert!(style.is_none());
+    }
+
+    #[test]
+    fn decodes_cea608_text_across_cdat_and_cdt2_packets() {
+        let cdat = sized_box(b"cdat", &cea608_byte_pair(b'H', b'i'));
+        let cdt2 = sized_box(b"cdt2", &cea608_byte_pair(b'Y', b'o'));
+        let sample = [cdat, cdt2].concat();
+
+        let (decoded, _) = decode_sample(SubtitleCodec::Cea608, None, &sample).unwrap();
+        assert_eq!(decoded, "HiYo");
+    }
+
+    #[test]
+    fn skips_cea608_control_codes_and_null_padding() {
+        let mut pairs = Vec::new();
+        pairs.extend_from_slice(&cea608_byte_pair(0x14, 0x20)); // control code pair: skipped entirely
+        pairs.extend_from_slice(&cea608_byte_pair(b'O', 0)); // null padding byte: skipped
+        pairs.extend_from_slice(&cea608_byte_pair(b'K', b'!'));
+        let cdat = sized_box(b"cdat", &pairs);
+
+        let (decoded, _) = decode_sample(SubtitleCodec::Cea608, None, &cdat).unwrap();
+        assert_eq!(decoded, "OK!");
+    }
+
+    #[test]
+    fn maps_cea608_special_characters_outside_ascii() {
+        let pairs = cea608_byte_pair(0x27, 0x7e); // right single quote, ñ
+        let cdat = sized_box(b"cdat", &pairs);
+
+        let (decoded, _) = decode_sample(SubtitleCodec::Cea608, None, &cdat).unwrap();
+        assert_eq!(decoded, "\u{2019}\u{00f1}");
+    }
+
+    #[test]
+    fn applies_a_per_sample_tbox_override() {
+        let text = "Hi";
+        let mut sample = (text.len() as u16).to_be_bytes().to_vec();
+        sample.extend_from_slice(text.as_bytes());
+
+        let mut tbox_body = Vec::new();
+        for v in [10i16, 20, 30, 40] {
+            tbox_body.extend_from_slice(&v.to_be_bytes());
+        }
+        sample.extend_from_slice(&sized_box(b"tbox", &tbox_body));
+
+        let (_, style) = decode_sample(SubtitleCodec::Tx3g, None, &sample).unwrap();
+        assert_eq!(style.unwrap().box_position, Some(TextBoxPosition { top: 10, left: 20, bottom: 30, right: 40 }));
+    }
+}
@@ -0,0 +1,439 @@
+//! Muxes extracted [`SubtitleEntry`] cues into a new text track on an
+//! existing MP4/M4A file, completing an extract-edit-reinsert workflow
+//! entirely within the crate.
+//!
+//! This buffers the whole input in memory: patching every existing chunk
+//! offset that the new track's insertion shifts requires rewriting the file
+//! anyway, so there's no streaming-friendly way to do this short of a full
+//! box-tree rewrite (see the `stco`/`co64` patch below).
+//!
+//! Limitations of this first pass: cues are assumed to be in order and
+//! non-overlapping, with no gap-filling empty samples inserted between them
+//! (a cue's sample duration is simply `end - start`), and offsets are kept
+//! as 32-bit (`stco`), which caps the usable file size at 4GB.
+
+use std::io;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::formats::mp4::boxes::{self, BoxHeader};
+use crate::stream::SeekableStream;
+use crate::subtitle::SubtitleEntry;
+
+/// Which ISO-BMFF text-track sample format to mux the cues as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtitleCodec {
+    /// 3GPP Timed Text (`tx3g`), the QuickTime/3GPP closed-caption format.
+    Tx3g,
+    /// WebVTT-in-ISOBMFF (`wvtt`), per ISO/IEC 14496-30.
+    Wvtt,
+    /// CEA-608 line-21 closed captions in QuickTime's `c608` sample format.
+    /// Read-only: [`embed_subtitle_track`] doesn't support muxing this
+    /// format.
+    Cea608,
+}
+
+struct SliceStream<'a>(&'a [u8]);
+
+impl SeekableStream for SliceStream<'_> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = offset as usize;
+        let end = start + buf.len();
+        if end > self.0.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "read past end of buffer"));
+        }
+        buf.copy_from_slice(&self.0[start..end]);
+        Ok(())
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.0.len() as u64)
+    }
+}
+
+fn write_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    let mut b = Vec::with_capacity(body.len() + 8);
+    b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+    b.extend_from_slice(box_type);
+    b.extend_from_slice(body);
+    b
+}
+
+/// Reads `input` in full, appends a new `trak` carrying `entries` as a
+/// `codec` text track, and returns the resulting MP4 bytes.
+///
+/// Fails with [`Error::Malformed`] if `input` has no `moov` box to embed
+/// the new track into.
+pub fn embed_subtitle_track<S: SeekableStream>(
+    input: &mut S,
+    entries: &[SubtitleEntry],
+    codec: SubtitleCodec,
+) -> Result<Vec<u8>> {
+    let len = input.len()?;
+    let mut buf = vec![0u8; len as usize];
+    input.read_at(0, &mut buf)?;
+
+    let mut reader = SliceStream(&buf);
+    let top_level = boxes::children(&mut reader, 0, len)?;
+    let moov = *top_level
+        .iter()
+        .find(|b| &b.box_type == b"moov")
+        .ok_or_else(|| Error::Malformed { format: "mp4", reason: "no moov box to embed a subtitle track into".into() })?;
+
+    let track_id = (boxes::children(&mut reader, moov.body_start, moov.end)?
+        .into_iter()
+        .filter(|b| &b.box_type == b"trak")
+        .count()
+        + 1) as u32;
+
+    let insert_pos = moov.end;
+    let duration_ms = entries.iter().map(|e| e.end).max().unwrap_or(Duration::ZERO).as_millis() as u32;
+
+    let (sample_entry, samples): (Vec<u8>, Vec<Vec<u8>>) = match codec {
+        SubtitleCodec::Tx3g => (tx3g_sample_entry(), entries.iter().map(|e| tx3g_sample(&e.text)).collect()),
+        SubtitleCodec::Wvtt => (wvtt_sample_entry(), entries.iter().map(|e| wvtt_sample(&e.text)).collect()),
+        SubtitleCodec::Cea608 => {
+            return Err(Error::Unsupported("embedding a CEA-608 caption track is not supported; c608 is read-only".into()))
+        }
+    };
+
+    let trak = build_trak(track_id, duration_ms, entries, &sample_entry, &samples, insert_pos, len)?;
+    let delta = trak.len() as u64;
+
+    patch_moov_size(&mut buf, &moov, delta)?;
+    patch_chunk_offsets(&buf, &moov, insert_pos, delta)?.into_iter().for_each(|(at, value)| {
+        buf_write_u32(&mut buf, at, value);
+    });
+
+    let mdat_body: Vec<u8> = samples.concat();
+    let mdat = write_box(b"mdat", &mdat_body);
+
+    let mut out = Vec::with_capacity(buf.len() + trak.len() + mdat.len());
+    out.extend_from_slice(&buf[..insert_pos as usize]);
+    out.extend_from_slice(&trak);
+    out.extend_from_slice(&buf[insert_pos as usize..]);
+    out.extend_from_slice(&mdat);
+
+    Ok(out)
+}
+
+fn checked_u32(v: u64) -> Result<u32> {
+    u32::try_from(v).map_err(|_| Error::Unsupported("subtitle muxing into files over 4GB".into()))
+}
+
+fn buf_write_u32(buf: &mut [u8], at: u64, value: u32) {
+    let at = at as usize;
+    buf[at..at + 4].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Patches the `moov` box's own size field to account for the new track.
+fn patch_moov_size(buf: &mut [u8], moov: &BoxHeader, delta: u64) -> Result<()> {
+    let new_size = checked_u32(moov.size() + delta)?;
+    buf_write_u32(buf, moov.start, new_size);
+    Ok(())
+}
+
+/// Finds every `stco` chunk-offset table under `moov` and returns the
+/// `(absolute_offset, new_value)` patches needed for entries that point at
+/// or past `insert_pos`, the position the new track is spliced into.
+///
+/// `co64` tables aren't patched since this crate only ever writes 32-bit
+/// `stco` offsets; a source file using `co64` is left untouched here and
+/// its track data may end up misaligned (see the module-level limitations).
+fn patch_chunk_offsets(buf: &[u8], moov: &BoxHeader, insert_pos: u64, delta: u64) -> Result<Vec<(u64, u32)>> {
+    let mut reader = SliceStream(buf);
+    let mut patches = Vec::new();
+
+    for stco in boxes::find_all_recursive(&mut reader, moov.body_start, moov.end, b"stco")? {
+        let mut count_buf = [0u8; 4];
+        reader.read_at(stco.body_start + 4, &mut count_buf)?;
+        let count = u32::from_be_bytes(count_buf);
+
+        for i in 0..count {
+            let at = stco.body_start + 8 + u64::from(i) * 4;
+            let mut value_buf = [0u8; 4];
+            reader.read_at(at, &mut value_buf)?;
+            let value = u32::from_be_bytes(value_buf);
+            if u64::from(value) >= insert_pos {
+                patches.push((at, checked_u32(u64::from(value) + delta)?));
+            }
+        }
+    }
+
+    Ok(patches)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn build_trak(
+    track_id: u32,
+    duration_ms: u32,
+    entries: &[SubtitleEntry],
+    sample_entry: &[u8],
+    samples: &[Vec<u8>],
+    insert_pos: u64,
+    original_len: u64,
+) -> Result<Vec<u8>> {
+    // The trak's own length affects where the new mdat lands, and the new
+    // mdat's position is what the stco table needs — so build everything
+    // but the stco table first, measure it, then fill stco in afterward.
+    let tkhd = build_tkhd(track_id, duration_ms);
+    let mdhd = build_mdhd(duration_ms);
+    let hdlr = build_hdlr();
+    let dinf = build_dinf();
+
+    let stsd = write_box(b"stsd", &[&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), sample_entry].concat());
+    let stts = build_stts(entries);
+    let stsc = build_stsc();
+    let stsz = build_stsz(samples);
+
+    // Everything up to (but not including) stco, so we know its own size
+    // contribution before computing absolute chunk offsets.
+    let stbl_prefix_len = stsd.len() + stts.len() + stsc.len() + stsz.len();
+    let minf_prefix = [build_media_header(), dinf.clone()].concat();
+    let stbl_header_len = 8; // `stbl` box header itself
+    let minf_header_len = 8;
+    let mdia_header_len = 8;
+    let trak_header_len = 8;
+
+    // Absolute offset (within the final, post-splice file) where this
+    // track's stco table will sit, used only to size stco ahead of time;
+    // its own bytes don't need to know this, just its length.
+    // box header (8) + version/flags (4) + entry_count (4) + one u32 per sample
+    let stco_len = 16 + samples.len() * 4;
+
+    let trak_len_without_stco_values = trak_header_len
+        + tkhd.len()
+        + mdia_header_len
+        + mdhd.len()
+        + hdlr.len()
+        + minf_header_len
+        + minf_prefix.len()
+        + stbl_header_len
+        + stbl_prefix_len
+        + stco_len;
+
+    let new_mdat_start = original_len + trak_len_without_stco_values as u64 + 8; // mdat body starts after its own header
+    let mut offset = new_mdat_start;
+    let mut chunk_offsets = Vec::with_capacity(samples.len());
+    for sample in samples {
+        chunk_offsets.push(checked_u32(offset)?);
+        offset += sample.len() as u64;
+    }
+    let stco = build_stco(&chunk_offsets);
+
+    let stbl = write_box(b"stbl", &[stsd, stts, stsc, stsz, stco].concat());
+    let minf = write_box(b"minf", &[minf_prefix, stbl].concat());
+    let mdia = write_box(b"mdia", &[mdhd, hdlr, minf].concat());
+    let trak = write_box(b"trak", &[tkhd, mdia].concat());
+
+    debug_assert_eq!(trak.len(), trak_len_without_stco_values);
+    let _ = insert_pos;
+    Ok(trak)
+}
+
+fn build_tkhd(track_id: u32, duration_ms: u32) -> Vec<u8> {
+    let mut body = Vec::with_capacity(84);
+    body.push(0); // version
+    body.extend_from_slice(&[0, 0, 0x07]); // flags: enabled | in movie | in preview
+    body.extend_from_slice(&0u32.to_be_bytes()); // creation_time
+    body.extend_from_slice(&0u32.to_be_bytes()); // modification_time
+    body.extend_from_slice(&track_id.to_be_bytes());
+    body.extend_from_slice(&0u32.to_be_bytes()); // reserved
+    body.extend_from_slice(&duration_ms.to_be_bytes());
+    body.extend_from_slice(&[0u8; 8]); // reserved
+    body.extend_from_slice(&0i16.to_be_bytes()); // layer
+    body.extend_from_slice(&0i16.to_be_bytes()); // alternate_group
+    body.extend_from_slice(&0i16.to_be_bytes()); // volume (not audio)
+    body.extend_from_slice(&0u16.to_be_bytes()); // reserved
+    for v in [0x0001_0000i32, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000] {
+        body.extend_from_slice(&v.to_be_bytes());
+    }
+    body.extend_from_slice(&0u32.to_be_bytes()); // width
+    body.extend_from_slice(&0u32.to_be_bytes()); // height
+    write_box(b"tkhd", &body)
+}
+
+fn build_mdhd(duration_ms: u32) -> Vec<u8> {
+    let mut body = Vec::with_capacity(24);
+    body.push(0);
+    body.extend_from_slice(&[0, 0, 0]);
+    body.extend_from_slice(&0u32.to_be_bytes()); // creation_time
+    body.extend_from_slice(&0u32.to_be_bytes()); // modification_time
+    body.extend_from_slice(&1000u32.to_be_bytes()); // timescale: milliseconds
+    body.extend_from_slice(&duration_ms.to_be_bytes());
+    body.extend_from_slice(&0x55C4u16.to_be_bytes()); // language: "und"
+    body.extend_from_slice(&0u16.to_be_bytes()); // pre_defined
+    write_box(b"mdhd", &body)
+}
+
+fn build_hdlr() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_be_bytes()); // version/flags
+    body.extend_from_slice(&0u32.to_be_bytes()); // pre_defined
+    body.extend_from_slice(b"text"); // handler_type
+    body.extend_from_slice(&[0u8; 12]); // reserved
+    body.extend_from_slice(b"SubtitleHandler\0");
+    write_box(b"hdlr", &body)
+}
+
+/// A `nmhd` (null media header) is the simplest media-header box that ISO
+/// base media allows for a track with no type-specific header, which text
+/// tracks don't strictly require.
+fn build_media_header() -> Vec<u8> {
+    write_box(b"nmhd", &0u32.to_be_bytes())
+}
+
+fn build_dinf() -> Vec<u8> {
+    let url = write_box(b"url ", &[0, 0, 0, 1]); // flags = 0x000001: data is in this file
+    let dref = write_box(b"dref", &[&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &url].concat());
+    write_box(b"dinf", &dref)
+}
+
+fn build_stts(entries: &[SubtitleEntry]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_be_bytes());
+    body.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for entry in entries {
+        let delta_ms = entry.end.saturating_sub(entry.start).as_millis().max(1) as u32;
+        body.extend_from_slice(&1u32.to_be_bytes()); // sample_count
+        body.extend_from_slice(&delta_ms.to_be_bytes());
+    }
+    write_box(b"stts", &body)
+}
+
+fn build_stsc() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_be_bytes());
+    body.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+    body.extend_from_slice(&1u32.to_be_bytes()); // first_chunk
+    body.extend_from_slice(&1u32.to_be_bytes()); // samples_per_chunk
+    body.extend_from_slice(&1u32.to_be_bytes()); // sample_description_index
+    write_box(b"stsc", &body)
+}
+
+fn build_stsz(samples: &[Vec<u8>]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_be_bytes());
+    body.extend_from_slice(&0u32.to_be_bytes()); // sample_size: 0 means "see table"
+    body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+    for sample in samples {
+        body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+    }
+    write_box(b"stsz", &body)
+}
+
+fn build_stco(offsets: &[u32]) -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&0u32.to_be_bytes());
+    body.extend_from_slice(&(offsets.len() as u32).to_be_bytes());
+    for offset in offsets {
+        body.extend_from_slice(&offset.to_be_bytes());
+    }
+    write_box(b"stco", &body)
+}
+
+fn tx3g_sample_entry() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&[0u8; 6]); // reserved
+    body.extend_from_slice(&1u16.to_be_bytes()); // data_reference_index
+    body.extend_from_slice(&0u32.to_be_bytes()); // displayFlags
+    body.push(0); // horizontal-justification
+    body.push(0); // vertical-justification
+    body.extend_from_slice(&[0, 0, 0, 0]); // background-color-rgba
+    body.extend_from_slice(&[0i16.to_be_bytes(), 0i16.to_be_bytes(), 0i16.to_be_bytes(), 0i16.to_be_bytes()].concat()); // default text box
+    body.extend_from_slice(&0u16.to_be_bytes()); // style: startChar
+    body.extend_from_slice(&0u16.to_be_bytes()); // style: endChar
+    body.extend_from_slice(&1u16.to_be_bytes()); // style: font-ID
+    body.push(0); // style: face-style-flags
+    body.push(18); // style: font-size
+    body.extend_from_slice(&[255, 255, 255, 255]); // style: text-color-rgba
+
+    let font_name = b"Serif";
+    let mut ftab_body = Vec::new();
+    ftab_body.extend_from_slice(&1u16.to_be_bytes()); // entry-count
+    ftab_body.extend_from_slice(&1u16.to_be_bytes()); // font-ID
+    ftab_body.push(font_name.len() as u8);
+    ftab_body.extend_from_slice(font_name);
+    body.extend_from_slice(&write_box(b"ftab", &ftab_body));
+
+    write_box(b"tx3g", &body)
+}
+
+fn tx3g_sample(text: &str) -> Vec<u8> {
+    let mut sample = Vec::with_capacity(text.len() + 2);
+    sample.extend_from_slice(&(text.len() as u16).to_be_bytes());
+    sample.extend_from_slice(text.as_bytes());
+    sample
+}
+
+fn wvtt_sample_entry() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(&[0u8; 6]); // reserved
+    body.extend_from_slice(&1u16.to_be_bytes()); // data_reference_index
+    body.extend_from_slice(&write_box(b"vttC", b"WEBVTT\n"));
+    write_box(b"wvtt", &body)
+}
+
+fn wvtt_sample(text: &str) -> Vec<u8> {
+    let payl = write_box(b"payl", text.as_bytes());
+    write_box(b"vttc", &payl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        write_box(box_type, body)
+    }
+
+    fn sample_mp4() -> Vec<u8> {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mut mvhd_body = vec![0u8; 20];
+        mvhd_body[12..16].copy_from_slice(&1000u32.to_be_bytes());
+        mvhd_body[16..20].copy_from_slice(&2000u32.to_be_bytes());
+        let mvhd = sized_box(b"mvhd", &mvhd_body);
+        let moov = sized_box(b"moov", &mvhd);
+        let mdat = sized_box(b"mdat", &[0u8; 16]);
+        [ftyp, moov, mdat].concat()
+    }
+
+    #[test]
+    fn embeds_tx3g_track_and_appends_new_mdat() {
+        let mut stream = MemorySeekableStream::new(sample_mp4());
+        let entries = vec![
+            SubtitleEntry {
+                start: Duration::from_secs(0),
+                end: Duration::from_secs(1),
+                text: "Hello".into(),
+                style: None,
+                track_id: 0,
+                language: String::new(),
+                sample_index: 0,
+                raw: Vec::new(),
+            },
+            SubtitleEntry {
+                start: Duration::from_secs(1),
+                end: Duration::from_secs(2),
+                text: "World".into(),
+                style: None,
+                track_id: 0,
+                language: String::new(),
+                sample_index: 1,
+                raw: Vec::new(),
+            },
+        ];
+
+        let out = embed_subtitle_track(&mut stream, &entries, SubtitleCodec::Tx3g).unwrap();
+
+        let mut reader = SliceStream(&out);
+        let len = reader.len().unwrap();
+        let top_level = boxes::children(&mut reader, 0, len).unwrap();
+        assert_eq!(top_level.iter().filter(|b| &b.box_type == b"mdat").count(), 2);
+
+        let moov = top_level.iter().find(|b| &b.box_type == b"moov").unwrap();
+        let trak = boxes::find_child(&mut reader, moov.body_start, moov.end, b"trak").unwrap();
+        assert!(trak.is_some());
+    }
+}
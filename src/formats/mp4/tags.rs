@@ -0,0 +1,705 @@
+//! `moov.udta.meta.ilst` ("iTunes-style") atom parsing.
+
+use std::collections::HashMap;
+
+use super::boxes::{self, BoxHeader};
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+
+/// Maps well-known `ilst` atom types to the tag names we expose.
+fn standard_tag_name(box_type: &[u8; 4]) -> Option<&'static str> {
+    match box_type {
+        b"\xa9nam" => Some("title"),
+        b"\xa9ART" => Some("artist"),
+        b"\xa9alb" => Some("album"),
+        b"\xa9day" => Some("date"),
+        b"\xa9gen" => Some("genre"),
+        b"\xa9cmt" => Some("comment"),
+        b"\xa9wrt" => Some("composer"),
+        _ => None,
+    }
+}
+
+/// Reads the `data` atom nested directly under `parent`, returning its
+/// payload (after the 4-byte type-and-locale prefix every `data` atom has).
+fn read_data_payload<S: SeekableStream>(stream: &mut S, parent: &BoxHeader) -> Result<Option<Vec<u8>>> {
+    let Some(data) = boxes::find_child(stream, parent.body_start, parent.end, b"data")? else {
+        return Ok(None);
+    };
+    if data.body_len() < 8 {
+        return Ok(Some(Vec::new()));
+    }
+    let payload_len = (data.body_len() - 8) as usize;
+    let mut payload = vec![0u8; payload_len];
+    stream.read_at(data.body_start + 8, &mut payload)?;
+    Ok(Some(payload))
+}
+
+/// Decodes a `data` atom's locale field into an ISO-639-2/T-style language
+/// code, using the same 5-bits-per-letter packing as `mdhd`'s language
+/// field (a code of `0` is treated as undefined, like `mdhd`'s `0x55C4`).
+fn decode_language_code(code: u16) -> String {
+    if code == 0 {
+        return "und".to_string();
+    }
+    let letters =
+        [((code >> 10) & 0x1F) as u8 + 0x60, ((code >> 5) & 0x1F) as u8 + 0x60, (code & 0x1F) as u8 + 0x60];
+    String::from_utf8(letters.to_vec()).unwrap_or_else(|_| "und".to_string())
+}
+
+/// Reads one `data` atom's locale and payload. A `data` atom's body is a
+/// 4-byte type indicator, a 4-byte locale indicator (country in the high
+/// 16 bits, language in the low 16), then the value itself.
+fn read_data_atom<S: SeekableStream>(stream: &mut S, data: &BoxHeader) -> Result<(String, Vec<u8>)> {
+    if data.body_len() < 8 {
+        return Ok(("und".to_string(), Vec::new()));
+    }
+    let mut header = [0u8; 8];
+    stream.read_at(data.body_start, &mut header)?;
+    let language = decode_language_code(u16::from_be_bytes(header[6..8].try_into().unwrap()));
+
+    let payload_len = (data.body_len() - 8) as usize;
+    let mut payload = vec![0u8; payload_len];
+    stream.read_at(data.body_start + 8, &mut payload)?;
+    Ok((language, payload))
+}
+
+/// Populates `tags` with every well-known atom found directly under `ilst`,
+/// and `localized_tags` with every language-specific variant of each (an
+/// atom may carry more than one `data` child, one per locale).
+pub(crate) fn read_standard_tags<S: SeekableStream>(
+    stream: &mut S,
+    ilst: &BoxHeader,
+    tags: &mut HashMap<String, String>,
+    localized_tags: &mut HashMap<String, HashMap<String, String>>,
+) -> Result<()> {
+    for atom in boxes::children(stream, ilst.body_start, ilst.end)? {
+        let Some(name) = standard_tag_name(&atom.box_type) else { continue };
+        for data in boxes::children(stream, atom.body_start, atom.end)?.into_iter().filter(|b| &b.box_type == b"data")
+        {
+            let (language, payload) = read_data_atom(stream, &data)?;
+            let value = String::from_utf8_lossy(&payload).to_string();
+            tags.entry(name.to_string()).or_insert_with(|| value.clone());
+            localized_tags.entry(name.to_string()).or_default().insert(language, value);
+        }
+    }
+    Ok(())
+}
+
+/// Reads `meta`'s `keys` box (a full box: version/flags, then an entry
+/// count, then one entry per key -- a 4-byte size, a 4-byte namespace
+/// (almost always `mdta`), and the key name itself) into an ordered list of
+/// key names, 0-indexed here though `ilst`'s numeric atom types reference
+/// them starting from 1.
+fn read_keys<S: SeekableStream>(stream: &mut S, keys: &BoxHeader) -> Result<Vec<String>> {
+    if keys.body_len() < 8 {
+        return Ok(Vec::new());
+    }
+    let mut count_bytes = [0u8; 4];
+    stream.read_at(keys.body_start + 4, &mut count_bytes)?;
+    let entry_count = u32::from_be_bytes(count_bytes);
+
+    // Each entry is at least 8 bytes (size + namespace) -- reject an
+    // entry_count the box couldn't possibly hold before sizing a `Vec` off
+    // it, so a corrupt/hostile entry_count near u32::MAX can't force a
+    // multi-GB allocation.
+    let available = keys.end.saturating_sub(keys.body_start + 8);
+    if u64::from(entry_count) > available / 8 {
+        return Err(Error::Malformed { format: "mp4", reason: "keys entry_count exceeds the box's available data".into() });
+    }
+
+    let mut names = Vec::with_capacity(entry_count as usize);
+    let mut offset = keys.body_start + 8;
+    for _ in 0..entry_count {
+        if offset + 8 > keys.end {
+            break;
+        }
+        let mut entry_header = [0u8; 8];
+        stream.read_at(offset, &mut entry_header)?;
+        let entry_size = u32::from_be_bytes(entry_header[0..4].try_into().unwrap());
+        if entry_size < 8 || offset + u64::from(entry_size) > keys.end {
+            break;
+        }
+
+        let mut name = vec![0u8; (entry_size - 8) as usize];
+        stream.read_at(offset + 8, &mut name)?;
+        names.push(String::from_utf8_lossy(&name).to_string());
+        offset += u64::from(entry_size);
+    }
+    Ok(names)
+}
+
+/// Populates `tags`/`localized_tags` from a QuickTime `mdta`-handler `meta`
+/// box, the scheme iPhones use for `com.apple.quicktime.*` metadata (model,
+/// software, live-photo ID, ...). Unlike an iTunes-style `ilst`, whose atoms
+/// are named by a recognizable fourCC, an `mdta` `ilst`'s atoms are named by
+/// a 1-based numeric index into `keys`, which is what resolves each entry to
+/// its actual key name.
+pub(crate) fn read_mdta_tags<S: SeekableStream>(
+    stream: &mut S,
+    keys: &BoxHeader,
+    ilst: &BoxHeader,
+    tags: &mut HashMap<String, String>,
+    localized_tags: &mut HashMap<String, HashMap<String, String>>,
+) -> Result<()> {
+    let key_names = read_keys(stream, keys)?;
+
+    for atom in boxes::children(stream, ilst.body_start, ilst.end)? {
+        let index = u32::from_be_bytes(atom.box_type) as usize;
+        let Some(name) = index.checked_sub(1).and_then(|i| key_names.get(i)) else { continue };
+
+        for data in boxes::children(stream, atom.body_start, atom.end)?.into_iter().filter(|b| &b.box_type == b"data")
+        {
+            let (language, payload) = read_data_atom(stream, &data)?;
+            let value = String::from_utf8_lossy(&payload).to_string();
+            tags.entry(name.clone()).or_insert_with(|| value.clone());
+            localized_tags.entry(name.clone()).or_default().insert(language, value);
+        }
+    }
+    Ok(())
+}
+
+/// iTunes' video/TV-metadata extension atoms, read directly under `ilst` by
+/// [`read_itunes_video_tags`]. Unlike [`read_standard_tags`]'s free-form tag
+/// map, these get dedicated [`crate::metadata::Metadata`] fields since
+/// media-library tooling expects them typed (an episode number is awkward
+/// to sort on as a string).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct ItunesVideoTags {
+    pub media_kind: Option<u8>,
+    pub tv_show: Option<String>,
+    pub tv_episode_id: Option<String>,
+    pub tv_season: Option<u32>,
+    pub tv_episode: Option<u32>,
+    pub content_rating: Option<u8>,
+    pub description: Option<String>,
+    pub long_description: Option<String>,
+}
+
+/// Reads a `data` atom's payload as UTF-8 text.
+fn read_data_text<S: SeekableStream>(stream: &mut S, parent: &BoxHeader) -> Result<Option<String>> {
+    Ok(read_data_payload(stream, parent)?.map(|payload| String::from_utf8_lossy(&payload).to_string()))
+}
+
+/// Reads a `data` atom's payload as a big-endian unsigned integer, iTunes'
+/// encoding for `tvsn`/`tves` (1, 2, or 4 bytes depending on the tool that
+/// wrote it).
+fn read_data_u32<S: SeekableStream>(stream: &mut S, parent: &BoxHeader) -> Result<Option<u32>> {
+    Ok(read_data_payload(stream, parent)?.and_then(|payload| match payload.len() {
+        1 => Some(u32::from(payload[0])),
+        2 => Some(u32::from(u16::from_be_bytes(payload[0..2].try_into().unwrap()))),
+        4 => Some(u32::from_be_bytes(payload[0..4].try_into().unwrap())),
+        _ => None,
+    }))
+}
+
+/// Reads every iTunes video/TV-metadata extension atom found directly under
+/// `ilst`: media kind (`stik`), TV show/episode fields
+/// (`tvsh`/`tven`/`tvsn`/`tves`), content rating (`rtng`), and description
+/// (`desc`/`ldes`).
+pub(crate) fn read_itunes_video_tags<S: SeekableStream>(stream: &mut S, ilst: &BoxHeader) -> Result<ItunesVideoTags> {
+    let mut tags = ItunesVideoTags::default();
+    for atom in boxes::children(stream, ilst.body_start, ilst.end)? {
+        match &atom.box_type {
+            b"stik" => tags.media_kind = read_data_payload(stream, &atom)?.and_then(|p| p.first().copied()),
+            b"tvsh" => tags.tv_show = read_data_text(stream, &atom)?,
+            b"tven" => tags.tv_episode_id = read_data_text(stream, &atom)?,
+            b"tvsn" => tags.tv_season = read_data_u32(stream, &atom)?,
+            b"tves" => tags.tv_episode = read_data_u32(stream, &atom)?,
+            b"rtng" => tags.content_rating = read_data_payload(stream, &atom)?.and_then(|p| p.first().copied()),
+            b"desc" => tags.description = read_data_text(stream, &atom)?,
+            b"ldes" => tags.long_description = read_data_text(stream, &atom)?,
+            _ => {}
+        }
+    }
+    Ok(tags)
+}
+
+/// Reads the `©lyr` atom's `data` payload as UTF-8 text: lyrics, which can
+/// be long and multi-line, get a dedicated [`crate::metadata::Metadata`]
+/// field instead of living in the generic tag map the way `©nam`/`©ART`/etc.
+/// do (see [`read_standard_tags`]).
+pub(crate) fn read_lyrics<S: SeekableStream>(stream: &mut S, ilst: &BoxHeader) -> Result<Option<String>> {
+    let Some(atom) = boxes::find_child(stream, ilst.body_start, ilst.end, b"\xa9lyr")? else { return Ok(None) };
+    read_data_text(stream, &atom)
+}
+
+/// The iTunes sort-order atoms, read directly under `ilst` by
+/// [`read_sort_tags`]. Like [`read_itunes_video_tags`], these get dedicated
+/// [`crate::metadata::Metadata`] fields rather than living in the generic
+/// tag map, since a library needs to tell a sort key apart from the display
+/// tag it overrides.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct SortTags {
+    pub sort_title: Option<String>,
+    pub sort_artist: Option<String>,
+    pub sort_album: Option<String>,
+}
+
+/// Reads the iTunes sort-order atoms found directly under `ilst`: sort title
+/// (`sonm`), sort artist (`soar`), and sort album (`soal`) -- the keys media
+/// library software alphabetizes by instead of the display tag, e.g. to
+/// sort "The Beatles" under "B".
+pub(crate) fn read_sort_tags<S: SeekableStream>(stream: &mut S, ilst: &BoxHeader) -> Result<SortTags> {
+    let mut tags = SortTags::default();
+    for atom in boxes::children(stream, ilst.body_start, ilst.end)? {
+        match &atom.box_type {
+            b"sonm" => tags.sort_title = read_data_text(stream, &atom)?,
+            b"soar" => tags.sort_artist = read_data_text(stream, &atom)?,
+            b"soal" => tags.sort_album = read_data_text(stream, &atom)?,
+            _ => {}
+        }
+    }
+    Ok(tags)
+}
+
+/// Maps well-known 3GPP (TS 26.244) asset info box types to the tag names
+/// we expose.
+fn asset_info_tag_name(box_type: &[u8; 4]) -> Option<&'static str> {
+    match box_type {
+        b"titl" => Some("title"),
+        b"auth" => Some("artist"),
+        b"dscp" => Some("description"),
+        b"cprt" => Some("copyright"),
+        _ => None,
+    }
+}
+
+/// Populates `tags`/`localized_tags` from every well-known 3GPP asset info
+/// box found directly under `udta` (`titl`, `auth`, `dscp`, `cprt`), the
+/// format phone recordings tend to use instead of an iTunes-style `ilst`.
+///
+/// Unlike an `ilst` atom (whose value lives in a nested `data` atom), each
+/// of these is itself a full box: version/flags (4 bytes), a packed
+/// language code (2 bytes, same scheme as `mdhd`), then the UTF-8 text.
+pub(crate) fn read_asset_info_tags<S: SeekableStream>(
+    stream: &mut S,
+    udta: &BoxHeader,
+    tags: &mut HashMap<String, String>,
+    localized_tags: &mut HashMap<String, HashMap<String, String>>,
+) -> Result<()> {
+    for atom in boxes::children(stream, udta.body_start, udta.end)? {
+        let Some(name) = asset_info_tag_name(&atom.box_type) else { continue };
+        if atom.body_len() < 6 {
+            continue;
+        }
+
+        let mut header = [0u8; 6];
+        stream.read_at(atom.body_start, &mut header)?;
+        let language = decode_language_code(u16::from_be_bytes(header[4..6].try_into().unwrap()));
+
+        let mut text = vec![0u8; (atom.body_len() - 6) as usize];
+        stream.read_at(atom.body_start + 6, &mut text)?;
+        let value = String::from_utf8_lossy(&text).trim_end_matches('\0').to_string();
+
+        tags.entry(name.to_string()).or_insert_with(|| value.clone());
+        localized_tags.entry(name.to_string()).or_default().insert(language, value);
+    }
+    Ok(())
+}
+
+/// Reads the `mean`/`name`/`data` triple of a `----` freeform atom,
+/// returning its domain (`mean`), name, and raw value.
+fn read_freeform<S: SeekableStream>(stream: &mut S, atom: &BoxHeader) -> Result<Option<(String, String, Vec<u8>)>> {
+    let Some(mean) = boxes::find_child(stream, atom.body_start, atom.end, b"mean")? else {
+        return Ok(None);
+    };
+    let Some(name) = boxes::find_child(stream, atom.body_start, atom.end, b"name")? else {
+        return Ok(None);
+    };
+    if mean.body_len() < 4 || name.body_len() < 4 {
+        return Ok(None);
+    }
+
+    let mut mean_str = vec![0u8; (mean.body_len() - 4) as usize];
+    stream.read_at(mean.body_start + 4, &mut mean_str)?;
+    let mut name_str = vec![0u8; (name.body_len() - 4) as usize];
+    stream.read_at(name.body_start + 4, &mut name_str)?;
+
+    let Some(value) = read_data_payload(stream, atom)? else { return Ok(None) };
+    Ok(Some((String::from_utf8_lossy(&mean_str).to_string(), String::from_utf8_lossy(&name_str).to_string(), value)))
+}
+
+/// Finds the `----` freeform atom named `iTunSMPB`, which carries AAC
+/// gapless-playback priming/padding and the original (trimmed) sample
+/// count, and returns its value as a string.
+pub(crate) fn find_itunsmpb<S: SeekableStream>(stream: &mut S, ilst: &BoxHeader) -> Result<Option<String>> {
+    for atom in boxes::children(stream, ilst.body_start, ilst.end)? {
+        if &atom.box_type != b"----" {
+            continue;
+        }
+        if let Some((mean, name, value)) = read_freeform(stream, &atom)? {
+            if mean == "com.apple.iTunes" && name == "iTunSMPB" {
+                return Ok(Some(String::from_utf8_lossy(&value).trim().to_string()));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Populates `freeform_tags` with every `----` freeform atom found directly
+/// under `ilst`, keyed as `"<mean>:<name>"` since different tools (iTunes,
+/// HandBrake, ...) each pick their own `mean` domain, and two tools' tags
+/// of the same `name` shouldn't collide.
+pub(crate) fn read_freeform_tags<S: SeekableStream>(
+    stream: &mut S,
+    ilst: &BoxHeader,
+    freeform_tags: &mut HashMap<String, String>,
+) -> Result<()> {
+    for atom in boxes::children(stream, ilst.body_start, ilst.end)? {
+        if &atom.box_type != b"----" {
+            continue;
+        }
+        if let Some((mean, name, value)) = read_freeform(stream, &atom)? {
+            freeform_tags.insert(format!("{mean}:{name}"), String::from_utf8_lossy(&value).to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Parses an `iTunSMPB` value, e.g.
+/// `" 00000000 00000840 00000172 0000000000834D80 ..."`, returning
+/// `(encoder_delay, padding, original_sample_count)`.
+pub(crate) fn parse_itunsmpb(value: &str) -> Option<(u32, u32, u64)> {
+    let mut fields = value.split_whitespace();
+    fields.next()?; // format/reserved field
+    let delay = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let padding = u32::from_str_radix(fields.next()?, 16).ok()?;
+    let original_samples = u64::from_str_radix(fields.next()?, 16).ok()?;
+    Some((delay, padding, original_samples))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    #[test]
+    fn decodes_localized_titles_from_multiple_data_atoms() {
+        fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(box_type);
+            b.extend_from_slice(body);
+            b
+        }
+
+        fn data_atom(locale: u16, text: &str) -> Vec<u8> {
+            let mut body = vec![1, 0, 0, 0]; // type indicator: UTF-8
+            body.extend_from_slice(&0u16.to_be_bytes()); // country
+            body.extend_from_slice(&locale.to_be_bytes()); // language
+            body.extend_from_slice(text.as_bytes());
+            sized_box(b"data", &body)
+        }
+
+        // "eng" packed per the mdhd scheme: e=5,n=14,g=7 -> 0b00101_01110_00111
+        let eng = (5u16 << 10) | (14 << 5) | 7;
+        let nam = sized_box(b"\xa9nam", &[data_atom(0, "Hello"), data_atom(eng, "Hello")].concat());
+        let ilst = sized_box(b"ilst", &nam);
+
+        let mut stream = MemorySeekableStream::new(ilst);
+        let len = stream.len().unwrap();
+        let ilst_header = boxes::children(&mut stream, 0, len).unwrap().remove(0);
+
+        let mut tags = HashMap::new();
+        let mut localized = HashMap::new();
+        read_standard_tags(&mut stream, &ilst_header, &mut tags, &mut localized).unwrap();
+
+        assert_eq!(tags.get("title"), Some(&"Hello".to_string()));
+        let title_locales = &localized["title"];
+        assert_eq!(title_locales.get("und"), Some(&"Hello".to_string()));
+        assert_eq!(title_locales.get("eng"), Some(&"Hello".to_string()));
+    }
+
+    #[test]
+    fn resolves_mdta_ilst_entries_by_index_into_keys() {
+        fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(box_type);
+            b.extend_from_slice(body);
+            b
+        }
+
+        fn key_entry(namespace: &[u8; 4], name: &str) -> Vec<u8> {
+            let mut entry = ((name.len() + 8) as u32).to_be_bytes().to_vec();
+            entry.extend_from_slice(namespace);
+            entry.extend_from_slice(name.as_bytes());
+            entry
+        }
+
+        fn data_atom(value: &str) -> Vec<u8> {
+            let mut body = vec![1, 0, 0, 0]; // type indicator: UTF-8
+            body.extend_from_slice(&0u32.to_be_bytes()); // locale
+            body.extend_from_slice(value.as_bytes());
+            sized_box(b"data", &body)
+        }
+
+        let keys_body = [
+            &0u32.to_be_bytes()[..],
+            &2u32.to_be_bytes(),
+            &key_entry(b"mdta", "com.apple.quicktime.model"),
+            &key_entry(b"mdta", "com.apple.quicktime.software"),
+        ]
+        .concat();
+        let keys = sized_box(b"keys", &keys_body);
+
+        let entry1 = sized_box(&1u32.to_be_bytes(), &data_atom("iPhone 15 Pro"));
+        let entry2 = sized_box(&2u32.to_be_bytes(), &data_atom("18.2"));
+        let ilst = sized_box(b"ilst", &[entry1, entry2].concat());
+
+        let mut combined = keys.clone();
+        combined.extend_from_slice(&ilst);
+        let mut stream = MemorySeekableStream::new(combined);
+        let len = stream.len().unwrap();
+        let headers = boxes::children(&mut stream, 0, len).unwrap();
+        let keys_header = headers[0];
+        let ilst_header = headers[1];
+
+        let mut tags = HashMap::new();
+        let mut localized = HashMap::new();
+        read_mdta_tags(&mut stream, &keys_header, &ilst_header, &mut tags, &mut localized).unwrap();
+
+        assert_eq!(tags.get("com.apple.quicktime.model"), Some(&"iPhone 15 Pro".to_string()));
+        assert_eq!(tags.get("com.apple.quicktime.software"), Some(&"18.2".to_string()));
+    }
+
+    #[test]
+    fn rejects_a_keys_entry_count_the_box_cant_possibly_hold() {
+        fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(box_type);
+            b.extend_from_slice(body);
+            b
+        }
+
+        let keys_body = [&0u32.to_be_bytes()[..], &u32::MAX.to_be_bytes()].concat();
+        let keys = sized_box(b"keys", &keys_body);
+        let ilst = sized_box(b"ilst", &[]);
+
+        let mut combined = keys.clone();
+        combined.extend_from_slice(&ilst);
+        let mut stream = MemorySeekableStream::new(combined);
+        let len = stream.len().unwrap();
+        let headers = boxes::children(&mut stream, 0, len).unwrap();
+        let keys_header = headers[0];
+        let ilst_header = headers[1];
+
+        let mut tags = HashMap::new();
+        let mut localized = HashMap::new();
+        let err = read_mdta_tags(&mut stream, &keys_header, &ilst_header, &mut tags, &mut localized).unwrap_err();
+
+        assert!(matches!(err, Error::Malformed { format: "mp4", .. }));
+    }
+
+    #[test]
+    fn decodes_3gpp_asset_info_boxes_under_udta() {
+        fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(box_type);
+            b.extend_from_slice(body);
+            b
+        }
+
+        fn asset_info_box(box_type: &[u8; 4], language: u16, text: &str) -> Vec<u8> {
+            let mut body = vec![0u8; 4]; // version/flags
+            body.extend_from_slice(&language.to_be_bytes());
+            body.extend_from_slice(text.as_bytes());
+            body.push(0); // trailing null, as many encoders emit
+            sized_box(box_type, &body)
+        }
+
+        let titl = asset_info_box(b"titl", 0, "My Video");
+        let cprt = asset_info_box(b"cprt", 0, "(c) 2026");
+        let udta = sized_box(b"udta", &[titl, cprt].concat());
+
+        let mut stream = MemorySeekableStream::new(udta);
+        let len = stream.len().unwrap();
+        let udta_header = boxes::children(&mut stream, 0, len).unwrap().remove(0);
+
+        let mut tags = HashMap::new();
+        let mut localized = HashMap::new();
+        read_asset_info_tags(&mut stream, &udta_header, &mut tags, &mut localized).unwrap();
+
+        assert_eq!(tags.get("title"), Some(&"My Video".to_string()));
+        assert_eq!(tags.get("copyright"), Some(&"(c) 2026".to_string()));
+        assert_eq!(localized["title"].get("und"), Some(&"My Video".to_string()));
+    }
+
+    #[test]
+    fn reads_custom_freeform_tags_keyed_by_mean_and_name() {
+        fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(box_type);
+            b.extend_from_slice(body);
+            b
+        }
+
+        fn data_atom(value: &[u8]) -> Vec<u8> {
+            let mut body = vec![0u8; 8];
+            body.extend_from_slice(value);
+            sized_box(b"data", &body)
+        }
+
+        fn freeform_atom(mean: &str, name: &str, value: &str) -> Vec<u8> {
+            let mean_box = sized_box(b"mean", &[&[0u8; 4][..], mean.as_bytes()].concat());
+            let name_box = sized_box(b"name", &[&[0u8; 4][..], name.as_bytes()].concat());
+            let data = data_atom(value.as_bytes());
+            sized_box(b"----", &[mean_box, name_box, data].concat())
+        }
+
+        let handbrake = freeform_atom("org.handbrake", "Encoder", "x264");
+        let custom = freeform_atom("com.example.tooling", "BatchID", "42");
+        let ilst = sized_box(b"ilst", &[handbrake, custom].concat());
+
+        let mut stream = MemorySeekableStream::new(ilst);
+        let len = stream.len().unwrap();
+        let ilst_header = boxes::children(&mut stream, 0, len).unwrap().remove(0);
+
+        let mut freeform_tags = HashMap::new();
+        read_freeform_tags(&mut stream, &ilst_header, &mut freeform_tags).unwrap();
+
+        assert_eq!(freeform_tags.get("org.handbrake:Encoder"), Some(&"x264".to_string()));
+        assert_eq!(freeform_tags.get("com.example.tooling:BatchID"), Some(&"42".to_string()));
+    }
+
+    #[test]
+    fn reads_itunes_video_and_tv_metadata_atoms() {
+        fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(box_type);
+            b.extend_from_slice(body);
+            b
+        }
+
+        fn int_data_atom(value: &[u8]) -> Vec<u8> {
+            let mut body = vec![0u8; 8]; // type indicator + locale
+            body.extend_from_slice(value);
+            sized_box(b"data", &body)
+        }
+
+        fn text_data_atom(value: &str) -> Vec<u8> {
+            let mut body = vec![1, 0, 0, 0]; // type indicator: UTF-8
+            body.extend_from_slice(&0u32.to_be_bytes()); // locale
+            body.extend_from_slice(value.as_bytes());
+            sized_box(b"data", &body)
+        }
+
+        let stik = sized_box(b"stik", &int_data_atom(&[10])); // TV Show
+        let tvsh = sized_box(b"tvsh", &text_data_atom("Example Show"));
+        let tven = sized_box(b"tven", &text_data_atom("EX-103"));
+        let tvsn = sized_box(b"tvsn", &int_data_atom(&1u32.to_be_bytes()));
+        let tves = sized_box(b"tves", &int_data_atom(&3u32.to_be_bytes()));
+        let rtng = sized_box(b"rtng", &int_data_atom(&[2]));
+        let desc = sized_box(b"desc", &text_data_atom("A short synopsis."));
+        let ldes = sized_box(b"ldes", &text_data_atom("A much longer synopsis, with spoilers."));
+        let ilst = sized_box(b"ilst", &[stik, tvsh, tven, tvsn, tves, rtng, desc, ldes].concat());
+
+        let mut stream = MemorySeekableStream::new(ilst);
+        let len = stream.len().unwrap();
+        let ilst_header = boxes::children(&mut stream, 0, len).unwrap().remove(0);
+
+        let tags = read_itunes_video_tags(&mut stream, &ilst_header).unwrap();
+
+        assert_eq!(tags.media_kind, Some(10));
+        assert_eq!(tags.tv_show, Some("Example Show".to_string()));
+        assert_eq!(tags.tv_episode_id, Some("EX-103".to_string()));
+        assert_eq!(tags.tv_season, Some(1));
+        assert_eq!(tags.tv_episode, Some(3));
+        assert_eq!(tags.content_rating, Some(2));
+        assert_eq!(tags.description, Some("A short synopsis.".to_string()));
+        assert_eq!(tags.long_description, Some("A much longer synopsis, with spoilers.".to_string()));
+    }
+
+    #[test]
+    fn reads_multiline_lyrics_from_the_lyr_atom() {
+        fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(box_type);
+            b.extend_from_slice(body);
+            b
+        }
+
+        fn data_atom(text: &str) -> Vec<u8> {
+            let mut body = vec![1, 0, 0, 0]; // type indicator: UTF-8
+            body.extend_from_slice(&0u32.to_be_bytes()); // locale
+            body.extend_from_slice(text.as_bytes());
+            sized_box(b"data", &body)
+        }
+
+        let lyrics = "Line one\nLine two\nLine three";
+        let lyr = sized_box(b"\xa9lyr", &data_atom(lyrics));
+        let ilst = sized_box(b"ilst", &lyr);
+
+        let mut stream = MemorySeekableStream::new(ilst);
+        let len = stream.len().unwrap();
+        let ilst_header = boxes::children(&mut stream, 0, len).unwrap().remove(0);
+
+        assert_eq!(read_lyrics(&mut stream, &ilst_header).unwrap(), Some(lyrics.to_string()));
+    }
+
+    #[test]
+    fn returns_none_without_a_lyr_atom() {
+        fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(box_type);
+            b.extend_from_slice(body);
+            b
+        }
+
+        let ilst = sized_box(b"ilst", &[]);
+        let mut stream = MemorySeekableStream::new(ilst);
+        let len = stream.len().unwrap();
+        let ilst_header = boxes::children(&mut stream, 0, len).unwrap().remove(0);
+
+        assert_eq!(read_lyrics(&mut stream, &ilst_header).unwrap(), None);
+    }
+
+    #[test]
+    fn reads_sort_order_atoms() {
+        fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+            let mut b = Vec::new();
+            b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+            b.extend_from_slice(box_type);
+            b.extend_from_slice(body);
+            b
+        }
+
+        fn data_atom(text: &str) -> Vec<u8> {
+            let mut body = vec![1, 0, 0, 0]; // type indicator: UTF-8
+            body.extend_from_slice(&0u32.to_be_bytes()); // locale
+            body.extend_from_slice(text.as_bytes());
+            sized_box(b"data", &body)
+        }
+
+        let sonm = sized_box(b"sonm", &data_atom("Beatles, The"));
+        let soar = sized_box(b"soar", &data_atom("Beatles, The"));
+        let soal = sized_box(b"soal", &data_atom("White Album, The"));
+        let ilst = sized_box(b"ilst", &[sonm, soar, soal].concat());
+
+        let mut stream = MemorySeekableStream::new(ilst);
+        let len = stream.len().unwrap();
+        let ilst_header = boxes::children(&mut stream, 0, len).unwrap().remove(0);
+
+        let tags = read_sort_tags(&mut stream, &ilst_header).unwrap();
+
+        assert_eq!(tags.sort_title, Some("Beatles, The".to_string()));
+        assert_eq!(tags.sort_artist, Some("Beatles, The".to_string()));
+        assert_eq!(tags.sort_album, Some("White Album, The".to_string()));
+    }
+
+    #[test]
+    fn parses_itunsmpb_fields() {
+        let value = "00000000 00000840 00000172 0000000000834D80 00000000 00000000 00000000 00000000";
+        let (delay, padding, original_samples) = parse_itunsmpb(value).unwrap();
+        assert_eq!(delay, 0x840);
+        assert_eq!(padding, 0x172);
+        assert_eq!(original_samples, 0x0083_4D80);
+    }
+}
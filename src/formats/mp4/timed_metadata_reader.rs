@@ -0,0 +1,281 @@
+//! Decodes `mebx` timed-metadata samples (Apple's Metadata Media, used for
+//! camera-motion and detected-face metadata in iPhone footage) into named
+//! key/value pairs.
+//!
+//! Each `mebx` sample is itself a sequence of TLV items, one per metadata
+//! value present for that time range: `size` (4 bytes, including this
+//! header), a local key ID (4 bytes, 1-based), then `size - 8` bytes of
+//! value data. The key ID indexes into the track's `keys` box (the Metadata
+//! Key Table Box), which maps each local ID to a namespace and key value
+//! (usually a `mdta`-namespaced reverse-DNS string, e.g.
+//! `com.apple.quicktime.live-photo.vitality-score`).
+
+use std::io;
+
+use super::boxes::{self, BoxHeader};
+use super::sample_table::{ParseMode, SampleTable};
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+
+/// One entry of a `mebx` sample entry's `keys` box: a local key ID's
+/// namespace and key value.
+pub(crate) struct MetadataKey {
+    pub namespace: [u8; 4],
+    pub value: Vec<u8>,
+}
+
+impl MetadataKey {
+    /// A human-readable name for this key: its value as a UTF-8 string when
+    /// the namespace is `mdta` (the common case -- the value itself is a
+    /// reverse-DNS key name), otherwise a `namespace:value` label using the
+    /// value's FourCC if it's 4 bytes, or its hex bytes otherwise.
+    fn name(&self) -> String {
+        if &self.namespace == b"mdta" {
+            if let Ok(name) = String::from_utf8(self.value.clone()) {
+                return name;
+            }
+        }
+        let namespace = String::from_utf8_lossy(&self.namespace);
+        match <[u8; 4]>::try_from(self.value.as_slice()) {
+            Ok(fourcc) => format!("{namespace}:{}", String::from_utf8_lossy(&fourcc)),
+            Err(_) => format!("{namespace}:{}", hex_encode(&self.value)),
+        }
+    }
+}
+
+fn hex_encode(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// A `meta`-handler `mebx` track located under `moov`: its track ID, key
+/// table (for resolving each sample's local key IDs to names), samples, and
+/// media timescale.
+pub(crate) struct TimedMetadataTrack {
+    pub track_id: u32,
+    pub keys: Vec<MetadataKey>,
+    pub samples: SampleTable,
+    pub timescale: u32,
+}
+
+/// Locates every `mebx` timed-metadata track under `moov`, in file order,
+/// from a single scan of the box tree.
+pub(crate) fn find_all_timed_metadata_tracks<S: SeekableStream>(stream: &mut S) -> Result<Vec<TimedMetadataTrack>> {
+    let len = stream.len()?;
+    let top_level = boxes::children(stream, 0, len)?;
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else { return Ok(Vec::new()) };
+
+    let mut tracks = Vec::new();
+    for trak in boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak") {
+        let Some(tkhd) = boxes::find_child(stream, trak.body_start, trak.end, b"tkhd")? else { continue };
+        let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { continue };
+        let Some(hdlr) = boxes::find_child(stream, mdia.body_start, mdia.end, b"hdlr")? else { continue };
+
+        let mut handler_type = [0u8; 4];
+        stream.read_at(hdlr.body_start + 8, &mut handler_type)?;
+        if &handler_type != b"meta" {
+            continue;
+        }
+
+        let Some(mdhd) = boxes::find_child(stream, mdia.body_start, mdia.end, b"mdhd")? else { continue };
+        let Some((timescale, _)) = super::read_timescale_and_duration(stream, &mdhd)? else { continue };
+        let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else { continue };
+        let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else { continue };
+        let Some(stsd) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsd")? else { continue };
+
+        // stsd is a full box: version/flags (4 bytes), entry count (4
+        // bytes), then the first sample entry.
+        let first_entry_start = stsd.body_start + 8;
+        if first_entry_start + 8 > stsd.end {
+            continue;
+        }
+        let Some(entry) = boxes::read_box_header(stream, first_entry_start, stsd.end)? else { continue };
+        if &entry.box_type != b"mebx" {
+            continue;
+        }
+
+        let Some(keys_box) = boxes::find_child(stream, entry.body_start, entry.end, b"keys")? else { continue };
+        let keys = read_keys(stream, &keys_box)?;
+
+        let Some(samples) = SampleTable::from_stbl(stream, &stbl, ParseMode::Strict)? else { continue };
+        let track_id = super::read_track_id(stream, &tkhd)?;
+        tracks.push(TimedMetadataTrack { track_id, keys, samples, timescale });
+    }
+
+    Ok(tracks)
+}
+
+/// Reads a `keys` box (the Metadata Key Table Box): version/flags (4 bytes),
+/// `entry_count` (4 bytes), then that many entries of `key_size` (4 bytes,
+/// including itself), `key_namespace` (4 bytes), and `key_size - 8` bytes of
+/// key value. Keys are 1-based; `keys[0]` here is local key ID 1.
+fn read_keys<S: SeekableStream>(stream: &mut S, keys_box: &BoxHeader) -> Result<Vec<MetadataKey>> {
+    if keys_box.body_len() < 8 {
+        return Ok(Vec::new());
+    }
+
+    let mut header = [0u8; 4];
+    stream.read_at(keys_box.body_start + 4, &mut header)?;
+    let entry_count = u32::from_be_bytes(header);
+
+    // Each entry is at least 8 bytes (key_size + key_namespace) -- reject
+    // an entry_count the box couldn't possibly hold before sizing a `Vec`
+    // off it, so a corrupt/hostile entry_count near u32::MAX can't force a
+    // multi-GB allocation.
+    let available = keys_box.end.saturating_sub(keys_box.body_start + 8);
+    if u64::from(entry_count) > available / 8 {
+        return Err(Error::Malformed { format: "mp4", reason: "keys entry_count exceeds the box's available data".into() });
+    }
+
+    let mut keys = Vec::with_capacity(entry_count as usize);
+    let mut offset = keys_box.body_start + 8;
+    for _ in 0..entry_count {
+        if offset + 8 > keys_box.end {
+            break;
+        }
+        let mut entry_header = [0u8; 8];
+        stream.read_at(offset, &mut entry_header)?;
+        let key_size = u32::from_be_bytes(entry_header[0..4].try_into().unwrap()) as u64;
+        let namespace: [u8; 4] = entry_header[4..8].try_into().unwrap();
+
+        if key_size < 8 || offset + key_size > keys_box.end {
+            break;
+        }
+        let mut value = vec![0u8; (key_size - 8) as usize];
+        stream.read_at(offset + 8, &mut value)?;
+        keys.push(MetadataKey { namespace, value });
+
+        offset += key_size;
+    }
+
+    Ok(keys)
+}
+
+/// Decodes one `mebx` sample into its key/value pairs, resolving each item's
+/// local key ID against `keys`. Unrecognized key IDs (out of range for
+/// `keys`) are skipped rather than treated as an error, since a future
+/// key table revision shouldn't break decoding of the values this reader
+/// does recognize.
+pub(crate) fn decode_sample(keys: &[MetadataKey], sample: &[u8]) -> Result<Vec<(String, Vec<u8>)>> {
+    let mut reader = SliceStream(sample);
+    let len = sample.len() as u64;
+
+    let mut values = Vec::new();
+    for item in boxes::children(&mut reader, 0, len)? {
+        let key_id = u32::from_be_bytes(item.box_type);
+        let Some(key) = key_id.checked_sub(1).and_then(|index| keys.get(index as usize)) else { continue };
+
+        let mut data = vec![0u8; item.body_len() as usize];
+        reader.read_at(item.body_start, &mut data)?;
+        values.push((key.name(), data));
+    }
+
+    Ok(values)
+}
+
+struct SliceStream<'a>(&'a [u8]);
+
+impl SeekableStream for SliceStream<'_> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = offset as usize;
+        let end = start + buf.len();
+        if end > self.0.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "read past end of buffer"));
+        }
+        buf.copy_from_slice(&self.0[start..end]);
+        Ok(())
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.0.len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn key_entry(namespace: &[u8; 4], value: &[u8]) -> Vec<u8> {
+        let mut entry = ((value.len() + 8) as u32).to_be_bytes().to_vec();
+        entry.extend_from_slice(namespace);
+        entry.extend_from_slice(value);
+        entry
+    }
+
+    #[test]
+    fn names_a_key_from_its_mdta_string_value() {
+        let key = MetadataKey { namespace: *b"mdta", value: b"com.apple.quicktime.live-photo.vitality-score".to_vec() };
+        assert_eq!(key.name(), "com.apple.quicktime.live-photo.vitality-score");
+    }
+
+    #[test]
+    fn names_a_key_from_a_fourcc_value_in_a_non_mdta_namespace() {
+        let key = MetadataKey { namespace: *b"mdln", value: b"clsf".to_vec() };
+        assert_eq!(key.name(), "mdln:clsf");
+    }
+
+    #[test]
+    fn reads_keys_from_a_key_table_box() {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&2u32.to_be_bytes()); // entry_count
+        body.extend_from_slice(&key_entry(b"mdta", b"com.example.one"));
+        body.extend_from_slice(&key_entry(b"mdta", b"com.example.two"));
+        let keys_box = sized_box(b"keys", &body);
+
+        let mut stream = MemorySeekableStream::new(keys_box);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+
+        let keys = read_keys(&mut stream, &header).unwrap();
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].name(), "com.example.one");
+        assert_eq!(keys[1].name(), "com.example.two");
+    }
+
+    #[test]
+    fn rejects_a_keys_entry_count_the_box_cant_possibly_hold() {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&u32::MAX.to_be_bytes()); // entry_count: far more than the box holds
+        let keys_box = sized_box(b"keys", &body);
+
+        let mut stream = MemorySeekableStream::new(keys_box);
+        let len = stream.len().unwrap();
+        let header = boxes::read_box_header(&mut stream, 0, len).unwrap().unwrap();
+
+        let result = read_keys(&mut stream, &header);
+        assert!(matches!(result, Err(Error::Malformed { format: "mp4", .. })));
+    }
+
+    #[test]
+    fn decodes_a_sample_resolving_local_key_ids_against_the_key_table() {
+        let keys = vec![
+            MetadataKey { namespace: *b"mdta", value: b"com.example.motion".to_vec() },
+            MetadataKey { namespace: *b"mdta", value: b"com.example.faces".to_vec() },
+        ];
+
+        let mut sample = Vec::new();
+        sample.extend_from_slice(&sized_box(&1u32.to_be_bytes(), b"moving"));
+        sample.extend_from_slice(&sized_box(&2u32.to_be_bytes(), b"2"));
+
+        let values = decode_sample(&keys, &sample).unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0], ("com.example.motion".to_string(), b"moving".to_vec()));
+        assert_eq!(values[1], ("com.example.faces".to_string(), b"2".to_vec()));
+    }
+
+    #[test]
+    fn skips_an_item_whose_key_id_is_out_of_range() {
+        let keys = vec![MetadataKey { namespace: *b"mdta", value: b"com.example.motion".to_vec() }];
+
+        let sample = sized_box(&9u32.to_be_bytes(), b"ignored");
+        assert!(decode_sample(&keys, &sample).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,811 @@
+//! A stream-owning convenience wrapper around [`SampleTable`], for advanced
+//! callers that want raw per-sample access (size, offset, timestamp, sync
+//! flag, and the sample's bytes) without re-deriving `stsc`/`stco` math
+//! themselves -- the same lookups [`crate::thumbnails`] and
+//! [`super::export_annexb_h264`] already use internally, now exposed
+//! generically for any track.
+
+use super::boxes::{self, BoxHeader};
+use super::cenc::{self, SampleAuxInfo, TrackEncryptionInfo};
+use super::channel_layout;
+use super::dolby_vision::{self, DolbyVisionConfig};
+use super::sample_table::{ParseMode, SampleTable};
+use super::TrackFlags;
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+/// One sample's location and timing, from [`TrackReader::sample_info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleInfo {
+    /// The sample's size in bytes, per `stsz`.
+    pub size: u32,
+    /// The sample's absolute byte offset in the stream, resolved via
+    /// `stsc`/`stco`/`co64`.
+    pub offset: u64,
+    /// The sample's decode timestamp, in the track's own timescale, per
+    /// `stts`.
+    pub timestamp: u64,
+    /// Whether this is a sync sample (e.g. an IDR frame), per `stss`. A
+    /// track with no `stss` box has every sample as a sync sample.
+    pub is_sync: bool,
+    /// This sample's CENC initialization vector and subsample clear/
+    /// protected byte ranges, if the track is encrypted (`tenc` present)
+    /// and the sample table or fragment has a `senc`/`saiz`+`saio` entry
+    /// for it. `None` for an unprotected track.
+    pub encryption: Option<SampleEncryption>,
+}
+
+/// A sample's per-sample CENC (ISO/IEC 23001-7 Common Encryption) side-data:
+/// its initialization vector, and, for subsample encryption, the clear/
+/// protected byte ranges a decryptor should apply the IV's cipher to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleEncryption {
+    /// The track's default key ID, from `tenc`.
+    pub key_id: [u8; 16],
+    /// This sample's initialization vector.
+    pub iv: Vec<u8>,
+    /// `(clear_bytes, protected_bytes)` pairs splitting the sample into
+    /// subsamples, in order. Empty means the whole sample is protected.
+    pub subsamples: Vec<(u16, u32)>,
+}
+
+/// A track's declared buffering and bitrate, from its sample entry's `btrt`
+/// box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitrateInfo {
+    /// The decoding buffer size, in bytes, needed for the elementary stream.
+    pub buffer_size: u32,
+    /// The maximum bitrate, in bits per second, over any window in the
+    /// stream.
+    pub max_bitrate: u32,
+    /// The average bitrate, in bits per second, over the whole stream.
+    pub avg_bitrate: u32,
+}
+
+/// Raw sample access for one `trak`, over any [`SeekableStream`].
+pub struct TrackReader<S: SeekableStream> {
+    stream: S,
+    table: SampleTable,
+    encryption: Option<(TrackEncryptionInfo, Vec<SampleAuxInfo>)>,
+    flags: TrackFlags,
+    bitrate: Option<BitrateInfo>,
+    sample_rate: Option<u32>,
+    channel_layout: Option<String>,
+    dolby_vision: Option<DolbyVisionConfig>,
+    timescale: u32,
+}
+
+impl<S: SeekableStream> TrackReader<S> {
+    /// Opens the `track_index`-th `trak` (0-based, in file order) of
+    /// `stream`, or `None` if there's no such track, or it has no
+    /// `stsz`/`stts` boxes to build a sample table from. Equivalent to
+    /// [`Self::open_with_mode`] with [`ParseMode::Strict`].
+    pub fn open(stream: S, track_index: usize) -> Result<Option<Self>> {
+        Self::open_with_mode(stream, track_index, ParseMode::Strict)
+    }
+
+    /// [`Self::open`], with `mode` controlling how the resulting reader's
+    /// sample lookups respond to an inconsistent sample table (see
+    /// [`ParseMode`]) instead of always erroring.
+    pub fn open_with_mode(mut stream: S, track_index: usize, mode: ParseMode) -> Result<Option<Self>> {
+        let Some((tkhd, mdia, stbl, stsd)) = find_track_boxes(&mut stream, track_index)? else { return Ok(None) };
+        let Some(table) = SampleTable::from_stbl(&mut stream, &stbl, mode)? else { return Ok(None) };
+        let encryption = read_track_encryption(&mut stream, &stbl, &stsd)?;
+        let flags = super::read_track_flags(&mut stream, &tkhd)?;
+        let bitrate = read_btrt(&mut stream, &stsd)?;
+        let sample_rate = read_audio_sample_rate(&mut stream, &mdia, &stsd)?;
+        let channel_layout = read_track_channel_layout(&mut stream, &mdia, &stsd)?;
+        let dolby_vision = read_track_dolby_vision_config(&mut stream, &mdia, &stsd)?;
+        let timescale = read_track_timescale(&mut stream, &mdia)?;
+        Ok(Some(Self { stream, table, encryption, flags, bitrate, sample_rate, channel_layout, dolby_vision, timescale }))
+    }
+
+    /// The number of samples in the track, per `stsz`.
+    pub fn sample_count(&self) -> u32 {
+        self.table.sample_count()
+    }
+
+    /// Whether `tkhd`'s `Track_enabled` flag is set.
+    pub fn enabled(&self) -> bool {
+        self.flags.enabled
+    }
+
+    /// Whether `tkhd`'s `Track_in_movie` flag is set.
+    pub fn in_movie(&self) -> bool {
+        self.flags.in_movie
+    }
+
+    /// The track's `tkhd.alternate_group`; tracks sharing a non-zero group
+    /// are alternates of each other (e.g. the same audio in different
+    /// languages), and only one should be played at a time.
+    pub fn alternate_group(&self) -> u16 {
+        self.flags.alternate_group
+    }
+
+    /// The track's declared buffer size and bitrates, from its sample
+    /// entry's `btrt` box, if present. This is the muxer's own declared
+    /// figure, not an estimate computed from sample sizes and duration.
+    pub fn bitrate(&self) -> Option<BitrateInfo> {
+        self.bitrate
+    }
+
+    /// The track's audio sample rate in Hz, for a `soun` handler track,
+    /// preferring the true rate from an `esds`/`AudioSpecificConfig` (which
+    /// resolves HE-AAC's SBR extension rate) over the sample entry's legacy
+    /// 16.16 fixed-point field. `None` for a non-audio track, or one whose
+    /// sample entry this crate doesn't recognize.
+    pub fn sample_rate(&self) -> Option<u32> {
+        self.sample_rate
+    }
+
+    /// The track's named channel layout (e.g. `"5.1"`, `"7.1"`), from its
+    /// sample entry's `chnl`, `dec3`, or `dac3` box. `None` when no such box
+    /// is present, or its layout isn't one this crate names -- callers still
+    /// have the bare channel count from the container's own metadata.
+    pub fn channel_layout(&self) -> Option<&str> {
+        self.channel_layout.as_deref()
+    }
+
+    /// The track's Dolby Vision profile, level, and base-layer
+    /// compatibility ID, from its sample entry's `dvcC`/`dvvC` box.
+    /// `None` for a non-Dolby-Vision track.
+    pub fn dolby_vision(&self) -> Option<DolbyVisionConfig> {
+        self.dolby_vision
+    }
+
+    /// The track's `mdia.mdhd` timescale, i.e. the units [`SampleInfo::timestamp`]
+    /// is expressed in, or `0` if the track has no `mdhd`.
+    pub fn timescale(&self) -> u32 {
+        self.timescale
+    }
+
+    /// The track's `stts` runs as `(sample_count, sample_delta)` pairs, for
+    /// a caller that only needs the distinct inter-sample deltas (e.g.
+    /// [`crate::analysis::track_frame_rate`]) rather than every sample's
+    /// resolved timestamp via [`Self::sample_info`].
+    pub(crate) fn stts_runs(&mut self) -> Result<Vec<(u32, u32)>> {
+        self.table.stts_runs(&mut self.stream)
+    }
+
+    /// The size, offset, timestamp, sync flag, and (for an encrypted track)
+    /// encryption info of sample `index` (0-based), each read directly out
+    /// of its respective table entry rather than materializing the whole
+    /// table up front.
+    pub fn sample_info(&mut self, index: u32) -> Result<SampleInfo> {
+        let encryption = self.encryption.as_ref().map(|(track, samples)| SampleEncryption {
+            key_id: track.default_kid,
+            iv: samples.get(index as usize).map(|s| s.iv.clone()).unwrap_or_default(),
+            subsamples: samples.get(index as usize).map(|s| s.subsamples.clone()).unwrap_or_default(),
+        });
+
+        Ok(SampleInfo {
+            size: self.table.size(&mut self.stream, index)?,
+            offset: self.table.offset(&mut self.stream, index)?,
+            timestamp: self.table.timestamp(&mut self.stream, index)?,
+            is_sync: self.table.is_sync_sample(&mut self.stream, index)?,
+            encryption,
+        })
+    }
+
+    /// Reads sample `index` (0-based)'s bytes from the stream.
+    pub fn read_sample(&mut self, index: u32) -> Result<Vec<u8>> {
+        let size = self.table.size(&mut self.stream, index)?;
+        let offset = self.table.offset(&mut self.stream, index)?;
+        let mut data = vec![0u8; size as usize];
+        self.stream.read_at(offset, &mut data)?;
+        Ok(data)
+    }
+}
+
+/// Finds the `track_index`-th `trak`'s `tkhd`, `mdia`, `stbl`, and `stsd` box
+/// headers.
+fn find_track_boxes<S: SeekableStream>(
+    stream: &mut S,
+    track_index: usize,
+) -> Result<Option<(BoxHeader, BoxHeader, BoxHeader, BoxHeader)>> {
+    let len = stream.len()?;
+    let top_level = boxes::children(stream, 0, len)?;
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else { return Ok(None) };
+
+    let traks: Vec<_> =
+        boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak").collect();
+    let Some(trak) = traks.get(track_index) else { return Ok(None) };
+
+    let Some(tkhd) = boxes::find_child(stream, trak.body_start, trak.end, b"tkhd")? else { return Ok(None) };
+    let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else { return Ok(None) };
+    let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else { return Ok(None) };
+    let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else { return Ok(None) };
+    let Some(stsd) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsd")? else { return Ok(None) };
+
+    Ok(Some((tkhd, mdia, stbl, stsd)))
+}
+
+/// Reads the track's audio sample rate, if it's a `soun` handler track with
+/// a recognizable sample entry. Mirrors [`super::read_audio_track`]'s
+/// legacy-fields-then-`esds`-override logic.
+fn read_audio_sample_rate<S: SeekableStream>(
+    stream: &mut S,
+    mdia: &BoxHeader,
+    stsd: &BoxHeader,
+) -> Result<Option<u32>> {
+    let Some(hdlr) = boxes::find_child(stream, mdia.body_start, mdia.end, b"hdlr")? else { return Ok(None) };
+
+    let mut handler_type = [0u8; 4];
+    stream.read_at(hdlr.body_start + 8, &mut handler_type)?;
+    if &handler_type != b"soun" {
+        return Ok(None);
+    }
+
+    let first_entry_start = stsd.body_start + 8;
+    if first_entry_start + 8 > stsd.end {
+        return Ok(None);
+    }
+    let Some(entry) = boxes::read_box_header(stream, first_entry_start, stsd.end)? else { return Ok(None) };
+
+    if let Some(aac) = super::esds::read_aac_config(stream, &entry)? {
+        return Ok(Some(aac.sample_rate));
+    }
+
+    let mut audio_fields = [0u8; 20];
+    stream.read_at(entry.body_start + 8, &mut audio_fields)?;
+    Ok(Some(u32::from_be_bytes(audio_fields[16..20].try_into().unwrap()) >> 16))
+}
+
+/// Reads the track's channel layout from `stsd`'s first sample entry, if
+/// it's a `soun` handler track with a `chnl`, `dec3`, or `dac3` child box.
+fn read_track_channel_layout<S: SeekableStream>(
+    stream: &mut S,
+    mdia: &BoxHeader,
+    stsd: &BoxHeader,
+) -> Result<Option<String>> {
+    let Some(hdlr) = boxes::find_child(stream, mdia.body_start, mdia.end, b"hdlr")? else { return Ok(None) };
+
+    let mut handler_type = [0u8; 4];
+    stream.read_at(hdlr.body_start + 8, &mut handler_type)?;
+    if &handler_type != b"soun" {
+        return Ok(None);
+    }
+
+    let first_entry_start = stsd.body_start + 8;
+    if first_entry_start + 8 > stsd.end {
+        return Ok(None);
+    }
+    let Some(entry) = boxes::read_box_header(stream, first_entry_start, stsd.end)? else { return Ok(None) };
+    channel_layout::read_channel_layout(stream, &entry)
+}
+
+/// Reads the track's Dolby Vision configuration from `stsd`'s first sample
+/// entry, if it's a `vide` handler track with a `dvcC`/`dvvC` child box.
+fn read_track_dolby_vision_config<S: SeekableStream>(
+    stream: &mut S,
+    mdia: &BoxHeader,
+    stsd: &BoxHeader,
+) -> Result<Option<DolbyVisionConfig>> {
+    let Some(hdlr) = boxes::find_child(stream, mdia.body_start, mdia.end, b"hdlr")? else { return Ok(None) };
+
+    let mut handler_type = [0u8; 4];
+    stream.read_at(hdlr.body_start + 8, &mut handler_type)?;
+    if &handler_type != b"vide" {
+        return Ok(None);
+    }
+
+    let first_entry_start = stsd.body_start + 8;
+    if first_entry_start + 8 > stsd.end {
+        return Ok(None);
+    }
+    let Some(entry) = boxes::read_box_header(stream, first_entry_start, stsd.end)? else { return Ok(None) };
+    dolby_vision::read_dolby_vision_config(stream, entry.body_start, entry.end)
+}
+
+/// Reads `mdia`'s `mdhd` timescale, or `0` if it's missing or unreadable.
+/// Mirrors [`super::track_timescale`], which takes a `trak` rather than an
+/// already-resolved `mdia`.
+fn read_track_timescale<S: SeekableStream>(stream: &mut S, mdia: &BoxHeader) -> Result<u32> {
+    let Some(mdhd) = boxes::find_child(stream, mdia.body_start, mdia.end, b"mdhd")? else { return Ok(0) };
+    Ok(super::read_timescale_and_duration(stream, &mdhd)?.map_or(0, |(timescale, _)| timescale))
+}
+
+/// Reads `btrt` out of `stsd`'s first sample entry, if it has one.
+/// `btrt` is a plain (non-full) box: `bufferSizeDB` (4), `maxBitrate` (4),
+/// `avgBitrate` (4).
+fn read_btrt<S: SeekableStream>(stream: &mut S, stsd: &BoxHeader) -> Result<Option<BitrateInfo>> {
+    let first_entry_start = stsd.body_start + 8;
+    if first_entry_start + 8 > stsd.end {
+        return Ok(None);
+    }
+    let Some(entry) = boxes::read_box_header(stream, first_entry_start, stsd.end)? else { return Ok(None) };
+    let Some(btrt) = boxes::find_child(stream, entry.body_start, entry.end, b"btrt")? else { return Ok(None) };
+
+    let mut body = [0u8; 12];
+    stream.read_at(btrt.body_start, &mut body)?;
+    Ok(Some(BitrateInfo {
+        buffer_size: u32::from_be_bytes(body[0..4].try_into().unwrap()),
+        max_bitrate: u32::from_be_bytes(body[4..8].try_into().unwrap()),
+        avg_bitrate: u32::from_be_bytes(body[8..12].try_into().unwrap()),
+    }))
+}
+
+/// Reads `tenc` from `stsd`, then (if the track is protected) every
+/// sample's IV and subsample map from `stbl`'s `senc` or `saiz`/`saio`.
+fn read_track_encryption<S: SeekableStream>(
+    stream: &mut S,
+    stbl: &BoxHeader,
+    stsd: &BoxHeader,
+) -> Result<Option<(TrackEncryptionInfo, Vec<SampleAuxInfo>)>> {
+    let Some(track_info) = cenc::read_tenc(stream, stsd)? else { return Ok(None) };
+    let samples = cenc::read_sample_encryption(stream, stbl, track_info.per_sample_iv_size)?.unwrap_or_default();
+    Ok(Some((track_info, samples)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn tkhd_box(flags: u32, alternate_group: u16) -> Vec<u8> {
+        let mut body = vec![0u8; 84];
+        body[0..4].copy_from_slice(&flags.to_be_bytes());
+        body[34..36].copy_from_slice(&alternate_group.to_be_bytes());
+        sized_box(b"tkhd", &body)
+    }
+
+    fn sample_mp4() -> Vec<u8> {
+        let samples: [&[u8]; 2] = [&[1, 2, 3], &[4, 5]];
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes());
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stts_body.extend_from_slice(&1000u32.to_be_bytes());
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let mut stss_body = vec![0u8; 4];
+        stss_body.extend_from_slice(&1u32.to_be_bytes());
+        stss_body.extend_from_slice(&1u32.to_be_bytes()); // sample 1 (1-based) is a sync sample
+        let stss = sized_box(b"stss", &stss_body);
+
+        // Chunk offset is patched in after we know where `mdat`'s body lands.
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let entry = sized_box(b"mp4a", &[0u8; 8]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &entry].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let stbl = sized_box(b"stbl", &[stsz, stts, stsc, stss, stsd, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr = sized_box(b"hdlr", &[0u8; 20]);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let tkhd = tkhd_box(0x3, 0); // enabled + in_movie, no alternate group
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn reads_sample_info_and_bytes_without_a_format_specific_api() {
+        let mut reader = TrackReader::open(MemorySeekableStream::new(sample_mp4()), 0).unwrap().unwrap();
+
+        assert_eq!(reader.sample_count(), 2);
+        assert!(reader.enabled());
+        assert!(reader.in_movie());
+        assert_eq!(reader.alternate_group(), 0);
+
+        let first = reader.sample_info(0).unwrap();
+        assert_eq!(first.size, 3);
+        assert_eq!(first.timestamp, 0);
+        assert!(first.is_sync);
+        assert_eq!(reader.read_sample(0).unwrap(), vec![1, 2, 3]);
+
+        let second = reader.sample_info(1).unwrap();
+        assert_eq!(second.size, 2);
+        assert_eq!(second.timestamp, 1000);
+        assert!(!second.is_sync);
+        assert_eq!(reader.read_sample(1).unwrap(), vec![4, 5]);
+    }
+
+    #[test]
+    fn returns_none_for_an_out_of_range_track_index() {
+        let reader = TrackReader::open(MemorySeekableStream::new(sample_mp4()), 1).unwrap();
+        assert!(reader.is_none());
+    }
+
+    fn encrypted_sample_mp4() -> Vec<u8> {
+        let samples: [&[u8]; 1] = [&[9, 9, 9]];
+        let kid = [5u8; 16];
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes());
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stts_body.extend_from_slice(&1000u32.to_be_bytes());
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let mut tenc_body = vec![0u8; 4]; // version/flags
+        tenc_body.push(0); // reserved
+        tenc_body.push(1); // default_isProtected
+        tenc_body.push(8); // default_Per_Sample_IV_Size
+        tenc_body.extend_from_slice(&kid);
+        let tenc = sized_box(b"tenc", &tenc_body);
+        let schi = sized_box(b"schi", &tenc);
+        let sinf = sized_box(b"sinf", &schi);
+        let entry = sized_box(b"encv", &sinf);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &entry].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let iv = [3u8; 8];
+        let mut senc_body = 0u32.to_be_bytes().to_vec(); // flags: no subsamples
+        senc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        senc_body.extend_from_slice(&iv);
+        let senc = sized_box(b"senc", &senc_body);
+
+        let stbl = sized_box(b"stbl", &[stsz, stts, stsc, stsd, senc, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr = sized_box(b"hdlr", &[0u8; 20]);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let tkhd = tkhd_box(0x1, 7); // enabled only, alternate group 7
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn surfaces_the_ivs_and_key_id_of_an_encrypted_tracks_samples() {
+        let mut reader = TrackReader::open(MemorySeekableStream::new(encrypted_sample_mp4()), 0).unwrap().unwrap();
+
+        let info = reader.sample_info(0).unwrap();
+        let encryption = info.encryption.unwrap();
+        assert_eq!(encryption.key_id, [5u8; 16]);
+        assert_eq!(encryption.iv, vec![3u8; 8]);
+        assert!(encryption.subsamples.is_empty());
+    }
+
+    fn sample_mp4_with_btrt() -> Vec<u8> {
+        let samples: [&[u8]; 1] = [&[7, 7, 7]];
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes());
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stts_body.extend_from_slice(&1000u32.to_be_bytes());
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let mut btrt_body = Vec::new();
+        btrt_body.extend_from_slice(&1500u32.to_be_bytes()); // buffer_size
+        btrt_body.extend_from_slice(&256_000u32.to_be_bytes()); // max_bitrate
+        btrt_body.extend_from_slice(&192_000u32.to_be_bytes()); // avg_bitrate
+        let btrt = sized_box(b"btrt", &btrt_body);
+
+        let entry = sized_box(b"mp4a", &btrt);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &entry].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let stbl = sized_box(b"stbl", &[stsz, stts, stsc, stsd, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr = sized_box(b"hdlr", &[0u8; 20]);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let tkhd = tkhd_box(0x3, 0);
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn surfaces_buffer_size_and_bitrates_from_a_btrt_box() {
+        let reader = TrackReader::open(MemorySeekableStream::new(sample_mp4_with_btrt()), 0).unwrap().unwrap();
+
+        let bitrate = reader.bitrate().unwrap();
+        assert_eq!(bitrate.buffer_size, 1500);
+        assert_eq!(bitrate.max_bitrate, 256_000);
+        assert_eq!(bitrate.avg_bitrate, 192_000);
+    }
+
+    #[test]
+    fn leaves_bitrate_none_without_a_btrt_box() {
+        let reader = TrackReader::open(MemorySeekableStream::new(sample_mp4()), 0).unwrap().unwrap();
+        assert!(reader.bitrate().is_none());
+    }
+
+    #[test]
+    fn surfaces_disabled_and_alternate_group_flags_from_tkhd() {
+        let reader = TrackReader::open(MemorySeekableStream::new(encrypted_sample_mp4()), 0).unwrap().unwrap();
+
+        assert!(reader.enabled());
+        assert!(!reader.in_movie());
+        assert_eq!(reader.alternate_group(), 7);
+    }
+
+    fn sample_mp4_with_audio_sample_rate() -> Vec<u8> {
+        let samples: [&[u8]; 1] = [&[9, 9, 9]];
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes());
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stts_body.extend_from_slice(&1000u32.to_be_bytes());
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let mut sample_entry_body = vec![0u8; 8]; // reserved + data_reference_index
+        sample_entry_body.extend_from_slice(&[0u8; 8]); // version/revision/vendor
+        sample_entry_body.extend_from_slice(&2u16.to_be_bytes()); // channels
+        sample_entry_body.extend_from_slice(&16u16.to_be_bytes()); // sample size
+        sample_entry_body.extend_from_slice(&[0u8; 4]); // pre_defined + reserved
+        sample_entry_body.extend_from_slice(&(48000u32 << 16).to_be_bytes()); // sample rate
+        let entry = sized_box(b"mp4a", &sample_entry_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &entry].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let stbl = sized_box(b"stbl", &[stsz, stts, stsc, stsd, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"soun", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let tkhd = tkhd_box(0x3, 0);
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn surfaces_sample_rate_from_the_legacy_audio_fields() {
+        let reader = TrackReader::open(MemorySeekableStream::new(sample_mp4_with_audio_sample_rate()), 0)
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(reader.sample_rate(), Some(48000));
+    }
+
+    #[test]
+    fn leaves_sample_rate_none_for_a_non_audio_handler() {
+        let reader = TrackReader::open(MemorySeekableStream::new(sample_mp4()), 0).unwrap().unwrap();
+        assert!(reader.sample_rate().is_none());
+    }
+
+    fn sample_mp4_with_chnl() -> Vec<u8> {
+        let samples: [&[u8]; 1] = [&[9, 9, 9]];
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes());
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stts_body.extend_from_slice(&1000u32.to_be_bytes());
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let chnl_body = [0u8, 0, 0, 0, 0x1, 6]; // version/flags, channel-structured, layout 6 (5.1)
+        let chnl = sized_box(b"chnl", &chnl_body);
+        let mut entry_body = vec![0u8; 28];
+        entry_body.extend_from_slice(&chnl);
+        let entry = sized_box(b"ec-3", &entry_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &entry].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let stbl = sized_box(b"stbl", &[stsz, stts, stsc, stsd, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"soun", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let tkhd = tkhd_box(0x3, 0);
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn surfaces_channel_layout_from_a_chnl_box() {
+        let reader = TrackReader::open(MemorySeekableStream::new(sample_mp4_with_chnl()), 0).unwrap().unwrap();
+        assert_eq!(reader.channel_layout(), Some("5.1"));
+    }
+
+    #[test]
+    fn leaves_channel_layout_none_without_a_recognized_box() {
+        let reader = TrackReader::open(MemorySeekableStream::new(sample_mp4()), 0).unwrap().unwrap();
+        assert!(reader.channel_layout().is_none());
+    }
+
+    #[test]
+    fn leaves_encryption_none_for_an_unprotected_track() {
+        let mut reader = TrackReader::open(MemorySeekableStream::new(sample_mp4()), 0).unwrap().unwrap();
+        assert!(reader.sample_info(0).unwrap().encryption.is_none());
+    }
+
+    fn sample_mp4_with_dvcc() -> Vec<u8> {
+        let samples: [&[u8]; 1] = [&[9, 9, 9]];
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes());
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stts_body.extend_from_slice(&1000u32.to_be_bytes());
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        // dv_profile=8, dv_level=9, rpu/el/bl present, compat_id=2.
+        let packed: u32 = (8u32 << 17) | (9u32 << 11) | (0b111 << 8) | (2u32 << 4);
+        let mut dvcc_body = vec![1, 0]; // dv_version_major, dv_version_minor
+        dvcc_body.extend_from_slice(&packed.to_be_bytes()[1..]);
+        let dvcc = sized_box(b"dvcC", &dvcc_body);
+
+        let mut entry_body = vec![0u8; 78];
+        entry_body.extend_from_slice(&dvcc);
+        let entry = sized_box(b"hev1", &entry_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &entry].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let stbl = sized_box(b"stbl", &[stsz, stts, stsc, stsd, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[hdlr, minf].concat());
+        let tkhd = tkhd_box(0x3, 0);
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn surfaces_dolby_vision_profile_level_and_compatibility_id_from_dvcc() {
+        let reader = TrackReader::open(MemorySeekableStream::new(sample_mp4_with_dvcc()), 0).unwrap().unwrap();
+
+        let config = reader.dolby_vision().unwrap();
+        assert_eq!(config.profile, 8);
+        assert_eq!(config.level, 9);
+        assert_eq!(config.bl_signal_compatibility_id, 2);
+    }
+
+    #[test]
+    fn leaves_dolby_vision_none_for_a_plain_video_track() {
+        let reader = TrackReader::open(MemorySeekableStream::new(sample_mp4()), 0).unwrap().unwrap();
+        assert!(reader.dolby_vision().is_none());
+    }
+}
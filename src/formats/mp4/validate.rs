@@ -0,0 +1,609 @@
+//! Structural conformance checks for MP4/QuickTime files, for upload
+//! pipelines that want to reject an obviously broken file before spending
+//! time decoding or re-muxing it. This only checks the container's own
+//! internal consistency (box bounds, table counts); it doesn't decode
+//! samples or validate codec-specific payloads.
+
+use super::boxes::{self, BoxHeader};
+use super::sample_table::{ParseMode, SampleTable};
+use crate::error::Result;
+use crate::stream::SeekableStream;
+
+/// Most sample-bounds violations reported per track by
+/// [`check_sample_bounds`] -- a badly truncated file can have thousands of
+/// out-of-bounds samples, and a validation report is for diagnosis, not an
+/// exhaustive sample-by-sample audit.
+const MAX_SAMPLE_BOUND_ISSUES_PER_TRACK: usize = 10;
+
+/// How serious a [`ValidationIssue`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// The file is structurally broken in a way that will likely fail to
+    /// parse or play.
+    Error,
+    /// Unusual but survivable; worth a human's attention, not grounds for
+    /// rejecting the file.
+    Warning,
+}
+
+/// One structural problem found by [`validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationIssue {
+    /// How serious this issue is.
+    pub severity: Severity,
+    /// A human-readable description of the problem.
+    pub message: String,
+    /// The byte offset the issue relates to, if there's a single one (a
+    /// box's start, a chunk offset) rather than one spanning the whole
+    /// file (a missing mandatory box).
+    pub offset: Option<u64>,
+}
+
+/// The result of [`validate`]: every structural issue found, in the order
+/// they were discovered while walking the box tree.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidationReport {
+    /// The issues found, if any. Empty means the file looks structurally
+    /// sound.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Whether the file has no [`Severity::Error`]-level issues. A file can
+    /// still have [`Severity::Warning`]s and be valid.
+    pub fn is_valid(&self) -> bool {
+        !self.issues.iter().any(|issue| issue.severity == Severity::Error)
+    }
+}
+
+/// Checks `stream` for structural problems: a truncated or out-of-bounds
+/// top-level box, missing mandatory boxes, and, for each track, an `stts`
+/// whose runs don't add up to `stsz`'s declared sample count, or an
+/// `stco`/`co64` chunk offset that lands past the end of the file or inside
+/// a non-`mdat` box instead of the sample data it's supposed to point to.
+///
+/// Unlike the rest of this crate's parsing, a box that's too corrupt to
+/// read is reported as an [`Severity::Error`] issue rather than failing the
+/// whole call with [`crate::Error::Malformed`] -- the point of a validation
+/// report is to describe what's wrong with a bad file, not just refuse it.
+pub fn validate<S: SeekableStream>(stream: &mut S) -> Result<ValidationReport> {
+    let mut report = ValidationReport::default();
+    let file_len = stream.len()?;
+    let top_level = lenient_top_level_boxes(stream, file_len, &mut report)?;
+
+    let Some(moov) = top_level.iter().find(|b| &b.box_type == b"moov") else {
+        report.issues.push(ValidationIssue {
+            severity: Severity::Error,
+            message: "missing mandatory moov box".into(),
+            offset: None,
+        });
+        return Ok(report);
+    };
+    if !top_level.iter().any(|b| &b.box_type == b"mdat") {
+        report.issues.push(ValidationIssue {
+            severity: Severity::Warning,
+            message: "no mdat box; file has no sample data (fragmented or metadata-only)".into(),
+            offset: None,
+        });
+    }
+
+    let traks: Vec<_> =
+        boxes::children(stream, moov.body_start, moov.end)?.into_iter().filter(|b| &b.box_type == b"trak").collect();
+    for (index, trak) in traks.iter().enumerate() {
+        validate_track(stream, trak, index, file_len, &top_level, &mut report)?;
+    }
+
+    Ok(report)
+}
+
+/// Walks the top-level boxes the same way [`boxes::children`] does, except
+/// a box whose header is truncated or claims to extend past `file_len`
+/// stops the walk and records an issue instead of failing the whole call.
+fn lenient_top_level_boxes<S: SeekableStream>(
+    stream: &mut S,
+    file_len: u64,
+    report: &mut ValidationReport,
+) -> Result<Vec<BoxHeader>> {
+    let mut out = Vec::new();
+    let mut offset = 0u64;
+    loop {
+        match boxes::read_box_header(stream, offset, file_len) {
+            Ok(None) => break,
+            Ok(Some(header)) => {
+                offset = header.end;
+                out.push(header);
+            }
+            Err(_) => {
+                report.issues.push(ValidationIssue {
+                    severity: Severity::Error,
+                    message: format!("box at offset {offset} is truncated or extends past end of file ({file_len} bytes)"),
+                    offset: Some(offset),
+                });
+                break;
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn validate_track<S: SeekableStream>(
+    stream: &mut S,
+    trak: &BoxHeader,
+    index: usize,
+    file_len: u64,
+    top_level: &[BoxHeader],
+    report: &mut ValidationReport,
+) -> Result<()> {
+    let Some(mdia) = boxes::find_child(stream, trak.body_start, trak.end, b"mdia")? else {
+        report.issues.push(missing_box_issue(index, "mdia", trak.start));
+        return Ok(());
+    };
+    let Some(minf) = boxes::find_child(stream, mdia.body_start, mdia.end, b"minf")? else {
+        report.issues.push(missing_box_issue(index, "minf", mdia.start));
+        return Ok(());
+    };
+    let Some(stbl) = boxes::find_child(stream, minf.body_start, minf.end, b"stbl")? else {
+        report.issues.push(missing_box_issue(index, "stbl", minf.start));
+        return Ok(());
+    };
+    let Some(stsz) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stsz")? else {
+        report.issues.push(missing_box_issue(index, "stsz", stbl.start));
+        return Ok(());
+    };
+    let Some(stts) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stts")? else {
+        report.issues.push(missing_box_issue(index, "stts", stbl.start));
+        return Ok(());
+    };
+
+    let mut sample_count_buf = [0u8; 4];
+    stream.read_at(stsz.body_start + 8, &mut sample_count_buf)?;
+    let sample_count = u64::from(u32::from_be_bytes(sample_count_buf));
+
+    let stts_sample_total = sum_stts_run_counts(stream, &stts)?;
+    if stts_sample_total != sample_count {
+        report.issues.push(ValidationIssue {
+            severity: Severity::Error,
+            message: format!(
+                "track {index}: stts runs cover {stts_sample_total} samples but stsz declares {sample_count}"
+            ),
+            offset: Some(stts.start),
+        });
+    }
+
+    if let Some(stco) = boxes::find_child(stream, stbl.body_start, stbl.end, b"stco")? {
+        check_chunk_offsets(stream, &stco, 4, index, file_len, top_level, report)?;
+    } else if let Some(co64) = boxes::find_child(stream, stbl.body_start, stbl.end, b"co64")? {
+        check_chunk_offsets(stream, &co64, 8, index, file_len, top_level, report)?;
+    }
+
+    check_sample_bounds(stream, &stbl, index, file_len, top_level, report)?;
+
+    Ok(())
+}
+
+fn missing_box_issue(track_index: usize, box_type: &str, parent_offset: u64) -> ValidationIssue {
+    ValidationIssue {
+        severity: Severity::Error,
+        message: format!("track {track_index} has no {box_type} box"),
+        offset: Some(parent_offset),
+    }
+}
+
+/// `stts` is a full box: version/flags (4), entry_count (4), then
+/// `(sample_count, sample_delta)` pairs of 4-byte fields each. Sums the
+/// `sample_count` half of every run.
+fn sum_stts_run_counts<S: SeekableStream>(stream: &mut S, stts: &BoxHeader) -> Result<u64> {
+    let mut entry_count_buf = [0u8; 4];
+    stream.read_at(stts.body_start + 4, &mut entry_count_buf)?;
+    let entry_count = u32::from_be_bytes(entry_count_buf);
+
+    let mut total = 0u64;
+    for i in 0..entry_count {
+        let mut run_count_buf = [0u8; 4];
+        stream.read_at(stts.body_start + 8 + u64::from(i) * 8, &mut run_count_buf)?;
+        total += u64::from(u32::from_be_bytes(run_count_buf));
+    }
+    Ok(total)
+}
+
+/// Flags a chunk offset in `chunk_box` (`stco`, 4-byte entries, or `co64`,
+/// 8-byte entries) that's at or past the end of the file, or that lands
+/// inside some other top-level box instead of the `mdat` it's meant to
+/// point into.
+fn check_chunk_offsets<S: SeekableStream>(
+    stream: &mut S,
+    chunk_box: &BoxHeader,
+    entry_size: u64,
+    track_index: usize,
+    file_len: u64,
+    top_level: &[BoxHeader],
+    report: &mut ValidationReport,
+) -> Result<()> {
+    let mut count_buf = [0u8; 4];
+    stream.read_at(chunk_box.body_start + 4, &mut count_buf)?;
+    let count = u32::from_be_bytes(count_buf);
+
+    for i in 0..count {
+        let mut buf = [0u8; 8];
+        stream.read_at(chunk_box.body_start + 8 + u64::from(i) * entry_size, &mut buf[8 - entry_size as usize..])?;
+        let offset = u64::from_be_bytes(buf);
+
+        if offset >= file_len {
+            report.issues.push(ValidationIssue {
+                severity: Severity::Error,
+                message: format!("track {track_index}: chunk offset {offset} is at or past end of file ({file_len} bytes)"),
+                offset: Some(chunk_box.start),
+            });
+            continue;
+        }
+        if top_level.iter().any(|b| &b.box_type == b"mdat" && offset >= b.start && offset < b.end) {
+            continue;
+        }
+        if let Some(overlapped) = top_level.iter().find(|b| &b.box_type != b"mdat" && offset >= b.start && offset < b.end) {
+            report.issues.push(ValidationIssue {
+                severity: Severity::Error,
+                message: format!(
+                    "track {track_index}: chunk offset {offset} overlaps the {} box instead of pointing into mdat",
+                    String::from_utf8_lossy(&overlapped.box_type)
+                ),
+                offset: Some(chunk_box.start),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that every sample's `stsc`/`stco`-resolved offset and `stsz` size
+/// together land entirely within the file, and within an `mdat` box rather
+/// than some other top-level box. [`check_chunk_offsets`] above only looks
+/// at each chunk's first sample, so it can miss later samples in an
+/// over-long chunk, or ones a truncated upload clipped off; this walks
+/// every sample via [`SampleTable`] to catch those. Stops after
+/// [`MAX_SAMPLE_BOUND_ISSUES_PER_TRACK`] violations so a badly corrupt
+/// file doesn't produce one issue per sample.
+fn check_sample_bounds<S: SeekableStream>(
+    stream: &mut S,
+    stbl: &BoxHeader,
+    track_index: usize,
+    file_len: u64,
+    top_level: &[BoxHeader],
+    report: &mut ValidationReport,
+) -> Result<()> {
+    let Some(table) = SampleTable::from_stbl(stream, stbl, ParseMode::Lenient)? else { return Ok(()) };
+    // Resolved once up front in a single forward pass -- see
+    // `SampleTable::offsets`'s doc comment for why calling
+    // `SampleTable::offset` per sample here would be O(n^2).
+    let offsets = table.offsets(stream)?;
+
+    let mut violations = 0usize;
+    for index in 0..table.sample_count() {
+        if violations >= MAX_SAMPLE_BOUND_ISSUES_PER_TRACK {
+            report.issues.push(ValidationIssue {
+                severity: Severity::Warning,
+                message: format!(
+                    "track {track_index}: stopped after {MAX_SAMPLE_BOUND_ISSUES_PER_TRACK} sample bounds violations; more may remain"
+                ),
+                offset: None,
+            });
+            break;
+        }
+
+        let Some(offset) = offsets[index as usize] else { continue };
+        let Ok(size) = table.size(stream, index) else { continue };
+        let Some(end) = offset.checked_add(u64::from(size)) else {
+            report.issues.push(ValidationIssue {
+                severity: Severity::Error,
+                message: format!("track {track_index}: sample {index} offset {offset} + size {size} overflows"),
+                offset: Some(offset),
+            });
+            violations += 1;
+            continue;
+        };
+
+        if end > file_len {
+            report.issues.push(ValidationIssue {
+                severity: Severity::Error,
+                message: format!(
+                    "track {track_index}: sample {index} spans bytes {offset}..{end}, past end of file ({file_len} bytes)"
+                ),
+                offset: Some(offset),
+            });
+            violations += 1;
+            continue;
+        }
+
+        if !top_level.iter().any(|b| &b.box_type == b"mdat" && offset >= b.start && end <= b.end) {
+            report.issues.push(ValidationIssue {
+                severity: Severity::Error,
+                message: format!(
+                    "track {track_index}: sample {index} at bytes {offset}..{end} falls outside any mdat box"
+                ),
+                offset: Some(offset),
+            });
+            violations += 1;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn build_stsz(sample_count: u32) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&0u32.to_be_bytes()); // sample_size
+        body.extend_from_slice(&sample_count.to_be_bytes());
+        sized_box(b"stsz", &body)
+    }
+
+    fn build_stts(runs: &[(u32, u32)]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&(runs.len() as u32).to_be_bytes());
+        for (count, delta) in runs {
+            body.extend_from_slice(&count.to_be_bytes());
+            body.extend_from_slice(&delta.to_be_bytes());
+        }
+        sized_box(b"stts", &body)
+    }
+
+    fn build_stsz_with_sizes(sizes: &[u32]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&0u32.to_be_bytes()); // sample_size: 0 means per-sample entries follow
+        body.extend_from_slice(&(sizes.len() as u32).to_be_bytes());
+        for size in sizes {
+            body.extend_from_slice(&size.to_be_bytes());
+        }
+        sized_box(b"stsz", &body)
+    }
+
+    fn build_stsc(entries: &[(u32, u32)]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+        for (first_chunk, samples_per_chunk) in entries {
+            body.extend_from_slice(&first_chunk.to_be_bytes());
+            body.extend_from_slice(&samples_per_chunk.to_be_bytes());
+            body.extend_from_slice(&1u32.to_be_bytes()); // sample_description_index
+        }
+        sized_box(b"stsc", &body)
+    }
+
+    fn build_stco(chunk_offsets: &[u32]) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags
+        body.extend_from_slice(&(chunk_offsets.len() as u32).to_be_bytes());
+        for offset in chunk_offsets {
+            body.extend_from_slice(&offset.to_be_bytes());
+        }
+        sized_box(b"stco", &body)
+    }
+
+    /// A `moov` (with one track whose sample table matches the given
+    /// arguments) followed by an 8-byte `mdat`, starting at offset 0.
+    fn valid_mp4(sample_count: u32, stts_runs: &[(u32, u32)], chunk_offsets: &[u32]) -> Vec<u8> {
+        let mut stbl_body = build_stsz(sample_count);
+        stbl_body.extend_from_slice(&build_stts(stts_runs));
+        stbl_body.extend_from_slice(&build_stco(chunk_offsets));
+        let stbl = sized_box(b"stbl", &stbl_body);
+
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &minf);
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+        let mdat = sized_box(b"mdat", &[0u8; 8]);
+
+        let mut data = moov;
+        data.extend_from_slice(&mdat);
+        data
+    }
+
+    #[test]
+    fn reports_no_issues_for_a_structurally_sound_file() {
+        // moov's mdat-relative chunk offset is the length of moov itself,
+        // i.e. right at the start of the mdat this test helper appends.
+        let moov_only = valid_mp4(4, &[(4, 1000)], &[0]);
+        let moov_len = {
+            let mut probe = MemorySeekableStream::new(moov_only.clone());
+            let len = probe.len().unwrap();
+            boxes::read_box_header(&mut probe, 0, len).unwrap().unwrap().end as u32
+        };
+        let data = valid_mp4(4, &[(4, 1000)], &[moov_len]);
+
+        let report = validate(&mut MemorySeekableStream::new(data)).unwrap();
+        assert!(report.is_valid());
+        assert!(report.issues.is_empty());
+    }
+
+    #[test]
+    fn flags_a_missing_moov_box() {
+        let mdat = sized_box(b"mdat", &[0u8; 4]);
+        let report = validate(&mut MemorySeekableStream::new(mdat)).unwrap();
+
+        assert!(!report.is_valid());
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].severity, Severity::Error);
+        assert!(report.issues[0].message.contains("moov"));
+    }
+
+    #[test]
+    fn warns_about_a_missing_mdat_without_failing_validation() {
+        let moov = sized_box(b"moov", &sized_box(b"trak", &sized_box(b"mdia", &sized_box(b"minf", &{
+            let mut stbl_body = build_stsz(0);
+            stbl_body.extend_from_slice(&build_stts(&[]));
+            sized_box(b"stbl", &stbl_body)
+        }))));
+
+        let report = validate(&mut MemorySeekableStream::new(moov)).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.issues.len(), 1);
+        assert_eq!(report.issues[0].severity, Severity::Warning);
+        assert!(report.issues[0].message.contains("mdat"));
+    }
+
+    #[test]
+    fn flags_an_stsz_stts_sample_count_mismatch() {
+        // stsz declares 5 samples, but stts' one run only covers 4.
+        let data = valid_mp4(5, &[(4, 1000)], &[0]);
+        let report = validate(&mut MemorySeekableStream::new(data)).unwrap();
+
+        assert!(!report.is_valid());
+        assert!(report
+            .issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error && issue.message.contains("stts runs cover")));
+    }
+
+    #[test]
+    fn flags_a_chunk_offset_past_the_end_of_the_file() {
+        let data = valid_mp4(4, &[(4, 1000)], &[1_000_000]);
+        let report = validate(&mut MemorySeekableStream::new(data)).unwrap();
+
+        assert!(!report.is_valid());
+        assert!(report
+            .issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error && issue.message.contains("past end of file")));
+    }
+
+    #[test]
+    fn flags_a_chunk_offset_that_lands_inside_moov_instead_of_mdat() {
+        // Offset 0 is the very start of moov, not the mdat that follows it.
+        let data = valid_mp4(4, &[(4, 1000)], &[0]);
+        let report = validate(&mut MemorySeekableStream::new(data)).unwrap();
+
+        assert!(!report.is_valid());
+        assert!(report.issues.iter().any(|issue| issue.severity == Severity::Error && issue.message.contains("overlaps the moov box")));
+    }
+
+    /// A `moov` (with one track whose `stsz` entries, `stsc`, and `stco`
+    /// match the given arguments) followed by an `mdat` of `mdat_len` zero
+    /// bytes, starting right after `moov`.
+    fn mp4_with_sample_sizes(sizes: &[u32], mdat_len: u32) -> Vec<u8> {
+        // Placeholder chunk offset of 0, patched below once moov's real
+        // length (and so mdat's start offset) is known.
+        let build = |chunk_offset: u32| -> Vec<u8> {
+            let mut stbl_body = build_stsz_with_sizes(sizes);
+            stbl_body.extend_from_slice(&build_stts(&[(sizes.len() as u32, 1000)]));
+            stbl_body.extend_from_slice(&build_stsc(&[(1, sizes.len() as u32)]));
+            stbl_body.extend_from_slice(&build_stco(&[chunk_offset]));
+            let stbl = sized_box(b"stbl", &stbl_body);
+            let minf = sized_box(b"minf", &stbl);
+            let mdia = sized_box(b"mdia", &minf);
+            let trak = sized_box(b"trak", &mdia);
+            sized_box(b"moov", &trak)
+        };
+
+        let moov_len = build(0).len() as u32;
+        let moov = build(moov_len);
+
+        let mdat = sized_box(b"mdat", &vec![0u8; mdat_len as usize]);
+        let mut data = moov;
+        data.extend_from_slice(&mdat);
+        data
+    }
+
+    #[test]
+    fn reports_no_sample_bounds_issues_when_samples_fit_within_mdat() {
+        let data = mp4_with_sample_sizes(&[4, 4, 4, 4], 16);
+        let report = validate(&mut MemorySeekableStream::new(data)).unwrap();
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn flags_a_sample_that_spills_past_the_end_of_a_truncated_mdat() {
+        // mdat only has room for 12 of the 16 declared sample bytes.
+        let data = mp4_with_sample_sizes(&[4, 4, 4, 4], 4);
+        let report = validate(&mut MemorySeekableStream::new(data)).unwrap();
+
+        assert!(!report.is_valid());
+        assert!(report
+            .issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error && issue.message.contains("past end of file")));
+    }
+
+    #[test]
+    fn stops_reporting_sample_bounds_violations_after_the_cap() {
+        let sizes = vec![4u32; 20];
+        let data = mp4_with_sample_sizes(&sizes, 8);
+        let report = validate(&mut MemorySeekableStream::new(data)).unwrap();
+
+        let violation_count =
+            report.issues.iter().filter(|issue| issue.message.contains("past end of file")).count();
+        assert_eq!(violation_count, MAX_SAMPLE_BOUND_ISSUES_PER_TRACK);
+        assert!(report.issues.iter().any(|issue| issue.message.contains("stopped after")));
+    }
+
+    /// Like [`mp4_with_sample_sizes`], but splits `sizes` across two chunks
+    /// (`split` samples in the first, the rest in the second) at distinct
+    /// offsets, to exercise [`super::sample_table::SampleTable::offsets`]'s
+    /// chunk-to-chunk bookkeeping rather than just one chunk's running sum.
+    fn mp4_with_two_chunks(sizes: &[u32], split: u32, mdat_len: u32) -> Vec<u8> {
+        let first_chunk_len: u32 = sizes[..split as usize].iter().sum();
+
+        let build = |chunk_offsets: [u32; 2]| -> Vec<u8> {
+            let mut stbl_body = build_stsz_with_sizes(sizes);
+            stbl_body.extend_from_slice(&build_stts(&[(sizes.len() as u32, 1000)]));
+            stbl_body.extend_from_slice(&build_stsc(&[(1, split), (2, sizes.len() as u32 - split)]));
+            stbl_body.extend_from_slice(&build_stco(&chunk_offsets));
+            let stbl = sized_box(b"stbl", &stbl_body);
+            let minf = sized_box(b"minf", &stbl);
+            let mdia = sized_box(b"mdia", &minf);
+            let trak = sized_box(b"trak", &mdia);
+            sized_box(b"moov", &trak)
+        };
+
+        let moov_len = build([0, 0]).len() as u32;
+        let moov = build([moov_len, moov_len + first_chunk_len]);
+
+        let mdat = sized_box(b"mdat", &vec![0u8; mdat_len as usize]);
+        let mut data = moov;
+        data.extend_from_slice(&mdat);
+        data
+    }
+
+    #[test]
+    fn reports_no_sample_bounds_issues_across_a_chunk_boundary() {
+        let data = mp4_with_two_chunks(&[4, 4, 4, 4, 4], 2, 20);
+        let report = validate(&mut MemorySeekableStream::new(data)).unwrap();
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn flags_a_sample_that_spills_past_the_end_of_mdat_in_the_second_chunk() {
+        // mdat only has room for the first chunk's bytes, not the second's.
+        let data = mp4_with_two_chunks(&[4, 4, 4, 4, 4], 2, 8);
+        let report = validate(&mut MemorySeekableStream::new(data)).unwrap();
+
+        assert!(!report.is_valid());
+        assert!(report
+            .issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error && issue.message.contains("past end of file")));
+    }
+
+    #[test]
+    fn flags_a_box_that_claims_to_extend_past_the_end_of_the_file() {
+        let mut bogus_moov = sized_box(b"moov", &[]);
+        // Overwrite the size field with something larger than the buffer.
+        bogus_moov[0..4].copy_from_slice(&1_000_000u32.to_be_bytes());
+
+        let report = validate(&mut MemorySeekableStream::new(bogus_moov)).unwrap();
+        assert!(!report.is_valid());
+        assert!(report
+            .issues
+            .iter()
+            .any(|issue| issue.severity == Severity::Error && issue.message.contains("truncated or extends past end of file")));
+    }
+}
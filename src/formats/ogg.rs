@@ -0,0 +1,260 @@
+//! Ogg bitstream metadata parsing, covering the Opus and Vorbis mappings.
+//!
+//! Sample rate, channel count, and tags come from the identification and
+//! comment packets at the start of the stream. Duration is derived from
+//! the granule position of the very last page, which requires a read from
+//! the tail of the stream.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::metadata::Metadata;
+use crate::stream::SeekableStream;
+
+const PAGE_HEADER_LEN: u64 = 27;
+const TAIL_WINDOW: u64 = 64 * 1024;
+
+/// The granule position sentinel value meaning "no packet completes on
+/// this page".
+const NO_GRANULE: u64 = u64::MAX;
+
+enum OggCodec {
+    Opus,
+    Vorbis,
+}
+
+pub(crate) fn extract_metadata<S: SeekableStream>(stream: &mut S) -> Result<Metadata> {
+    let len = stream.len()?;
+    let mut metadata = Metadata::default();
+
+    let packets = read_leading_packets(stream, len, 2)?;
+    let ident = packets.first().ok_or_else(|| Error::Malformed {
+        format: "ogg",
+        reason: "stream has no pages".into(),
+    })?;
+
+    let (codec, sample_rate, channels) = if ident.starts_with(b"OpusHead") {
+        let (rate, channels) = parse_opus_head(ident)?;
+        (OggCodec::Opus, rate, channels)
+    } else if ident.len() >= 7 && ident[0] == 1 && &ident[1..7] == b"vorbis" {
+        let (rate, channels) = parse_vorbis_ident(ident)?;
+        (OggCodec::Vorbis, rate, channels)
+    } else {
+        return Err(Error::Unsupported("unrecognized Ogg mapping".into()));
+    };
+
+    metadata.sample_rate = Some(sample_rate);
+    metadata.channels = Some(channels);
+
+    if let Some(comment_packet) = packets.get(1) {
+        let comment_data = match codec {
+            OggCodec::Opus => comment_packet.strip_prefix(b"OpusTags"),
+            OggCodec::Vorbis => comment_packet.strip_prefix(&[0x03][..]).and_then(|p| p.strip_prefix(b"vorbis")),
+        };
+        if let Some(data) = comment_data {
+            read_vorbis_comments(data, &mut metadata.tags);
+        }
+    }
+
+    if let Some(granule) = last_granule_position(stream, len)? {
+        // Opus granule positions are always expressed in 48kHz units,
+        // regardless of the stream's original input sample rate.
+        let rate = match codec {
+            OggCodec::Opus => 48_000,
+            OggCodec::Vorbis => sample_rate,
+        };
+        if rate > 0 {
+            metadata.duration = Some(Duration::from_secs_f64(granule as f64 / f64::from(rate)));
+        }
+    }
+
+    Ok(metadata)
+}
+
+fn parse_opus_head(data: &[u8]) -> Result<(u32, u16)> {
+    if data.len() < 19 {
+        return Err(Error::Malformed { format: "ogg", reason: "truncated OpusHead".into() });
+    }
+    let channels = u16::from(data[9]);
+    let sample_rate = u32::from_le_bytes(data[12..16].try_into().unwrap());
+    Ok((sample_rate, channels))
+}
+
+fn parse_vorbis_ident(data: &[u8]) -> Result<(u32, u16)> {
+    if data.len() < 16 {
+        return Err(Error::Malformed { format: "ogg", reason: "truncated Vorbis identification header".into() });
+    }
+    let channels = u16::from(data[11]);
+    let sample_rate = u32::from_le_bytes(data[12..16].try_into().unwrap());
+    Ok((sample_rate, channels))
+}
+
+/// Reads `want` packets from the start of the stream, reassembling packets
+/// that are split across Ogg page segments per the lacing table.
+fn read_leading_packets<S: SeekableStream>(stream: &mut S, len: u64, want: usize) -> Result<Vec<Vec<u8>>> {
+    let mut packets = Vec::new();
+    let mut current = Vec::new();
+    let mut offset = 0u64;
+
+    'pages: while packets.len() < want && offset + PAGE_HEADER_LEN <= len {
+        let mut header = [0u8; PAGE_HEADER_LEN as usize];
+        stream.read_at(offset, &mut header)?;
+        if &header[0..4] != b"OggS" {
+            break;
+        }
+        let page_segments = header[26] as usize;
+
+        let mut segment_table = vec![0u8; page_segments];
+        stream.read_at(offset + PAGE_HEADER_LEN, &mut segment_table)?;
+        let mut data_offset = offset + PAGE_HEADER_LEN + page_segments as u64;
+
+        for &segment_len in &segment_table {
+            let mut segment = vec![0u8; segment_len as usize];
+            stream.read_at(data_offset, &mut segment)?;
+            current.extend_from_slice(&segment);
+            data_offset += u64::from(segment_len);
+
+            if segment_len < 255 {
+                packets.push(std::mem::take(&mut current));
+                if packets.len() == want {
+                    break 'pages;
+                }
+            }
+        }
+
+        offset = data_offset;
+    }
+
+    Ok(packets)
+}
+
+/// Scans backward from the tail of the stream for the last Ogg page that
+/// completes a packet, returning its granule position.
+fn last_granule_position<S: SeekableStream>(stream: &mut S, len: u64) -> Result<Option<u64>> {
+    let window = TAIL_WINDOW.min(len);
+    let start = len - window;
+    let mut buf = vec![0u8; window as usize];
+    stream.read_at(start, &mut buf)?;
+
+    let mut search_end = buf.len();
+    while search_end >= 4 {
+        let Some(pos) = buf[..search_end].windows(4).rposition(|w| w == b"OggS") else { break };
+        if pos + 14 <= buf.len() {
+            let granule = u64::from_le_bytes(buf[pos + 6..pos + 14].try_into().unwrap());
+            if granule != NO_GRANULE {
+                return Ok(Some(granule));
+            }
+        }
+        search_end = pos;
+    }
+
+    Ok(None)
+}
+
+/// Reads the Vorbis comment block format (shared by Vorbis and Opus):
+/// a length-prefixed vendor string followed by length-prefixed
+/// `KEY=value` comments, all little-endian.
+fn read_vorbis_comments(data: &[u8], tags: &mut HashMap<String, String>) {
+    if data.len() < 4 {
+        return;
+    }
+    let vendor_len = u32::from_le_bytes(data[0..4].try_into().unwrap()) as usize;
+    let mut pos = 4 + vendor_len;
+    if pos + 4 > data.len() {
+        return;
+    }
+
+    let comment_count = u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap());
+    pos += 4;
+
+    for _ in 0..comment_count {
+        if pos + 4 > data.len() {
+            break;
+        }
+        let comment_len = u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()) as usize;
+        pos += 4;
+        if pos + comment_len > data.len() {
+            break;
+        }
+        let comment = String::from_utf8_lossy(&data[pos..pos + comment_len]);
+        pos += comment_len;
+
+        if let Some((key, value)) = comment.split_once('=') {
+            tags.insert(key.to_ascii_lowercase(), value.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn vorbis_comment_block(comments: &[(&str, &str)]) -> Vec<u8> {
+        let mut body = Vec::new();
+        let vendor = b"media-parser test";
+        body.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        body.extend_from_slice(vendor);
+        body.extend_from_slice(&(comments.len() as u32).to_le_bytes());
+        for (k, v) in comments {
+            let comment = format!("{k}={v}");
+            body.extend_from_slice(&(comment.len() as u32).to_le_bytes());
+            body.extend_from_slice(comment.as_bytes());
+        }
+        body
+    }
+
+    fn ogg_page(granule: u64, sequence: u32, packet: &[u8], is_last_page: bool) -> Vec<u8> {
+        let mut segments = Vec::new();
+        let mut remaining = packet.len();
+        while remaining >= 255 {
+            segments.push(255u8);
+            remaining -= 255;
+        }
+        segments.push(remaining as u8);
+
+        let mut page = Vec::new();
+        page.extend_from_slice(b"OggS");
+        page.push(0); // version
+        page.push(if is_last_page { 0x04 } else { 0x00 }); // header type
+        page.extend_from_slice(&granule.to_le_bytes());
+        page.extend_from_slice(&1u32.to_le_bytes()); // serial number
+        page.extend_from_slice(&sequence.to_le_bytes());
+        page.extend_from_slice(&0u32.to_le_bytes()); // checksum (unchecked by our parser)
+        page.push(segments.len() as u8);
+        page.extend_from_slice(&segments);
+        page.extend_from_slice(packet);
+        page
+    }
+
+    fn sample_opus_ogg() -> Vec<u8> {
+        let mut head = b"OpusHead".to_vec();
+        head.push(1); // version
+        head.push(2); // channels
+        head.extend_from_slice(&312u16.to_le_bytes()); // pre-skip
+        head.extend_from_slice(&48000u32.to_le_bytes()); // input sample rate
+        head.extend_from_slice(&0u16.to_le_bytes()); // output gain
+        head.push(0); // channel mapping family
+
+        let mut tags = b"OpusTags".to_vec();
+        tags.extend_from_slice(&vorbis_comment_block(&[("TITLE", "Test Track")]));
+
+        let mut ogg = Vec::new();
+        ogg.extend_from_slice(&ogg_page(0, 0, &head, false));
+        ogg.extend_from_slice(&ogg_page(0, 1, &tags, false));
+        ogg.extend_from_slice(&ogg_page(48_000 * 3, 2, &[0u8; 4], true));
+        ogg
+    }
+
+    #[test]
+    fn parses_opus_metadata_and_tail_duration() {
+        let mut stream = MemorySeekableStream::new(sample_opus_ogg());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.sample_rate, Some(48000));
+        assert_eq!(metadata.channels, Some(2));
+        assert_eq!(metadata.tags.get("title"), Some(&"Test Track".to_string()));
+        assert_eq!(metadata.duration, Some(Duration::from_secs(3)));
+    }
+}
@@ -0,0 +1,233 @@
+//! RIFF/WAVE (`.wav`) metadata parsing.
+//!
+//! Reads the `fmt ` chunk for sample rate and channel count, the `data`
+//! chunk's length to derive duration, and `LIST`/`INFO` sub-chunks for tags.
+
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::metadata::Metadata;
+use crate::stream::SeekableStream;
+
+const RIFF_HEADER_LEN: u64 = 12;
+const CHUNK_HEADER_LEN: u64 = 8;
+
+/// Maps RIFF `INFO` list sub-chunk IDs to the tag names we expose.
+fn info_tag_name(id: &[u8; 4]) -> Option<&'static str> {
+    match id {
+        b"INAM" => Some("title"),
+        b"IART" => Some("artist"),
+        b"IPRD" => Some("album"),
+        b"ICRD" => Some("date"),
+        b"IGNR" => Some("genre"),
+        b"ICMT" => Some("comment"),
+        _ => None,
+    }
+}
+
+pub(crate) fn extract_metadata<S: SeekableStream>(stream: &mut S) -> Result<Metadata> {
+    let len = stream.len()?;
+    if len < RIFF_HEADER_LEN {
+        return Err(Error::Malformed { format: "wav", reason: "file shorter than RIFF header".into() });
+    }
+
+    let mut metadata = Metadata::default();
+    let mut offset = RIFF_HEADER_LEN;
+    let mut byte_rate: Option<u32> = None;
+    let mut data_size: Option<u32> = None;
+
+    while offset + CHUNK_HEADER_LEN <= len {
+        let mut header = [0u8; 8];
+        stream.read_at(offset, &mut header)?;
+        let id: [u8; 4] = header[0..4].try_into().unwrap();
+        let size = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let body_offset = offset + CHUNK_HEADER_LEN;
+
+        match &id {
+            b"fmt " => {
+                let mut body = [0u8; 16];
+                let n = (size as usize).min(body.len());
+                stream.read_at(body_offset, &mut body[..n])?;
+                metadata.channels = Some(u16::from_le_bytes(body[2..4].try_into().unwrap()));
+                metadata.sample_rate = Some(u32::from_le_bytes(body[4..8].try_into().unwrap()));
+                byte_rate = Some(u32::from_le_bytes(body[8..12].try_into().unwrap()));
+            }
+            b"data" => {
+                data_size = Some(size);
+            }
+            b"LIST" => {
+                read_list_tags(stream, body_offset, size, &mut metadata)?;
+            }
+            _ => {}
+        }
+
+        // Chunks are padded to an even number of bytes.
+        let advance = u64::from(size) + (size % 2) as u64;
+        offset = body_offset + advance;
+    }
+
+    if let (Some(bytes), Some(rate)) = (data_size, byte_rate) {
+        if rate > 0 {
+            metadata.duration = Some(Duration::from_secs_f64(f64::from(bytes) / f64::from(rate)));
+        }
+    }
+
+    Ok(metadata)
+}
+
+/// The subset of a WAV file's `fmt `/`data` chunks needed to read raw PCM
+/// samples directly, e.g. for [`crate::waveform`]'s peak extraction.
+pub(crate) struct PcmLayout {
+    pub channels: u16,
+    pub bits_per_sample: u16,
+    pub data_start: u64,
+    pub data_size: u32,
+}
+
+/// Walks the file's chunks for `fmt `/`data`, same as [`extract_metadata`],
+/// returning `None` if either is missing or `fmt ` doesn't describe PCM
+/// (format tag `1`).
+pub(crate) fn find_pcm_layout<S: SeekableStream>(stream: &mut S) -> Result<Option<PcmLayout>> {
+    let len = stream.len()?;
+    if len < RIFF_HEADER_LEN {
+        return Err(Error::Malformed { format: "wav", reason: "file shorter than RIFF header".into() });
+    }
+
+    let mut offset = RIFF_HEADER_LEN;
+    let mut fmt: Option<(u16, u16)> = None;
+    let mut data: Option<(u64, u32)> = None;
+
+    while offset + CHUNK_HEADER_LEN <= len {
+        let mut header = [0u8; 8];
+        stream.read_at(offset, &mut header)?;
+        let id: [u8; 4] = header[0..4].try_into().unwrap();
+        let size = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let body_offset = offset + CHUNK_HEADER_LEN;
+
+        match &id {
+            b"fmt " => {
+                let mut body = [0u8; 16];
+                let n = (size as usize).min(body.len());
+                stream.read_at(body_offset, &mut body[..n])?;
+                let format_tag = u16::from_le_bytes(body[0..2].try_into().unwrap());
+                if format_tag == 1 {
+                    let channels = u16::from_le_bytes(body[2..4].try_into().unwrap());
+                    let bits_per_sample = u16::from_le_bytes(body[14..16].try_into().unwrap());
+                    fmt = Some((channels, bits_per_sample));
+                }
+            }
+            b"data" => {
+                data = Some((body_offset, size));
+            }
+            _ => {}
+        }
+
+        let advance = u64::from(size) + (size % 2) as u64;
+        offset = body_offset + advance;
+    }
+
+    Ok(match (fmt, data) {
+        (Some((channels, bits_per_sample)), Some((data_start, data_size))) => {
+            Some(PcmLayout { channels, bits_per_sample, data_start, data_size })
+        }
+        _ => None,
+    })
+}
+
+fn read_list_tags<S: SeekableStream>(
+    stream: &mut S,
+    body_offset: u64,
+    size: u32,
+    metadata: &mut Metadata,
+) -> Result<()> {
+    if size < 4 {
+        return Ok(());
+    }
+
+    let mut list_type = [0u8; 4];
+    stream.read_at(body_offset, &mut list_type)?;
+    if &list_type != b"INFO" {
+        return Ok(());
+    }
+
+    let list_end = body_offset + u64::from(size);
+    let mut offset = body_offset + 4;
+
+    while offset + CHUNK_HEADER_LEN <= list_end {
+        let mut header = [0u8; 8];
+        stream.read_at(offset, &mut header)?;
+        let id: [u8; 4] = header[0..4].try_into().unwrap();
+        let sub_size = u32::from_le_bytes(header[4..8].try_into().unwrap());
+        let sub_body_offset = offset + CHUNK_HEADER_LEN;
+
+        if let Some(name) = info_tag_name(&id) {
+            let mut buf = vec![0u8; sub_size as usize];
+            stream.read_at(sub_body_offset, &mut buf)?;
+            // INFO strings are NUL-terminated C strings; trim trailing NULs.
+            let end = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+            if let Ok(value) = String::from_utf8(buf[..end].to_vec()) {
+                metadata.tags.insert(name.to_string(), value);
+            }
+        }
+
+        let advance = u64::from(sub_size) + (sub_size % 2) as u64;
+        offset = sub_body_offset + advance;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    /// A trivial in-memory stream for exercising format parsers in tests.
+    fn le_chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(id);
+        chunk.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn sample_wav() -> Vec<u8> {
+        let mut fmt_body = Vec::new();
+        fmt_body.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        fmt_body.extend_from_slice(&2u16.to_le_bytes()); // channels
+        fmt_body.extend_from_slice(&44100u32.to_le_bytes()); // sample rate
+        fmt_body.extend_from_slice(&176400u32.to_le_bytes()); // byte rate
+        fmt_body.extend_from_slice(&4u16.to_le_bytes()); // block align
+        fmt_body.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+
+        let mut info_body = Vec::new();
+        info_body.extend_from_slice(b"INFO");
+        info_body.extend_from_slice(&le_chunk(b"INAM", b"Test Title\0"));
+
+        let mut riff_body = Vec::new();
+        riff_body.extend_from_slice(b"WAVE");
+        riff_body.extend_from_slice(&le_chunk(b"fmt ", &fmt_body));
+        riff_body.extend_from_slice(&le_chunk(b"LIST", &info_body));
+        riff_body.extend_from_slice(&le_chunk(b"data", &[0u8; 17640]));
+
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(riff_body.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&riff_body);
+        wav
+    }
+
+    #[test]
+    fn parses_fmt_data_and_tags() {
+        let mut stream = MemorySeekableStream::new(sample_wav());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.sample_rate, Some(44100));
+        assert_eq!(metadata.channels, Some(2));
+        assert_eq!(metadata.duration, Some(Duration::from_secs_f64(0.1)));
+        assert_eq!(metadata.tags.get("title"), Some(&"Test Title".to_string()));
+    }
+}
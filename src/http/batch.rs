@@ -0,0 +1,153 @@
+//! Extracting metadata from many URLs while reusing one [`Client`]/
+//! connection pool, so a large catalog scan doesn't pay a fresh TLS
+//! handshake for every file the way building a standalone
+//! [`super::SeekableHttpStream`] per URL would.
+
+use reqwest::blocking::Client;
+
+use crate::cancellation::CancellationToken;
+use crate::error::{Error, Result};
+use crate::metadata::{extract_metadata, Metadata};
+
+use super::SeekableHttpStream;
+
+/// Tuning knobs for [`BatchProcessor::process_with`].
+pub struct BatchOptions {
+    /// How many URLs may be in flight at once. `1` (the default) processes
+    /// them sequentially.
+    pub max_concurrency: usize,
+    /// Checked before each URL (or each batch, under concurrency); once
+    /// cancelled, every URL not yet started is reported as
+    /// [`Error::Cancelled`] instead of being fetched.
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self { max_concurrency: 1, cancellation: None }
+    }
+}
+
+/// One URL's outcome from [`BatchProcessor::process`]/[`BatchProcessor::process_with`],
+/// pairing it with its result so a failure on one URL doesn't lose track of
+/// which URL it was or abort the rest of the batch.
+pub struct BatchItem {
+    /// The URL this result came from, in case callers processed `urls` out
+    /// of its original order or just want it alongside the result.
+    pub url: String,
+    /// The extracted metadata, or the error that stopped extraction.
+    pub result: Result<Metadata>,
+}
+
+/// Extracts metadata from many URLs, sharing one [`Client`] (and its
+/// connection pool) across all of them.
+pub struct BatchProcessor {
+    client: Client,
+}
+
+impl BatchProcessor {
+    /// Builds a processor around a fresh [`Client`] with reqwest's
+    /// defaults.
+    pub fn new() -> Self {
+        Self { client: Client::new() }
+    }
+
+    /// Builds a processor around an already-configured `client`, e.g. one
+    /// with custom timeouts, a proxy, or default headers already set up.
+    pub fn with_client(client: Client) -> Self {
+        Self { client }
+    }
+
+    /// Extracts metadata from each of `urls`, one at a time. Shorthand for
+    /// [`Self::process_with`] with `max_concurrency: 1`.
+    pub fn process(&self, urls: &[String]) -> Vec<BatchItem> {
+        self.process_with(urls, &BatchOptions::default())
+    }
+
+    /// Extracts metadata from each of `urls`, issuing up to
+    /// `options.max_concurrency` requests at once, to cut wall-clock time
+    /// on a large catalog scan.
+    pub fn process_with(&self, urls: &[String], options: &BatchOptions) -> Vec<BatchItem> {
+        if options.max_concurrency <= 1 || urls.len() <= 1 {
+            return urls
+                .iter()
+                .map(|url| match options.cancellation.as_ref().map(CancellationToken::check) {
+                    Some(Err(err)) => BatchItem { url: url.clone(), result: Err(err) },
+                    _ => BatchItem { url: url.clone(), result: self.fetch_one(url) },
+                })
+                .collect();
+        }
+
+        let mut items: Vec<Option<BatchItem>> = (0..urls.len()).map(|_| None).collect();
+        std::thread::scope(|scope| {
+            for batch in urls.iter().enumerate().collect::<Vec<_>>().chunks(options.max_concurrency) {
+                if options.cancellation.as_ref().is_some_and(|token| token.is_cancelled()) {
+                    for &(i, url) in batch {
+                        items[i] = Some(BatchItem { url: url.clone(), result: Err(Error::Cancelled) });
+                    }
+                    continue;
+                }
+
+                let handles: Vec<_> = batch.iter().map(|&(i, url)| (i, url, scope.spawn(move || self.fetch_one(url)))).collect();
+                for (i, url, handle) in handles {
+                    let result = handle.join().unwrap_or_else(|_| Err(Error::Io(std::io::Error::other("batch worker panicked"))));
+                    items[i] = Some(BatchItem { url: url.clone(), result });
+                }
+            }
+        });
+
+        items.into_iter().map(|item| item.expect("every index is filled exactly once above")).collect()
+    }
+
+    fn fetch_one(&self, url: &str) -> Result<Metadata> {
+        let mut stream = SeekableHttpStream::builder(url).client(self.client.clone()).build();
+        extract_metadata(&mut stream)
+    }
+}
+
+impl Default for BatchProcessor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_pairs_each_url_with_an_error_result_when_unreachable() {
+        let processor = BatchProcessor::new();
+        let urls = vec!["http://127.0.0.1:0/a.mp4".to_string(), "http://127.0.0.1:0/b.mp4".to_string()];
+        let items = processor.process(&urls);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].url, "http://127.0.0.1:0/a.mp4");
+        assert_eq!(items[1].url, "http://127.0.0.1:0/b.mp4");
+        assert!(items[0].result.is_err());
+        assert!(items[1].result.is_err());
+    }
+
+    #[test]
+    fn process_with_concurrency_preserves_url_order_in_results() {
+        let processor = BatchProcessor::new();
+        let urls: Vec<String> = (0..5).map(|i| format!("http://127.0.0.1:0/{i}.mp4")).collect();
+        let items = processor.process_with(&urls, &BatchOptions { max_concurrency: 3, ..Default::default() });
+
+        let result_urls: Vec<&str> = items.iter().map(|item| item.url.as_str()).collect();
+        let expected: Vec<&str> = urls.iter().map(String::as_str).collect();
+        assert_eq!(result_urls, expected);
+    }
+
+    #[test]
+    fn process_with_stops_early_once_cancelled() {
+        let processor = BatchProcessor::new();
+        let token = CancellationToken::new();
+        token.cancel();
+        let urls = vec!["http://127.0.0.1:0/a.mp4".to_string()];
+        let items = processor.process_with(&urls, &BatchOptions { cancellation: Some(token), ..Default::default() });
+
+        assert_eq!(items.len(), 1);
+        assert!(matches!(items[0].result, Err(Error::Cancelled)));
+    }
+}
@@ -0,0 +1,69 @@
+//! A fixed-capacity, block-aligned LRU byte cache keyed by offset, used by
+//! [`super::SeekableHttpStream`] to avoid re-requesting the same range
+//! repeatedly during `moov` scanning and sample downloads.
+
+use std::collections::{HashMap, VecDeque};
+
+pub(crate) struct BlockCache {
+    block_size: u64,
+    capacity: usize,
+    blocks: HashMap<u64, Vec<u8>>,
+    /// Least-recently-used first; touched blocks move to the back.
+    order: VecDeque<u64>,
+}
+
+impl BlockCache {
+    pub(crate) fn new(block_size: u64, capacity: usize) -> Self {
+        Self { block_size, capacity, blocks: HashMap::new(), order: VecDeque::new() }
+    }
+
+    pub(crate) fn block_size(&self) -> u64 {
+        self.block_size
+    }
+
+    /// Rounds `offset` down to the start of the block containing it.
+    pub(crate) fn block_start(&self, offset: u64) -> u64 {
+        (offset / self.block_size) * self.block_size
+    }
+
+    pub(crate) fn get(&mut self, block_start: u64) -> Option<&[u8]> {
+        if !self.blocks.contains_key(&block_start) {
+            return None;
+        }
+        self.touch(block_start);
+        self.blocks.get(&block_start).map(Vec::as_slice)
+    }
+
+    pub(crate) fn insert(&mut self, block_start: u64, data: Vec<u8>) {
+        if !self.blocks.contains_key(&block_start) && self.blocks.len() >= self.capacity {
+            if let Some(evicted) = self.order.pop_front() {
+                self.blocks.remove(&evicted);
+            }
+        }
+        self.blocks.insert(block_start, data);
+        self.touch(block_start);
+    }
+
+    fn touch(&mut self, block_start: u64) {
+        self.order.retain(|b| *b != block_start);
+        self.order.push_back(block_start);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_least_recently_used_block_past_capacity() {
+        let mut cache = BlockCache::new(16, 2);
+        cache.insert(0, vec![0; 16]);
+        cache.insert(16, vec![1; 16]);
+        cache.get(0); // touch block 0 so block 16 becomes the LRU one
+        cache.insert(32, vec![2; 16]);
+
+        assert!(cache.get(0).is_some());
+        assert!(cache.get(16).is_none());
+        assert!(cache.get(32).is_some());
+    }
+}
@@ -0,0 +1,191 @@
+//! Enough of an HLS media playlist (RFC 8216) to map a target timestamp to
+//! the segment that covers it, and fetch that segment over HTTP as a
+//! [`SeekableHttpStream`] for thumbnail/subtitle extraction -- so a caller
+//! with an `.m3u8` URL doesn't have to pre-flatten the asset into one file.
+//!
+//! Multivariant (master) playlists, `#EXT-X-BYTERANGE`, and live/event
+//! playlist reloading aren't handled; this only covers a VOD media
+//! playlist's segment list.
+
+use std::io;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::http::SeekableHttpStream;
+
+/// One segment of a media playlist: its URI (already resolved against the
+/// playlist's own URL, if relative) and duration (`#EXTINF`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaylistSegment {
+    pub uri: String,
+    pub duration: Duration,
+}
+
+/// A parsed HLS media playlist.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaPlaylist {
+    /// The `#EXT-X-TARGETDURATION` value, i.e. the upper bound any one
+    /// segment's `#EXTINF` duration is expected to stay under.
+    pub target_duration: Duration,
+    /// The playlist's segments, in playback order.
+    pub segments: Vec<PlaylistSegment>,
+}
+
+impl MediaPlaylist {
+    /// The segment covering `target` -- the one where the sum of every
+    /// earlier segment's duration is `<= target`. Returns `None` if
+    /// `target` is at or past the playlist's total duration.
+    pub fn segment_at(&self, target: Duration) -> Option<&PlaylistSegment> {
+        let mut elapsed = Duration::ZERO;
+        for segment in &self.segments {
+            let next = elapsed + segment.duration;
+            if target < next {
+                return Some(segment);
+            }
+            elapsed = next;
+        }
+        None
+    }
+}
+
+/// Parses a media playlist's text, resolving each segment's `URI` against
+/// `playlist_url` if it's relative.
+pub fn parse_media_playlist(text: &str, playlist_url: &str) -> Result<MediaPlaylist> {
+    let mut target_duration = Duration::ZERO;
+    let mut segments = Vec::new();
+    let mut pending_duration = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if let Some(value) = line.strip_prefix("#EXT-X-TARGETDURATION:") {
+            let secs: u64 = value
+                .trim()
+                .parse()
+                .map_err(|_| Error::Malformed { format: "hls", reason: "invalid EXT-X-TARGETDURATION".into() })?;
+            target_duration = Duration::from_secs(secs);
+        } else if let Some(value) = line.strip_prefix("#EXTINF:") {
+            let secs_str = value.split(',').next().unwrap_or("").trim();
+            let secs: f64 =
+                secs_str.parse().map_err(|_| Error::Malformed { format: "hls", reason: "invalid EXTINF duration".into() })?;
+            pending_duration = Some(Duration::from_secs_f64(secs));
+        } else if !line.is_empty() && !line.starts_with('#') {
+            let duration = pending_duration
+                .take()
+                .ok_or_else(|| Error::Malformed { format: "hls", reason: "segment URI with no preceding EXTINF".into() })?;
+            segments.push(PlaylistSegment { uri: resolve_uri(playlist_url, line), duration });
+        }
+    }
+
+    Ok(MediaPlaylist { target_duration, segments })
+}
+
+/// Resolves `uri` against `playlist_url` if it's relative (has no scheme),
+/// by replacing everything after `playlist_url`'s last `/`.
+fn resolve_uri(playlist_url: &str, uri: &str) -> String {
+    if uri.contains("://") {
+        return uri.to_string();
+    }
+    match playlist_url.rfind('/') {
+        Some(idx) => format!("{}/{}", &playlist_url[..idx], uri),
+        None => uri.to_string(),
+    }
+}
+
+fn to_io_error(err: reqwest::Error) -> io::Error {
+    if err.is_timeout() {
+        io::Error::new(io::ErrorKind::TimedOut, err)
+    } else {
+        io::Error::other(err)
+    }
+}
+
+/// Fetches `playlist_url` and parses it as a media playlist.
+pub fn fetch_media_playlist(playlist_url: &str) -> Result<MediaPlaylist> {
+    let response = reqwest::blocking::get(playlist_url)
+        .and_then(|r| r.error_for_status())
+        .map_err(to_io_error)?;
+    let text = response.text().map_err(to_io_error)?;
+    parse_media_playlist(&text, playlist_url)
+}
+
+/// Opens the segment of `playlist` covering `target` as a
+/// [`SeekableHttpStream`], for feeding into [`crate::thumbnails`] or
+/// [`crate::subtitle`] extraction.
+///
+/// Only fMP4 segments (`.mp4`/`.m4s`) are supported: this crate has no
+/// MPEG-TS demuxer, so a `.ts` segment fails with [`Error::Unsupported`]
+/// rather than silently returning wrong data.
+pub fn open_segment_at(playlist: &MediaPlaylist, target: Duration) -> Result<SeekableHttpStream> {
+    let segment = playlist
+        .segment_at(target)
+        .ok_or_else(|| Error::Malformed { format: "hls", reason: "target timestamp is past the playlist's end".into() })?;
+
+    if segment.uri.ends_with(".ts") {
+        return Err(Error::Unsupported(
+            "MPEG-TS HLS segments aren't supported; only fMP4 (.mp4/.m4s) segments can be parsed".into(),
+        ));
+    }
+
+    Ok(SeekableHttpStream::new(segment.uri.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYLIST: &str = "#EXTM3U\n\
+#EXT-X-VERSION:7\n\
+#EXT-X-TARGETDURATION:4\n\
+#EXT-X-PLAYLIST-TYPE:VOD\n\
+#EXTINF:4.0,\n\
+segment0.m4s\n\
+#EXTINF:4.0,\n\
+segment1.m4s\n\
+#EXTINF:2.0,\n\
+segment2.m4s\n\
+#EXT-X-ENDLIST\n";
+
+    #[test]
+    fn parses_target_duration_and_segment_list() {
+        let playlist = parse_media_playlist(PLAYLIST, "https://cdn.example.com/video/index.m3u8").unwrap();
+
+        assert_eq!(playlist.target_duration, Duration::from_secs(4));
+        assert_eq!(playlist.segments.len(), 3);
+        assert_eq!(playlist.segments[0].uri, "https://cdn.example.com/video/segment0.m4s");
+        assert_eq!(playlist.segments[0].duration, Duration::from_secs_f64(4.0));
+        assert_eq!(playlist.segments[2].duration, Duration::from_secs_f64(2.0));
+    }
+
+    #[test]
+    fn leaves_absolute_segment_uris_untouched() {
+        let text = "#EXTM3U\n#EXTINF:4.0,\nhttps://other.example.com/seg.m4s\n";
+        let playlist = parse_media_playlist(text, "https://cdn.example.com/video/index.m3u8").unwrap();
+
+        assert_eq!(playlist.segments[0].uri, "https://other.example.com/seg.m4s");
+    }
+
+    #[test]
+    fn rejects_a_segment_uri_with_no_preceding_extinf() {
+        let text = "#EXTM3U\nsegment0.m4s\n";
+        assert!(parse_media_playlist(text, "https://cdn.example.com/index.m3u8").is_err());
+    }
+
+    #[test]
+    fn finds_the_segment_covering_a_target_timestamp() {
+        let playlist = parse_media_playlist(PLAYLIST, "https://cdn.example.com/index.m3u8").unwrap();
+
+        assert_eq!(playlist.segment_at(Duration::ZERO).unwrap().uri, "https://cdn.example.com/segment0.m4s");
+        assert_eq!(playlist.segment_at(Duration::from_secs_f64(3.9)).unwrap().uri, "https://cdn.example.com/segment0.m4s");
+        assert_eq!(playlist.segment_at(Duration::from_secs_f64(4.1)).unwrap().uri, "https://cdn.example.com/segment1.m4s");
+        assert_eq!(playlist.segment_at(Duration::from_secs_f64(9.0)).unwrap().uri, "https://cdn.example.com/segment2.m4s");
+        assert!(playlist.segment_at(Duration::from_secs_f64(10.1)).is_none());
+    }
+
+    #[test]
+    fn refuses_to_open_a_ts_segment() {
+        let text = "#EXTM3U\n#EXTINF:4.0,\nsegment0.ts\n";
+        let playlist = parse_media_playlist(text, "https://cdn.example.com/index.m3u8").unwrap();
+
+        assert!(matches!(open_segment_at(&playlist, Duration::ZERO), Err(Error::Unsupported(_))));
+    }
+}
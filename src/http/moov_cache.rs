@@ -0,0 +1,147 @@
+//! A cache for parsed container metadata, keyed by a remote asset's URL
+//! (and, when known, its `ETag`/size), so repeat operations against the
+//! same asset -- e.g. a thumbnail extraction run right after a metadata
+//! pass -- can skip re-downloading and re-parsing its `moov`.
+//!
+//! [`InMemoryMoovCache`] is the bundled in-process implementation;
+//! implement [`MoovCache`] yourself to back this with Redis, a database
+//! row, or a local file for a cache that survives past this process.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::SeekableHttpStream;
+use crate::error::Result;
+use crate::metadata::{extract_metadata, Metadata};
+use crate::stream::SeekableStream;
+
+/// Identifies one version of a remote asset for cache lookups: its URL
+/// plus whatever validator is known for it (an `ETag`, or failing that a
+/// size), so a cache entry isn't served after the asset changes
+/// server-side. Two keys for the same URL but a different `etag`/`size`
+/// are different cache entries.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MoovCacheKey {
+    pub url: String,
+    pub etag: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl MoovCacheKey {
+    /// Builds a key from `url` alone. A cache using this key in isolation
+    /// can't detect a changed asset at the same URL; chain
+    /// [`Self::with_etag`]/[`Self::with_size`] once a validator is known.
+    pub fn new(url: impl Into<String>) -> Self {
+        Self { url: url.into(), etag: None, size: None }
+    }
+
+    /// Sets the key's `ETag`.
+    pub fn with_etag(mut self, etag: impl Into<String>) -> Self {
+        self.etag = Some(etag.into());
+        self
+    }
+
+    /// Sets the key's size, in bytes.
+    pub fn with_size(mut self, size: u64) -> Self {
+        self.size = Some(size);
+        self
+    }
+}
+
+/// A cache for parsed [`Metadata`], keyed by [`MoovCacheKey`]. Implement
+/// this to back the cache with external storage; [`InMemoryMoovCache`] is
+/// the bundled in-process implementation.
+pub trait MoovCache: Send + Sync {
+    /// Returns the cached metadata for `key`, if any.
+    fn get(&self, key: &MoovCacheKey) -> Option<Metadata>;
+    /// Stores `metadata` under `key`, replacing whatever was cached there.
+    fn put(&self, key: &MoovCacheKey, metadata: Metadata);
+}
+
+/// An in-process [`MoovCache`] backed by a plain map. Entries live for the
+/// cache's lifetime -- there's no eviction or TTL, since caching moov data
+/// across a handful of repeat operations on the same asset doesn't need
+/// either. Safe to share across threads behind an [`std::sync::Arc`].
+#[derive(Debug, Default)]
+pub struct InMemoryMoovCache {
+    entries: Mutex<HashMap<MoovCacheKey, Metadata>>,
+}
+
+impl InMemoryMoovCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl MoovCache for InMemoryMoovCache {
+    fn get(&self, key: &MoovCacheKey) -> Option<Metadata> {
+        self.entries.lock().expect("moov cache lock poisoned").get(key).cloned()
+    }
+
+    fn put(&self, key: &MoovCacheKey, metadata: Metadata) {
+        self.entries.lock().expect("moov cache lock poisoned").insert(key.clone(), metadata);
+    }
+}
+
+/// Extracts `stream`'s metadata, consulting `cache` first and skipping the
+/// `moov` download and parse entirely on a hit.
+///
+/// The cache key is built from `stream`'s URL and length (a `HEAD`
+/// request, not a `moov` fetch) rather than its `ETag`: capturing an `ETag`
+/// requires a range request into the body, which is exactly what a hit is
+/// meant to avoid. That means a server that replaces a same-length file at
+/// the same URL (rare, but possible) could serve a stale hit. A caller
+/// that already knows an asset's `ETag` up front (e.g. from a prior
+/// listing call) should build a [`MoovCacheKey::with_etag`] key itself and
+/// call [`MoovCache::get`]/[`MoovCache::put`] directly instead of this
+/// convenience.
+pub fn extract_metadata_cached(stream: &mut SeekableHttpStream, cache: &dyn MoovCache) -> Result<Metadata> {
+    let mut key = MoovCacheKey::new(stream.url());
+    if let Ok(size) = stream.len() {
+        key = key.with_size(size);
+    }
+
+    if let Some(metadata) = cache.get(&key) {
+        return Ok(metadata);
+    }
+
+    let metadata = extract_metadata(stream)?;
+    cache.put(&key, metadata.clone());
+    Ok(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_memory_cache_returns_none_before_any_put() {
+        let cache = InMemoryMoovCache::new();
+        let key = MoovCacheKey::new("https://example.com/a.mp4").with_size(100);
+        assert_eq!(cache.get(&key), None);
+    }
+
+    #[test]
+    fn in_memory_cache_roundtrips_a_put_entry() {
+        let cache = InMemoryMoovCache::new();
+        let key = MoovCacheKey::new("https://example.com/a.mp4").with_etag("\"abc123\"");
+        let metadata = Metadata { sample_rate: Some(44100), ..Default::default() };
+
+        cache.put(&key, metadata.clone());
+
+        assert_eq!(cache.get(&key), Some(metadata));
+    }
+
+    #[test]
+    fn keys_with_different_validators_for_the_same_url_are_distinct_entries() {
+        let cache = InMemoryMoovCache::new();
+        let old = MoovCacheKey::new("https://example.com/a.mp4").with_size(100);
+        let new = MoovCacheKey::new("https://example.com/a.mp4").with_size(200);
+
+        cache.put(&old, Metadata { sample_rate: Some(44100), ..Default::default() });
+
+        assert_eq!(cache.get(&new), None);
+        assert!(cache.get(&old).is_some());
+    }
+}
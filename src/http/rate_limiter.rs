@@ -0,0 +1,76 @@
+//! A simple token-bucket byte-rate limiter shared across
+//! [`super::SeekableHttpStream`]'s fetch paths (cache fill, direct range
+//! downloads, and read-ahead prefetch), so a background job can cap its
+//! bandwidth instead of saturating a shared link.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub(crate) struct RateLimiter {
+    bytes_per_sec: u64,
+    state: Mutex<State>,
+}
+
+struct State {
+    /// Bytes currently available to spend without blocking.
+    available: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(bytes_per_sec: u64) -> Self {
+        Self { bytes_per_sec, state: Mutex::new(State { available: bytes_per_sec as f64, last_refill: Instant::now() }) }
+    }
+
+    /// Blocks the calling thread just long enough to keep this limiter's
+    /// long-run average throughput at or below `bytes_per_sec`, after
+    /// accounting for `bytes` just transferred.
+    pub(crate) fn throttle(&self, bytes: usize) {
+        if self.bytes_per_sec == 0 {
+            return;
+        }
+
+        let wait = {
+            let mut state = self.state.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+            let now = Instant::now();
+            let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+            state.last_refill = now;
+            state.available = (state.available + elapsed * self.bytes_per_sec as f64).min(self.bytes_per_sec as f64);
+            state.available -= bytes as f64;
+
+            if state.available < 0.0 {
+                let deficit = -state.available;
+                state.available = 0.0;
+                Duration::from_secs_f64(deficit / self.bytes_per_sec as f64)
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if !wait.is_zero() {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unlimited_rate_never_sleeps() {
+        let limiter = RateLimiter::new(0);
+        let start = Instant::now();
+        limiter.throttle(10_000_000);
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+
+    #[test]
+    fn exceeding_the_budget_forces_a_wait() {
+        let limiter = RateLimiter::new(1_000);
+        let start = Instant::now();
+        limiter.throttle(1_000); // drains the initial burst allowance
+        limiter.throttle(500); // must now wait for refill
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+}
@@ -0,0 +1,38 @@
+//! Request/byte counters backing [`super::SeekableHttpStream::stats`].
+//!
+//! Atomics, rather than a plain struct behind a `Mutex`, because the
+//! counters are updated both from [`super::SeekableHttpStream::read_at`]
+//! and from the background threads spawned by read-ahead and
+//! [`super::SeekableHttpStream::download_sample_ranges_with`].
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+#[derive(Default)]
+pub(crate) struct StreamStatsTracker {
+    requests: AtomicU64,
+    bytes_downloaded: AtomicU64,
+    bytes_read: AtomicU64,
+}
+
+impl StreamStatsTracker {
+    pub(crate) fn record_download(&self, bytes: usize) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.bytes_downloaded.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_read(&self, bytes: usize) {
+        self.bytes_read.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn requests(&self) -> u64 {
+        self.requests.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn bytes_downloaded(&self) -> u64 {
+        self.bytes_downloaded.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn bytes_read(&self) -> u64 {
+        self.bytes_read.load(Ordering::Relaxed)
+    }
+}
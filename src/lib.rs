@@ -0,0 +1,76 @@
+//! `media-parser` reads container and codec metadata out of media files
+//! (local, in-memory, or remote) without decoding audio or video samples.
+//!
+//! The entry point for most consumers is [`extract_metadata`], which sniffs
+//! the container format from a [`stream::SeekableStream`] and delegates to
+//! the matching format parser in [`formats`].
+
+pub mod analysis;
+pub mod bif;
+pub mod bits;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod cancellation;
+pub mod chapters;
+pub mod container;
+pub mod error;
+pub mod formats;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod metadata;
+pub mod progress;
+#[cfg(feature = "progressive")]
+pub mod progressive;
+pub mod probe;
+#[cfg(feature = "s3")]
+pub mod s3;
+pub mod session;
+pub mod storyboard;
+pub mod stream;
+pub mod subtitle;
+pub mod thumbnails;
+pub mod timed_metadata;
+pub mod waveform;
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub mod wasm;
+
+pub use analysis::{track_bitrate_timeline, track_frame_rate, BitrateWindow, FrameRateInfo};
+pub use bif::{export_bif_from_thumbnails, write_bif, BifFrame};
+pub use bits::reader::BitReader;
+pub use bits::writer::BitWriter;
+pub use cancellation::CancellationToken;
+pub use chapters::{extract_chapters, ChapterEntry};
+pub use container::ContainerFormat;
+pub use error::{Error, Result};
+pub use formats::mp4::dump::{dump, BoxNode};
+pub use formats::mp4::validate::{validate as validate_mp4, Severity, ValidationIssue, ValidationReport};
+pub use formats::mp4::{
+    embed_subtitle_track, export_annexb_h264, optimize_for_streaming, read_fragment_index, BitrateInfo,
+    DolbyVisionConfig, FragmentEntry, FragmentIndex, ParseMode, SampleEncryption, SampleInfo, SampleTable,
+    SubtitleCodec, TrackReader,
+};
+#[cfg(feature = "http")]
+pub use http::{
+    extract_metadata_cached, BatchItem, BatchOptions, BatchProcessor, DownloadOptions, InMemoryMoovCache, MoovCache,
+    MoovCacheKey, SeekableHttpStream, SeekableHttpStreamBuilder,
+};
+#[cfg(feature = "http")]
+pub use http::hls::{fetch_media_playlist, open_segment_at, parse_media_playlist, MediaPlaylist, PlaylistSegment};
+pub use metadata::{extract_metadata, extract_metadata_with, quick_metadata, ImageItem, Metadata, Picture};
+pub use probe::{probe, BoxInfo, ProbeResult};
+pub use progress::{ExtractOptions, ProgressEvent, ProgressStage};
+#[cfg(feature = "s3")]
+pub use s3::SeekableS3Stream;
+pub use session::{MediaBundle, MediaParser};
+#[cfg(feature = "mmap")]
+pub use stream::MmapSeekableStream;
+pub use storyboard::{build_storyboard, Storyboard, StoryboardOptions, StoryboardTile};
+pub use stream::{MemorySeekableStream, PrefixStream, SeekableStream, SegmentedStream, StreamStats};
+pub use subtitle::{
+    decode_image_subtitle, extract_all_subtitles, extract_all_subtitles_with, extract_image_subtitles,
+    ImageSubtitleEntry, ImageSubtitleTrack, SubtitleEntry, SubtitleStyle, SubtitleTimingOptions, SubtitleTrack,
+    TextBoxPosition,
+};
+pub use thumbnails::{decode, decode_yuv, DecodedImage, ThumbnailData, VideoDecoder, YuvFrame, YuvPlane};
+pub use timed_metadata::{extract_timed_metadata, TimedMetadataEntry, TimedMetadataTrack};
+pub use waveform::{extract_wav_peaks, render_waveform, PeakPair, WaveformOptions};
@@ -0,0 +1,456 @@
+//! The public metadata model returned by [`extract_metadata`].
+//!
+//! [`Metadata`] is this crate's only metadata type -- there's no separate
+//! `CompleteMetadata`, `metadata::types` module, or per-stream `StreamInfo`
+//! list to consolidate it with; every format parser under [`formats`]
+//! populates this one struct directly.
+
+use std::collections::HashMap;
+
+use crate::container::ContainerFormat;
+use crate::error::Result;
+use crate::formats;
+use crate::progress::{ExtractOptions, ProgressStage};
+use crate::stream::{PrefixStream, SeekableStream};
+
+/// Metadata extracted from a media file, without decoding any samples.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metadata {
+    /// The container format the metadata was extracted from.
+    pub format: Option<ContainerFormat>,
+    /// Duration of the media, if known.
+    pub duration: Option<std::time::Duration>,
+    /// Audio sample rate in Hz, if the source has an audio track.
+    pub sample_rate: Option<u32>,
+    /// Number of audio channels, if the source has an audio track.
+    pub channels: Option<u16>,
+    /// A human-readable audio codec label (e.g. `"AAC-LC"`, `"HE-AAC"`),
+    /// when it could be determined more precisely than from the container's
+    /// codec fourCC alone. Currently only populated for MP4/QuickTime `esds`
+    /// (AAC) audio tracks.
+    pub audio_codec: Option<String>,
+    /// A human-readable video codec label (e.g. `"H.264/AVC High@4.1"`,
+    /// `"Apple ProRes 422 HQ"`), augmented with profile/level when the
+    /// codec is `avc1`/`avc3`/`hvc1`/`hev1` and has an `avcC`/`hvcC`.
+    /// MP4/QuickTime only; falls back to the raw sample-entry fourCC for
+    /// codecs outside the registry.
+    pub video_codec: Option<String>,
+    /// Free-form tags (e.g. title, artist) keyed by tag name. When a tag has
+    /// more than one localized value, this holds whichever one was seen
+    /// first; see [`Metadata::localized_tags`] for the rest.
+    pub tags: HashMap<String, String>,
+    /// Every localized variant of each tag in [`Metadata::tags`], keyed by
+    /// tag name and then by ISO-639-2/T-style language code (e.g. `"eng"`,
+    /// or `"und"` when the source didn't specify one).
+    pub localized_tags: HashMap<String, HashMap<String, String>>,
+    /// Custom `----` (mean/name/data) freeform atoms, e.g. encoder settings
+    /// or tooling-specific IDs written by HandBrake, iTunes, etc. Keyed as
+    /// `"<mean>:<name>"`. MP4/QuickTime only.
+    pub freeform_tags: HashMap<String, String>,
+    /// Embedded cover art or other pictures, if any were present.
+    pub pictures: Vec<Picture>,
+    /// Pixel width of the primary video/image, if known.
+    pub width: Option<u32>,
+    /// Pixel height of the primary video/image, if known.
+    pub height: Option<u32>,
+    /// The `ftyp` major brand, for ISO-BMFF-family containers.
+    pub major_brand: Option<String>,
+    /// The `ftyp` compatible brands list, for ISO-BMFF-family containers.
+    pub compatible_brands: Vec<String>,
+    /// HEIF/AVIF items (images, thumbnails, Exif blobs, ...) declared in
+    /// the file's `iinf` box.
+    pub image_items: Vec<ImageItem>,
+    /// The raw XMP packet (an XML string), if the file has one embedded in
+    /// a top-level `uuid` box -- common in camera-produced files.
+    pub xmp: Option<String>,
+    /// Whether `moov` precedes `mdat` ("faststart"), for ISO-BMFF-family
+    /// containers. A faststart file can be parsed or progressively streamed
+    /// (see [`crate::progressive`]) without seeking to the tail; `None` if
+    /// the format has no such distinction. See [`crate::probe::ProbeResult`]
+    /// for the equivalent field on a cheap structural probe, and
+    /// [`crate::SeekableStream::stats`] for how many bytes a remote source
+    /// actually had to fetch.
+    pub is_faststart: Option<bool>,
+    /// Whether the source was missing bytes its own box headers claimed it
+    /// should have -- e.g. an upload interrupted mid-`mdat` -- for
+    /// ISO-BMFF-family containers. `None` if the format has no such
+    /// distinction. A truncated file with `moov` at the front can still
+    /// yield full metadata (only `mdat`'s tail is affected), which is why
+    /// this is a flag alongside the result rather than a parse failure.
+    pub is_truncated: Option<bool>,
+    /// iTunes' media kind (`stik`), e.g. `9` = Movie, `10` = TV Show, `11` =
+    /// Booklet, `14` = Ringtone -- see Apple's/AtomicParsley's `stik` table
+    /// for the full list. MP4/QuickTime only.
+    pub media_kind: Option<u8>,
+    /// The TV show name (`tvsh`), for episodic video. MP4/QuickTime only.
+    pub tv_show: Option<String>,
+    /// The TV episode's unique ID (`tven`, e.g. `"S01E03"`-style tooling
+    /// IDs), distinct from [`Metadata::tv_episode`]'s episode *number*.
+    /// MP4/QuickTime only.
+    pub tv_episode_id: Option<String>,
+    /// The TV season number (`tvsn`). MP4/QuickTime only.
+    pub tv_season: Option<u32>,
+    /// The TV episode number (`tves`). MP4/QuickTime only.
+    pub tv_episode: Option<u32>,
+    /// iTunes' content rating (`rtng`), e.g. `0` = none, `2` = explicit,
+    /// `4` = clean. MP4/QuickTime only.
+    pub content_rating: Option<u8>,
+    /// A short description (`desc`). MP4/QuickTime only.
+    pub description: Option<String>,
+    /// A longer description (`ldes`), e.g. a full episode synopsis.
+    /// MP4/QuickTime only.
+    pub long_description: Option<String>,
+    /// Lyrics (`©lyr`), which can be long and multi-line, so they get a
+    /// dedicated field instead of living in [`Metadata::tags`] alongside
+    /// short, single-line tags like title/artist. MP4/QuickTime only.
+    pub lyrics: Option<String>,
+    /// The sort-order title (`sonm`), for alphabetizing titles that start
+    /// with an article or symbol a library should ignore (e.g. sorting "The
+    /// Title" under "Title, The"). MP4/QuickTime only.
+    pub sort_title: Option<String>,
+    /// The sort-order artist (`soar`). MP4/QuickTime only.
+    pub sort_artist: Option<String>,
+    /// The sort-order album (`soal`). MP4/QuickTime only.
+    pub sort_album: Option<String>,
+    /// Spherical/360 video projection (`"equirectangular"`, `"cubemap"`,
+    /// or `"mesh"`), from a video sample entry's `sv3d` box or, for files
+    /// that predate it, the legacy Google Spherical Video V1 XML `uuid`
+    /// box. MP4/QuickTime only.
+    pub spherical_projection: Option<String>,
+    /// Stereoscopic layout (`"mono"`, `"top-bottom"`, or `"left-right"`),
+    /// from the same sources as [`Metadata::spherical_projection`].
+    /// MP4/QuickTime only.
+    pub stereo_mode: Option<String>,
+    /// Initial view orientation in degrees as `(yaw, pitch, roll)`, from
+    /// the legacy Google Spherical Video V1 XML `uuid` box only -- `sv3d`
+    /// has no equivalent field. MP4/QuickTime only.
+    pub initial_view: Option<(f64, f64, f64)>,
+    /// Ambisonic order (e.g. `1` for first-order, 4 channels), from an
+    /// audio sample entry's `SA3D` box. MP4/QuickTime only.
+    pub ambisonic_order: Option<u32>,
+    /// Ambisonic channel ordering convention (`"ACN"` or `"SID"`), from the
+    /// same `SA3D` box as [`Metadata::ambisonic_order`]. MP4/QuickTime only.
+    pub ambisonic_channel_ordering: Option<String>,
+    /// The video sample entry's `depth` field (bits per pixel). Present for
+    /// any video track, but most meaningful for distinguishing
+    /// professional codec variants (e.g. ProRes 4444's alpha channel)
+    /// that consumer codecs don't use. MP4/QuickTime only.
+    pub video_bit_depth: Option<u16>,
+    /// The video sample entry's `compressorname` field, when non-empty --
+    /// typically a vendor/variant string (e.g. `"AVdn"`) written by
+    /// professional capture/editing tools like Avid for ProRes/DNx
+    /// masters. MP4/QuickTime only.
+    pub video_vendor: Option<String>,
+}
+
+/// One item declared in an HEIF/AVIF file's `iinf` item-information box.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageItem {
+    /// The item's ID, as referenced by `iloc`/`ipma`/`pitm`.
+    pub id: u32,
+    /// The item's four-character type, e.g. `"hvc1"`, `"av01"`, or `"Exif"`.
+    pub item_type: String,
+    /// Whether this is the file's primary item (per the `pitm` box).
+    pub primary: bool,
+}
+
+/// An embedded picture (e.g. cover art) extracted from a media file's tags.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Picture {
+    /// The role the picture plays, per the source format's picture-type enum
+    /// (e.g. FLAC's `PICTURE` block types; `3` is "front cover" there).
+    pub picture_type: u32,
+    /// The picture's MIME type, e.g. `"image/jpeg"`.
+    pub mime_type: String,
+    /// An optional human-readable description of the picture.
+    pub description: String,
+    /// The raw, still-encoded image bytes.
+    pub data: Vec<u8>,
+}
+
+/// Sniffs `stream`'s container format and extracts its metadata.
+pub fn extract_metadata<S: SeekableStream>(stream: &mut S) -> Result<Metadata> {
+    extract_metadata_with(stream, &mut ExtractOptions::new())
+}
+
+/// Like [`extract_metadata`], but reports progress through
+/// `options.on_progress` as extraction moves between stages, and honors
+/// `options`'s cancellation, timeout, and output-limiting knobs.
+pub fn extract_metadata_with<S: SeekableStream>(stream: &mut S, options: &mut ExtractOptions) -> Result<Metadata> {
+    match options.max_bytes {
+        Some(max_bytes) => extract_metadata_inner(&mut PrefixStream::new(stream, max_bytes), options),
+        None => extract_metadata_inner(stream, options),
+    }
+}
+
+fn extract_metadata_inner<S: SeekableStream>(stream: &mut S, options: &mut ExtractOptions) -> Result<Metadata> {
+    let deadline = options.timeout.map(|timeout| std::time::Instant::now() + timeout);
+
+    options.check_cancelled()?;
+    options.check_deadline(deadline)?;
+    options.report(ProgressStage::DetectFormat, None, None);
+    let format = ContainerFormat::sniff(stream)?;
+
+    options.check_cancelled()?;
+    options.check_deadline(deadline)?;
+    let total = stream.len().ok();
+    options.report(ProgressStage::ParseContainer, None, total);
+    let mut metadata = match format {
+        ContainerFormat::Wav => formats::wav::extract_metadata(stream)?,
+        ContainerFormat::Flac => formats::flac::extract_metadata(stream)?,
+        ContainerFormat::Mp4 => formats::mp4::extract_metadata(stream)?,
+        ContainerFormat::Ogg => formats::ogg::extract_metadata(stream)?,
+        ContainerFormat::Heif => formats::mp4::heif::extract_metadata(stream)?,
+    };
+    metadata.format = Some(format);
+    if matches!(format, ContainerFormat::Mp4 | ContainerFormat::Heif) {
+        let (boxes, truncated) = formats::mp4::top_level_layout_tolerant(stream)?;
+        metadata.is_faststart = crate::probe::faststart_from_boxes(&boxes);
+        metadata.is_truncated = Some(truncated);
+    }
+    options.report(ProgressStage::ParseContainer, total, total);
+
+    if let Some(max_pictures) = options.max_pictures {
+        metadata.pictures.truncate(max_pictures);
+    }
+
+    Ok(metadata)
+}
+
+/// Extracts whatever metadata can be determined from at most the first
+/// `max_bytes` of `stream`, never reading its tail.
+///
+/// This is cheap triage for large remote files: it fails instead of
+/// falling back to a tail read if a format needs data beyond the prefix
+/// (for example, a non-faststart MP4 whose `moov` sits after `mdat`).
+pub fn quick_metadata<S: SeekableStream>(stream: &mut S, max_bytes: u64) -> Result<Metadata> {
+    extract_metadata_with(stream, &mut ExtractOptions::new().max_bytes(max_bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sample_wav() -> Vec<u8> {
+        let mut fmt_body = Vec::new();
+        fmt_body.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        fmt_body.extend_from_slice(&2u16.to_le_bytes()); // channels
+        fmt_body.extend_from_slice(&44100u32.to_le_bytes()); // sample rate
+        fmt_body.extend_from_slice(&176400u32.to_le_bytes()); // byte rate
+        fmt_body.extend_from_slice(&4u16.to_le_bytes()); // block align
+        fmt_body.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+
+        let mut riff_body = Vec::new();
+        riff_body.extend_from_slice(b"WAVE");
+        riff_body.extend_from_slice(b"fmt ");
+        riff_body.extend_from_slice(&(fmt_body.len() as u32).to_le_bytes());
+        riff_body.extend_from_slice(&fmt_body);
+        riff_body.extend_from_slice(b"data");
+        riff_body.extend_from_slice(&4u32.to_le_bytes());
+        riff_body.extend_from_slice(&[0u8; 4]);
+
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(riff_body.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&riff_body);
+        wav
+    }
+
+    #[test]
+    fn extract_metadata_with_reports_detect_and_parse_stages() {
+        let mut stream = MemorySeekableStream::new(sample_wav());
+        let mut stages = Vec::new();
+        let metadata = {
+            let mut options = ExtractOptions::new().on_progress(|event| stages.push(event.stage));
+            extract_metadata_with(&mut stream, &mut options).unwrap()
+        };
+
+        assert_eq!(metadata.format, Some(ContainerFormat::Wav));
+        assert_eq!(stages, vec![ProgressStage::DetectFormat, ProgressStage::ParseContainer, ProgressStage::ParseContainer]);
+    }
+
+    #[test]
+    fn extract_metadata_with_stops_early_once_deadline_has_passed() {
+        let mut stream = MemorySeekableStream::new(sample_wav());
+        let mut options = ExtractOptions::new().timeout(std::time::Duration::from_secs(0));
+        // A zero-length budget has already elapsed by the time the first
+        // stage boundary is checked.
+        std::thread::sleep(std::time::Duration::from_millis(1));
+
+        let err = extract_metadata_with(&mut stream, &mut options).unwrap_err();
+
+        assert!(matches!(err, crate::Error::Timeout));
+    }
+
+    fn flac_picture_block(description: &str) -> Vec<u8> {
+        let mime = b"image/jpeg";
+        let data = b"not-really-a-jpeg";
+        let mut body = Vec::new();
+        body.extend_from_slice(&3u32.to_be_bytes()); // picture_type: front cover
+        body.extend_from_slice(&(mime.len() as u32).to_be_bytes());
+        body.extend_from_slice(mime);
+        body.extend_from_slice(&(description.len() as u32).to_be_bytes());
+        body.extend_from_slice(description.as_bytes());
+        body.extend_from_slice(&[0u8; 16]); // width, height, color depth, colors used
+        body.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        body.extend_from_slice(data);
+        body
+    }
+
+    fn flac_block(block_type: u8, is_last: bool, body: &[u8]) -> Vec<u8> {
+        let mut block = Vec::new();
+        let flag = if is_last { 0x80 } else { 0 };
+        block.push(flag | block_type);
+        block.extend_from_slice(&(body.len() as u32).to_be_bytes()[1..4]);
+        block.extend_from_slice(body);
+        block
+    }
+
+    fn sample_flac_with_pictures(count: usize) -> Vec<u8> {
+        let mut flac = Vec::new();
+        flac.extend_from_slice(b"fLaC");
+        flac.extend_from_slice(&flac_block(0, count == 0, &[0u8; 34])); // STREAMINFO
+        for i in 0..count {
+            let is_last = i + 1 == count;
+            flac.extend_from_slice(&flac_block(6, is_last, &flac_picture_block(&format!("cover {i}"))));
+        }
+        flac
+    }
+
+    #[test]
+    fn extract_metadata_with_stops_reading_at_max_bytes() {
+        let wav = sample_wav();
+        let mut stream = MemorySeekableStream::new(wav.clone());
+        let mut options = ExtractOptions::new().max_bytes(4);
+
+        let err = extract_metadata_with(&mut stream, &mut options).unwrap_err();
+
+        assert!(matches!(err, crate::Error::UnrecognizedFormat));
+    }
+
+    #[test]
+    fn extract_metadata_with_max_bytes_allows_a_large_enough_budget() {
+        let wav = sample_wav();
+        let len = wav.len() as u64;
+        let mut stream = MemorySeekableStream::new(wav);
+        let mut options = ExtractOptions::new().max_bytes(len);
+
+        let metadata = extract_metadata_with(&mut stream, &mut options).unwrap();
+
+        assert_eq!(metadata.format, Some(ContainerFormat::Wav));
+    }
+
+    #[test]
+    fn extract_metadata_with_truncates_pictures_to_max_pictures() {
+        let mut stream = MemorySeekableStream::new(sample_flac_with_pictures(3));
+        let mut options = ExtractOptions::new().max_pictures(2);
+
+        let metadata = extract_metadata_with(&mut stream, &mut options).unwrap();
+
+        assert_eq!(metadata.pictures.len(), 2);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn metadata_round_trips_through_json() {
+        let mut stream = MemorySeekableStream::new(sample_wav());
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        let json = serde_json::to_string(&metadata).unwrap();
+        let rehydrated: Metadata = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(metadata, rehydrated);
+    }
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[test]
+    fn reports_faststart_for_an_mp4_with_moov_before_mdat() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let moov = sized_box(b"moov", &[]);
+        let mdat = sized_box(b"mdat", &[1, 2, 3, 4]);
+
+        let mut data = ftyp;
+        data.extend_from_slice(&moov);
+        data.extend_from_slice(&mdat);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.is_faststart, Some(true));
+    }
+
+    #[test]
+    fn reports_not_truncated_for_a_structurally_complete_mp4() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let moov = sized_box(b"moov", &[]);
+        let mdat = sized_box(b"mdat", &[1, 2, 3, 4]);
+
+        let mut data = ftyp;
+        data.extend_from_slice(&moov);
+        data.extend_from_slice(&mdat);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.is_truncated, Some(false));
+    }
+
+    #[test]
+    fn reports_truncated_and_still_yields_metadata_when_mdats_tail_is_missing() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let moov = sized_box(b"moov", &[]);
+        // mdat's header claims 16 bytes of payload, but the stream is cut
+        // off after only 4 of them -- an interrupted upload.
+        let mut mdat = sized_box(b"mdat", &[0u8; 16]);
+        mdat.truncate(8 + 4);
+
+        let mut data = ftyp;
+        data.extend_from_slice(&moov);
+        data.extend_from_slice(&mdat);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.is_truncated, Some(true));
+        assert_eq!(metadata.is_faststart, Some(true));
+    }
+
+    #[test]
+    fn reports_not_faststart_for_an_mp4_with_mdat_before_moov() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mdat = sized_box(b"mdat", &[1, 2, 3, 4]);
+        let moov = sized_box(b"moov", &[]);
+
+        let mut data = ftyp;
+        data.extend_from_slice(&mdat);
+        data.extend_from_slice(&moov);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let metadata = extract_metadata(&mut stream).unwrap();
+
+        assert_eq!(metadata.is_faststart, Some(false));
+    }
+
+    #[test]
+    fn extract_metadata_with_stops_early_once_cancelled() {
+        let mut stream = MemorySeekableStream::new(sample_wav());
+        let token = crate::CancellationToken::new();
+        token.cancel();
+        let mut options = ExtractOptions::new().cancellation(token);
+
+        let err = extract_metadata_with(&mut stream, &mut options).unwrap_err();
+
+        assert!(matches!(err, crate::Error::Cancelled));
+    }
+}
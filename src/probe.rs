@@ -0,0 +1,254 @@
+//! Structural probing: cheap, header-only inspection of a container's
+//! layout, as opposed to the full metadata extraction in [`crate::metadata`].
+
+use std::time::Duration;
+
+use crate::container::ContainerFormat;
+use crate::error::Result;
+use crate::formats;
+use crate::stream::SeekableStream;
+
+/// One top-level box/chunk in a container, as seen without reading its
+/// payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoxInfo {
+    /// The four-character box type, e.g. `"moov"` or `"mdat"`.
+    pub name: String,
+    /// The absolute byte offset where the box begins (its header, not its
+    /// payload).
+    pub offset: u64,
+    /// The total size of the box, header included.
+    pub size: u64,
+}
+
+/// A compact per-track summary gathered by [`probe`], cheap enough to read
+/// alongside the top-level box layout: what kind of track it is, what
+/// codec its samples use, and what language it's tagged with. Audio/video
+/// dimensions, full tag parsing, and everything else [`crate::metadata`]
+/// covers are left to [`crate::extract_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TrackSummary {
+    /// The track's `tkhd.track_ID`.
+    pub track_id: u32,
+    /// The track's `hdlr` handler type, e.g. `"vide"`, `"soun"`, `"text"`.
+    pub handler: String,
+    /// The track's first sample entry's four-character codec type, e.g.
+    /// `"avc1"` or `"mp4a"`, if it has one.
+    pub codec: Option<String>,
+    /// The track's `mdhd.language`, as an ISO 639-2/T code (e.g. `"eng"`,
+    /// or `"und"` if unset).
+    pub language: String,
+}
+
+/// The result of a structural [`probe`]: the container format, the layout
+/// of its top-level boxes/chunks, and -- for track-based formats -- its
+/// overall duration and a per-track summary.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProbeResult {
+    /// The sniffed container format.
+    pub format: ContainerFormat,
+    /// Top-level boxes/chunks in file order, gathered with header-only
+    /// reads.
+    pub boxes: Vec<BoxInfo>,
+    /// Whether `moov` precedes `mdat`, i.e. the file is "faststart" and can
+    /// be parsed or progressively streamed without seeking to the tail.
+    /// `None` if the format has no such distinction, or if `boxes` is
+    /// missing a `moov` or `mdat` entry to compare.
+    pub is_faststart: Option<bool>,
+    /// Overall duration, from `mvhd`. `None` if the format has no `moov`
+    /// (e.g. HEIF, which is item- rather than track-based) or the `moov`
+    /// it has is missing `mvhd`.
+    pub duration: Option<Duration>,
+    /// One summary per `trak` under `moov`, in file order. Empty for
+    /// formats with no `moov` (e.g. HEIF, WAV, FLAC, Ogg).
+    pub tracks: Vec<TrackSummary>,
+}
+
+/// Sniffs `stream`'s container format and reads its top-level box/chunk
+/// layout without reading any box payloads, so remote sources need only a
+/// handful of small range requests. For MP4/QuickTime, also reads overall
+/// duration and a per-track summary -- still no sample tables, tags, or
+/// pictures -- so a single cheap probe can answer "what is this file"
+/// without the cost of a full [`crate::extract_metadata`] pass.
+pub fn probe<S: SeekableStream>(stream: &mut S) -> Result<ProbeResult> {
+    let format = ContainerFormat::sniff(stream)?;
+
+    let boxes = match format {
+        ContainerFormat::Mp4 | ContainerFormat::Heif => formats::mp4::top_level_layout(stream)?,
+        ContainerFormat::Wav | ContainerFormat::Flac | ContainerFormat::Ogg => Vec::new(),
+    };
+    let is_faststart = faststart_from_boxes(&boxes);
+
+    let mut duration = None;
+    let mut tracks = Vec::new();
+    if format == ContainerFormat::Mp4 {
+        if let Some(moov) = formats::mp4::find_top_level_moov(stream)? {
+            duration = formats::mp4::movie_duration(stream, &moov)?;
+            tracks = formats::mp4::track_summaries(stream, &moov)?.into_iter().map(Into::into).collect();
+        }
+    }
+
+    Ok(ProbeResult { format, boxes, is_faststart, duration, tracks })
+}
+
+impl From<formats::mp4::TrackSummary> for TrackSummary {
+    fn from(summary: formats::mp4::TrackSummary) -> Self {
+        Self { track_id: summary.track_id, handler: summary.handler, codec: summary.codec, language: summary.language }
+    }
+}
+
+/// Determines whether `moov` precedes `mdat` among a container's top-level
+/// boxes. Shared by [`probe`] and [`crate::metadata::extract_metadata_with`]
+/// so the two don't drift on what "faststart" means.
+///
+/// To see how many bytes a remote source actually had to fetch to locate
+/// `moov` (e.g. to confirm a tail seek was needed), read
+/// [`crate::SeekableStream::stats`] after extraction instead: this function
+/// only reports box order, not transfer counts.
+pub(crate) fn faststart_from_boxes(boxes: &[BoxInfo]) -> Option<bool> {
+    let moov = boxes.iter().find(|b| b.name == "moov")?;
+    let mdat = boxes.iter().find(|b| b.name == "mdat")?;
+    Some(moov.offset < mdat.offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[test]
+    fn reports_top_level_mp4_box_layout() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let moov = sized_box(b"moov", &[]);
+        let mdat = sized_box(b"mdat", &[1, 2, 3, 4]);
+
+        let mut data = ftyp.clone();
+        data.extend_from_slice(&moov);
+        data.extend_from_slice(&mdat);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let result = probe(&mut stream).unwrap();
+
+        assert_eq!(result.format, ContainerFormat::Mp4);
+        assert_eq!(
+            result.boxes,
+            vec![
+                BoxInfo { name: "ftyp".into(), offset: 0, size: ftyp.len() as u64 },
+                BoxInfo { name: "moov".into(), offset: ftyp.len() as u64, size: moov.len() as u64 },
+                BoxInfo {
+                    name: "mdat".into(),
+                    offset: (ftyp.len() + moov.len()) as u64,
+                    size: mdat.len() as u64
+                },
+            ]
+        );
+        assert_eq!(result.is_faststart, Some(true));
+    }
+
+    #[test]
+    fn reports_not_faststart_when_mdat_precedes_moov() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mdat = sized_box(b"mdat", &[1, 2, 3, 4]);
+        let moov = sized_box(b"moov", &[]);
+
+        let mut data = ftyp;
+        data.extend_from_slice(&mdat);
+        data.extend_from_slice(&moov);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let result = probe(&mut stream).unwrap();
+
+        assert_eq!(result.is_faststart, Some(false));
+    }
+
+    #[test]
+    fn reports_duration_and_track_summaries_for_an_mp4() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+
+        let mut mvhd_body = vec![0u8; 20];
+        mvhd_body[12..16].copy_from_slice(&1000u32.to_be_bytes()); // timescale
+        mvhd_body[16..20].copy_from_slice(&5000u32.to_be_bytes()); // duration: 5s
+        let mvhd = sized_box(b"mvhd", &mvhd_body);
+
+        let mut tkhd_body = vec![0u8; 20];
+        tkhd_body[12..16].copy_from_slice(&7u32.to_be_bytes()); // track_ID
+        let tkhd = sized_box(b"tkhd", &tkhd_body);
+
+        let mut mdhd_body = vec![0u8; 22];
+        mdhd_body[12..16].copy_from_slice(&1000u32.to_be_bytes()); // timescale
+        mdhd_body[20..22].copy_from_slice(&0x15C7u16.to_be_bytes()); // language: "eng"
+        let mdhd = sized_box(b"mdhd", &mdhd_body);
+
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        let avc1 = sized_box(b"avc1", &[0u8; 28]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &avc1].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+        let stbl = sized_box(b"stbl", &stsd);
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+
+        let moov = sized_box(b"moov", &[mvhd, trak].concat());
+        let mdat = sized_box(b"mdat", &[0u8; 4]);
+
+        let mut data = ftyp;
+        data.extend_from_slice(&moov);
+        data.extend_from_slice(&mdat);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let result = probe(&mut stream).unwrap();
+
+        assert_eq!(result.duration, Some(std::time::Duration::from_secs(5)));
+        assert_eq!(result.tracks.len(), 1);
+        assert_eq!(result.tracks[0].track_id, 7);
+        assert_eq!(result.tracks[0].handler, "vide");
+        assert_eq!(result.tracks[0].codec, Some("avc1".into()));
+        assert_eq!(result.tracks[0].language, "eng");
+    }
+
+    #[test]
+    fn reports_no_faststart_verdict_for_formats_without_moov_or_mdat() {
+        let wav = {
+            let mut fmt_body = Vec::new();
+            fmt_body.extend_from_slice(&1u16.to_le_bytes());
+            fmt_body.extend_from_slice(&2u16.to_le_bytes());
+            fmt_body.extend_from_slice(&44100u32.to_le_bytes());
+            fmt_body.extend_from_slice(&176400u32.to_le_bytes());
+            fmt_body.extend_from_slice(&4u16.to_le_bytes());
+            fmt_body.extend_from_slice(&16u16.to_le_bytes());
+
+            let mut riff_body = Vec::new();
+            riff_body.extend_from_slice(b"WAVE");
+            riff_body.extend_from_slice(b"fmt ");
+            riff_body.extend_from_slice(&(fmt_body.len() as u32).to_le_bytes());
+            riff_body.extend_from_slice(&fmt_body);
+            riff_body.extend_from_slice(b"data");
+            riff_body.extend_from_slice(&4u32.to_le_bytes());
+            riff_body.extend_from_slice(&[0u8; 4]);
+
+            let mut wav = Vec::new();
+            wav.extend_from_slice(b"RIFF");
+            wav.extend_from_slice(&(riff_body.len() as u32).to_le_bytes());
+            wav.extend_from_slice(&riff_body);
+            wav
+        };
+
+        let mut stream = MemorySeekableStream::new(wav);
+        let result = probe(&mut stream).unwrap();
+
+        assert_eq!(result.is_faststart, None);
+    }
+}
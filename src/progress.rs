@@ -0,0 +1,138 @@
+//! Progress reporting for long-running extraction, so UIs and job runners
+//! can show something better than silence while a large or remote file is
+//! being processed.
+
+use std::time::{Duration, Instant};
+
+use crate::cancellation::CancellationToken;
+
+/// A stage [`ExtractOptions::on_progress`] can be called for.
+///
+/// Not every stage applies to every call: [`Self::DownloadSamples`] and
+/// [`Self::Decode`] are reported by callers that go on to pull sample data
+/// or decode it (this crate's own [`crate::extract_metadata`] never leaves
+/// the container's header boxes, so it only ever reports
+/// [`Self::DetectFormat`] and [`Self::ParseContainer`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressStage {
+    /// Sniffing which container format the stream holds.
+    DetectFormat,
+    /// Reading the container's header/index structure (e.g. an MP4 `moov`
+    /// box, a FLAC `STREAMINFO` block).
+    ParseContainer,
+    /// Downloading sample data, e.g. thumbnail frames or subtitle chunks.
+    DownloadSamples,
+    /// Decoding previously downloaded sample data.
+    Decode,
+}
+
+/// One progress update reported through [`ExtractOptions::on_progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProgressEvent {
+    /// Which stage this update is for.
+    pub stage: ProgressStage,
+    /// Bytes processed so far within `stage`, if known.
+    pub bytes_done: Option<u64>,
+    /// The total bytes expected for `stage`, if known.
+    pub bytes_total: Option<u64>,
+}
+
+/// Options accepted by [`crate::extract_metadata_with`].
+#[derive(Default)]
+pub struct ExtractOptions<'a> {
+    /// Called with a [`ProgressEvent`] each time extraction moves to (or
+    /// makes headway within) a new stage.
+    pub on_progress: Option<Box<dyn FnMut(ProgressEvent) + 'a>>,
+    /// Checked between stages; if cancelled, extraction stops early with
+    /// [`crate::Error::Cancelled`] instead of finishing an abandoned job.
+    pub cancellation: Option<CancellationToken>,
+    /// An overall wall-clock budget for the whole extraction, measured from
+    /// when [`crate::extract_metadata_with`] is called. Checked between
+    /// stages; once it elapses, extraction stops early with
+    /// [`crate::Error::Timeout`] instead of running unbounded against a
+    /// slow or stalled remote source.
+    pub timeout: Option<Duration>,
+    /// Caps parsing to at most this many bytes from the start of the
+    /// stream, never reading its tail. Equivalent to [`crate::quick_metadata`],
+    /// but composable with the rest of these options.
+    pub max_bytes: Option<u64>,
+    /// Caps the number of [`crate::Picture`]s returned in
+    /// [`crate::Metadata::pictures`], discarding any beyond this count
+    /// instead of holding them (and their image data) in memory.
+    pub max_pictures: Option<usize>,
+}
+
+impl<'a> ExtractOptions<'a> {
+    /// Options with no progress reporting, equivalent to `Default::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the progress callback.
+    pub fn on_progress(mut self, callback: impl FnMut(ProgressEvent) + 'a) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets the cancellation token checked between stages.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Sets the overall wall-clock budget for the whole extraction.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Caps parsing to at most the first `max_bytes` of the stream.
+    pub fn max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Caps the number of pictures returned to at most `max_pictures`.
+    pub fn max_pictures(mut self, max_pictures: usize) -> Self {
+        self.max_pictures = Some(max_pictures);
+        self
+    }
+
+    pub(crate) fn report(&mut self, stage: ProgressStage, bytes_done: Option<u64>, bytes_total: Option<u64>) {
+        if let Some(callback) = self.on_progress.as_mut() {
+            callback(ProgressEvent { stage, bytes_done, bytes_total });
+        }
+    }
+
+    pub(crate) fn check_cancelled(&self) -> crate::error::Result<()> {
+        match &self.cancellation {
+            Some(token) => token.check(),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns [`crate::Error::Timeout`] if `deadline` (computed from
+    /// [`Self::timeout`] by the caller) has passed.
+    pub(crate) fn check_deadline(&self, deadline: Option<Instant>) -> crate::error::Result<()> {
+        match deadline {
+            Some(deadline) if Instant::now() > deadline => Err(crate::error::Error::Timeout),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn on_progress_builder_stores_callback_invoked_on_report() {
+        let mut stages = Vec::new();
+        {
+            let mut options = ExtractOptions::new().on_progress(|event| stages.push(event.stage));
+            options.report(ProgressStage::DetectFormat, None, None);
+            options.report(ProgressStage::ParseContainer, Some(10), Some(100));
+        }
+        assert_eq!(stages, vec![ProgressStage::DetectFormat, ProgressStage::ParseContainer]);
+    }
+}
@@ -0,0 +1,181 @@
+//! Parsing MP4/M4A metadata from a forward-only, non-seekable source (an
+//! `AsyncRead`, e.g. stdin or a pipe receiving a live upload).
+//!
+//! This only works for "faststart" files where `moov` precedes `mdat`: we
+//! can't seek back for a trailing `moov`, and we refuse to buffer an
+//! unbounded amount of sample data looking for one. If `mdat` is seen
+//! before `moov`, [`Error::SeekRequired`] is returned immediately instead
+//! of reading (and discarding) the rest of the stream.
+
+use std::io;
+
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::error::{Error, Result};
+use crate::metadata::{self, Metadata};
+use crate::stream::SeekableStream;
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A [`SeekableStream`] over bytes already buffered in memory, used to hand
+/// the fully-buffered `ftyp`/`moov` prefix to the ordinary MP4 parser.
+struct BufferedStream<'a>(&'a [u8]);
+
+impl SeekableStream for BufferedStream<'_> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = offset as usize;
+        let end = start + buf.len();
+        if end > self.0.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "read past end of buffered prefix"));
+        }
+        buf.copy_from_slice(&self.0[start..end]);
+        Ok(())
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.0.len() as u64)
+    }
+}
+
+/// Parses metadata from `reader` without seeking, as bytes arrive.
+///
+/// Returns [`Error::SeekRequired`] if `mdat` appears before `moov`, since a
+/// non-faststart file's `moov` lives at the end and can't be reached
+/// without seeking.
+pub async fn parse_progressive<R: AsyncRead + Unpin>(reader: &mut R) -> Result<Metadata> {
+    let mut buf = Vec::new();
+    let mut offset = 0usize;
+
+    loop {
+        let header_len = ensure_buffered(reader, &mut buf, offset + 8).await?;
+        let _ = header_len;
+
+        let size32 = u32::from_be_bytes(buf[offset..offset + 4].try_into().unwrap());
+        let box_type: [u8; 4] = buf[offset + 4..offset + 8].try_into().unwrap();
+
+        // `mdat` always means "stop scanning forward and seek", regardless of
+        // its declared size -- including `size == 0` ("extends to end of
+        // file"), which is common for streamed/live MP4s and would otherwise
+        // fall through to the size checks below.
+        if &box_type == b"mdat" {
+            return Err(Error::SeekRequired);
+        }
+
+        let (header_size, box_size) = if size32 == 1 {
+            ensure_buffered(reader, &mut buf, offset + 16).await?;
+            let size64 = u64::from_be_bytes(buf[offset + 8..offset + 16].try_into().unwrap());
+            (16usize, size64)
+        } else if size32 == 0 {
+            // A non-`mdat` top-level box extending to the end of the file
+            // can't be skipped without knowing the stream's total length,
+            // which this forward-only, non-seekable parse never has.
+            return Err(Error::Unsupported("a top-level box with size 0 (\"extends to end of file\") requires seeking".into()));
+        } else {
+            (8usize, u64::from(size32))
+        };
+
+        if box_size < header_size as u64 {
+            return Err(Error::Malformed { format: "mp4", reason: "box size smaller than its header".into() });
+        }
+
+        let box_end = offset + box_size as usize;
+        ensure_buffered(reader, &mut buf, box_end).await?;
+
+        if &box_type == b"moov" {
+            let mut stream = BufferedStream(&buf[..box_end]);
+            return metadata::extract_metadata(&mut stream);
+        }
+
+        offset = box_end;
+    }
+}
+
+/// Reads more of `reader` into `buf` until it holds at least `needed`
+/// bytes, returning an error if the stream ends first.
+async fn ensure_buffered<R: AsyncRead + Unpin>(reader: &mut R, buf: &mut Vec<u8>, needed: usize) -> Result<usize> {
+    while buf.len() < needed {
+        let mut chunk = vec![0u8; READ_CHUNK_SIZE];
+        let n = reader.read(&mut chunk).await?;
+        if n == 0 {
+            return Err(Error::Malformed { format: "mp4", reason: "stream ended before moov was found".into() });
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    Ok(buf.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[tokio::test]
+    async fn parses_faststart_prefix() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mut mvhd_body = vec![0u8; 20];
+        mvhd_body[12..16].copy_from_slice(&1000u32.to_be_bytes()); // timescale
+        mvhd_body[16..20].copy_from_slice(&500u32.to_be_bytes()); // duration
+        let mvhd = sized_box(b"mvhd", &mvhd_body);
+        let moov = sized_box(b"moov", &mvhd);
+        let mdat = sized_box(b"mdat", &[0u8; 64]);
+
+        let mut input = ftyp;
+        input.extend_from_slice(&moov);
+        input.extend_from_slice(&mdat);
+
+        let mut reader = &input[..];
+        let metadata = parse_progressive(&mut reader).await.unwrap();
+
+        assert_eq!(metadata.duration, Some(std::time::Duration::from_millis(500)));
+    }
+
+    #[tokio::test]
+    async fn requires_seeking_when_mdat_precedes_moov() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mdat = sized_box(b"mdat", &[0u8; 64]);
+
+        let mut input = ftyp;
+        input.extend_from_slice(&mdat);
+
+        let mut reader = &input[..];
+        let err = parse_progressive(&mut reader).await.unwrap_err();
+        assert!(matches!(err, Error::SeekRequired));
+    }
+
+    #[tokio::test]
+    async fn requires_seeking_for_an_mdat_declared_with_size_zero() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mut mdat = vec![0u8; 4]; // size == 0: extends to end of file
+        mdat.extend_from_slice(b"mdat");
+        mdat.extend_from_slice(&[0u8; 64]);
+
+        let mut input = ftyp;
+        input.extend_from_slice(&mdat);
+
+        let mut reader = &input[..];
+        let err = parse_progressive(&mut reader).await.unwrap_err();
+        assert!(matches!(err, Error::SeekRequired));
+    }
+
+    #[tokio::test]
+    async fn reports_unsupported_for_a_non_mdat_box_declared_with_size_zero() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let mut free = vec![0u8; 4]; // size == 0: extends to end of file
+        free.extend_from_slice(b"free");
+        free.extend_from_slice(&[0u8; 16]);
+
+        let mut input = ftyp;
+        input.extend_from_slice(&free);
+
+        let mut reader = &input[..];
+        let err = parse_progressive(&mut reader).await.unwrap_err();
+        assert!(matches!(err, Error::Unsupported(_)));
+    }
+}
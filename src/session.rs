@@ -0,0 +1,185 @@
+//! A session object that keeps a stream and its parsed metadata together,
+//! so a caller that needs more than one kind of output from the same file
+//! doesn't re-open and re-sniff it for each one.
+
+use crate::error::Result;
+use crate::metadata::{extract_metadata, Metadata};
+use crate::stream::SeekableStream;
+use crate::subtitle::SubtitleEntry;
+use crate::thumbnails::{self, ThumbnailData};
+
+/// An open media file, with its container metadata parsed at most once.
+///
+/// [`Self::metadata`] parses and caches on first call; later calls (and
+/// [`Self::thumbnail`]/[`Self::subtitles`]) reuse the cached result instead
+/// of re-downloading or re-parsing the container's header boxes. Thumbnail
+/// and subtitle extraction don't yet read from a shared sample table (see
+/// [`crate::thumbnails`] and [`crate::subtitle`]), so today they only save
+/// the format-sniff and header-parse work `metadata()` already cached, not
+/// a further pass over sample data.
+pub struct MediaParser<S: SeekableStream> {
+    stream: S,
+    metadata: Option<Metadata>,
+}
+
+impl<S: SeekableStream> MediaParser<S> {
+    /// Opens `stream` for extraction, without parsing anything yet.
+    pub fn open(stream: S) -> Self {
+        Self { stream, metadata: None }
+    }
+
+    /// Returns this file's container metadata, parsing it on first call and
+    /// returning the cached result afterward.
+    pub fn metadata(&mut self) -> Result<&Metadata> {
+        if self.metadata.is_none() {
+            self.metadata = Some(extract_metadata(&mut self.stream)?);
+        }
+        Ok(self.metadata.as_ref().expect("just populated above"))
+    }
+
+    /// Extracts the file's primary still image, per
+    /// [`thumbnails::extract_primary_image`].
+    pub fn thumbnail(&mut self) -> Result<ThumbnailData> {
+        thumbnails::extract_primary_image(&mut self.stream)
+    }
+
+    /// Extracts the file's subtitle cues.
+    ///
+    /// No subtitle extraction backend is implemented yet (only the
+    /// [`SubtitleEntry`] cue type and muxing side exist so far), so this
+    /// always fails.
+    pub fn subtitles(&mut self) -> Result<Vec<SubtitleEntry>> {
+        Err(crate::error::Error::Unsupported("no subtitle extraction backend is implemented yet".into()))
+    }
+
+    /// Runs [`Self::metadata`], [`Self::thumbnail`], and [`Self::subtitles`]
+    /// against this one open stream and collects their results into a
+    /// [`MediaBundle`], so a caller that wants all three doesn't have to
+    /// call each separately.
+    ///
+    /// This reuses whatever [`Self::metadata`] already cached, but -- as
+    /// noted on this struct -- thumbnail and subtitle extraction don't yet
+    /// share a sample-table or byte-range plan with metadata extraction or
+    /// with each other, so this is a convenience for calling all three, not
+    /// yet a merged download plan across them.
+    ///
+    /// [`MediaBundle::truncated`] is carried over from
+    /// [`Metadata::is_truncated`], for a source whose tail (e.g. the rest of
+    /// `mdat`) was cut off: `metadata` is still complete in that case, while
+    /// `thumbnail`/`subtitles` may fail if the sample they needed fell in
+    /// the missing part of the file.
+    pub fn extract_all(&mut self) -> Result<MediaBundle> {
+        let metadata = self.metadata()?.clone();
+        let thumbnail = self.thumbnail();
+        let subtitles = self.subtitles();
+        let truncated = metadata.is_truncated.unwrap_or(false);
+        Ok(MediaBundle { metadata, thumbnail, subtitles, truncated })
+    }
+}
+
+/// The combined result of [`MediaParser::extract_all`]: container metadata
+/// plus whatever thumbnail/subtitle extraction could produce from the same
+/// file. Thumbnail and subtitle extraction are kept as their own
+/// [`Result`]s, rather than failing the whole bundle, since a file commonly
+/// has metadata worth reading even when it has no cover art or subtitle
+/// track.
+pub struct MediaBundle {
+    /// The file's container metadata.
+    pub metadata: Metadata,
+    /// The file's primary still image, per [`MediaParser::thumbnail`].
+    pub thumbnail: Result<ThumbnailData>,
+    /// The file's subtitle cues, per [`MediaParser::subtitles`].
+    pub subtitles: Result<Vec<SubtitleEntry>>,
+    /// Whether the source was missing bytes its own box headers claimed it
+    /// should have, per [`Metadata::is_truncated`]. `false` for formats with
+    /// no such distinction.
+    pub truncated: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sample_wav() -> Vec<u8> {
+        let mut fmt_body = Vec::new();
+        fmt_body.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        fmt_body.extend_from_slice(&2u16.to_le_bytes()); // channels
+        fmt_body.extend_from_slice(&44100u32.to_le_bytes()); // sample rate
+        fmt_body.extend_from_slice(&176400u32.to_le_bytes()); // byte rate
+        fmt_body.extend_from_slice(&4u16.to_le_bytes()); // block align
+        fmt_body.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+
+        let mut riff_body = Vec::new();
+        riff_body.extend_from_slice(b"WAVE");
+        riff_body.extend_from_slice(b"fmt ");
+        riff_body.extend_from_slice(&(fmt_body.len() as u32).to_le_bytes());
+        riff_body.extend_from_slice(&fmt_body);
+        riff_body.extend_from_slice(b"data");
+        riff_body.extend_from_slice(&4u32.to_le_bytes());
+        riff_body.extend_from_slice(&[0u8; 4]);
+
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(riff_body.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&riff_body);
+        wav
+    }
+
+    #[test]
+    fn metadata_is_parsed_once_and_then_cached() {
+        let mut parser = MediaParser::open(MemorySeekableStream::new(sample_wav()));
+
+        let first = parser.metadata().unwrap().clone();
+        let second = parser.metadata().unwrap().clone();
+
+        assert_eq!(first, second);
+        assert_eq!(first.sample_rate, Some(44100));
+    }
+
+    #[test]
+    fn thumbnail_and_subtitles_surface_unsupported_until_backends_exist() {
+        let mut parser = MediaParser::open(MemorySeekableStream::new(sample_wav()));
+
+        assert!(matches!(parser.thumbnail(), Err(crate::Error::Unsupported(_))));
+        assert!(matches!(parser.subtitles(), Err(crate::Error::Unsupported(_))));
+    }
+
+    #[test]
+    fn extract_all_bundles_metadata_with_thumbnail_and_subtitle_results() {
+        let mut parser = MediaParser::open(MemorySeekableStream::new(sample_wav()));
+
+        let bundle = parser.extract_all().unwrap();
+
+        assert_eq!(bundle.metadata.sample_rate, Some(44100));
+        assert!(matches!(bundle.thumbnail, Err(crate::Error::Unsupported(_))));
+        assert!(matches!(bundle.subtitles, Err(crate::Error::Unsupported(_))));
+        assert!(!bundle.truncated);
+    }
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    #[test]
+    fn extract_all_reports_truncated_when_mdat_was_cut_short() {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+        let moov = sized_box(b"moov", &[]);
+        let mut mdat_header = sized_box(b"mdat", &[0u8; 16]); // claims 16 bytes of payload
+        mdat_header.truncate(8 + 4); // but the stream only actually has 4 of them
+
+        let mut data = ftyp;
+        data.extend_from_slice(&moov);
+        data.extend_from_slice(&mdat_header);
+
+        let mut parser = MediaParser::open(MemorySeekableStream::new(data));
+        let bundle = parser.extract_all().unwrap();
+
+        assert!(bundle.truncated);
+        assert_eq!(bundle.metadata.is_faststart, Some(true));
+    }
+}
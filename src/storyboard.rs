@@ -0,0 +1,201 @@
+//! Storyboard generation: tiles a run of decoded thumbnails into a single
+//! sprite image and writes the WebVTT file with `#xywh=` media-fragment
+//! cues that map time ranges to tiles, the shape video.js/hls.js preview
+//! plugins consume directly.
+//!
+//! Tiling is pure pixel work this crate already does the raw-RGB side of
+//! (see [`crate::waveform::render_waveform`] for the same [`DecodedImage`]
+//! convention), so [`build_storyboard`] takes already-decoded tiles -- via
+//! [`crate::thumbnails::decode`] on a keyframe pass, say -- rather than
+//! decoding video itself. This crate bundles no image encoder, so turning
+//! [`Storyboard::sprite`] into a JPEG/PNG file is left to the caller, same
+//! as [`crate::waveform`] and [`crate::bif`].
+
+use std::fmt::Write as _;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::thumbnails::DecodedImage;
+
+/// One tile of a storyboard: the time range it covers and its decoded
+/// pixels. Every tile passed to [`build_storyboard`] must share the same
+/// [`DecodedImage`] width and height.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoryboardTile {
+    pub start: Duration,
+    pub end: Duration,
+    pub image: DecodedImage,
+}
+
+/// Configures [`build_storyboard`]'s sprite layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoryboardOptions {
+    /// Number of tiles per sprite row. Capped at `tiles.len()`; additional
+    /// rows are added as needed to fit every tile.
+    pub columns: u32,
+}
+
+impl Default for StoryboardOptions {
+    fn default() -> Self {
+        Self { columns: 10 }
+    }
+}
+
+/// A sprite image plus the WebVTT text of cues pointing at its `#xywh=`
+/// regions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Storyboard {
+    pub sprite: DecodedImage,
+    pub vtt: String,
+}
+
+/// Tiles `tiles` into one sprite image laid out `options.columns` wide
+/// (row-major, filled in order), and writes one WebVTT cue per tile whose
+/// text is `{sprite_url}#xywh={x},{y},{width},{height}` -- the
+/// media-fragment syntax video.js/hls.js preview plugins expect.
+///
+/// `sprite_url` is written into every cue verbatim (e.g. `"sprite.jpg"` or
+/// a full URL); it isn't validated, since this crate has no opinion on
+/// where the caller serves the encoded sprite from.
+///
+/// `tiles` must be non-empty, sorted by ascending start time with no
+/// overlapping ranges, and every tile's image must share the same width
+/// and height -- otherwise [`Error::Unsupported`].
+pub fn build_storyboard(tiles: &[StoryboardTile], sprite_url: &str, options: &StoryboardOptions) -> Result<Storyboard> {
+    if tiles.is_empty() {
+        return Err(Error::Unsupported("a storyboard needs at least one tile".into()));
+    }
+    if options.columns == 0 {
+        return Err(Error::Unsupported("columns must be greater than zero".into()));
+    }
+
+    let tile_width = tiles[0].image.width;
+    let tile_height = tiles[0].image.height;
+    for tile in tiles {
+        if tile.image.width != tile_width || tile.image.height != tile_height {
+            return Err(Error::Unsupported("all storyboard tiles must share the same dimensions".into()));
+        }
+    }
+    for pair in tiles.windows(2) {
+        if pair[1].start < pair[0].end {
+            return Err(Error::Unsupported("storyboard tiles must be sorted by ascending, non-overlapping time ranges".into()));
+        }
+    }
+
+    let columns = options.columns.min(tiles.len() as u32);
+    let rows = (tiles.len() as u32).div_ceil(columns);
+    let sprite_width = columns * tile_width;
+    let sprite_height = rows * tile_height;
+
+    let mut rgb = vec![0u8; sprite_width as usize * sprite_height as usize * 3];
+    for (i, tile) in tiles.iter().enumerate() {
+        let col = i as u32 % columns;
+        let row = i as u32 / columns;
+        let dst_x = col * tile_width;
+        let dst_y = row * tile_height;
+        for y in 0..tile_height {
+            let src_start = (y * tile_width) as usize * 3;
+            let src_end = src_start + tile_width as usize * 3;
+            let dst_start = ((dst_y + y) * sprite_width + dst_x) as usize * 3;
+            let dst_end = dst_start + tile_width as usize * 3;
+            rgb[dst_start..dst_end].copy_from_slice(&tile.image.rgb[src_start..src_end]);
+        }
+    }
+
+    let mut vtt = String::from("WEBVTT\n\n");
+    for (i, tile) in tiles.iter().enumerate() {
+        let col = i as u32 % columns;
+        let row = i as u32 / columns;
+        let x = col * tile_width;
+        let y = row * tile_height;
+        let _ = writeln!(
+            vtt,
+            "{}\n{} --> {}\n{sprite_url}#xywh={x},{y},{tile_width},{tile_height}\n",
+            i + 1,
+            format_vtt_timestamp(tile.start),
+            format_vtt_timestamp(tile.end),
+        );
+    }
+
+    Ok(Storyboard { sprite: DecodedImage { width: sprite_width, height: sprite_height, rgb }, vtt })
+}
+
+/// Formats `d` as a WebVTT cue timestamp (`HH:MM:SS.mmm`).
+fn format_vtt_timestamp(d: Duration) -> String {
+    let total_millis = d.as_millis();
+    let millis = total_millis % 1000;
+    let total_secs = total_millis / 1000;
+    let secs = total_secs % 60;
+    let total_mins = total_secs / 60;
+    let mins = total_mins % 60;
+    let hours = total_mins / 60;
+    format!("{hours:02}:{mins:02}:{secs:02}.{millis:03}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_tile(start_secs: u64, color: u8) -> StoryboardTile {
+        StoryboardTile {
+            start: Duration::from_secs(start_secs),
+            end: Duration::from_secs(start_secs + 1),
+            image: DecodedImage { width: 2, height: 2, rgb: vec![color; 2 * 2 * 3] },
+        }
+    }
+
+    #[test]
+    fn tiles_images_into_a_row_major_sprite_grid() {
+        let tiles = vec![solid_tile(0, 1), solid_tile(1, 2), solid_tile(2, 3)];
+        let options = StoryboardOptions { columns: 2 };
+
+        let storyboard = build_storyboard(&tiles, "sprite.jpg", &options).unwrap();
+
+        assert_eq!(storyboard.sprite.width, 4);
+        assert_eq!(storyboard.sprite.height, 4);
+        // Tile 2 (color 3) is the first tile of the second row.
+        let idx = (2 * 4) * 3;
+        assert_eq!(&storyboard.sprite.rgb[idx..idx + 3], &[3, 3, 3]);
+    }
+
+    #[test]
+    fn writes_a_cue_with_an_xywh_fragment_per_tile() {
+        let tiles = vec![solid_tile(0, 1), solid_tile(1, 2)];
+        let options = StoryboardOptions { columns: 2 };
+
+        let storyboard = build_storyboard(&tiles, "sprite.jpg", &options).unwrap();
+
+        assert!(storyboard.vtt.starts_with("WEBVTT\n\n"));
+        assert!(storyboard.vtt.contains("00:00:00.000 --> 00:00:01.000"));
+        assert!(storyboard.vtt.contains("sprite.jpg#xywh=0,0,2,2"));
+        assert!(storyboard.vtt.contains("00:00:01.000 --> 00:00:02.000"));
+        assert!(storyboard.vtt.contains("sprite.jpg#xywh=2,0,2,2"));
+    }
+
+    #[test]
+    fn rejects_tiles_with_mismatched_dimensions() {
+        let mut tiles = vec![solid_tile(0, 1)];
+        tiles.push(StoryboardTile {
+            start: Duration::from_secs(1),
+            end: Duration::from_secs(2),
+            image: DecodedImage { width: 3, height: 2, rgb: vec![0; 3 * 2 * 3] },
+        });
+
+        assert!(build_storyboard(&tiles, "sprite.jpg", &StoryboardOptions::default()).is_err());
+    }
+
+    #[test]
+    fn rejects_overlapping_tiles() {
+        let tiles = vec![
+            StoryboardTile { start: Duration::from_secs(0), end: Duration::from_secs(2), image: DecodedImage { width: 2, height: 2, rgb: vec![0; 12] } },
+            StoryboardTile { start: Duration::from_secs(1), end: Duration::from_secs(3), image: DecodedImage { width: 2, height: 2, rgb: vec![0; 12] } },
+        ];
+
+        assert!(build_storyboard(&tiles, "sprite.jpg", &StoryboardOptions::default()).is_err());
+    }
+
+    #[test]
+    fn rejects_an_empty_tile_list() {
+        assert!(build_storyboard(&[], "sprite.jpg", &StoryboardOptions::default()).is_err());
+    }
+}
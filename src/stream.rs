@@ -0,0 +1,337 @@
+//! Abstractions over the byte sources that format parsers read from.
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+/// A source of bytes that supports random access, used by every format
+/// parser so that local files, in-memory buffers, and (eventually) remote
+/// sources can all be probed the same way.
+pub trait SeekableStream {
+    /// Reads `buf.len()` bytes starting at `offset`, failing if the stream
+    /// is shorter than `offset + buf.len()`.
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()>;
+
+    /// Returns the total length of the stream in bytes.
+    fn len(&mut self) -> io::Result<u64>;
+
+    /// Returns `true` if the stream has no bytes.
+    fn is_empty(&mut self) -> io::Result<bool> {
+        Ok(self.len()? == 0)
+    }
+
+    /// Returns a snapshot of this stream's request/byte counters, for
+    /// observability pipelines. Callers typically read this after a call
+    /// like [`crate::extract_metadata`] returns, e.g. to see how much of a
+    /// remote file a `moov` scan actually had to download.
+    ///
+    /// Local, in-memory sources have nothing meaningful to report and use
+    /// the all-zero default; [`crate::SeekableHttpStream`] and
+    /// [`crate::SeekableS3Stream`] override this with real counts.
+    fn stats(&self) -> StreamStats {
+        StreamStats::default()
+    }
+}
+
+/// Request/byte counters reported by [`SeekableStream::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StreamStats {
+    /// Number of range requests issued against the underlying source.
+    pub requests: u64,
+    /// Bytes actually transferred over the network (or equivalent), which
+    /// can exceed [`Self::bytes_read`] due to block-cache granularity.
+    pub bytes_downloaded: u64,
+    /// Bytes returned to callers through [`SeekableStream::read_at`].
+    pub bytes_read: u64,
+    /// `bytes_downloaded` as a percentage of the stream's total length, if
+    /// the length is known.
+    pub percent_of_file: Option<f64>,
+}
+
+/// A [`SeekableStream`] backed by a local [`File`].
+///
+/// Not available on `wasm32-unknown-unknown`, which has no real filesystem:
+/// use [`crate::wasm::fetch_seekable_stream`] (feature `wasm`) there instead.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileSeekableStream {
+    file: File,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileSeekableStream {
+    /// Opens the file at `path` for reading.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self { file: File::open(path)? })
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SeekableStream for FileSeekableStream {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.file.seek(SeekFrom::Start(offset))?;
+        self.file.read_exact(buf)
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        self.file.metadata().map(|m| m.len())
+    }
+}
+
+/// A [`SeekableStream`] over bytes already held in memory (an uploaded
+/// file, a test fixture, ...), so callers don't need to write to a
+/// temporary file just to get a [`SeekableStream`].
+pub struct MemorySeekableStream {
+    data: Vec<u8>,
+}
+
+impl MemorySeekableStream {
+    /// Wraps `data`, taking ownership of it.
+    pub fn new(data: Vec<u8>) -> Self {
+        Self { data }
+    }
+}
+
+impl SeekableStream for MemorySeekableStream {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = offset as usize;
+        let end = start.checked_add(buf.len()).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "read range overflows usize")
+        })?;
+        if end > self.data.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "read past end of buffer"));
+        }
+        buf.copy_from_slice(&self.data[start..end]);
+        Ok(())
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.data.len() as u64)
+    }
+}
+
+/// A [`SeekableStream`] over a memory-mapped local file, so parsing can
+/// read directly from the OS page cache instead of copying `moov` buffers
+/// and sample ranges through [`FileSeekableStream`]'s `read`/`seek` calls.
+#[cfg(feature = "mmap")]
+pub struct MmapSeekableStream {
+    map: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MmapSeekableStream {
+    /// Opens and maps the file at `path` for reading.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: mutating the backing file while it's mapped is undefined
+        // behavior; callers are responsible for not doing that, same as
+        // any other use of `memmap2::Mmap`.
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self { map })
+    }
+}
+
+#[cfg(feature = "mmap")]
+impl SeekableStream for MmapSeekableStream {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let start = offset as usize;
+        let end = start.checked_add(buf.len()).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "read range overflows usize")
+        })?;
+        if end > self.map.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "read past end of mapped file"));
+        }
+        buf.copy_from_slice(&self.map[start..end]);
+        Ok(())
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.map.len() as u64)
+    }
+}
+
+/// Wraps another [`SeekableStream`] so that reads past a fixed byte budget
+/// fail instead of reaching the underlying source, used by
+/// [`crate::metadata::quick_metadata`] to guarantee it never reads a
+/// stream's tail.
+pub struct PrefixStream<'a, S: SeekableStream> {
+    inner: &'a mut S,
+    max_bytes: u64,
+}
+
+impl<'a, S: SeekableStream> PrefixStream<'a, S> {
+    /// Wraps `inner`, capping visible length at `max_bytes`.
+    pub fn new(inner: &'a mut S, max_bytes: u64) -> Self {
+        Self { inner, max_bytes }
+    }
+}
+
+impl<S: SeekableStream> SeekableStream for PrefixStream<'_, S> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let end = offset.checked_add(buf.len() as u64).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "read range overflows u64")
+        })?;
+        if end > self.max_bytes {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "read extends past the configured prefix budget",
+            ));
+        }
+        self.inner.read_at(offset, buf)
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        Ok(self.inner.len()?.min(self.max_bytes))
+    }
+}
+
+/// Stitches an init segment (`ftyp`+`moov`) and one or more media segments
+/// (`moof`+`mdat`) into a single logical stream, so a packaged DASH asset
+/// can be fed straight into [`crate::extract_metadata`],
+/// [`crate::extract_all_subtitles`], and [`crate::thumbnails`] exactly like
+/// a self-contained progressive MP4: a `trun`'s `data_offset` is relative to
+/// its own `moof`'s start, and concatenating segments in order preserves
+/// that relationship without rewriting any offsets.
+///
+/// Each segment is expected to hold a whole number of top-level boxes --
+/// [`SeekableStream::read_at`] fails if a single read would need bytes from
+/// more than one segment, which a well-formed DASH asset's box framing
+/// never requires.
+pub struct SegmentedStream {
+    segments: Vec<Box<dyn SeekableStream>>,
+    /// The logical offset where each segment starts, lazily computed (it
+    /// requires querying every segment's own length) and cached.
+    offsets: Option<Vec<u64>>,
+}
+
+impl SegmentedStream {
+    /// Wraps `init_segment` followed by `media_segments`, in that order, as
+    /// a single logical stream.
+    pub fn new(init_segment: Box<dyn SeekableStream>, media_segments: Vec<Box<dyn SeekableStream>>) -> Self {
+        let mut segments = vec![init_segment];
+        segments.extend(media_segments);
+        Self { segments, offsets: None }
+    }
+
+    fn ensure_offsets(&mut self) -> io::Result<()> {
+        if self.offsets.is_none() {
+            let mut offsets = Vec::with_capacity(self.segments.len());
+            let mut total = 0u64;
+            for segment in &mut self.segments {
+                offsets.push(total);
+                total += segment.len()?;
+            }
+            self.offsets = Some(offsets);
+        }
+        Ok(())
+    }
+}
+
+impl SeekableStream for SegmentedStream {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let end = offset
+            .checked_add(buf.len() as u64)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "read range overflows u64"))?;
+        self.ensure_offsets()?;
+
+        let segment_index = {
+            let offsets = self.offsets.as_ref().expect("ensure_offsets just populated this");
+            offsets.partition_point(|&start| start <= offset) - 1
+        };
+        let segment_start = self.offsets.as_ref().expect("ensure_offsets just populated this")[segment_index];
+        let segment = &mut self.segments[segment_index];
+        let segment_len = segment.len()?;
+
+        if end - segment_start > segment_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "read spans more than one DASH segment"));
+        }
+        segment.read_at(offset - segment_start, buf)
+    }
+
+    fn len(&mut self) -> io::Result<u64> {
+        self.ensure_offsets()?;
+        let last_index = self.segments.len() - 1;
+        let last_start = self.offsets.as_ref().expect("ensure_offsets just populated this")[last_index];
+        Ok(last_start + self.segments[last_index].len()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    #[test]
+    fn prefix_stream_reports_capped_length() {
+        let mut inner = MemorySeekableStream::new(vec![0u8; 100]);
+        let mut prefix = PrefixStream::new(&mut inner, 10);
+        assert_eq!(prefix.len().unwrap(), 10);
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_stream_reads_bytes_at_offset() {
+        let path = std::env::temp_dir().join(format!("media-parser-mmap-test-{}", std::process::id()));
+        std::fs::write(&path, b"hello mmap").unwrap();
+
+        let mut stream = MmapSeekableStream::open(&path).unwrap();
+        let mut buf = [0u8; 4];
+        stream.read_at(6, &mut buf).unwrap();
+        assert_eq!(&buf, b"mmap");
+        assert_eq!(stream.len().unwrap(), 10);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn memory_stream_reads_bytes_at_offset() {
+        let mut stream = MemorySeekableStream::new(vec![1, 2, 3, 4, 5]);
+        let mut buf = [0u8; 2];
+        stream.read_at(2, &mut buf).unwrap();
+        assert_eq!(buf, [3, 4]);
+        assert_eq!(stream.len().unwrap(), 5);
+        assert!(stream.read_at(4, &mut [0u8; 2]).is_err());
+    }
+
+    #[test]
+    fn prefix_stream_rejects_reads_past_budget() {
+        let mut inner = MemorySeekableStream::new(vec![0u8; 100]);
+        let mut prefix = PrefixStream::new(&mut inner, 10);
+        let mut buf = [0u8; 1];
+        assert!(prefix.read_at(9, &mut buf).is_ok());
+        assert!(prefix.read_at(10, &mut buf).is_err());
+    }
+
+    #[test]
+    fn segmented_stream_resolves_reads_by_logical_offset_across_segments() {
+        let init = MemorySeekableStream::new(vec![1, 2, 3]);
+        let media1 = MemorySeekableStream::new(vec![4, 5]);
+        let media2 = MemorySeekableStream::new(vec![6, 7, 8]);
+        let mut stream = SegmentedStream::new(Box::new(init), vec![Box::new(media1), Box::new(media2)]);
+
+        assert_eq!(stream.len().unwrap(), 8);
+
+        let mut buf = [0u8; 2];
+        stream.read_at(1, &mut buf).unwrap();
+        assert_eq!(buf, [2, 3]);
+
+        // media1 starts right after init's 3 bytes, at logical offset 3.
+        stream.read_at(3, &mut buf).unwrap();
+        assert_eq!(buf, [4, 5]);
+
+        // media2 starts at logical offset 5.
+        stream.read_at(6, &mut buf).unwrap();
+        assert_eq!(buf, [7, 8]);
+    }
+
+    #[test]
+    fn segmented_stream_rejects_a_read_spanning_more_than_one_segment() {
+        let init = MemorySeekableStream::new(vec![1, 2, 3]);
+        let media = MemorySeekableStream::new(vec![4, 5]);
+        let mut stream = SegmentedStream::new(Box::new(init), vec![Box::new(media)]);
+
+        assert!(stream.read_at(2, &mut [0u8; 2]).is_err());
+    }
+}
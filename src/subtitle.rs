@@ -0,0 +1,840 @@
+//! Subtitle/caption cue types shared between extraction and muxing.
+
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+use crate::formats::mp4::subtitle_reader::{self, ImageSubtitleTrack as Mp4ImageSubtitleTrack, TextTrack};
+use crate::stream::SeekableStream;
+
+/// One subtitle cue: a span of time and the text shown during it.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubtitleEntry {
+    /// When the cue starts being shown.
+    pub start: Duration,
+    /// When the cue stops being shown.
+    pub end: Duration,
+    /// The cue's text, already stripped of any source-format markup.
+    pub text: String,
+    /// Styling for this cue, if the source track carries any. Only
+    /// populated for `tx3g`; `wvtt` cues never set this.
+    pub style: Option<SubtitleStyle>,
+    /// The source track's `tkhd.track_ID`, for disambiguating cues pulled
+    /// from [`stream_entries`] against a multi-track file (it only ever
+    /// reads the first text track, so this is constant across its output,
+    /// but still lets a caller label the cue without re-deriving it).
+    pub track_id: u32,
+    /// The source track's `mdhd.language`, as an ISO 639-2/T code (e.g.
+    /// `"eng"`).
+    pub language: String,
+    /// 0-based index of this cue's sample within the track's decode order.
+    pub sample_index: u32,
+    /// The cue's still-encoded sample bytes (a `tx3g` text box or a `wvtt`
+    /// `vttc` box, before [`Self::text`]/[`Self::style`] were decoded from
+    /// it), for callers with their own handling of payloads this crate's
+    /// decoder doesn't cover.
+    pub raw: Vec<u8>,
+}
+
+/// A `tx3g` cue's styling, merged from its sample-entry defaults and any
+/// per-sample `styl`/`hlit`/`tbox` override records.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubtitleStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    /// RGBA text color.
+    pub text_color: [u8; 4],
+    /// The cue's on-screen placement, from `tx3g`'s default text box or a
+    /// per-sample `tbox` override.
+    pub box_position: Option<TextBoxPosition>,
+    /// A highlighted character range within the cue's text (`hlit`), e.g.
+    /// for karaoke-style reveal.
+    pub highlight: Option<(u16, u16)>,
+}
+
+impl Default for SubtitleStyle {
+    fn default() -> Self {
+        Self { bold: false, italic: false, underline: false, text_color: [255, 255, 255, 255], box_position: None, highlight: None }
+    }
+}
+
+/// A cue's placement, in `tx3g`'s top/left/bottom/right `BoxRecord` layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextBoxPosition {
+    pub top: i16,
+    pub left: i16,
+    pub bottom: i16,
+    pub right: i16,
+}
+
+/// Returns `stream`'s cues one at a time, decoding each sample as it's
+/// reached rather than collecting the whole track into a `Vec` up front.
+///
+/// This crate's streams only fetch the byte ranges each read actually
+/// touches (e.g. [`crate::http::SeekableHttpStream`]'s block cache), so for
+/// a remote source this spreads a long movie's subtitle downloads out over
+/// however many cues the caller actually consumes, rather than blocking on
+/// every cue before handing back the first one.
+///
+/// Only MP4/QuickTime `tx3g`, `wvtt`, and `c608` text tracks are supported;
+/// fails with [`Error::Unsupported`] if `stream` has none of them.
+pub fn stream_entries<S: SeekableStream>(stream: S) -> Result<impl Iterator<Item = Result<SubtitleEntry>>> {
+    stream_entries_with(stream, SubtitleTimingOptions::new())
+}
+
+/// Like [`stream_entries`], but adjusts every cue's `start`/`end` by
+/// `timing` before returning it, for fixing drift or matching a
+/// re-encoded video's frame rate.
+pub fn stream_entries_with<S: SeekableStream>(
+    mut stream: S,
+    timing: SubtitleTimingOptions,
+) -> Result<impl Iterator<Item = Result<SubtitleEntry>>> {
+    let track = subtitle_reader::find_text_track(&mut stream)?
+        .ok_or_else(|| Error::Unsupported("no tx3g/wvtt/c608 text track was found".into()))?;
+
+    Ok(SubtitleEntryIter { stream, track, index: 0, timing })
+}
+
+/// A constant offset and/or rate factor applied to subtitle cue timestamps,
+/// for fixing drift or re-timing cues cut against a different frame rate
+/// than the video they're now paired with. Applied as `timestamp * rate +
+/// offset_secs`.
+#[derive(Debug, Clone, Copy)]
+pub struct SubtitleTimingOptions {
+    /// Added to every timestamp after `rate` is applied. Negative values
+    /// shift cues earlier; the result is clamped to zero.
+    pub offset_secs: f64,
+    /// Multiplied into every timestamp before `offset_secs` is added, e.g.
+    /// `24.0 / 23.976` to re-time cues cut for one frame rate onto another.
+    pub rate: f64,
+}
+
+impl Default for SubtitleTimingOptions {
+    fn default() -> Self {
+        Self { offset_secs: 0.0, rate: 1.0 }
+    }
+}
+
+impl SubtitleTimingOptions {
+    /// No offset, no rescaling -- equivalent to `Default::default()`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the constant offset, in seconds.
+    pub fn offset_secs(mut self, offset_secs: f64) -> Self {
+        self.offset_secs = offset_secs;
+        self
+    }
+
+    /// Sets the rate factor.
+    pub fn rate(mut self, rate: f64) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    fn apply(&self, d: Duration) -> Duration {
+        Duration::from_secs_f64((d.as_secs_f64() * self.rate + self.offset_secs).max(0.0))
+    }
+}
+
+/// One subtitle track's identity (track ID and language, from `tkhd`/`mdhd`)
+/// and its fully-decoded cues.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubtitleTrack {
+    /// The track's `tkhd.track_ID`.
+    pub track_id: u32,
+    /// The track's `mdhd.language`, as an ISO 639-2/T code (e.g. `"eng"`).
+    pub language: String,
+    /// The track's cues, in file order.
+    pub entries: Vec<SubtitleEntry>,
+}
+
+/// Extracts every `tx3g`/`wvtt`/`c608` text track in `stream`, each grouped by its
+/// track ID and language, from a single read of `moov`.
+///
+/// Unlike [`stream_entries`], which only ever sees the first text track,
+/// this handles multi-language files in one pass. Returns an empty `Vec` if
+/// `stream` has no text tracks.
+pub fn extract_all_subtitles<S: SeekableStream>(stream: S) -> Result<Vec<SubtitleTrack>> {
+    extract_all_subtitles_with(stream, SubtitleTimingOptions::new())
+}
+
+/// Like [`extract_all_subtitles`], but adjusts every cue's `start`/`end` by
+/// `timing` before returning it.
+pub fn extract_all_subtitles_with<S: SeekableStream>(mut stream: S, timing: SubtitleTimingOptions) -> Result<Vec<SubtitleTrack>> {
+    let tracks = subtitle_reader::find_all_text_tracks(&mut stream)?;
+
+    tracks
+        .into_iter()
+        .map(|track| {
+            let entries = (0..track.samples.sample_count())
+                .map(|index| decode_entry(&mut stream, &track, index, &timing))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(SubtitleTrack { track_id: track.track_id, language: track.language.clone(), entries })
+        })
+        .collect()
+}
+
+/// One bitmap subtitle cue: a span of time and its still-encoded image
+/// sample. Unlike [`SubtitleEntry`], there's no decoded `text`/`style` here
+/// -- see [`decode_image_subtitle`] for why.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageSubtitleEntry {
+    /// When the cue starts being shown.
+    pub start: Duration,
+    /// When the cue stops being shown.
+    pub end: Duration,
+    /// The source track's `tkhd.track_ID`.
+    pub track_id: u32,
+    /// The source track's `mdhd.language`, as an ISO 639-2/T code.
+    pub language: String,
+    /// 0-based index of this cue's sample within the track's decode order.
+    pub sample_index: u32,
+    /// The cue's still-encoded bitmap sample bytes (e.g. DVD subpicture RLE
+    /// data), for callers with their own decoder.
+    pub raw: Vec<u8>,
+}
+
+/// A bitmap subtitle track's identity (track ID and language) and its
+/// still-encoded cues.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageSubtitleTrack {
+    /// The track's `tkhd.track_ID`.
+    pub track_id: u32,
+    /// The track's `mdhd.language`, as an ISO 639-2/T code.
+    pub language: String,
+    /// The track's cues, in file order.
+    pub entries: Vec<ImageSubtitleEntry>,
+}
+
+/// Extracts every `mp4s` bitmap subtitle track in `stream` (e.g. DVD
+/// subpicture/VobSub muxed into MP4), each grouped by its track ID and
+/// language, from a single read of `moov`. Returns an empty `Vec` if
+/// `stream` has no such tracks.
+///
+/// Each cue's [`ImageSubtitleEntry::raw`] is its sample's still-encoded
+/// bitmap data; call [`decode_image_subtitle`] to get pixels out of it, or
+/// bring your own decoder for now, since that function currently always
+/// fails -- see its doc comment.
+pub fn extract_image_subtitles<S: SeekableStream>(mut stream: S) -> Result<Vec<ImageSubtitleTrack>> {
+    let tracks = subtitle_reader::find_all_image_subtitle_tracks(&mut stream)?;
+
+    tracks
+        .into_iter()
+        .map(|track| {
+            let entries = (0..track.samples.sample_count())
+                .map(|index| decode_image_entry(&mut stream, &track, index))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(ImageSubtitleTrack { track_id: track.track_id, language: track.language.clone(), entries })
+        })
+        .collect()
+}
+
+/// Decodes a bitmap subtitle sample's raw payload (e.g. DVD subpicture RLE
+/// data from an [`ImageSubtitleEntry::raw`]) into image bytes.
+///
+/// Not implemented: this crate has no RLE/palette decoder for bitmap
+/// subtitle formats and no image-encoding dependency to produce PNG bytes
+/// with, so this always fails with [`Error::Unsupported`] rather than
+/// guessing at a decode. [`extract_image_subtitles`] still locates these
+/// tracks and their cue timing/raw bytes, for a caller with its own decoder.
+pub fn decode_image_subtitle(_raw: &[u8]) -> Result<Vec<u8>> {
+    Err(Error::Unsupported("bitmap subtitle decoding is not implemented (no RLE/palette decoder or image encoder)".into()))
+}
+
+/// Decodes sample `index` (0-based) of `track` into an [`ImageSubtitleEntry`],
+/// deriving its end time from the next sample's timestamp. The last sample
+/// has no following sample, so its `end` equals its `start`.
+fn decode_image_entry<S: SeekableStream>(
+    stream: &mut S,
+    track: &Mp4ImageSubtitleTrack,
+    index: u32,
+) -> Result<ImageSubtitleEntry> {
+    let size = track.samples.size(stream, index)?;
+    let offset = track.samples.offset(stream, index)?;
+    let mut data = vec![0u8; size as usize];
+    stream.read_at(offset, &mut data)?;
+
+    let start_ticks = track.samples.timestamp(stream, index)?;
+    let end_ticks =
+        if index + 1 < track.samples.sample_count() { track.samples.timestamp(stream, index + 1)? } else { start_ticks };
+
+    Ok(ImageSubtitleEntry {
+        start: ticks_to_duration(start_ticks, track.timescale),
+        end: ticks_to_duration(end_ticks, track.timescale),
+        track_id: track.track_id,
+        language: track.language.clone(),
+        sample_index: index,
+        raw: data,
+    })
+}
+
+struct SubtitleEntryIter<S: SeekableStream> {
+    stream: S,
+    track: TextTrack,
+    index: u32,
+    timing: SubtitleTimingOptions,
+}
+
+impl<S: SeekableStream> Iterator for SubtitleEntryIter<S> {
+    type Item = Result<SubtitleEntry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.track.samples.sample_count() {
+            return None;
+        }
+
+        let entry = decode_entry(&mut self.stream, &self.track, self.index, &self.timing);
+        self.index += 1;
+        Some(entry)
+    }
+}
+
+/// Decodes sample `index` (0-based) of `track` into a [`SubtitleEntry`],
+/// deriving its end time from the next sample's timestamp and adjusting
+/// both by `timing`. The last sample has no following sample, so its `end`
+/// equals its `start` (before `timing` is applied).
+fn decode_entry<S: SeekableStream>(
+    stream: &mut S,
+    track: &TextTrack,
+    index: u32,
+    timing: &SubtitleTimingOptions,
+) -> Result<SubtitleEntry> {
+    let size = track.samples.size(stream, index)?;
+    let offset = track.samples.offset(stream, index)?;
+    let mut data = vec![0u8; size as usize];
+    stream.read_at(offset, &mut data)?;
+
+    let start_ticks = track.samples.timestamp(stream, index)?;
+    let end_ticks =
+        if index + 1 < track.samples.sample_count() { track.samples.timestamp(stream, index + 1)? } else { start_ticks };
+
+    let (text, style) = subtitle_reader::decode_sample(track.codec, track.default_style.as_ref(), &data)?;
+    Ok(SubtitleEntry {
+        start: timing.apply(ticks_to_duration(start_ticks, track.timescale)),
+        end: timing.apply(ticks_to_duration(end_ticks, track.timescale)),
+        text,
+        style,
+        track_id: track.track_id,
+        language: track.language.clone(),
+        sample_index: index,
+        raw: data,
+    })
+}
+
+fn ticks_to_duration(ticks: u64, timescale: u32) -> Duration {
+    if timescale == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(ticks as f64 / f64::from(timescale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn tx3g_sample(text: &str) -> Vec<u8> {
+        let mut sample = (text.len() as u16).to_be_bytes().to_vec();
+        sample.extend_from_slice(text.as_bytes());
+        sample
+    }
+
+    fn tkhd_box(track_id: u32) -> Vec<u8> {
+        let mut body = vec![0u8; 20];
+        body[12..16].copy_from_slice(&track_id.to_be_bytes());
+        sized_box(b"tkhd", &body)
+    }
+
+    fn mdhd_box(language: u16) -> Vec<u8> {
+        let mut body = vec![0u8; 22];
+        body[12..16].copy_from_slice(&1000u32.to_be_bytes()); // timescale: milliseconds
+        body[20..22].copy_from_slice(&language.to_be_bytes());
+        sized_box(b"mdhd", &body)
+    }
+
+    fn sample_mp4_with_tx3g_track(cues: &[&str]) -> Vec<u8> {
+        sample_mp4_with_tx3g_track_full(1, 0x15C7, cues) // language: "eng"
+    }
+
+    fn sample_mp4_with_tx3g_track_full(track_id: u32, language: u16, cues: &[&str]) -> Vec<u8> {
+        let tkhd = tkhd_box(track_id);
+        let mdhd = mdhd_box(language);
+
+        let tx3g = sized_box(b"tx3g", &[0u8; 6]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &tx3g].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let samples: Vec<Vec<u8>> = cues.iter().map(|text| tx3g_sample(text)).collect();
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in &samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for _ in &samples {
+            stts_body.extend_from_slice(&1u32.to_be_bytes()); // sample_count
+            stts_body.extend_from_slice(&1000u32.to_be_bytes()); // sample_delta: 1 second
+        }
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let stbl = sized_box(b"stbl", &[stsd, stts, stsc, stsz, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"text", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn streams_cues_one_at_a_time_with_timestamps() {
+        let data = sample_mp4_with_tx3g_track(&["Hello", "World"]);
+        let entries: Vec<SubtitleEntry> =
+            stream_entries(MemorySeekableStream::new(data)).unwrap().collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].text, "Hello");
+        assert_eq!(entries[0].start, Duration::ZERO);
+        assert_eq!(entries[0].end, Duration::from_secs(1));
+        assert_eq!(entries[1].text, "World");
+        assert_eq!(entries[1].start, Duration::from_secs(1));
+        // The last cue has no following sample to derive an end time from.
+        assert_eq!(entries[1].end, Duration::from_secs(1));
+
+        assert_eq!(entries[0].track_id, 1);
+        assert_eq!(entries[0].language, "eng");
+        assert_eq!(entries[0].sample_index, 0);
+        assert_eq!(entries[1].sample_index, 1);
+        assert_eq!(entries[0].raw, tx3g_sample("Hello"));
+    }
+
+    #[test]
+    fn applies_a_constant_offset_and_rate_to_cue_timestamps() {
+        let data = sample_mp4_with_tx3g_track(&["Hello", "World"]);
+        let timing = SubtitleTimingOptions::new().rate(2.0).offset_secs(0.5);
+        let entries: Vec<SubtitleEntry> =
+            stream_entries_with(MemorySeekableStream::new(data), timing).unwrap().collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(entries[0].start, Duration::from_secs_f64(0.5));
+        assert_eq!(entries[0].end, Duration::from_secs_f64(2.5));
+        assert_eq!(entries[1].start, Duration::from_secs_f64(2.5));
+    }
+
+    #[test]
+    fn clamps_a_negative_offset_to_zero_instead_of_underflowing() {
+        let data = sample_mp4_with_tx3g_track(&["Hello"]);
+        let timing = SubtitleTimingOptions::new().offset_secs(-10.0);
+        let entries: Vec<SubtitleEntry> =
+            stream_entries_with(MemorySeekableStream::new(data), timing).unwrap().collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(entries[0].start, Duration::ZERO);
+    }
+
+    /// Builds a single `trak` with a placeholder (zero) `stco` chunk offset,
+    /// plus the raw sample bytes that need to land in `mdat`. The caller
+    /// patches the offset in once every track's position in `mdat` is known.
+    fn build_trak_with_placeholder_offset(track_id: u32, language: u16, cues: &[&str]) -> (Vec<u8>, Vec<u8>) {
+        let tkhd = tkhd_box(track_id);
+        let mdhd = mdhd_box(language);
+
+        let tx3g = sized_box(b"tx3g", &[0u8; 6]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &tx3g].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let samples: Vec<Vec<u8>> = cues.iter().map(|text| tx3g_sample(text)).collect();
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in &samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for _ in &samples {
+            stts_body.extend_from_slice(&1u32.to_be_bytes()); // sample_count
+            stts_body.extend_from_slice(&1000u32.to_be_bytes()); // sample_delta: 1 second
+        }
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        // Chunk offset is a placeholder; it always ends up as the trak's
+        // last 4 bytes, since stco is stbl's last child box.
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let stbl = sized_box(b"stbl", &[stsd, stts, stsc, stsz, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"text", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+
+        (trak, samples.concat())
+    }
+
+    fn sample_mp4_with_two_tx3g_tracks() -> Vec<u8> {
+        let (trak1, samples1) = build_trak_with_placeholder_offset(1, 0x15C7, &["Hola"]); // "eng"
+        let (trak2, samples2) = build_trak_with_placeholder_offset(2, 0x1A45, &["Bonjour"]); // "fre"
+
+        let mut moov = sized_box(b"moov", &[trak1.clone(), trak2.clone()].concat());
+        let mdat_start = (moov.len() + 8) as u64;
+
+        let trak1_offset_pos = 8 + trak1.len() - 4;
+        moov[trak1_offset_pos..trak1_offset_pos + 4].copy_from_slice(&(mdat_start as u32).to_be_bytes());
+
+        let trak2_offset_pos = 8 + trak1.len() + trak2.len() - 4;
+        let trak2_sample_offset = mdat_start + samples1.len() as u64;
+        moov[trak2_offset_pos..trak2_offset_pos + 4].copy_from_slice(&(trak2_sample_offset as u32).to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &[samples1, samples2].concat());
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn extracts_all_tracks_grouped_by_track_id_and_language() {
+        let data = sample_mp4_with_two_tx3g_tracks();
+        let tracks = extract_all_subtitles(MemorySeekableStream::new(data)).unwrap();
+
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].track_id, 1);
+        assert_eq!(tracks[0].language, "eng");
+        assert_eq!(tracks[0].entries.len(), 1);
+        assert_eq!(tracks[0].entries[0].text, "Hola");
+
+        assert_eq!(tracks[1].track_id, 2);
+        assert_eq!(tracks[1].language, "fre");
+        assert_eq!(tracks[1].entries.len(), 1);
+        assert_eq!(tracks[1].entries[0].text, "Bonjour");
+    }
+
+    fn wvtt_sample(text: &str) -> Vec<u8> {
+        let payl = sized_box(b"payl", text.as_bytes());
+        sized_box(b"vttc", &payl)
+    }
+
+    fn fragment_tfhd(track_id: u32) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version/flags: no optional fields
+        body.extend_from_slice(&track_id.to_be_bytes());
+        sized_box(b"tfhd", &body)
+    }
+
+    fn fragment_tfdt(base_media_decode_time: u32) -> Vec<u8> {
+        let mut body = vec![0u8; 4]; // version 0
+        body.extend_from_slice(&base_media_decode_time.to_be_bytes());
+        sized_box(b"tfdt", &body)
+    }
+
+    fn fragment_trun(data_offset: i32, sample_size: u32) -> Vec<u8> {
+        let flags = 0x01u32 | 0x200; // data-offset-present | sample-size-present
+        let mut body = flags.to_be_bytes().to_vec();
+        body.extend_from_slice(&1u32.to_be_bytes()); // sample_count
+        body.extend_from_slice(&data_offset.to_be_bytes());
+        body.extend_from_slice(&sample_size.to_be_bytes());
+        sized_box(b"trun", &body)
+    }
+
+    /// One `moof` + `mdat` pair carrying a single `wvtt` sample for
+    /// `track_id`, with `base_media_decode_time` in the track's timescale.
+    fn fragment(track_id: u32, base_media_decode_time: u32, text: &str) -> Vec<u8> {
+        let sample = wvtt_sample(text);
+        let traf = sized_box(
+            b"traf",
+            &[fragment_tfhd(track_id), fragment_tfdt(base_media_decode_time), fragment_trun(0, sample.len() as u32)].concat(),
+        );
+        let mut moof = sized_box(b"moof", &traf);
+
+        // trun's data_offset is relative to this moof's own start; the
+        // sample sits right after mdat's 8-byte header. trun is the last
+        // child box in moof, laid out as flags/sample_count/data_offset/
+        // sample_size, so data_offset is the second-to-last 4-byte field.
+        let data_offset = moof.len() as i32 + 8;
+        let trun_data_offset_pos = moof.len() - 8;
+        moof[trun_data_offset_pos..trun_data_offset_pos + 4].copy_from_slice(&data_offset.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &sample);
+        [moof, mdat].concat()
+    }
+
+    /// A `moov` with one fragmented `wvtt` track (its `stbl` has `stsd` but
+    /// no `stsz`/`stts`), followed by two `moof`/`mdat` fragments carrying
+    /// its actual samples.
+    fn fragmented_wvtt_mp4(cues: &[(u32, &str)]) -> Vec<u8> {
+        let track_id = 1;
+        let tkhd = tkhd_box(track_id);
+        let mdhd = mdhd_box(0x15C7); // "eng"
+
+        let wvtt = sized_box(b"wvtt", &[0u8; 6]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &wvtt].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+        let stbl = sized_box(b"stbl", &stsd);
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"text", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let moov = sized_box(b"moov", &trak);
+
+        let fragments: Vec<u8> = cues.iter().flat_map(|(time, text)| fragment(track_id, *time, text)).collect();
+        [moov, fragments].concat()
+    }
+
+    #[test]
+    fn reconstructs_cues_from_fragmented_wvtt_samples_across_moof_boundaries() {
+        let data = fragmented_wvtt_mp4(&[(0, "Hi"), (1000, "There")]);
+        let tracks = extract_all_subtitles(MemorySeekableStream::new(data)).unwrap();
+
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].entries.len(), 2);
+        assert_eq!(tracks[0].entries[0].text, "Hi");
+        assert_eq!(tracks[0].entries[0].start, Duration::ZERO);
+        assert_eq!(tracks[0].entries[0].end, Duration::from_secs(1));
+        assert_eq!(tracks[0].entries[1].text, "There");
+        assert_eq!(tracks[0].entries[1].start, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn reconstructs_cues_from_a_dash_init_segment_plus_separately_fetched_media_segments() {
+        let track_id = 1;
+        let tkhd = tkhd_box(track_id);
+        let mdhd = mdhd_box(0x15C7); // "eng"
+
+        let wvtt = sized_box(b"wvtt", &[0u8; 6]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &wvtt].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+        let stbl = sized_box(b"stbl", &stsd);
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"text", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let moov = sized_box(b"moov", &trak);
+        let ftyp = sized_box(b"ftyp", b"isommp42isomdash");
+        let init_segment = [ftyp, moov].concat();
+
+        let media_segment_1 = fragment(track_id, 0, "Hi");
+        let media_segment_2 = fragment(track_id, 1000, "There");
+
+        let stream = crate::stream::SegmentedStream::new(
+            Box::new(MemorySeekableStream::new(init_segment)),
+            vec![Box::new(MemorySeekableStream::new(media_segment_1)), Box::new(MemorySeekableStream::new(media_segment_2))],
+        );
+
+        let tracks = extract_all_subtitles(stream).unwrap();
+
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].entries.len(), 2);
+        assert_eq!(tracks[0].entries[0].text, "Hi");
+        assert_eq!(tracks[0].entries[0].start, Duration::ZERO);
+        assert_eq!(tracks[0].entries[1].text, "There");
+        assert_eq!(tracks[0].entries[1].start, Duration::from_secs(1));
+    }
+
+    fn sample_mp4_with_c608_track(cue_pairs: &[(u8, u8)]) -> Vec<u8> {
+        let tkhd = tkhd_box(1);
+        let mdhd = mdhd_box(0x15C7); // "eng"
+
+        let c608 = sized_box(b"c608", &[0u8; 6]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &c608].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let samples: Vec<Vec<u8>> = cue_pairs
+            .iter()
+            .map(|&(a, b)| sized_box(b"cdat", &[a, b]))
+            .collect();
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in &samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for _ in &samples {
+            stts_body.extend_from_slice(&1u32.to_be_bytes()); // sample_count
+            stts_body.extend_from_slice(&1000u32.to_be_bytes()); // sample_delta: 1 second
+        }
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let stbl = sized_box(b"stbl", &[stsd, stts, stsc, stsz, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"clcp", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn streams_cues_from_a_c608_caption_track() {
+        let data = sample_mp4_with_c608_track(&[(b'H', b'i'), (b'Y', b'o')]);
+        let entries: Vec<SubtitleEntry> =
+            stream_entries(MemorySeekableStream::new(data)).unwrap().collect::<Result<Vec<_>>>().unwrap();
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].text, "Hi");
+        assert_eq!(entries[0].start, Duration::ZERO);
+        assert_eq!(entries[1].text, "Yo");
+        assert_eq!(entries[1].start, Duration::from_secs(1));
+        assert!(entries[0].style.is_none());
+    }
+
+    #[test]
+    fn reports_unsupported_without_a_text_track() {
+        let hdlr_body = [&[0u8; 8][..], b"soun", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &hdlr);
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        assert!(matches!(stream_entries(MemorySeekableStream::new(moov)), Err(Error::Unsupported(_))));
+    }
+
+    fn sample_mp4_with_mp4s_track(samples: &[&[u8]]) -> Vec<u8> {
+        let tkhd = tkhd_box(1);
+        let mdhd = mdhd_box(0x15C7); // "eng"
+
+        let mp4s = sized_box(b"mp4s", &[0u8; 6]);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &mp4s].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for _ in samples {
+            stts_body.extend_from_slice(&1u32.to_be_bytes()); // sample_count
+            stts_body.extend_from_slice(&1000u32.to_be_bytes()); // sample_delta: 1 second
+        }
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let stbl = sized_box(b"stbl", &[stsd, stts, stsc, stsz, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"subp", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn extracts_image_subtitle_tracks_with_raw_samples_and_timestamps() {
+        let data = sample_mp4_with_mp4s_track(&[&[0xDE, 0xAD], &[0xBE, 0xEF, 0x01]]);
+        let tracks = extract_image_subtitles(MemorySeekableStream::new(data)).unwrap();
+
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].track_id, 1);
+        assert_eq!(tracks[0].language, "eng");
+        assert_eq!(tracks[0].entries.len(), 2);
+        assert_eq!(tracks[0].entries[0].raw, vec![0xDE, 0xAD]);
+        assert_eq!(tracks[0].entries[0].start, Duration::ZERO);
+        assert_eq!(tracks[0].entries[0].end, Duration::from_secs(1));
+        assert_eq!(tracks[0].entries[1].raw, vec![0xBE, 0xEF, 0x01]);
+        assert_eq!(tracks[0].entries[1].start, Duration::from_secs(1));
+        // The last cue has no following sample to derive an end time from.
+        assert_eq!(tracks[0].entries[1].end, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn returns_an_empty_vec_without_any_image_subtitle_tracks() {
+        let data = sample_mp4_with_tx3g_track(&["Hello"]);
+        assert_eq!(extract_image_subtitles(MemorySeekableStream::new(data)).unwrap(), Vec::new());
+    }
+
+    #[test]
+    fn decode_image_subtitle_is_unimplemented() {
+        assert!(matches!(decode_image_subtitle(&[0xDE, 0xAD]), Err(Error::Unsupported(_))));
+    }
+}
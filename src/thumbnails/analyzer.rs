@@ -0,0 +1,109 @@
+//! Luminance-based blank/black frame detection, for skipping fades and
+//! black frames that a naive evenly-spaced thumbnail selection would
+//! otherwise land on.
+//!
+//! This operates on already-decoded pixel data: this crate has no bundled
+//! decoder (see the [`super`] module docs), so a caller wires this in after
+//! decoding a candidate frame, advancing to the next one if it's rejected.
+
+/// Thresholds [`is_blank_frame`] uses to decide whether a decoded frame
+/// looks blank (dark and/or flat) rather than a usable thumbnail candidate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BlankFrameThresholds {
+    /// Mean luminance (0.0-255.0) at or below which a frame is considered
+    /// too dark to use, e.g. a black frame or a fade-to-black.
+    pub max_mean_luminance: f64,
+    /// Luminance variance at or below which a frame is considered too flat
+    /// (too little detail) to use, e.g. a solid color card or a fade.
+    pub min_luminance_variance: f64,
+}
+
+impl Default for BlankFrameThresholds {
+    fn default() -> Self {
+        // Chosen so an actually-black or solid-color frame (luminance near
+        // 0 and/or essentially no variance) is rejected, while ordinary
+        // footage -- which varies far more, even in dim scenes -- passes.
+        Self { max_mean_luminance: 16.0, min_luminance_variance: 25.0 }
+    }
+}
+
+/// An 8-bit grayscale luminance buffer for one decoded frame, as a decoder
+/// backend would hand off for blank-frame analysis.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LuminanceFrame {
+    pub width: u32,
+    pub height: u32,
+    pub luminance: Vec<u8>,
+}
+
+/// Returns `true` if `frame` looks blank (dark and/or flat) per
+/// `thresholds`. A frame with no pixels is always considered blank.
+pub fn is_blank_frame(frame: &LuminanceFrame, thresholds: &BlankFrameThresholds) -> bool {
+    if frame.luminance.is_empty() {
+        return true;
+    }
+
+    let pixel_count = frame.luminance.len() as f64;
+    let mean = frame.luminance.iter().map(|&p| f64::from(p)).sum::<f64>() / pixel_count;
+    let variance = frame.luminance.iter().map(|&p| (f64::from(p) - mean).powi(2)).sum::<f64>() / pixel_count;
+
+    mean <= thresholds.max_mean_luminance || variance <= thresholds.min_luminance_variance
+}
+
+/// Returns the index of the first frame in `candidates` (in order) that
+/// isn't blank per [`is_blank_frame`], for advancing an evenly-spaced
+/// thumbnail selection off a fade or black frame. `None` if every
+/// candidate is blank.
+pub fn first_acceptable_frame(candidates: &[LuminanceFrame], thresholds: &BlankFrameThresholds) -> Option<usize> {
+    candidates.iter().position(|frame| !is_blank_frame(frame, thresholds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_frame(luminance: u8, pixel_count: usize) -> LuminanceFrame {
+        LuminanceFrame { width: pixel_count as u32, height: 1, luminance: vec![luminance; pixel_count] }
+    }
+
+    #[test]
+    fn rejects_a_solid_black_frame() {
+        let frame = solid_frame(0, 100);
+        assert!(is_blank_frame(&frame, &BlankFrameThresholds::default()));
+    }
+
+    #[test]
+    fn rejects_a_solid_gray_card_even_though_its_not_dark() {
+        let frame = solid_frame(128, 100);
+        assert!(is_blank_frame(&frame, &BlankFrameThresholds::default()));
+    }
+
+    #[test]
+    fn accepts_a_frame_with_enough_brightness_and_detail() {
+        let luminance: Vec<u8> = (0..100).map(|i| if i % 2 == 0 { 40 } else { 200 }).collect();
+        let frame = LuminanceFrame { width: 100, height: 1, luminance };
+        assert!(!is_blank_frame(&frame, &BlankFrameThresholds::default()));
+    }
+
+    #[test]
+    fn treats_an_empty_frame_as_blank() {
+        let frame = LuminanceFrame { width: 0, height: 0, luminance: Vec::new() };
+        assert!(is_blank_frame(&frame, &BlankFrameThresholds::default()));
+    }
+
+    #[test]
+    fn first_acceptable_frame_skips_leading_blank_candidates() {
+        let candidates = vec![solid_frame(0, 10), solid_frame(0, 10), LuminanceFrame {
+            width: 10,
+            height: 1,
+            luminance: (0..10).map(|i| if i % 2 == 0 { 40 } else { 220 }).collect(),
+        }];
+        assert_eq!(first_acceptable_frame(&candidates, &BlankFrameThresholds::default()), Some(2));
+    }
+
+    #[test]
+    fn first_acceptable_frame_returns_none_when_every_candidate_is_blank() {
+        let candidates = vec![solid_frame(0, 10), solid_frame(255, 10)];
+        assert_eq!(first_acceptable_frame(&candidates, &BlankFrameThresholds::default()), None);
+    }
+}
@@ -0,0 +1,64 @@
+//! A [`VideoDecoder`] for AV1 (`av01`) backed by `dav1d`, a dedicated AV1
+//! decoder -- lighter to link than pulling in all of `ffmpeg` just for AV1
+//! thumbnails.
+//!
+//! `dav1d`'s bindings link against the system `libdav1d` via its build
+//! script (`pkg-config`, or `DAV1D_*` environment variables -- see the
+//! `dav1d` crate's README), which isn't present in this repository's own
+//! CI sandbox, so this module can't be exercised there. It's gated behind
+//! the `dav1d` feature the same way `ffmpeg_backend` gates its own
+//! dependency.
+//!
+//! `dav1d` only hands back planar YUV. [`VideoDecoder::decode_yuv`] returns
+//! those planes as-is; [`VideoDecoder::decode`] converts them to the RGB
+//! this crate's [`DecodedImage`] carries with a plain BT.601 conversion
+//! (see [`super::decoder::yuv_to_rgb`]), rather than pulling in a second
+//! dependency just for color space conversion.
+
+use super::decoder::{yuv_to_rgb, DecodedImage, VideoDecoder, YuvFrame, YuvPlane};
+use super::ThumbnailData;
+use crate::error::{Error, Result};
+
+pub(crate) struct Dav1dDecoder;
+
+impl VideoDecoder for Dav1dDecoder {
+    fn supports(&self, codec: &str) -> bool {
+        codec == "av01"
+    }
+
+    fn decode(&self, thumbnail: &ThumbnailData) -> Result<DecodedImage> {
+        Ok(yuv_to_rgb(&self.decode_yuv(thumbnail)?))
+    }
+
+    fn decode_yuv(&self, thumbnail: &ThumbnailData) -> Result<YuvFrame> {
+        let mut decoder = dav1d::Decoder::new().map_err(to_malformed)?;
+        decoder.send_data(thumbnail.data.clone(), None, None, None).map_err(to_malformed)?;
+
+        let picture = loop {
+            match decoder.get_picture() {
+                Ok(picture) => break picture,
+                Err(dav1d::Error::Again) => {
+                    return Err(Error::Malformed { format: "av1", reason: "decoder needs more data than one sample provides".into() })
+                }
+                Err(e) => return Err(to_malformed(e)),
+            }
+        };
+
+        let width = picture.width();
+        let height = picture.height();
+        let y_stride = picture.stride(dav1d::PlanarImageComponent::Y) as usize;
+        let uv_stride = picture.stride(dav1d::PlanarImageComponent::U) as usize;
+
+        Ok(YuvFrame {
+            width,
+            height,
+            y: YuvPlane { stride: y_stride, data: picture.plane(dav1d::PlanarImageComponent::Y).to_vec() },
+            u: YuvPlane { stride: uv_stride, data: picture.plane(dav1d::PlanarImageComponent::U).to_vec() },
+            v: YuvPlane { stride: uv_stride, data: picture.plane(dav1d::PlanarImageComponent::V).to_vec() },
+        })
+    }
+}
+
+fn to_malformed(e: dav1d::Error) -> Error {
+    Error::Malformed { format: "av1", reason: e.to_string() }
+}
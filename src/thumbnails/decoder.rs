@@ -0,0 +1,206 @@
+//! Pixel-decoder backends for the still-encoded frames [`super::ThumbnailData`]
+//! carries. This crate bundles no decoder by default -- see the
+//! [module docs](super) -- so [`decode`] only succeeds once a backend
+//! feature is enabled: `dav1d` (AV1 only) or `ffmpeg` (broader coverage,
+//! including AV1). If both are enabled, `dav1d` is tried first for `av01`
+//! since it's a dedicated AV1 decoder rather than a general-purpose one.
+
+use super::ThumbnailData;
+use crate::error::{Error, Result};
+
+/// A decoded still image: tightly packed, row-major 8-bit RGB, no padding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedImage {
+    /// Pixel width of the image.
+    pub width: u32,
+    /// Pixel height of the image.
+    pub height: u32,
+    /// `width * height * 3` bytes of interleaved 8-bit RGB.
+    pub rgb: Vec<u8>,
+}
+
+/// One 8-bit sample plane of a [`YuvFrame`], as the decoder laid it out --
+/// `stride` bytes per row, which can exceed `width` (or `width / 2` for a
+/// subsampled chroma plane) due to decoder-internal alignment padding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YuvPlane {
+    /// Bytes per row, including any trailing padding.
+    pub stride: usize,
+    /// `stride * height` (or `stride * height / 2` for a 4:2:0 chroma
+    /// plane) bytes of sample data, row-major.
+    pub data: Vec<u8>,
+}
+
+/// A decoded still frame's raw planar YUV (4:2:0, 8-bit per sample, no
+/// color space conversion or encoding applied), for consumers that want to
+/// feed decoded video straight into their own vision pipeline instead of
+/// paying for an RGB conversion and/or an image encode they're just going
+/// to decode again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct YuvFrame {
+    /// Pixel width of the luma (Y) plane.
+    pub width: u32,
+    /// Pixel height of the luma (Y) plane.
+    pub height: u32,
+    /// Luma samples, one per pixel.
+    pub y: YuvPlane,
+    /// Blue-difference chroma samples, subsampled 2x2 (one sample per 2x2
+    /// luma block).
+    pub u: YuvPlane,
+    /// Red-difference chroma samples, subsampled 2x2 (one sample per 2x2
+    /// luma block).
+    pub v: YuvPlane,
+}
+
+/// A backend that can turn a [`ThumbnailData`]'s still-encoded bytes into
+/// pixels. [`decode`] picks among the compiled-in backends at runtime based
+/// on [`VideoDecoder::supports`].
+pub trait VideoDecoder {
+    /// Returns `true` if this backend can decode `codec` (a four-character
+    /// sample-entry type, e.g. `"avc1"`, `"hev1"`, `"av01"`).
+    fn supports(&self, codec: &str) -> bool;
+
+    /// Decodes `thumbnail`'s still-encoded sample to pixels.
+    fn decode(&self, thumbnail: &ThumbnailData) -> Result<DecodedImage>;
+
+    /// Decodes `thumbnail`'s still-encoded sample to its raw planar YUV,
+    /// without the RGB conversion [`VideoDecoder::decode`] applies.
+    ///
+    /// The default implementation always fails with [`Error::Unsupported`];
+    /// only backends that expose 8-bit 4:2:0 planes directly (no format
+    /// conversion already baked in) override it.
+    fn decode_yuv(&self, _thumbnail: &ThumbnailData) -> Result<YuvFrame> {
+        Err(Error::Unsupported("this decoder backend doesn't expose raw YUV output".into()))
+    }
+}
+
+/// Decodes `thumbnail` to pixels using whichever compiled-in backend
+/// supports its codec.
+///
+/// Returns [`Error::Unsupported`] if no backend feature is enabled, or none
+/// of the enabled backends support `thumbnail.codec`.
+pub fn decode(thumbnail: &ThumbnailData) -> Result<DecodedImage> {
+    #[cfg(feature = "dav1d")]
+    {
+        let backend = super::dav1d_backend::Dav1dDecoder;
+        if backend.supports(&thumbnail.codec) {
+            return backend.decode(thumbnail);
+        }
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    {
+        let backend = super::ffmpeg_backend::FfmpegDecoder;
+        if backend.supports(&thumbnail.codec) {
+            return backend.decode(thumbnail);
+        }
+    }
+
+    Err(Error::Unsupported(format!("no decoder backend is configured for codec {:?}", thumbnail.codec)))
+}
+
+/// Decodes `thumbnail` to its raw planar YUV using whichever compiled-in
+/// backend supports its codec, skipping the RGB conversion [`decode`]
+/// applies.
+///
+/// Returns [`Error::Unsupported`] if no backend feature is enabled, none of
+/// the enabled backends support `thumbnail.codec`, or the backend that does
+/// can't hand back raw planes for this frame (see
+/// [`VideoDecoder::decode_yuv`]).
+pub fn decode_yuv(thumbnail: &ThumbnailData) -> Result<YuvFrame> {
+    #[cfg(feature = "dav1d")]
+    {
+        let backend = super::dav1d_backend::Dav1dDecoder;
+        if backend.supports(&thumbnail.codec) {
+            return backend.decode_yuv(thumbnail);
+        }
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    {
+        let backend = super::ffmpeg_backend::FfmpegDecoder;
+        if backend.supports(&thumbnail.codec) {
+            return backend.decode_yuv(thumbnail);
+        }
+    }
+
+    Err(Error::Unsupported(format!("no decoder backend is configured for codec {:?}", thumbnail.codec)))
+}
+
+/// Converts 8-bit 4:2:0 planar YUV (BT.601) to the interleaved RGB
+/// [`DecodedImage`] carries. Shared by backends whose native decode output
+/// is already planar YUV, so they don't each reimplement the same math.
+#[cfg(any(feature = "dav1d", feature = "ffmpeg"))]
+pub(crate) fn yuv_to_rgb(frame: &YuvFrame) -> DecodedImage {
+    let (width, height) = (frame.width as usize, frame.height as usize);
+    let mut rgb = Vec::with_capacity(width * height * 3);
+
+    for row in 0..height {
+        for col in 0..width {
+            let y_value = f32::from(frame.y.data[row * frame.y.stride + col]);
+            let u_value = f32::from(frame.u.data[(row / 2) * frame.u.stride + col / 2]) - 128.0;
+            let v_value = f32::from(frame.v.data[(row / 2) * frame.v.stride + col / 2]) - 128.0;
+
+            rgb.push((y_value + 1.402 * v_value).clamp(0.0, 255.0) as u8);
+            rgb.push((y_value - 0.344_136 * u_value - 0.714_136 * v_value).clamp(0.0, 255.0) as u8);
+            rgb.push((y_value + 1.772 * u_value).clamp(0.0, 255.0) as u8);
+        }
+    }
+
+    DecodedImage { width: frame.width, height: frame.height, rgb }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(feature = "ffmpeg"))]
+    fn reports_unsupported_without_a_decoder_backend_compiled_in() {
+        let thumbnail = ThumbnailData {
+            width: 1,
+            height: 1,
+            codec: "avc1".into(),
+            data: vec![0],
+            sample_index: 0,
+            is_keyframe: true,
+            pts: std::time::Duration::ZERO,
+        };
+        let err = decode(&thumbnail).unwrap_err();
+        assert!(matches!(err, Error::Unsupported(_)));
+    }
+
+    #[test]
+    #[cfg(not(feature = "ffmpeg"))]
+    fn reports_unsupported_for_yuv_output_without_a_decoder_backend_compiled_in() {
+        let thumbnail = ThumbnailData {
+            width: 1,
+            height: 1,
+            codec: "avc1".into(),
+            data: vec![0],
+            sample_index: 0,
+            is_keyframe: true,
+            pts: std::time::Duration::ZERO,
+        };
+        let err = decode_yuv(&thumbnail).unwrap_err();
+        assert!(matches!(err, Error::Unsupported(_)));
+    }
+
+    #[test]
+    #[cfg(any(feature = "dav1d", feature = "ffmpeg"))]
+    fn converts_a_solid_yuv_frame_to_the_expected_gray_rgb() {
+        let frame = YuvFrame {
+            width: 2,
+            height: 2,
+            y: YuvPlane { stride: 2, data: vec![128, 128, 128, 128] },
+            u: YuvPlane { stride: 1, data: vec![128] },
+            v: YuvPlane { stride: 1, data: vec![128] },
+        };
+
+        let image = yuv_to_rgb(&frame);
+
+        assert_eq!(image.width, 2);
+        assert_eq!(image.height, 2);
+        assert_eq!(image.rgb, vec![128, 128, 128, 128, 128, 128, 128, 128, 128, 128, 128, 128]);
+    }
+}
@@ -0,0 +1,386 @@
+//! Frame and still-image extraction.
+//!
+//! Locating the right sample or item is metadata work this crate always
+//! does; decoding it to pixels requires a codec, which this crate does not
+//! bundle by default. Extraction functions here return still-encoded
+//! [`ThumbnailData`] regardless; pass it to [`decoder::decode`] to get
+//! actual RGB pixels, or [`decoder::decode_yuv`] for the decoder's raw
+//! planar YUV with no color conversion, once a backend feature (`dav1d` or
+//! `ffmpeg`) is enabled.
+
+pub mod analyzer;
+#[cfg(feature = "dav1d")]
+mod dav1d_backend;
+pub mod decoder;
+#[cfg(feature = "ffmpeg")]
+mod ffmpeg_backend;
+
+use crate::error::{Error, Result};
+use crate::stream::SeekableStream;
+
+pub use decoder::{decode, decode_yuv, DecodedImage, VideoDecoder, YuvFrame, YuvPlane};
+
+/// A decoded (or, for now, still-encoded) still image extracted from a
+/// media file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThumbnailData {
+    /// Pixel width of the image.
+    pub width: u32,
+    /// Pixel height of the image.
+    pub height: u32,
+    /// The four-character codec type of [`Self::data`] (e.g. `"avc1"`,
+    /// `"hev1"`, `"av01"`), for picking a decoder backend. Empty if the
+    /// source format doesn't carry one (e.g. HEIF items, which aren't
+    /// populated by this crate yet -- see [`extract_primary_image`]).
+    pub codec: String,
+    /// The image's still-encoded bytes (e.g. an HEVC or AV1 I-frame),
+    /// pending a decoder backend to turn them into pixels.
+    pub data: Vec<u8>,
+    /// 0-based index of this sample within its track's decode order, for
+    /// correlating a thumbnail back to the sample it came from. `0` for a
+    /// source without per-sample indexing (e.g. HEIF items).
+    pub sample_index: u32,
+    /// Whether this sample is a sync sample (e.g. an IDR frame), per the
+    /// track's `stss`. `true` for a source without sync-sample tracking
+    /// (e.g. HEIF items, which have no predictive coding to worry about).
+    pub is_keyframe: bool,
+    /// Decode timestamp relative to the track's start, from `stts` (no
+    /// `ctts` composition offset applied -- this crate doesn't read
+    /// composition time yet). [`Duration::ZERO`] for a source without
+    /// sample timing (e.g. HEIF items).
+    pub pts: std::time::Duration,
+}
+
+/// Extracts the primary image item from an HEIF/AVIF source.
+///
+/// This currently locates the primary item via [`crate::metadata::extract_metadata`]
+/// but cannot decode it: no decoder backend is configured in this build.
+pub fn extract_primary_image<S: SeekableStream>(_stream: &mut S) -> Result<ThumbnailData> {
+    Err(Error::Unsupported("no decoder backend is configured for primary image extraction".into()))
+}
+
+/// Extracts the first video track's first sample as still-encoded bytes,
+/// for MP4/QuickTime sources with a video track.
+///
+/// `moov` is descended box-by-box (`trak`/`mdia`/`minf`/`stbl`, then
+/// `stsz`/`stsc`/`stco` for the one sample's size and offset), so this
+/// never buffers the whole `moov` or any of `mdat` beyond that one sample,
+/// regardless of how large either is. This doesn't decode the sample to
+/// pixels (no decoder backend is configured in this build), and doesn't
+/// yet pick a real keyframe via `stss` -- it's just the first sample in
+/// decode order.
+pub fn extract_video_thumbnail<S: SeekableStream>(stream: &mut S) -> Result<ThumbnailData> {
+    match crate::formats::mp4::first_video_sample(stream)? {
+        Some(frame) => Ok(ThumbnailData {
+            width: frame.width,
+            height: frame.height,
+            codec: frame.codec,
+            data: frame.data,
+            sample_index: frame.sample_index,
+            is_keyframe: frame.is_keyframe,
+            pts: frame.pts,
+        }),
+        None => Err(Error::Unsupported("no video track with a readable sample was found".into())),
+    }
+}
+
+/// Returns the still-encoded samples needed to reconstruct the frame at
+/// `target_sample_index` (0-based, in the first video track's decode
+/// order): every sample from the nearest preceding sync sample through the
+/// target, inclusive.
+///
+/// A decoder reproduces an exact, possibly non-sync, frame by feeding these
+/// forward in order and discarding every output but the last. This crate
+/// has no bundled decoder, so it stops at locating and returning the
+/// encoded samples themselves, in decode order.
+pub fn video_samples_for_exact_frame<S: SeekableStream>(stream: &mut S, target_sample_index: u32) -> Result<Vec<ThumbnailData>> {
+    match crate::formats::mp4::video_samples_from_preceding_sync_sample(stream, target_sample_index)? {
+        Some(frames) => Ok(frames
+            .into_iter()
+            .map(|f| ThumbnailData {
+                width: f.width,
+                height: f.height,
+                codec: f.codec,
+                data: f.data,
+                sample_index: f.sample_index,
+                is_keyframe: f.is_keyframe,
+                pts: f.pts,
+            })
+            .collect()),
+        None => Err(Error::Unsupported("no video track with a readable sample at that index was found".into())),
+    }
+}
+
+/// Resolves `positions` (fractions of the file's overall duration, e.g.
+/// `[0.1, 0.5, 0.9]`) to sample indices in the first video track, via
+/// `mvhd`'s duration and the track's own timescale/`stts`. This is what VOD
+/// thumbnail pipelines usually want instead of evenly spaced sample counts,
+/// since it lands at the same relative point in the video regardless of
+/// frame rate or edit length.
+pub fn video_sample_indices_at_positions<S: SeekableStream>(stream: &mut S, positions: &[f64]) -> Result<Vec<u32>> {
+    match crate::formats::mp4::video_sample_indices_at_positions(stream, positions)? {
+        Some(indices) => Ok(indices),
+        None => Err(Error::Unsupported("no video track with duration and timing info was found".into())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn sample_mp4_with_video_track(sample: &[u8], mdat_offset_placeholder: u32) -> Vec<u8> {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+
+        let mut mdhd_body = vec![0u8; 20];
+        mdhd_body[12..16].copy_from_slice(&1000u32.to_be_bytes()); // timescale
+        let mdhd = sized_box(b"mdhd", &mdhd_body);
+
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        let mut avc1_body = vec![0u8; 8]; // reserved + data_reference_index
+        avc1_body.extend_from_slice(&[0u8; 16]); // pre_defined/reserved/pre_defined[3]
+        avc1_body.extend_from_slice(&64u16.to_be_bytes()); // width
+        avc1_body.extend_from_slice(&48u16.to_be_bytes()); // height
+        let avc1 = sized_box(b"avc1", &avc1_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &avc1].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes()); // sample_size == 0: per-sample table follows
+        stsz_body.extend_from_slice(&1u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+        stts_body.extend_from_slice(&1u32.to_be_bytes()); // sample_count
+        stts_body.extend_from_slice(&1000u32.to_be_bytes()); // sample_delta
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes()); // first_chunk
+        stsc_body.extend_from_slice(&1u32.to_be_bytes()); // samples_per_chunk
+        stsc_body.extend_from_slice(&1u32.to_be_bytes()); // sample_description_index
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let mut stco_body = vec![0u8; 4];
+        stco_body.extend_from_slice(&1u32.to_be_bytes());
+        stco_body.extend_from_slice(&mdat_offset_placeholder.to_be_bytes());
+        let stco = sized_box(b"stco", &stco_body);
+
+        let stbl = sized_box(b"stbl", &[stsd, stsz, stts, stsc, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        let mdat = sized_box(b"mdat", sample);
+
+        let mut data = ftyp;
+        data.extend_from_slice(&moov);
+        data.extend_from_slice(&mdat);
+        data
+    }
+
+    fn sample_mp4_with_video_samples(samples: &[&[u8]], sync_samples: &[u32], chunk_offset: u32) -> Vec<u8> {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+
+        let mut mdhd_body = vec![0u8; 20];
+        mdhd_body[12..16].copy_from_slice(&1000u32.to_be_bytes()); // timescale
+        let mdhd = sized_box(b"mdhd", &mdhd_body);
+
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        let mut avc1_body = vec![0u8; 8]; // reserved + data_reference_index
+        avc1_body.extend_from_slice(&[0u8; 16]); // pre_defined/reserved/pre_defined[3]
+        avc1_body.extend_from_slice(&64u16.to_be_bytes()); // width
+        avc1_body.extend_from_slice(&48u16.to_be_bytes()); // height
+        let avc1 = sized_box(b"avc1", &avc1_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &avc1].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes()); // sample_size == 0: per-sample table follows
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        stts_body.extend_from_slice(&1000u32.to_be_bytes()); // sample_delta
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes());
+        stsc_body.extend_from_slice(&1u32.to_be_bytes()); // first_chunk
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes()); // samples_per_chunk
+        stsc_body.extend_from_slice(&1u32.to_be_bytes()); // sample_description_index
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let mut stco_body = vec![0u8; 4];
+        stco_body.extend_from_slice(&1u32.to_be_bytes());
+        stco_body.extend_from_slice(&chunk_offset.to_be_bytes());
+        let stco = sized_box(b"stco", &stco_body);
+
+        let mut stss_body = vec![0u8; 4];
+        stss_body.extend_from_slice(&(sync_samples.len() as u32).to_be_bytes());
+        for sample_number in sync_samples {
+            stss_body.extend_from_slice(&sample_number.to_be_bytes());
+        }
+        let stss = sized_box(b"stss", &stss_body);
+
+        let stbl = sized_box(b"stbl", &[stsd, stsz, stts, stsc, stco, stss].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        let mdat = sized_box(b"mdat", &samples.concat());
+
+        let mut data = ftyp;
+        data.extend_from_slice(&moov);
+        data.extend_from_slice(&mdat);
+        data
+    }
+
+    fn sample_mp4_with_timed_video_samples(sample_count: u32, track_timescale: u32, sample_delta: u32) -> Vec<u8> {
+        let ftyp = sized_box(b"ftyp", b"isom\0\0\x02\0isomiso2mp41");
+
+        let mut mvhd_body = vec![0u8; 20];
+        let movie_timescale = 1000u32;
+        let movie_duration = (u64::from(sample_count) * u64::from(sample_delta) * u64::from(movie_timescale)
+            / u64::from(track_timescale)) as u32;
+        mvhd_body[12..16].copy_from_slice(&movie_timescale.to_be_bytes());
+        mvhd_body[16..20].copy_from_slice(&movie_duration.to_be_bytes());
+        let mvhd = sized_box(b"mvhd", &mvhd_body);
+
+        let mut mdhd_body = vec![0u8; 20];
+        mdhd_body[12..16].copy_from_slice(&track_timescale.to_be_bytes());
+        mdhd_body[16..20].copy_from_slice(&(sample_count * sample_delta).to_be_bytes());
+        let mdhd = sized_box(b"mdhd", &mdhd_body);
+
+        let hdlr_body = [&[0u8; 8][..], b"vide", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+
+        let mut avc1_body = vec![0u8; 8];
+        avc1_body.extend_from_slice(&[0u8; 16]);
+        avc1_body.extend_from_slice(&64u16.to_be_bytes());
+        avc1_body.extend_from_slice(&48u16.to_be_bytes());
+        let avc1 = sized_box(b"avc1", &avc1_body);
+
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &avc1].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&1u32.to_be_bytes()); // sample_size == 1: all samples that size
+        stsz_body.extend_from_slice(&sample_count.to_be_bytes());
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&1u32.to_be_bytes());
+        stts_body.extend_from_slice(&sample_count.to_be_bytes());
+        stts_body.extend_from_slice(&sample_delta.to_be_bytes());
+        let stts = sized_box(b"stts", &stts_body);
+
+        let stbl = sized_box(b"stbl", &[stsd, stsz, stts].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &[mvhd, trak].concat());
+
+        let mut data = ftyp;
+        data.extend_from_slice(&moov);
+        data
+    }
+
+    #[test]
+    fn resolves_fractional_positions_to_sample_indices() {
+        let data = sample_mp4_with_timed_video_samples(10, 1000, 1000);
+        let mut stream = MemorySeekableStream::new(data);
+
+        let indices = video_sample_indices_at_positions(&mut stream, &[0.0, 0.5, 0.9]).unwrap();
+
+        assert_eq!(indices, vec![0, 5, 9]);
+    }
+
+    #[test]
+    fn reports_unsupported_without_a_video_track() {
+        let mut stream = MemorySeekableStream::new(sized_box(b"moov", &[]));
+        let err = video_sample_indices_at_positions(&mut stream, &[0.5]).unwrap_err();
+        assert!(matches!(err, Error::Unsupported(_)));
+    }
+
+    #[test]
+    fn returns_samples_from_the_preceding_sync_sample_through_the_target() {
+        let samples: [&[u8]; 4] = [b"idr-0", b"delta-1", b"delta-2", b"idr-3"];
+        // Sample 0 (1-based 1) is the only sync sample here.
+        let probe = sample_mp4_with_video_samples(&samples, &[1], 0);
+        let mdat_payload_offset = (probe.len() - samples.concat().len()) as u32;
+        let data = sample_mp4_with_video_samples(&samples, &[1], mdat_payload_offset);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let frames = video_samples_for_exact_frame(&mut stream, 2).unwrap();
+
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].data, samples[0]);
+        assert_eq!(frames[1].data, samples[1]);
+        assert_eq!(frames[2].data, samples[2]);
+        assert_eq!(frames[0].sample_index, 0);
+        assert_eq!(frames[2].sample_index, 2);
+        assert!(frames[0].is_keyframe);
+        assert!(!frames[1].is_keyframe);
+        assert_eq!(frames[0].pts, std::time::Duration::ZERO);
+        assert_eq!(frames[1].pts, std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn reports_unsupported_for_an_out_of_range_sample_index() {
+        let mut stream = MemorySeekableStream::new(sized_box(b"moov", &[]));
+        let err = video_samples_for_exact_frame(&mut stream, 0).unwrap_err();
+        assert!(matches!(err, Error::Unsupported(_)));
+    }
+
+    #[test]
+    fn extracts_the_first_video_sample_as_a_still_encoded_thumbnail() {
+        let sample = b"not-really-encoded-video";
+        // Build once to learn where mdat's payload lands, then rebuild
+        // with the real chunk offset baked in.
+        let probe = sample_mp4_with_video_track(sample, 0);
+        let mdat_payload_offset = (probe.len() - sample.len()) as u32;
+        let data = sample_mp4_with_video_track(sample, mdat_payload_offset);
+
+        let mut stream = MemorySeekableStream::new(data);
+        let thumbnail = extract_video_thumbnail(&mut stream).unwrap();
+
+        assert_eq!(thumbnail.width, 64);
+        assert_eq!(thumbnail.height, 48);
+        assert_eq!(thumbnail.data, sample);
+        assert_eq!(thumbnail.sample_index, 0);
+        assert!(thumbnail.is_keyframe);
+    }
+
+    #[test]
+    fn reports_unsupported_when_there_is_no_video_track() {
+        let mut stream = MemorySeekableStream::new(sized_box(b"moov", &[]));
+        let err = extract_video_thumbnail(&mut stream).unwrap_err();
+        assert!(matches!(err, Error::Unsupported(_)));
+    }
+}
@@ -0,0 +1,208 @@
+//! Timed-metadata extraction: Apple's `mebx` Metadata Media, as found in
+//! iPhone footage's camera-motion and detected-face tracks.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::error::Result;
+use crate::formats::mp4::timed_metadata_reader;
+use crate::stream::SeekableStream;
+
+/// One `mebx` sample's decoded key/value pairs, over the time range they
+/// apply to.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedMetadataEntry {
+    /// When this entry starts applying.
+    pub start: Duration,
+    /// When this entry stops applying.
+    pub end: Duration,
+    /// The sample's decoded values, keyed by their resolved key name (e.g.
+    /// `com.apple.quicktime.live-photo.vitality-score`).
+    pub values: HashMap<String, Vec<u8>>,
+}
+
+/// A `meta`-handler timed-metadata track's identity (track ID) and its
+/// fully-decoded entries.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedMetadataTrack {
+    /// The track's `tkhd.track_ID`.
+    pub track_id: u32,
+    /// The track's entries, in file order.
+    pub entries: Vec<TimedMetadataEntry>,
+}
+
+/// Extracts every `mebx` timed-metadata track in `stream`, each grouped by
+/// its track ID, from a single read of `moov`. Returns an empty `Vec` if
+/// `stream` has no timed-metadata tracks.
+pub fn extract_timed_metadata<S: SeekableStream>(mut stream: S) -> Result<Vec<TimedMetadataTrack>> {
+    let tracks = timed_metadata_reader::find_all_timed_metadata_tracks(&mut stream)?;
+
+    tracks
+        .into_iter()
+        .map(|track| {
+            let entries = (0..track.samples.sample_count())
+                .map(|index| decode_entry(&mut stream, &track, index))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(TimedMetadataTrack { track_id: track.track_id, entries })
+        })
+        .collect()
+}
+
+/// Decodes sample `index` (0-based) of `track` into a [`TimedMetadataEntry`],
+/// deriving its end time from the next sample's timestamp. The last sample
+/// has no following sample, so its `end` equals its `start`.
+fn decode_entry<S: SeekableStream>(
+    stream: &mut S,
+    track: &timed_metadata_reader::TimedMetadataTrack,
+    index: u32,
+) -> Result<TimedMetadataEntry> {
+    let size = track.samples.size(stream, index)?;
+    let offset = track.samples.offset(stream, index)?;
+    let mut data = vec![0u8; size as usize];
+    stream.read_at(offset, &mut data)?;
+
+    let start_ticks = track.samples.timestamp(stream, index)?;
+    let end_ticks =
+        if index + 1 < track.samples.sample_count() { track.samples.timestamp(stream, index + 1)? } else { start_ticks };
+
+    let values = timed_metadata_reader::decode_sample(&track.keys, &data)?.into_iter().collect();
+    Ok(TimedMetadataEntry {
+        start: ticks_to_duration(start_ticks, track.timescale),
+        end: ticks_to_duration(end_ticks, track.timescale),
+        values,
+    })
+}
+
+fn ticks_to_duration(ticks: u64, timescale: u32) -> Duration {
+    if timescale == 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs_f64(ticks as f64 / f64::from(timescale))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn sized_box(box_type: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut b = Vec::new();
+        b.extend_from_slice(&((body.len() + 8) as u32).to_be_bytes());
+        b.extend_from_slice(box_type);
+        b.extend_from_slice(body);
+        b
+    }
+
+    fn tkhd_box(track_id: u32) -> Vec<u8> {
+        let mut body = vec![0u8; 20];
+        body[12..16].copy_from_slice(&track_id.to_be_bytes());
+        sized_box(b"tkhd", &body)
+    }
+
+    fn mdhd_box() -> Vec<u8> {
+        let mut body = vec![0u8; 22];
+        body[12..16].copy_from_slice(&1000u32.to_be_bytes()); // timescale: milliseconds
+        sized_box(b"mdhd", &body)
+    }
+
+    fn key_entry(namespace: &[u8; 4], value: &[u8]) -> Vec<u8> {
+        let mut entry = ((value.len() + 8) as u32).to_be_bytes().to_vec();
+        entry.extend_from_slice(namespace);
+        entry.extend_from_slice(value);
+        entry
+    }
+
+    fn mebx_sample(items: &[(u32, &[u8])]) -> Vec<u8> {
+        items.iter().flat_map(|(key_id, value)| sized_box(&key_id.to_be_bytes(), value)).collect()
+    }
+
+    fn sample_mp4_with_mebx_track(key_names: &[&str], samples: &[Vec<u8>]) -> Vec<u8> {
+        let tkhd = tkhd_box(1);
+        let mdhd = mdhd_box();
+
+        let mut keys_body = vec![0u8; 4]; // version/flags
+        keys_body.extend_from_slice(&(key_names.len() as u32).to_be_bytes());
+        for name in key_names {
+            keys_body.extend_from_slice(&key_entry(b"mdta", name.as_bytes()));
+        }
+        let keys = sized_box(b"keys", &keys_body);
+        let mebx = sized_box(b"mebx", &keys);
+        let stsd_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &mebx].concat();
+        let stsd = sized_box(b"stsd", &stsd_body);
+
+        let mut stsz_body = vec![0u8; 4];
+        stsz_body.extend_from_slice(&0u32.to_be_bytes());
+        stsz_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for sample in samples {
+            stsz_body.extend_from_slice(&(sample.len() as u32).to_be_bytes());
+        }
+        let stsz = sized_box(b"stsz", &stsz_body);
+
+        let mut stts_body = vec![0u8; 4];
+        stts_body.extend_from_slice(&(samples.len() as u32).to_be_bytes());
+        for _ in samples {
+            stts_body.extend_from_slice(&1u32.to_be_bytes()); // sample_count
+            stts_body.extend_from_slice(&1000u32.to_be_bytes()); // sample_delta: 1 second
+        }
+        let stts = sized_box(b"stts", &stts_body);
+
+        let mut stsc_body = vec![0u8; 4];
+        stsc_body.extend_from_slice(&1u32.to_be_bytes()); // entry_count
+        stsc_body.extend_from_slice(&1u32.to_be_bytes()); // first_chunk
+        stsc_body.extend_from_slice(&(samples.len() as u32).to_be_bytes()); // samples_per_chunk
+        stsc_body.extend_from_slice(&1u32.to_be_bytes()); // sample_description_index
+        let stsc = sized_box(b"stsc", &stsc_body);
+
+        let stco_body = [&0u32.to_be_bytes()[..], &1u32.to_be_bytes(), &0u32.to_be_bytes()].concat();
+        let stco = sized_box(b"stco", &stco_body);
+
+        let stbl = sized_box(b"stbl", &[stsd, stts, stsc, stsz, stco].concat());
+        let minf = sized_box(b"minf", &stbl);
+        let hdlr_body = [&[0u8; 8][..], b"meta", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &[mdhd, hdlr, minf].concat());
+        let trak = sized_box(b"trak", &[tkhd, mdia].concat());
+        let mut moov = sized_box(b"moov", &trak);
+
+        let mdat_body: Vec<u8> = samples.concat();
+        let mdat_start = (moov.len() + 8) as u32;
+        let stco_offset_pos = moov.len() - 4;
+        moov[stco_offset_pos..].copy_from_slice(&mdat_start.to_be_bytes());
+
+        let mdat = sized_box(b"mdat", &mdat_body);
+        [moov, mdat].concat()
+    }
+
+    #[test]
+    fn extracts_key_value_pairs_with_timestamps() {
+        let samples = vec![mebx_sample(&[(1, b"moving")]), mebx_sample(&[(1, b"still")])];
+        let data = sample_mp4_with_mebx_track(&["com.example.motion-state"], &samples);
+
+        let tracks = extract_timed_metadata(MemorySeekableStream::new(data)).unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].track_id, 1);
+        assert_eq!(tracks[0].entries.len(), 2);
+
+        assert_eq!(tracks[0].entries[0].start, Duration::ZERO);
+        assert_eq!(tracks[0].entries[0].end, Duration::from_secs(1));
+        assert_eq!(tracks[0].entries[0].values.get("com.example.motion-state"), Some(&b"moving".to_vec()));
+
+        assert_eq!(tracks[0].entries[1].start, Duration::from_secs(1));
+        // The last sample has no following sample to derive an end time from.
+        assert_eq!(tracks[0].entries[1].end, Duration::from_secs(1));
+        assert_eq!(tracks[0].entries[1].values.get("com.example.motion-state"), Some(&b"still".to_vec()));
+    }
+
+    #[test]
+    fn returns_an_empty_vec_without_a_mebx_track() {
+        let hdlr_body = [&[0u8; 8][..], b"soun", &[0u8; 12][..]].concat();
+        let hdlr = sized_box(b"hdlr", &hdlr_body);
+        let mdia = sized_box(b"mdia", &hdlr);
+        let trak = sized_box(b"trak", &mdia);
+        let moov = sized_box(b"moov", &trak);
+
+        assert!(extract_timed_metadata(MemorySeekableStream::new(moov)).unwrap().is_empty());
+    }
+}
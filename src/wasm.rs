@@ -0,0 +1,49 @@
+//! Fetching a [`SeekableStream`] in the browser or an edge worker.
+//!
+//! [`SeekableStream::read_at`] is synchronous, and `wasm32-unknown-unknown`
+//! has no way to block on a JS promise (there's no OS thread to park while
+//! one resolves, unlike [`crate::SeekableS3Stream`]'s internal Tokio
+//! runtime trick). So instead of lazily ranging over the remote resource
+//! the way [`crate::SeekableHttpStream`] does natively, [`fetch_seekable_stream`]
+//! downloads it up front with a single `fetch` call and hands back an
+//! ordinary [`MemorySeekableStream`] for parsing. That's the right
+//! trade-off for the metadata this crate extracts, which almost always
+//! lives in a bounded prefix or suffix of the file; it isn't a fit for
+//! multi-gigabyte sources, which should be range-probed from a server-side
+//! Tokio runtime instead.
+
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, Request, RequestInit, Response};
+
+use crate::error::{Error, Result};
+use crate::stream::MemorySeekableStream;
+
+/// Downloads `url` with the browser's `fetch` API and wraps the body in a
+/// [`MemorySeekableStream`] ready for [`crate::extract_metadata`].
+pub async fn fetch_seekable_stream(url: &str) -> Result<MemorySeekableStream> {
+    let headers = Headers::new().map_err(js_error)?;
+    headers.set("Range", "bytes=0-").map_err(js_error)?;
+
+    let mut init = RequestInit::new();
+    init.headers(&headers);
+
+    let request = Request::new_with_str_and_init(url, &init).map_err(js_error)?;
+
+    let window = web_sys::window().ok_or_else(|| Error::Unsupported("no `window` available to fetch from".into()))?;
+    let response_value = JsFuture::from(window.fetch_with_request(&request)).await.map_err(js_error)?;
+    let response: Response = response_value.dyn_into().map_err(js_error)?;
+
+    if !response.ok() {
+        return Err(Error::Unsupported(format!("fetch failed with status {}", response.status())));
+    }
+
+    let buffer = JsFuture::from(response.array_buffer().map_err(js_error)?).await.map_err(js_error)?;
+    let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+
+    Ok(MemorySeekableStream::new(bytes))
+}
+
+fn js_error(value: wasm_bindgen::JsValue) -> Error {
+    Error::Unsupported(format!("{value:?}"))
+}
@@ -0,0 +1,292 @@
+//! Audio waveform peak extraction, for building waveform UIs without
+//! decoding (or bundling a decoder for) the whole track.
+//!
+//! PCM is read directly off the stream a window at a time, so this costs no
+//! more I/O than the samples actually needed -- the same
+//! [`crate::stream::SeekableStream`] abstraction every other format reader
+//! uses, so a remote source (e.g. [`crate::http::SeekableHttpStream`]) only
+//! fetches the byte ranges a window's samples fall in. Compressed formats
+//! (AAC, etc.) would need a real decoder to get PCM out first, which this
+//! crate does not bundle; only WAV's already-PCM `data` chunk is supported.
+
+use crate::error::{Error, Result};
+use crate::formats::wav;
+use crate::stream::SeekableStream;
+use crate::thumbnails::DecodedImage;
+
+/// The minimum and maximum sample values within one window, for rendering
+/// one bar/column of a waveform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PeakPair {
+    pub min: i16,
+    pub max: i16,
+}
+
+/// Reads `stream` as a WAV file and computes one [`PeakPair`] per
+/// `samples_per_window` frames (a frame being one sample per channel).
+/// Multi-channel audio is folded down to a single peak pair per window by
+/// taking the min/max across all channels.
+///
+/// Only 16-bit PCM is supported; other bit depths or compressed formats
+/// (e.g. AAC, which would need a decoder this crate does not bundle) return
+/// [`Error::Unsupported`].
+pub fn extract_wav_peaks<S: SeekableStream>(stream: &mut S, samples_per_window: u32) -> Result<Vec<PeakPair>> {
+    if samples_per_window == 0 {
+        return Err(Error::Unsupported("samples_per_window must be greater than zero".into()));
+    }
+
+    let layout = wav::find_pcm_layout(stream)?.ok_or_else(|| Error::Unsupported("no PCM data chunk was found".into()))?;
+
+    if layout.bits_per_sample != 16 {
+        return Err(Error::Unsupported(format!("{}-bit PCM is not supported, only 16-bit", layout.bits_per_sample)));
+    }
+    if layout.channels == 0 {
+        return Err(Error::Malformed { format: "wav", reason: "fmt chunk declares zero channels".into() });
+    }
+
+    let bytes_per_frame = usize::from(layout.channels) * 2;
+    let frame_count = layout.data_size as usize / bytes_per_frame;
+
+    let mut peaks = Vec::with_capacity(frame_count.div_ceil(samples_per_window as usize));
+    let mut frames_read = 0usize;
+
+    while frames_read < frame_count {
+        let frames_this_window = (frame_count - frames_read).min(samples_per_window as usize);
+        let mut buf = vec![0u8; frames_this_window * bytes_per_frame];
+        stream.read_at(layout.data_start + (frames_read * bytes_per_frame) as u64, &mut buf)?;
+
+        let mut min = i16::MAX;
+        let mut max = i16::MIN;
+        for sample_bytes in buf.chunks_exact(2) {
+            let sample = i16::from_le_bytes([sample_bytes[0], sample_bytes[1]]);
+            min = min.min(sample);
+            max = max.max(sample);
+        }
+        peaks.push(PeakPair { min, max });
+
+        frames_read += frames_this_window;
+    }
+
+    Ok(peaks)
+}
+
+/// Configures [`render_waveform`]'s output image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaveformOptions {
+    /// Pixel width of the rendered image.
+    pub width: u32,
+    /// Pixel height of the rendered image.
+    pub height: u32,
+    /// 8-bit RGB fill color for pixels outside the waveform.
+    pub background: [u8; 3],
+    /// 8-bit RGB fill color for the waveform itself.
+    pub foreground: [u8; 3],
+}
+
+impl Default for WaveformOptions {
+    fn default() -> Self {
+        Self { width: 800, height: 200, background: [255, 255, 255], foreground: [0, 0, 0] }
+    }
+}
+
+/// Rasterizes `peaks` (as produced by [`extract_wav_peaks`]) into a
+/// [`DecodedImage`] -- the same raw-RGB shape [`crate::thumbnails::decode`]
+/// returns for video thumbnails, so a caller that already has a pixels-to-file
+/// encoder for one has it for the other. This crate bundles no image
+/// encoder, so turning the result into a PNG/JPEG file is left to the
+/// caller.
+///
+/// `peaks` is resampled (nearest-neighbor) to `options.width` columns, each
+/// drawn as a vertical bar from its window's min to max sample, scaled
+/// against the full `i16` range so silence renders as a flat line down the
+/// image's vertical center rather than being auto-normalized away.
+pub fn render_waveform(peaks: &[PeakPair], options: &WaveformOptions) -> Result<DecodedImage> {
+    if options.width == 0 || options.height == 0 {
+        return Err(Error::Unsupported("waveform width and height must both be greater than zero".into()));
+    }
+    if peaks.is_empty() {
+        return Err(Error::Unsupported("no peaks to render".into()));
+    }
+
+    let width = options.width;
+    let height = options.height;
+    let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+    for _ in 0..(width as usize * height as usize) {
+        rgb.extend_from_slice(&options.background);
+    }
+
+    let half_height = (height as i64 - 1) as f64 / 2.0;
+    let center = half_height;
+
+    for x in 0..width {
+        let peak_index = (x as u64 * peaks.len() as u64 / width as u64) as usize;
+        let peak = &peaks[peak_index.min(peaks.len() - 1)];
+
+        let top = (center - (peak.max as f64 / i16::MAX as f64) * half_height).round() as i64;
+        let bottom = (center - (peak.min as f64 / i16::MAX as f64) * half_height).round() as i64;
+        let top = top.clamp(0, height as i64 - 1) as u32;
+        let bottom = bottom.clamp(0, height as i64 - 1) as u32;
+
+        for y in top..=bottom {
+            let idx = ((y * width + x) as usize) * 3;
+            rgb[idx..idx + 3].copy_from_slice(&options.foreground);
+        }
+    }
+
+    Ok(DecodedImage { width, height, rgb })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::MemorySeekableStream;
+
+    fn le_chunk(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut chunk = Vec::new();
+        chunk.extend_from_slice(id);
+        chunk.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(body);
+        if body.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    }
+
+    fn mono_wav(samples: &[i16]) -> Vec<u8> {
+        let mut fmt_body = Vec::new();
+        fmt_body.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        fmt_body.extend_from_slice(&1u16.to_le_bytes()); // channels
+        fmt_body.extend_from_slice(&44100u32.to_le_bytes()); // sample rate
+        fmt_body.extend_from_slice(&88200u32.to_le_bytes()); // byte rate
+        fmt_body.extend_from_slice(&2u16.to_le_bytes()); // block align
+        fmt_body.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+
+        let mut data_body = Vec::new();
+        for &sample in samples {
+            data_body.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        let mut riff_body = Vec::new();
+        riff_body.extend_from_slice(b"WAVE");
+        riff_body.extend_from_slice(&le_chunk(b"fmt ", &fmt_body));
+        riff_body.extend_from_slice(&le_chunk(b"data", &data_body));
+
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(riff_body.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&riff_body);
+        wav
+    }
+
+    #[test]
+    fn computes_one_peak_pair_per_window() {
+        let samples: Vec<i16> = vec![0, 100, -100, 5, 5, 5, 50, -50];
+        let mut stream = MemorySeekableStream::new(mono_wav(&samples));
+
+        let peaks = extract_wav_peaks(&mut stream, 4).unwrap();
+
+        assert_eq!(peaks, vec![PeakPair { min: -100, max: 100 }, PeakPair { min: -50, max: 50 }]);
+    }
+
+    #[test]
+    fn handles_a_trailing_partial_window() {
+        let samples: Vec<i16> = vec![1, 2, 3];
+        let mut stream = MemorySeekableStream::new(mono_wav(&samples));
+
+        let peaks = extract_wav_peaks(&mut stream, 2).unwrap();
+
+        assert_eq!(peaks, vec![PeakPair { min: 1, max: 2 }, PeakPair { min: 3, max: 3 }]);
+    }
+
+    #[test]
+    fn folds_multiple_channels_into_one_peak_pair() {
+        let mut fmt_body = Vec::new();
+        fmt_body.extend_from_slice(&1u16.to_le_bytes());
+        fmt_body.extend_from_slice(&2u16.to_le_bytes()); // stereo
+        fmt_body.extend_from_slice(&44100u32.to_le_bytes());
+        fmt_body.extend_from_slice(&176400u32.to_le_bytes());
+        fmt_body.extend_from_slice(&4u16.to_le_bytes());
+        fmt_body.extend_from_slice(&16u16.to_le_bytes());
+
+        let mut data_body = Vec::new();
+        for &(left, right) in &[(10i16, -10i16), (20, -200)] {
+            data_body.extend_from_slice(&left.to_le_bytes());
+            data_body.extend_from_slice(&right.to_le_bytes());
+        }
+
+        let mut riff_body = Vec::new();
+        riff_body.extend_from_slice(b"WAVE");
+        riff_body.extend_from_slice(&le_chunk(b"fmt ", &fmt_body));
+        riff_body.extend_from_slice(&le_chunk(b"data", &data_body));
+
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(riff_body.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&riff_body);
+
+        let mut stream = MemorySeekableStream::new(wav);
+        let peaks = extract_wav_peaks(&mut stream, 2).unwrap();
+
+        assert_eq!(peaks, vec![PeakPair { min: -200, max: 20 }]);
+    }
+
+    #[test]
+    fn rejects_a_file_with_no_data_chunk() {
+        let mut fmt_body = Vec::new();
+        fmt_body.extend_from_slice(&1u16.to_le_bytes());
+        fmt_body.extend_from_slice(&1u16.to_le_bytes());
+        fmt_body.extend_from_slice(&44100u32.to_le_bytes());
+        fmt_body.extend_from_slice(&88200u32.to_le_bytes());
+        fmt_body.extend_from_slice(&2u16.to_le_bytes());
+        fmt_body.extend_from_slice(&16u16.to_le_bytes());
+
+        let mut riff_body = Vec::new();
+        riff_body.extend_from_slice(b"WAVE");
+        riff_body.extend_from_slice(&le_chunk(b"fmt ", &fmt_body));
+
+        let mut wav = Vec::new();
+        wav.extend_from_slice(b"RIFF");
+        wav.extend_from_slice(&(riff_body.len() as u32).to_le_bytes());
+        wav.extend_from_slice(&riff_body);
+
+        let mut stream = MemorySeekableStream::new(wav);
+        assert!(extract_wav_peaks(&mut stream, 4).is_err());
+    }
+
+    #[test]
+    fn renders_a_waveform_image_at_the_requested_size() {
+        let peaks = vec![PeakPair { min: -100, max: 100 }, PeakPair { min: -32768, max: 32767 }];
+        let options = WaveformOptions { width: 10, height: 20, ..Default::default() };
+
+        let image = render_waveform(&peaks, &options).unwrap();
+
+        assert_eq!(image.width, 10);
+        assert_eq!(image.height, 20);
+        assert_eq!(image.rgb.len(), 10 * 20 * 3);
+    }
+
+    #[test]
+    fn draws_foreground_pixels_at_full_scale_top_and_bottom() {
+        let peaks = vec![PeakPair { min: i16::MIN, max: i16::MAX }];
+        let options = WaveformOptions { width: 1, height: 5, background: [255, 255, 255], foreground: [0, 0, 0] };
+
+        let image = render_waveform(&peaks, &options).unwrap();
+
+        assert_eq!(&image.rgb[0..3], &[0, 0, 0]); // top row
+        assert_eq!(&image.rgb[12..15], &[0, 0, 0]); // bottom row (row 4 of 5)
+    }
+
+    #[test]
+    fn rejects_a_zero_sized_image() {
+        let peaks = vec![PeakPair { min: 0, max: 0 }];
+        let options = WaveformOptions { width: 0, ..Default::default() };
+
+        assert!(render_waveform(&peaks, &options).is_err());
+    }
+
+    #[test]
+    fn rejects_an_empty_peak_list() {
+        assert!(render_waveform(&[], &WaveformOptions::default()).is_err());
+    }
+}